@@ -0,0 +1,229 @@
+//! Print a breakdown of keys read from stdin, one key per line.
+//!
+//! Lines are hex by default (an optional `0x` prefix is accepted); prefix a line with
+//! `base64:` to pass padded standard base64 instead. Without a schema each key is read part by
+//! part with a `Decoder`, and every part is shown with its byte span, hex and escaped ASCII.
+//!
+//! `--schema` decodes each key with a `KeySchema` instead and shows every field's name, value,
+//! direction and byte span. The schema is a comma-separated list of `[name:]type[:desc]` fields,
+//! where `type` is one of `str`, `bytes`, `u8`..`u64`, `i8`..`i64`, `f32`, `f64`, `bool` and
+//! `uuid`; `--version N` adds the 1-byte layout version of `KeySchema::versioned`.
+//!
+//! ```text
+//! $ printf '666f6f00800000000000002a\n' | cargo run --example lexkey-inspect
+//! $ printf '666f6f00800000000000002a\n' \
+//!     | cargo run --example lexkey-inspect -- --schema tenant:str,seq:i64
+//! ```
+
+use std::io::{self, BufRead, Write};
+use std::process::ExitCode;
+
+use lexkey::desc::SortOrder;
+use lexkey::schema::{FieldType, KeySchema};
+use lexkey::{Decoder, LexKey};
+
+fn base64_digit(c: u8) -> Option<u8> {
+    match c {
+        b'A'..=b'Z' => Some(c - b'A'),
+        b'a'..=b'z' => Some(c - b'a' + 26),
+        b'0'..=b'9' => Some(c - b'0' + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    }
+}
+
+/// Decode padded standard base64, rejecting bad lengths, misplaced padding and non-zero
+/// padding bits.
+fn decode_base64(input: &str) -> Result<Vec<u8>, String> {
+    let input = input.as_bytes();
+    if !input.len().is_multiple_of(4) {
+        return Err(format!(
+            "base64 length {} is not a multiple of 4",
+            input.len()
+        ));
+    }
+    let mut out = Vec::with_capacity(input.len() / 4 * 3);
+    for (n, quad) in input.chunks_exact(4).enumerate() {
+        let last = (n + 1) * 4 == input.len();
+        let pad = quad.iter().rev().take_while(|&&c| c == b'=').count();
+        if pad > 2 || (pad > 0 && !last) {
+            return Err(format!(
+                "invalid base64 padding at offset {}",
+                n * 4 + 4 - pad
+            ));
+        }
+        let mut acc = 0u32;
+        for (i, &c) in quad[..4 - pad].iter().enumerate() {
+            let d = base64_digit(c)
+                .ok_or_else(|| format!("invalid base64 character at offset {}", n * 4 + i))?;
+            acc |= u32::from(d) << (18 - 6 * i);
+        }
+        let bytes = acc.to_be_bytes();
+        let len = 3 - pad;
+        if bytes[1 + len..].iter().any(|&b| b != 0) {
+            return Err("base64 padding bits are not zero".to_string());
+        }
+        out.extend_from_slice(&bytes[1..=len]);
+    }
+    Ok(out)
+}
+
+fn parse_line(line: &str) -> Result<LexKey, String> {
+    match line.strip_prefix("base64:") {
+        Some(b64) => decode_base64(b64.trim()).map(LexKey::from),
+        None => LexKey::from_hex(line).map_err(|e| e.to_string()),
+    }
+}
+
+fn parse_field_type(name: &str) -> Result<FieldType, String> {
+    Ok(match name {
+        "str" => FieldType::Str,
+        "bytes" => FieldType::Bytes,
+        "u8" => FieldType::U8,
+        "u16" => FieldType::U16,
+        "u32" => FieldType::U32,
+        "u64" => FieldType::U64,
+        "i8" => FieldType::I8,
+        "i16" => FieldType::I16,
+        "i32" => FieldType::I32,
+        "i64" => FieldType::I64,
+        "f32" => FieldType::F32,
+        "f64" => FieldType::F64,
+        "bool" => FieldType::Bool,
+        "uuid" => FieldType::Uuid,
+        _ => return Err(format!("unknown field type {name:?}")),
+    })
+}
+
+fn parse_schema(spec: &str, version: Option<u8>) -> Result<KeySchema, String> {
+    let mut fields = Vec::new();
+    let mut desc = Vec::new();
+    for (i, field) in spec.split(',').enumerate() {
+        let mut items: Vec<&str> = field.split(':').collect();
+        if items.last() == Some(&"desc") {
+            items.pop();
+            desc.push(i);
+        }
+        let (name, ty) = match items[..] {
+            [ty] => (i.to_string(), ty),
+            [name, ty] => (name.to_string(), ty),
+            _ => return Err(format!("invalid schema field {field:?}")),
+        };
+        fields.push((name, parse_field_type(ty)?));
+    }
+    let mut schema = KeySchema::named(fields);
+    for i in desc {
+        if schema.fields()[i].width().is_none() {
+            return Err(format!(
+                "field {i} is variable-width and cannot be descending"
+            ));
+        }
+        schema = schema.with_order(i, SortOrder::Desc);
+    }
+    Ok(match version {
+        Some(v) => schema.versioned(v),
+        None => schema,
+    })
+}
+
+fn parse_args() -> Result<Option<KeySchema>, String> {
+    let mut spec = None;
+    let mut version = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{arg} needs a value"));
+        match arg.as_str() {
+            "--schema" => spec = Some(value()?),
+            "--version" => {
+                let v = value()?;
+                version = Some(v.parse().map_err(|_| format!("invalid version {v:?}"))?);
+            }
+            _ => return Err(format!("unknown argument {arg:?}")),
+        }
+    }
+    match (spec, version) {
+        (Some(spec), version) => parse_schema(&spec, version).map(Some),
+        (None, Some(_)) => Err("--version needs --schema".to_string()),
+        (None, None) => Ok(None),
+    }
+}
+
+fn inspect_parts(out: &mut impl Write, key: &LexKey) -> io::Result<()> {
+    let bytes = key.as_bytes();
+    let mut dec = Decoder::new(bytes);
+    let mut i = 0;
+    while !dec.is_empty() {
+        let start = dec.position();
+        let part = dec.decode_part().expect("bytes remain");
+        let span = format!("{start}..{}", start + part.len());
+        writeln!(
+            out,
+            "  [{i}] {span:<8} {:<34} \"{}\"",
+            hex::encode(part),
+            part.escape_ascii()
+        )?;
+        i += 1;
+    }
+    // `decode_part` consumes a trailing separator, which ends an empty last part.
+    if bytes.last() == Some(&LexKey::SEPARATOR) {
+        writeln!(out, "  [{i}] {0}..{0}", bytes.len())?;
+    }
+    Ok(())
+}
+
+fn inspect_fields(out: &mut impl Write, key: &LexKey, schema: &KeySchema) -> io::Result<()> {
+    match key.explain_parts(schema) {
+        Ok(fields) => {
+            for field in fields {
+                let span = format!("{}..{}", field.span.start, field.span.end);
+                let order = match field.order {
+                    SortOrder::Asc => "asc",
+                    SortOrder::Desc => "desc",
+                };
+                writeln!(
+                    out,
+                    "  {:<12} {span:<8} {order:<4} {}",
+                    field.name, field.value
+                )?;
+            }
+            Ok(())
+        }
+        Err(e) => writeln!(out, "  error: {e}"),
+    }
+}
+
+fn inspect(out: &mut impl Write, key: &LexKey, schema: Option<&KeySchema>) -> io::Result<()> {
+    writeln!(out, "key:   {}", key.to_hex_string())?;
+    writeln!(out, "len:   {}", key.as_bytes().len())?;
+    writeln!(out, "parts: {}", key.to_debug_string())?;
+    match schema {
+        Some(schema) => inspect_fields(out, key, schema)?,
+        None => inspect_parts(out, key)?,
+    }
+    writeln!(out)
+}
+
+fn main() -> io::Result<ExitCode> {
+    let schema = match parse_args() {
+        Ok(schema) => schema,
+        Err(e) => {
+            eprintln!("lexkey-inspect: {e}");
+            return Ok(ExitCode::FAILURE);
+        }
+    };
+    let stdin = io::stdin();
+    let mut out = io::stdout().lock();
+    for line in stdin.lock().lines() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        match parse_line(line) {
+            Ok(key) => inspect(&mut out, &key, schema.as_ref())?,
+            Err(e) => writeln!(out, "error: {e}: {line}\n")?,
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
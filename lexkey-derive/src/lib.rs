@@ -0,0 +1,392 @@
+//! Derive macros for `lexkey`. Enable the `derive` feature of `cntryl-lexkey` and import them
+//! from `lexkey` rather than depending on this crate directly.
+
+use std::collections::HashSet;
+
+use proc_macro::TokenStream;
+use proc_macro2::{Literal, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, Attribute, Data, DeriveInput, Error, Field, Fields, Ident, LitInt, Type,
+    Variant,
+};
+
+/// Derive `Encodable` plus `to_lexkey` and `encode_into(&mut Encoder)` for a struct or enum.
+///
+/// See the `lexkey::derive` module for the encoding and the supported attributes.
+#[proc_macro_derive(LexKeyEncode, attributes(lexkey))]
+pub fn derive_lexkey_encode(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_encode(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Derive `Decodable` plus `from_lexkey(&[u8]) -> Result<Self, DecodeError>` for a struct or
+/// enum.
+///
+/// Decodes the layout `LexKeyEncode` writes, honouring the same attributes.
+#[proc_macro_derive(LexKeyDecode, attributes(lexkey))]
+pub fn derive_lexkey_decode(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_decode(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Per-field options parsed from `#[lexkey(...)]`.
+#[derive(Default)]
+struct FieldOpts {
+    desc: bool,
+    nulls_last: bool,
+}
+
+impl FieldOpts {
+    fn parse(field: &Field) -> syn::Result<Self> {
+        let mut opts = Self::default();
+        for attr in lexkey_attrs(&field.attrs) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("desc") {
+                    opts.desc = true;
+                } else if meta.path.is_ident("asc") {
+                    opts.desc = false;
+                } else if meta.path.is_ident("nulls_last") {
+                    opts.nulls_last = true;
+                } else if meta.path.is_ident("nulls_first") {
+                    opts.nulls_last = false;
+                } else {
+                    return Err(meta.error(
+                        "unsupported lexkey attribute; expected `asc`, `desc`, `nulls_first` or `nulls_last`",
+                    ));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(opts)
+    }
+}
+
+fn lexkey_attrs(attrs: &[Attribute]) -> impl Iterator<Item = &Attribute> {
+    attrs.iter().filter(|a| a.path().is_ident("lexkey"))
+}
+
+/// One field bound to a local variable, `__field{i}`, in patterns and constructors.
+struct Part<'a> {
+    var: Ident,
+    ty: &'a Type,
+    opts: FieldOpts,
+}
+
+fn parts(fields: &Fields) -> syn::Result<Vec<Part<'_>>> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            Ok(Part {
+                var: format_ident!("__field{i}"),
+                ty: &field.ty,
+                opts: FieldOpts::parse(field)?,
+            })
+        })
+        .collect()
+}
+
+/// `path { a: __field0, .. }`, `path(__field0, ..)` or `path`: a pattern and a constructor.
+fn bind(path: &TokenStream2, fields: &Fields, parts: &[Part<'_>]) -> TokenStream2 {
+    let vars = parts.iter().map(|p| &p.var);
+    match fields {
+        Fields::Named(named) => {
+            let idents = named.named.iter().map(|f| &f.ident);
+            quote!(#path { #(#idents: #vars),* })
+        }
+        Fields::Unnamed(_) => quote!(#path(#(#vars),*)),
+        Fields::Unit => quote!(#path),
+    }
+}
+
+/// The encoded length and encoding statements of bound `parts` joined by separators.
+///
+/// With `descending`, every field is written in the opposite of its own direction, so the whole
+/// value sorts in reverse; the separators between fields are left as they are.
+fn encode_parts(parts: &[Part<'_>], descending: bool) -> (TokenStream2, TokenStream2) {
+    let values: Vec<_> = parts
+        .iter()
+        .map(|p| {
+            let var = &p.var;
+            let value = if p.opts.nulls_last {
+                quote!(&::lexkey::NullsLast(#var.as_ref()))
+            } else {
+                quote!(#var)
+            };
+            if p.opts.desc == descending {
+                (value, quote!(encoded_len), quote!(encode_into))
+            } else {
+                (value, quote!(encoded_desc_len), quote!(encode_desc_into))
+            }
+        })
+        .collect();
+    let separators = parts.len().saturating_sub(1);
+    let lens = values
+        .iter()
+        .map(|(value, len, _)| quote!(::lexkey::Encodable::#len(#value)));
+    let len = quote!(0 #(+ #lens)* + #separators);
+    let stmts = values.iter().enumerate().map(|(i, (value, _, encode))| {
+        let sep = (i > 0).then(|| quote!(dst.push(::lexkey::LexKey::SEPARATOR);));
+        quote! {
+            #sep
+            ::lexkey::Encodable::#encode(#value, dst);
+        }
+    });
+    (len, quote!(#(#stmts)*))
+}
+
+/// Statements decoding `parts` in order into their bound variables, each in the opposite of its
+/// own direction with `descending`.
+fn decode_parts(parts: &[Part<'_>], descending: bool) -> TokenStream2 {
+    let stmts = parts.iter().enumerate().map(|(i, p)| {
+        let Part { var, ty, opts } = p;
+        let sep = (i > 0).then(|| quote!(dec.expect_separator()?;));
+        let method = if opts.desc == descending {
+            quote!(decode_value)
+        } else {
+            quote!(decode_value_desc)
+        };
+        quote! {
+            #sep
+            let #var = dec.#method::<#ty>()?;
+        }
+    });
+    quote!(#(#stmts)*)
+}
+
+/// Each variant's ordinal: `#[lexkey(ordinal = N)]`, or one more than the previous variant's.
+fn ordinals<'a>(variants: impl IntoIterator<Item = &'a Variant>) -> syn::Result<Vec<Literal>> {
+    let mut next = Some(0u16);
+    let mut seen = HashSet::new();
+    let mut out = Vec::new();
+    for variant in variants {
+        let mut ordinal = next;
+        for attr in lexkey_attrs(&variant.attrs) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("ordinal") {
+                    ordinal = Some(meta.value()?.parse::<LitInt>()?.base10_parse()?);
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported lexkey attribute; expected `ordinal = N`"))
+                }
+            })?;
+        }
+        let Some(ordinal) = ordinal else {
+            return Err(Error::new_spanned(
+                &variant.ident,
+                "enum ordinal overflows u16",
+            ));
+        };
+        if !seen.insert(ordinal) {
+            return Err(Error::new_spanned(
+                &variant.ident,
+                format!("duplicate enum ordinal {ordinal}"),
+            ));
+        }
+        next = ordinal.checked_add(1);
+        out.push(Literal::u16_suffixed(ordinal));
+    }
+    Ok(out)
+}
+
+/// The bodies of `encoded_len` and `encode_into`, or of their descending counterparts.
+fn encode_body(input: &DeriveInput, descending: bool) -> syn::Result<(TokenStream2, TokenStream2)> {
+    Ok(match &input.data {
+        Data::Struct(data) => {
+            let parts = parts(&data.fields)?;
+            let pattern = bind(&quote!(Self), &data.fields, &parts);
+            let (len, stmts) = encode_parts(&parts, descending);
+            (
+                quote! {
+                    let #pattern = self;
+                    #len
+                },
+                quote! {
+                    let #pattern = self;
+                    #stmts
+                },
+            )
+        }
+        Data::Enum(data) => {
+            let ordinals = ordinals(&data.variants)?;
+            let encode_ordinal = if descending {
+                quote!(encode_desc_into)
+            } else {
+                quote!(encode_into)
+            };
+            let mut len_arms = Vec::new();
+            let mut encode_arms = Vec::new();
+            for (variant, ordinal) in data.variants.iter().zip(&ordinals) {
+                let ident = &variant.ident;
+                let parts = parts(&variant.fields)?;
+                let pattern = bind(&quote!(Self::#ident), &variant.fields, &parts);
+                let (len, stmts) = encode_parts(&parts, descending);
+                len_arms.push(quote!(#pattern => 2 + #len));
+                encode_arms.push(quote! {
+                    #pattern => {
+                        ::lexkey::Encodable::#encode_ordinal(&#ordinal, dst);
+                        #stmts
+                    }
+                });
+            }
+            (
+                quote!(match self { #(#len_arms,)* }),
+                quote!(match self { #(#encode_arms)* }),
+            )
+        }
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "LexKeyEncode cannot be derived for unions",
+            ))
+        }
+    })
+}
+
+fn expand_encode(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let (len, encode) = encode_body(input, false)?;
+    let (desc_len, desc_encode) = encode_body(input, true)?;
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::lexkey::Encodable for #name #ty_generics #where_clause {
+            #[inline]
+            fn encoded_len(&self) -> usize {
+                #len
+            }
+
+            #[inline]
+            fn encode_into(&self, dst: &mut ::std::vec::Vec<u8>) -> usize {
+                let start = dst.len();
+                #encode
+                dst.len() - start
+            }
+
+            #[inline]
+            fn encoded_desc_len(&self) -> usize {
+                #desc_len
+            }
+
+            #[inline]
+            fn encode_desc_into(&self, dst: &mut ::std::vec::Vec<u8>) -> usize {
+                let start = dst.len();
+                #desc_encode
+                dst.len() - start
+            }
+        }
+
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Encode this value as a key.
+            #[must_use]
+            pub fn to_lexkey(&self) -> ::lexkey::LexKey {
+                let mut buf = ::std::vec::Vec::with_capacity(::lexkey::Encodable::encoded_len(self));
+                ::lexkey::Encodable::encode_into(self, &mut buf);
+                ::lexkey::LexKey::from_bytes(buf)
+            }
+
+            /// Append this value's key to `enc`, returning bytes written.
+            pub fn encode_into(&self, enc: &mut ::lexkey::Encoder) -> usize {
+                enc.encode_value_into(self)
+            }
+        }
+    })
+}
+
+/// The body of `decode_from`, or of `decode_desc_from` with `descending`.
+fn decode_body(input: &DeriveInput, descending: bool) -> syn::Result<TokenStream2> {
+    Ok(match &input.data {
+        Data::Struct(data) => {
+            let parts = parts(&data.fields)?;
+            let stmts = decode_parts(&parts, descending);
+            let construct = bind(&quote!(Self), &data.fields, &parts);
+            quote! {
+                #stmts
+                #construct
+            }
+        }
+        Data::Enum(data) => {
+            let ordinals = ordinals(&data.variants)?;
+            let mut arms = Vec::new();
+            for (variant, ordinal) in data.variants.iter().zip(&ordinals) {
+                let ident = &variant.ident;
+                let parts = parts(&variant.fields)?;
+                let stmts = decode_parts(&parts, descending);
+                let construct = bind(&quote!(Self::#ident), &variant.fields, &parts);
+                arms.push(quote! {
+                    #ordinal => {
+                        #stmts
+                        #construct
+                    }
+                });
+            }
+            let ordinal = if descending {
+                quote!(dec.decode_value_desc::<u16>()?)
+            } else {
+                quote!(dec.decode_enum_ordinal()?)
+            };
+            quote! {
+                match #ordinal {
+                    #(#arms)*
+                    _ => {
+                        return ::std::result::Result::Err(::lexkey::DecodeError::Invalid {
+                            offset: 0,
+                            expected: "enum ordinal",
+                        });
+                    }
+                }
+            }
+        }
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "LexKeyDecode cannot be derived for unions",
+            ))
+        }
+    })
+}
+
+fn expand_decode(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let decode = decode_body(input, false)?;
+    let desc_decode = decode_body(input, true)?;
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::lexkey::Decodable for #name #ty_generics #where_clause {
+            fn decode_from(src: &mut &[u8]) -> ::std::result::Result<Self, ::lexkey::DecodeError> {
+                let mut dec = ::lexkey::Decoder::new(*src);
+                let value = { #decode };
+                *src = dec.remaining();
+                ::std::result::Result::Ok(value)
+            }
+
+            fn decode_desc_from(src: &mut &[u8]) -> ::std::result::Result<Self, ::lexkey::DecodeError> {
+                let mut dec = ::lexkey::Decoder::new(*src);
+                let value = { #desc_decode };
+                *src = dec.remaining();
+                ::std::result::Result::Ok(value)
+            }
+        }
+
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Decode a key written by `to_lexkey`, rejecting trailing bytes.
+            ///
+            /// # Errors
+            ///
+            /// Returns a `DecodeError` if a field is truncated or invalid, a separator is
+            /// missing, or bytes remain after the last field.
+            pub fn from_lexkey(key: &[u8]) -> ::std::result::Result<Self, ::lexkey::DecodeError> {
+                let mut dec = ::lexkey::Decoder::new(key);
+                let value = dec.decode_value::<Self>()?;
+                dec.finish()?;
+                ::std::result::Result::Ok(value)
+            }
+        }
+    })
+}
@@ -0,0 +1,276 @@
+//! Order-preserving pseudonymization of keys, for reproducing production key distributions in
+//! staging.
+//!
+//! `anonymize` decodes a set of keys with a `KeySchema`, collects the distinct values of each
+//! field the `AnonymizePolicy` selects, and replaces every value with a pseudonym derived from
+//! its rank among them. Equal values get equal pseudonyms and smaller values smaller ones, so
+//! the anonymized keys sort, group and share prefixes exactly like the originals, while the
+//! values themselves are gone. The mapping depends only on the observed set, so the same input
+//! always produces the same output.
+//!
+//! Pseudonyms keep the field type. The value of rank `r` among `n` distinct values is:
+//!
+//! - `Str` and `Bytes`: the field name, `-` and `r` zero-padded to the width of `n - 1`, such as
+//!   `tenant-07`.
+//! - Unsigned integers: `r`. Signed integers: `r`, or `MIN + r` when `n` exceeds the
+//!   non-negative range (only possible for `i8` and `i16`).
+//! - `F32` and `F64`: the `r`-th smallest non-negative float (`from_bits(r)`).
+//! - `Bool`: `r == 1`. `Uuid`: `Uuid::from_u128(r)`.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use uuid::Uuid;
+
+use crate::schema::{FieldType, KeySchema, Value};
+use crate::{DecodeError, Encodable, LexKey};
+
+/// Which fields `anonymize` replaces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnonymizePolicy {
+    fields: Option<Vec<String>>,
+}
+
+impl AnonymizePolicy {
+    /// Replace every field.
+    #[must_use]
+    pub fn all() -> Self {
+        Self { fields: None }
+    }
+
+    /// Replace only the named fields; fields of schemas made with `KeySchema::new` are named by
+    /// their index (`"0"`, `"1"`, ...).
+    #[must_use]
+    pub fn fields<S: Into<String>>(names: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            fields: Some(names.into_iter().map(Into::into).collect()),
+        }
+    }
+
+    fn covers(&self, name: &str) -> bool {
+        self.fields
+            .as_ref()
+            .is_none_or(|fields| fields.iter().any(|f| f == name))
+    }
+}
+
+/// Replace the fields `policy` selects in every key with order-preserving pseudonyms.
+///
+/// Returns the anonymized keys in input order. Fields the policy does not select, and the
+/// version byte of a versioned schema, are kept as they are.
+///
+/// ```rust
+/// use lexkey::anonymize::{anonymize, AnonymizePolicy};
+/// use lexkey::encode_composite;
+/// use lexkey::schema::{FieldType, KeySchema};
+///
+/// let schema = KeySchema::named([("tenant", FieldType::Str), ("seq", FieldType::U32)]);
+/// let keys = [
+///     encode_composite!("zeta-corp", 9u32),
+///     encode_composite!("acme", 4u32),
+///     encode_composite!("zeta-corp", 2u32),
+/// ];
+///
+/// let anon = anonymize(&schema, &AnonymizePolicy::fields(["tenant"]), &keys)?;
+/// assert_eq!(anon[0], encode_composite!("tenant-1", 9u32));
+/// assert_eq!(anon[1], encode_composite!("tenant-0", 4u32));
+/// assert!(anon[2] < anon[0] && anon[1] < anon[2]);
+/// # Ok::<(), lexkey::DecodeError>(())
+/// ```
+///
+/// # Errors
+///
+/// Returns the `DecodeError` of the first key that does not decode under `schema`.
+pub fn anonymize<K: AsRef<[u8]>>(
+    schema: &KeySchema,
+    policy: &AnonymizePolicy,
+    keys: &[K],
+) -> Result<Vec<LexKey>, DecodeError> {
+    let mut rows = keys
+        .iter()
+        .map(|key| schema.decode(key.as_ref()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for (i, &field) in schema.fields().iter().enumerate() {
+        let name = schema.label(i);
+        if !policy.covers(&name) {
+            continue;
+        }
+        // Ascending encodings sort like the values, so ranks follow value order.
+        let observed: BTreeSet<Vec<u8>> = rows.iter().map(|row| encoded(&row[i])).collect();
+        let count = observed.len();
+        let ranks: BTreeMap<Vec<u8>, usize> = observed.into_iter().zip(0..).collect();
+        for row in &mut rows {
+            let rank = ranks[&encoded(&row[i])];
+            row[i] = pseudonym(field, &name, rank, count);
+        }
+    }
+
+    Ok(rows.iter().map(|row| reencode(schema, row)).collect())
+}
+
+fn encoded(value: &Value) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(value.encoded_len());
+    value.encode_into(&mut buf);
+    buf
+}
+
+fn reencode(schema: &KeySchema, values: &[Value]) -> LexKey {
+    schema
+        .encode(values)
+        .expect("pseudonyms keep the schema's field types")
+}
+
+/// The value of rank `rank` among `count` distinct values of `field`.
+fn pseudonym(field: FieldType, name: &str, rank: usize, count: usize) -> Value {
+    const FITS: &str = "a field has no more distinct values than its type";
+    let r = u128::try_from(rank).expect(FITS);
+    let n = u128::try_from(count).expect(FITS);
+    // Signed ranks start at zero unless there are more values than non-negative ones.
+    let signed = |min: i128, max: i128| {
+        let r = i128::try_from(r).expect(FITS);
+        if i128::try_from(n).expect(FITS) <= max + 1 {
+            r
+        } else {
+            min + r
+        }
+    };
+    match field {
+        FieldType::Str => Value::Str(text(name, rank, count)),
+        FieldType::Bytes => Value::Bytes(text(name, rank, count).into_bytes()),
+        FieldType::U8 => Value::U8(u8::try_from(r).expect(FITS)),
+        FieldType::U16 => Value::U16(u16::try_from(r).expect(FITS)),
+        FieldType::U32 => Value::U32(u32::try_from(r).expect(FITS)),
+        FieldType::U64 => Value::U64(u64::try_from(r).expect(FITS)),
+        FieldType::I8 => {
+            Value::I8(i8::try_from(signed(i8::MIN.into(), i8::MAX.into())).expect(FITS))
+        }
+        FieldType::I16 => {
+            Value::I16(i16::try_from(signed(i16::MIN.into(), i16::MAX.into())).expect(FITS))
+        }
+        FieldType::I32 => {
+            Value::I32(i32::try_from(signed(i32::MIN.into(), i32::MAX.into())).expect(FITS))
+        }
+        FieldType::I64 => {
+            Value::I64(i64::try_from(signed(i64::MIN.into(), i64::MAX.into())).expect(FITS))
+        }
+        FieldType::F32 => Value::F32(f32::from_bits(u32::try_from(r).expect(FITS))),
+        FieldType::F64 => Value::F64(f64::from_bits(u64::try_from(r).expect(FITS))),
+        FieldType::Bool => Value::Bool(rank == 1),
+        FieldType::Uuid => Value::Uuid(Uuid::from_u128(r)),
+    }
+}
+
+/// `name-rank`, with the rank zero-padded so every pseudonym of the field has the same length.
+fn text(name: &str, rank: usize, count: usize) -> String {
+    let width = count.saturating_sub(1).to_string().len();
+    format!("{name}-{rank:0width$}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::desc::SortOrder;
+
+    fn schema() -> KeySchema {
+        KeySchema::named([
+            ("tenant", FieldType::Str),
+            ("shard", FieldType::I8),
+            ("ts", FieldType::I64),
+            ("user", FieldType::Uuid),
+        ])
+        .with_order(2, SortOrder::Desc)
+        .versioned(4)
+    }
+
+    fn key(tenant: &str, shard: i8, ts: i64, user: u128) -> LexKey {
+        schema()
+            .encode(&[
+                Value::Str(tenant.into()),
+                Value::I8(shard),
+                Value::I64(ts),
+                Value::Uuid(Uuid::from_u128(user)),
+            ])
+            .unwrap()
+    }
+
+    #[test]
+    fn should_preserve_order_and_equality_of_keys() {
+        let mut keys = Vec::new();
+        for (i, tenant) in ["globex", "acme", "initech", "acme", "umbrella"]
+            .into_iter()
+            .enumerate()
+        {
+            for shard in [-3i8, 40, -3] {
+                let i = i64::try_from(i).unwrap();
+                keys.push(key(
+                    tenant,
+                    shard,
+                    1_000 * i - 7,
+                    0xfeed + u128::from(shard.unsigned_abs()),
+                ));
+            }
+        }
+        let anon = anonymize(&schema(), &AnonymizePolicy::all(), &keys).unwrap();
+
+        for i in 0..keys.len() {
+            for j in 0..keys.len() {
+                assert_eq!(keys[i].cmp(&keys[j]), anon[i].cmp(&anon[j]), "{i} vs {j}");
+            }
+        }
+        assert_eq!(
+            anon,
+            anonymize(&schema(), &AnonymizePolicy::all(), &keys).unwrap()
+        );
+
+        let fields = schema().decode(anon[3].as_bytes()).unwrap();
+        assert_eq!(fields[0], Value::Str("tenant-0".into()));
+        assert_eq!(fields[1], Value::I8(0));
+        assert_eq!(anon[3].as_bytes()[0], 4);
+    }
+
+    #[test]
+    fn should_only_replace_selected_fields() {
+        let keys = [key("acme", 5, 10, 1), key("globex", -5, 20, 2)];
+        let anon = anonymize(&schema(), &AnonymizePolicy::fields(["user"]), &keys).unwrap();
+        assert_eq!(anon[0], key("acme", 5, 10, 0));
+        assert_eq!(anon[1], key("globex", -5, 20, 1));
+
+        let unnamed = KeySchema::new([FieldType::Bytes, FieldType::U16]);
+        let keys = [
+            crate::encode_composite!(b"secret", 9u16),
+            crate::encode_composite!(b"other", 9u16),
+        ];
+        let anon = anonymize(&unnamed, &AnonymizePolicy::fields(["0"]), &keys).unwrap();
+        assert_eq!(anon[0], crate::encode_composite!(b"0-1", 9u16));
+        assert_eq!(anon[1], crate::encode_composite!(b"0-0", 9u16));
+    }
+
+    #[test]
+    fn should_use_the_whole_domain_of_narrow_signed_fields() {
+        let schema = KeySchema::new([FieldType::I8]);
+        let keys: Vec<LexKey> = (i8::MIN..=i8::MAX).rev().map(LexKey::encode_i8).collect();
+        let anon = anonymize(&schema, &AnonymizePolicy::all(), &keys).unwrap();
+        assert_eq!(anon, keys);
+
+        let floats = KeySchema::new([FieldType::F64]);
+        let keys = [LexKey::encode_f64(2.5), LexKey::encode_f64(-1e9)];
+        let anon = anonymize(&floats, &AnonymizePolicy::all(), &keys).unwrap();
+        assert_eq!(anon[1], LexKey::encode_f64(0.0));
+        assert!(anon[1] < anon[0]);
+    }
+
+    #[test]
+    fn should_report_undecodable_keys() {
+        let keys = [key("acme", 1, 1, 1), LexKey::encode_string("junk")];
+        assert!(matches!(
+            anonymize(&schema(), &AnonymizePolicy::all(), &keys),
+            Err(DecodeError::Invalid {
+                offset: 0,
+                expected: "schema version"
+            })
+        ));
+        assert_eq!(text("t", 7, 8), "t-7");
+        assert_eq!(text("t", 7, 11), "t-07");
+        assert_eq!(text("t", 0, 1), "t-0");
+    }
+}
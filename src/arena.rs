@@ -0,0 +1,122 @@
+//! Bulk key construction from a few large shared allocations.
+//!
+//! A `KeyArena` copies keys into large chunks and hands each one out as a `LexKey` viewing its
+//! slice of a chunk, so materializing millions of keys (for example to build a sorted table file) costs
+//! one allocation per chunk instead of one per key. A chunk is freed once every key in it is
+//! dropped.
+
+use bytes::BytesMut;
+
+use crate::{Encodable, LexKey};
+
+/// Chunk size used by `KeyArena::new`.
+const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Allocates keys as slices of large shared chunks.
+///
+/// ```rust
+/// use lexkey::{encode_composite, KeyArena};
+///
+/// let mut arena = KeyArena::new();
+/// let mut keys: Vec<_> = (0..1000u32).rev().map(|n| arena.encode(&("row", n))).collect();
+/// keys.sort();
+/// assert_eq!(keys[7], encode_composite!("row", 7u32));
+/// assert_eq!(arena.chunks(), 1);
+/// ```
+#[derive(Debug)]
+pub struct KeyArena {
+    chunk: BytesMut,
+    chunk_size: usize,
+    chunks: usize,
+    scratch: Vec<u8>,
+}
+
+impl KeyArena {
+    /// Create an arena that allocates 64 KiB chunks.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_chunk_size(DEFAULT_CHUNK_SIZE)
+    }
+
+    /// Create an arena that allocates chunks of `chunk_size` bytes.
+    ///
+    /// A key longer than `chunk_size` gets a chunk of its own.
+    #[must_use]
+    pub fn with_chunk_size(chunk_size: usize) -> Self {
+        Self {
+            chunk: BytesMut::new(),
+            chunk_size,
+            chunks: 0,
+            scratch: Vec::new(),
+        }
+    }
+
+    /// Copy `bytes` into the arena and return them as a key.
+    pub fn alloc(&mut self, bytes: &[u8]) -> LexKey {
+        if self.chunk.capacity() < bytes.len() {
+            // Start a fresh chunk; keys already handed out keep the old one alive.
+            self.chunk = BytesMut::with_capacity(self.chunk_size.max(bytes.len()));
+            self.chunks += 1;
+        }
+        self.chunk.extend_from_slice(bytes);
+        LexKey::from_bytes(self.chunk.split().freeze())
+    }
+
+    /// Encode `value` into the arena and return it as a key.
+    ///
+    /// Tuples encode as composites, so `arena.encode(&(tenant, id))` matches
+    /// `encode_composite!(tenant, id)`.
+    pub fn encode<T: Encodable + ?Sized>(&mut self, value: &T) -> LexKey {
+        let mut scratch = std::mem::take(&mut self.scratch);
+        scratch.clear();
+        value.encode_into(&mut scratch);
+        let key = self.alloc(&scratch);
+        self.scratch = scratch;
+        key
+    }
+
+    /// Number of chunks allocated so far.
+    #[must_use]
+    pub fn chunks(&self) -> usize {
+        self.chunks
+    }
+}
+
+impl Default for KeyArena {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_share_chunks_until_full() {
+        let mut arena = KeyArena::with_chunk_size(8);
+        let a = arena.alloc(b"abc");
+        let b = arena.alloc(b"defgh");
+        assert_eq!(arena.chunks(), 1);
+        assert_eq!(b.as_bytes().as_ptr(), a.as_bytes().as_ptr().wrapping_add(3));
+
+        let c = arena.alloc(b"i");
+        assert_eq!(arena.chunks(), 2);
+        let big = arena.alloc(&[7; 20]);
+        assert_eq!(arena.chunks(), 3);
+        assert_eq!(big.as_bytes().len(), 20);
+        assert_eq!(
+            [a, b, c].map(|k| k.to_hex_string()),
+            ["616263", "6465666768", "69"]
+        );
+    }
+
+    #[test]
+    fn should_encode_like_allocating_constructors() {
+        let mut arena = KeyArena::default();
+        assert_eq!(arena.encode(&-1i64), LexKey::encode_i64(-1));
+        assert_eq!(arena.encode("x"), LexKey::encode_string("x"));
+        assert!(arena.alloc(b"").is_empty());
+        assert_eq!(arena.chunks(), 1);
+    }
+}
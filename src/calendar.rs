@@ -0,0 +1,335 @@
+//! Encoders for local-time components (time of day, weekday and month) and time buckets.
+//!
+//! The components key recurring schedules ("every Tuesday at 09:30") where absolute timestamps
+//! do not apply. Each encoding is fixed width and sorts in calendar order. `Bucket` truncates
+//! absolute timestamps to a fixed boundary for time-series partition keys.
+
+use bytes::BufMut;
+
+use crate::{Encodable, Encoder, LexKey};
+
+/// Nanoseconds in one day; time-of-day values must be below this.
+pub const NANOS_PER_DAY: u64 = 86_400_000_000_000;
+
+/// Day of the week in ISO 8601 order, Monday first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Weekday {
+    Monday = 1,
+    Tuesday = 2,
+    Wednesday = 3,
+    Thursday = 4,
+    Friday = 5,
+    Saturday = 6,
+    Sunday = 7,
+}
+
+impl Weekday {
+    /// ISO 8601 day number, Monday = 1 through Sunday = 7. This is the encoded byte.
+    #[inline]
+    #[must_use]
+    pub const fn number(self) -> u8 {
+        self as u8
+    }
+
+    /// Look up a weekday by its ISO 8601 number.
+    #[must_use]
+    pub const fn from_number(n: u8) -> Option<Self> {
+        Some(match n {
+            1 => Self::Monday,
+            2 => Self::Tuesday,
+            3 => Self::Wednesday,
+            4 => Self::Thursday,
+            5 => Self::Friday,
+            6 => Self::Saturday,
+            7 => Self::Sunday,
+            _ => return None,
+        })
+    }
+}
+
+/// Month of the year, January first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Month {
+    January = 1,
+    February = 2,
+    March = 3,
+    April = 4,
+    May = 5,
+    June = 6,
+    July = 7,
+    August = 8,
+    September = 9,
+    October = 10,
+    November = 11,
+    December = 12,
+}
+
+impl Month {
+    /// Month number, January = 1 through December = 12. This is the encoded byte.
+    #[inline]
+    #[must_use]
+    pub const fn number(self) -> u8 {
+        self as u8
+    }
+
+    /// Look up a month by its number.
+    #[must_use]
+    pub const fn from_number(n: u8) -> Option<Self> {
+        Some(match n {
+            1 => Self::January,
+            2 => Self::February,
+            3 => Self::March,
+            4 => Self::April,
+            5 => Self::May,
+            6 => Self::June,
+            7 => Self::July,
+            8 => Self::August,
+            9 => Self::September,
+            10 => Self::October,
+            11 => Self::November,
+            12 => Self::December,
+            _ => return None,
+        })
+    }
+}
+
+/// Fixed-width time bucket for partitioning time-series keys.
+///
+/// Boundaries are aligned to the Unix epoch, so `Day` buckets start at midnight UTC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Bucket {
+    Minute,
+    Hour,
+    /// A UTC calendar day.
+    Day,
+}
+
+impl Bucket {
+    /// Length of the bucket in nanoseconds.
+    #[inline]
+    #[must_use]
+    pub const fn nanos(self) -> i64 {
+        match self {
+            Self::Minute => 60_000_000_000,
+            Self::Hour => 3_600_000_000_000,
+            Self::Day => 86_400_000_000_000,
+        }
+    }
+
+    /// Start of the bucket containing `nanos`, rounding down (also before the epoch).
+    ///
+    /// The partial bucket at the very start of the `i64` range saturates to `i64::MIN`.
+    #[inline]
+    #[must_use]
+    pub const fn truncate(self, nanos: i64) -> i64 {
+        nanos.saturating_sub(nanos.rem_euclid(self.nanos()))
+    }
+}
+
+impl LexKey {
+    /// Encode a local time of day as nanoseconds since midnight (8 bytes, big-endian).
+    ///
+    /// ```rust
+    /// use lexkey::LexKey;
+    /// let nine_thirty = LexKey::encode_time_of_day((9 * 3600 + 30 * 60) * 1_000_000_000);
+    /// let noon = LexKey::encode_time_of_day(12 * 3600 * 1_000_000_000);
+    /// assert!(nine_thirty < noon);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `nanos_since_midnight` is not below `calendar::NANOS_PER_DAY`.
+    #[inline]
+    #[must_use]
+    pub fn encode_time_of_day(nanos_since_midnight: u64) -> Self {
+        let mut buf = Vec::with_capacity(8);
+        Self::encode_time_of_day_into(&mut buf, nanos_since_midnight);
+        Self::from_bytes(buf)
+    }
+
+    /// Append the 8-byte time-of-day encoding into `dst` and return 8.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `nanos_since_midnight` is not below `calendar::NANOS_PER_DAY`.
+    #[inline]
+    pub fn encode_time_of_day_into<B: BufMut + ?Sized>(
+        dst: &mut B,
+        nanos_since_midnight: u64,
+    ) -> usize {
+        assert!(
+            nanos_since_midnight < NANOS_PER_DAY,
+            "time of day must be below 24h"
+        );
+        Self::encode_u64_into(dst, nanos_since_midnight)
+    }
+
+    /// Encode the start of the bucket containing a UTC timestamp (UNIX nanoseconds).
+    ///
+    /// The bytes are `encode_time_unix_nanos(bucket.truncate(nanos))`, so every timestamp in one
+    /// bucket shares a key and buckets sort chronologically.
+    ///
+    /// ```rust
+    /// use lexkey::calendar::Bucket;
+    /// use lexkey::LexKey;
+    ///
+    /// let t = 1_700_000_123_456_789_000;
+    /// assert_eq!(
+    ///     LexKey::encode_time_bucket(t, Bucket::Hour),
+    ///     LexKey::encode_time_unix_nanos(1_699_999_200_000_000_000)
+    /// );
+    /// assert_eq!(
+    ///     LexKey::encode_time_bucket(-1, Bucket::Day),
+    ///     LexKey::encode_time_unix_nanos(-86_400_000_000_000)
+    /// );
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn encode_time_bucket(nanos: i64, bucket: Bucket) -> Self {
+        Self::encode_time_unix_nanos(bucket.truncate(nanos))
+    }
+
+    /// Append the 8-byte bucket-start encoding into `dst` and return 8.
+    #[inline]
+    pub fn encode_time_bucket_into<B: BufMut + ?Sized>(
+        dst: &mut B,
+        nanos: i64,
+        bucket: Bucket,
+    ) -> usize {
+        Self::encode_i64_into(dst, bucket.truncate(nanos))
+    }
+
+    /// Encode a weekday as its ISO number in one byte (Monday `01` .. Sunday `07`).
+    #[inline]
+    #[must_use]
+    pub fn encode_weekday(day: Weekday) -> Self {
+        Self::encode_u8(day.number())
+    }
+
+    /// Encode a month as its number in one byte (January `01` .. December `0c`).
+    #[inline]
+    #[must_use]
+    pub fn encode_month(month: Month) -> Self {
+        Self::encode_u8(month.number())
+    }
+}
+
+impl Encoder {
+    /// Append the 8-byte time-of-day encoding.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `nanos_since_midnight` is not below `calendar::NANOS_PER_DAY`.
+    #[inline]
+    pub fn encode_time_of_day_into(&mut self, nanos_since_midnight: u64) -> usize {
+        LexKey::encode_time_of_day_into(&mut self.buf, nanos_since_midnight)
+    }
+
+    /// Append the 8-byte bucket-start encoding; see `LexKey::encode_time_bucket`.
+    #[inline]
+    pub fn encode_time_bucket_into(&mut self, nanos: i64, bucket: Bucket) -> usize {
+        LexKey::encode_time_bucket_into(&mut self.buf, nanos, bucket)
+    }
+
+    /// Append the one-byte weekday encoding.
+    #[inline]
+    pub fn encode_weekday_into(&mut self, day: Weekday) -> usize {
+        self.encode_u8_into(day.number())
+    }
+
+    /// Append the one-byte month encoding.
+    #[inline]
+    pub fn encode_month_into(&mut self, month: Month) -> usize {
+        self.encode_u8_into(month.number())
+    }
+}
+
+impl Encodable for Weekday {
+    #[inline]
+    fn encoded_len(&self) -> usize {
+        1
+    }
+
+    #[inline]
+    fn encode_into(&self, dst: &mut Vec<u8>) -> usize {
+        LexKey::encode_u8_into(dst, self.number())
+    }
+}
+
+impl Encodable for Month {
+    #[inline]
+    fn encoded_len(&self) -> usize {
+        1
+    }
+
+    #[inline]
+    fn encode_into(&self, dst: &mut Vec<u8>) -> usize {
+        LexKey::encode_u8_into(dst, self.number())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_order_weekdays_and_months_in_calendar_order() {
+        let days: Vec<_> = (1..=7)
+            .map(|n| LexKey::encode_weekday(Weekday::from_number(n).unwrap()))
+            .collect();
+        let months: Vec<_> = (1..=12)
+            .map(|n| LexKey::encode_month(Month::from_number(n).unwrap()))
+            .collect();
+
+        assert!(days.windows(2).all(|w| w[0] < w[1]));
+        assert!(months.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(
+            LexKey::encode_weekday(Weekday::Sunday).to_hex_string(),
+            "07"
+        );
+        assert_eq!(LexKey::encode_month(Month::December).to_hex_string(), "0c");
+        assert_eq!(Weekday::from_number(0), None);
+        assert_eq!(Month::from_number(13), None);
+    }
+
+    #[test]
+    fn should_key_schedule_by_weekday_then_time() {
+        let tue_9 = crate::encode_composite!(Weekday::Tuesday, 9 * 3_600_000_000_000u64);
+        let mon_23 = crate::encode_composite!(Weekday::Monday, 23 * 3_600_000_000_000u64);
+        assert!(mon_23 < tue_9);
+    }
+
+    #[test]
+    fn should_encode_time_of_day_bounds() {
+        assert_eq!(
+            LexKey::encode_time_of_day(0).to_hex_string(),
+            "0000000000000000"
+        );
+        let last = LexKey::encode_time_of_day(NANOS_PER_DAY - 1);
+        assert!(LexKey::encode_time_of_day(1) < last);
+    }
+
+    #[test]
+    #[should_panic(expected = "time of day must be below 24h")]
+    fn should_reject_time_of_day_past_midnight() {
+        let _ = LexKey::encode_time_of_day(NANOS_PER_DAY);
+    }
+
+    #[test]
+    fn should_share_bucket_keys_within_a_bucket() {
+        let day = Bucket::Day.nanos();
+        for bucket in [Bucket::Minute, Bucket::Hour, Bucket::Day] {
+            let n = bucket.nanos();
+            for start in [-day, 0, 3 * day] {
+                let key = LexKey::encode_time_bucket(start, bucket);
+                assert_eq!(LexKey::encode_time_bucket(start + n - 1, bucket), key);
+                assert!(LexKey::encode_time_bucket(start - 1, bucket) < key);
+                assert!(key < LexKey::encode_time_bucket(start + n, bucket));
+            }
+        }
+
+        let mut enc = Encoder::with_capacity(8);
+        enc.encode_time_bucket_into(i64::MIN + 1, Bucket::Minute);
+        assert_eq!(enc.as_slice(), [0u8; 8]);
+    }
+}
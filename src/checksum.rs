@@ -0,0 +1,191 @@
+//! Checksum-suffixed keys for detecting corruption.
+//!
+//! `LexKey::with_checksum` appends a 4-byte CRC-32 (IEEE) of the key body, big-endian, and
+//! `LexKey::verify_checksum` checks it and returns the body. Use this when keys travel through
+//! layers that may truncate or mangle bytes (text logs, message queues, hand-edited fixtures).
+//!
+//! Ordering conventions: the suffix sits after the body, so two checksummed keys compare by body
+//! first whenever neither body is a prefix of the other. Prefix scans keep working when the scan
+//! prefix is a body prefix, e.g. `KeyRange::prefix(b"tenant\x00")`. Bounds built from full bodies
+//! do not match checksummed keys exactly; either checksum the bounds too or compare bodies after
+//! `verify_checksum`. Keys whose last part is variable-length and may be a prefix of another key
+//! (`"ab"` vs `"abc"`) can interleave with the checksum bytes, so terminate such parts first.
+
+use std::fmt;
+
+use crate::{Encoder, LexKey};
+
+/// Width in bytes of the checksum suffix.
+pub const CHECKSUM_LEN: usize = 4;
+
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i: u32 = 0;
+    while i < 256 {
+        let mut crc = i;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i as usize] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// CRC-32 (IEEE 802.3, as used by zlib and gzip) of `bytes`.
+#[must_use]
+pub fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |crc, &b| {
+        CRC32_TABLE[((crc ^ u32::from(b)) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+/// Error returned when a checksummed key fails verification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChecksumError {
+    /// The key is shorter than the checksum suffix.
+    TooShort { len: usize },
+    /// The stored checksum does not match the body.
+    Mismatch { stored: u32, computed: u32 },
+}
+
+impl fmt::Display for ChecksumError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooShort { len } => {
+                write!(f, "key of {len} bytes is too short for a checksum suffix")
+            }
+            Self::Mismatch { stored, computed } => write!(
+                f,
+                "key checksum mismatch: stored {stored:08x}, computed {computed:08x}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ChecksumError {}
+
+impl LexKey {
+    /// Return this key followed by the big-endian CRC-32 of its bytes.
+    ///
+    /// ```rust
+    /// use lexkey::LexKey;
+    ///
+    /// let key = LexKey::encode_composite(&[b"tenant", b"42"]).with_checksum();
+    /// assert_eq!(key.verify_checksum(), Ok(&b"tenant\x0042"[..]));
+    ///
+    /// let mut bytes = key.as_bytes().to_vec();
+    /// bytes[0] ^= 0x20;
+    /// assert!(LexKey::from_bytes(bytes).verify_checksum().is_err());
+    /// ```
+    #[must_use]
+    pub fn with_checksum(&self) -> Self {
+        let body = self.as_bytes();
+        let mut buf = Vec::with_capacity(body.len() + CHECKSUM_LEN);
+        buf.extend_from_slice(body);
+        buf.extend_from_slice(&crc32(body).to_be_bytes());
+        Self::from_bytes(buf)
+    }
+
+    /// Check the trailing CRC-32 and return the key body without it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ChecksumError::TooShort` when the key has fewer than `CHECKSUM_LEN` bytes and
+    /// `ChecksumError::Mismatch` when the suffix does not match the body.
+    pub fn verify_checksum(&self) -> Result<&[u8], ChecksumError> {
+        let bytes = self.as_bytes();
+        let Some(split) = bytes.len().checked_sub(CHECKSUM_LEN) else {
+            return Err(ChecksumError::TooShort { len: bytes.len() });
+        };
+        let (body, suffix) = bytes.split_at(split);
+        let mut stored = [0u8; CHECKSUM_LEN];
+        stored.copy_from_slice(suffix);
+        let stored = u32::from_be_bytes(stored);
+        let computed = crc32(body);
+        if stored == computed {
+            Ok(body)
+        } else {
+            Err(ChecksumError::Mismatch { stored, computed })
+        }
+    }
+}
+
+impl Encoder {
+    /// Append the CRC-32 of the current key and return `CHECKSUM_LEN`.
+    ///
+    /// The current key is everything written since the last `finish_key` (or since `clear`), so
+    /// this composes with batched keys: write a key, `push_checksum`, then `finish_key`.
+    pub fn push_checksum(&mut self) -> usize {
+        let crc = crc32(&self.buf[self.current_key_start()..]);
+        self.encode_bytes_into(&crc.to_be_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_match_reference_crc32_values() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(
+            crc32(b"The quick brown fox jumps over the lazy dog"),
+            0x414F_A339
+        );
+    }
+
+    #[test]
+    fn should_detect_corruption_and_truncation() {
+        let key = LexKey::encode_composite(&[b"orders", b"2024"]).with_checksum();
+        let bytes = key.as_bytes();
+
+        let mut flipped = bytes.to_vec();
+        flipped[3] ^= 0x01;
+        assert!(matches!(
+            LexKey::from_bytes(flipped).verify_checksum(),
+            Err(ChecksumError::Mismatch { .. })
+        ));
+        let truncated = LexKey::from_bytes(bytes[1..].to_vec());
+        assert!(truncated.verify_checksum().is_err());
+        assert_eq!(
+            LexKey::from_bytes(vec![1, 2]).verify_checksum(),
+            Err(ChecksumError::TooShort { len: 2 })
+        );
+    }
+
+    #[test]
+    fn should_checksum_each_batched_key_separately() {
+        let mut enc = Encoder::with_capacity(32);
+        enc.encode_string_into("a");
+        enc.push_checksum();
+        enc.finish_key();
+        enc.encode_string_into("b");
+        enc.push_checksum();
+
+        let keys = enc.freeze_all();
+
+        assert_eq!(
+            keys[0],
+            LexKey::encode_string("a").with_checksum().as_bytes()
+        );
+        assert_eq!(
+            keys[1],
+            LexKey::encode_string("b").with_checksum().as_bytes()
+        );
+    }
+
+    #[test]
+    fn should_keep_body_order_for_terminated_parts() {
+        let a = LexKey::encode_composite(&[b"user", b"alice"]);
+        let b = LexKey::encode_composite(&[b"user", b"bob"]);
+        assert!(a.with_checksum() < b.with_checksum());
+    }
+}
@@ -0,0 +1,393 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{KeyRange, LexKey};
+
+/// An ordered in-memory set of keys with the crate's range semantics built in.
+///
+/// Keys iterate in raw byte order. `iter_prefix` scans the child range of a composite prefix
+/// (`prefix || 0x00` up to `prefix || 0x01`, see `KeyRange::children`), so it yields keys that
+/// continue the prefix's parts and not keys whose last prefix part merely starts the same way.
+///
+/// ```rust
+/// use lexkey::{KeySet, LexKey};
+///
+/// let mut set = KeySet::new();
+/// set.insert(LexKey::encode_composite(&[b"acme", b"alice"]));
+/// set.insert(LexKey::encode_composite(&[b"acme", b"bob"]));
+/// set.insert(LexKey::encode_composite(&[b"globex", b"carol"]));
+///
+/// assert_eq!(set.iter_prefix(&[b"acme"]).count(), 2);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct KeySet {
+    keys: BTreeSet<LexKey>,
+}
+
+impl KeySet {
+    /// Create an empty set.
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of keys in the set.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Check if the set is empty.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Insert a key, returning true if it was not already present.
+    #[inline]
+    pub fn insert(&mut self, key: LexKey) -> bool {
+        self.keys.insert(key)
+    }
+
+    /// Remove a key, returning true if it was present.
+    #[inline]
+    pub fn remove(&mut self, key: &LexKey) -> bool {
+        self.keys.remove(key)
+    }
+
+    /// Check if the set contains `key`.
+    #[inline]
+    #[must_use]
+    pub fn contains(&self, key: &LexKey) -> bool {
+        self.keys.contains(key)
+    }
+
+    /// Iterate all keys in ascending byte order.
+    #[inline]
+    #[must_use]
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &LexKey> + '_ {
+        self.keys.iter()
+    }
+
+    /// Iterate keys that extend the composite `parts` with at least one more part.
+    #[must_use]
+    pub fn iter_prefix(&self, parts: &[&[u8]]) -> impl DoubleEndedIterator<Item = &LexKey> + '_ {
+        self.iter_range(&KeyRange::children(
+            LexKey::encode_composite(parts).as_bytes(),
+        ))
+    }
+
+    /// Iterate keys inside `range` in ascending byte order.
+    ///
+    /// Empty or inverted ranges yield nothing.
+    #[must_use]
+    pub fn iter_range(&self, range: &KeyRange) -> impl DoubleEndedIterator<Item = &LexKey> + '_ {
+        let hits = if range.is_empty() {
+            None
+        } else {
+            Some(self.keys.range(range.clone()))
+        };
+        hits.into_iter().flatten()
+    }
+}
+
+impl FromIterator<LexKey> for KeySet {
+    fn from_iter<I: IntoIterator<Item = LexKey>>(iter: I) -> Self {
+        Self {
+            keys: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl Extend<LexKey> for KeySet {
+    fn extend<I: IntoIterator<Item = LexKey>>(&mut self, iter: I) {
+        self.keys.extend(iter);
+    }
+}
+
+impl<'a> IntoIterator for &'a KeySet {
+    type Item = &'a LexKey;
+    type IntoIter = std::collections::btree_set::Iter<'a, LexKey>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.keys.iter()
+    }
+}
+
+impl IntoIterator for KeySet {
+    type Item = LexKey;
+    type IntoIter = std::collections::btree_set::IntoIter<LexKey>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.keys.into_iter()
+    }
+}
+
+/// An ordered map keyed by `LexKey`, a thin wrapper over `BTreeMap<LexKey, V>`.
+///
+/// Intended for prototypes and caches that want lexkey range semantics without a storage engine.
+/// Prefix methods use the same child range as `KeySet::iter_prefix`.
+///
+/// ```rust
+/// use lexkey::{LexKey, LexMap};
+///
+/// let mut map = LexMap::new();
+/// map.insert(LexKey::encode_composite(&[b"acme", b"1"]), "first");
+/// map.insert(LexKey::encode_composite(&[b"acme", b"2"]), "second");
+///
+/// assert_eq!(map.first_in_prefix(&[b"acme"]).map(|(_, v)| *v), Some("first"));
+/// assert_eq!(map.last_in_prefix(&[b"acme"]).map(|(_, v)| *v), Some("second"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LexMap<V> {
+    entries: BTreeMap<LexKey, V>,
+}
+
+impl<V> Default for LexMap<V> {
+    fn default() -> Self {
+        Self {
+            entries: BTreeMap::new(),
+        }
+    }
+}
+
+impl<V> LexMap<V> {
+    /// Create an empty map.
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of entries in the map.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if the map is empty.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Insert a value, returning the previous value for `key` if any.
+    #[inline]
+    pub fn insert(&mut self, key: LexKey, value: V) -> Option<V> {
+        self.entries.insert(key, value)
+    }
+
+    /// Look up the value for `key`.
+    #[inline]
+    #[must_use]
+    pub fn get(&self, key: &LexKey) -> Option<&V> {
+        self.entries.get(key)
+    }
+
+    /// Look up the value for `key` mutably.
+    #[inline]
+    pub fn get_mut(&mut self, key: &LexKey) -> Option<&mut V> {
+        self.entries.get_mut(key)
+    }
+
+    /// Remove and return the value for `key`.
+    #[inline]
+    pub fn remove(&mut self, key: &LexKey) -> Option<V> {
+        self.entries.remove(key)
+    }
+
+    /// Check if the map contains `key`.
+    #[inline]
+    #[must_use]
+    pub fn contains_key(&self, key: &LexKey) -> bool {
+        self.entries.contains_key(key)
+    }
+
+    /// Iterate all entries in ascending key order.
+    #[inline]
+    #[must_use]
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&LexKey, &V)> + '_ {
+        self.entries.iter()
+    }
+
+    /// Iterate entries whose keys extend the composite `parts` with at least one more part.
+    #[must_use]
+    pub fn scan_prefix(
+        &self,
+        parts: &[&[u8]],
+    ) -> impl DoubleEndedIterator<Item = (&LexKey, &V)> + '_ {
+        self.scan_range(&KeyRange::children(
+            LexKey::encode_composite(parts).as_bytes(),
+        ))
+    }
+
+    /// Iterate entries inside `range` in ascending key order.
+    ///
+    /// Empty or inverted ranges yield nothing.
+    #[must_use]
+    pub fn scan_range(
+        &self,
+        range: &KeyRange,
+    ) -> impl DoubleEndedIterator<Item = (&LexKey, &V)> + '_ {
+        let hits = if range.is_empty() {
+            None
+        } else {
+            Some(self.entries.range(range.clone()))
+        };
+        hits.into_iter().flatten()
+    }
+
+    /// Return the smallest entry whose key extends `parts`.
+    #[must_use]
+    pub fn first_in_prefix(&self, parts: &[&[u8]]) -> Option<(&LexKey, &V)> {
+        self.scan_prefix(parts).next()
+    }
+
+    /// Return the largest entry whose key extends `parts`.
+    #[must_use]
+    pub fn last_in_prefix(&self, parts: &[&[u8]]) -> Option<(&LexKey, &V)> {
+        self.scan_prefix(parts).next_back()
+    }
+}
+
+impl<V> FromIterator<(LexKey, V)> for LexMap<V> {
+    fn from_iter<I: IntoIterator<Item = (LexKey, V)>>(iter: I) -> Self {
+        Self {
+            entries: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<V> Extend<(LexKey, V)> for LexMap<V> {
+    fn extend<I: IntoIterator<Item = (LexKey, V)>>(&mut self, iter: I) {
+        self.entries.extend(iter);
+    }
+}
+
+impl<V> IntoIterator for LexMap<V> {
+    type Item = (LexKey, V);
+    type IntoIter = std::collections::btree_map::IntoIter<LexKey, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn composite(parts: &[&[u8]]) -> LexKey {
+        LexKey::encode_composite(parts)
+    }
+
+    #[test]
+    fn should_insert_and_contain_keys() {
+        let mut set = KeySet::new();
+        let k = composite(&[b"acme", b"alice"]);
+
+        assert!(set.insert(k.clone()));
+        assert!(!set.insert(k.clone()));
+        assert!(set.contains(&k));
+        assert_eq!(set.len(), 1);
+        assert!(set.remove(&k));
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn should_iterate_prefix_in_order() {
+        let set: KeySet = [
+            composite(&[b"acme", b"carol"]),
+            composite(&[b"globex", b"dave"]),
+            composite(&[b"acme", b"alice"]),
+            composite(&[b"acme"]),
+            composite(&[b"acme2", b"erin"]),
+            LexKey::from(&b"acme\xff"[..]),
+        ]
+        .into_iter()
+        .collect();
+
+        let hits: Vec<_> = set.iter_prefix(&[b"acme"]).cloned().collect();
+
+        assert_eq!(
+            hits,
+            vec![
+                composite(&[b"acme", b"alice"]),
+                composite(&[b"acme", b"carol"])
+            ]
+        );
+        assert_eq!(set.iter_prefix(&[b"initech"]).count(), 0);
+    }
+
+    #[test]
+    fn should_iterate_range_and_skip_inverted_ranges() {
+        let set: KeySet = [&b"a"[..], b"b", b"c", b"d"]
+            .into_iter()
+            .map(LexKey::from)
+            .collect();
+        let b = LexKey::from(&b"b"[..]);
+        let d = LexKey::from(&b"d"[..]);
+
+        let hits: Vec<_> = set
+            .iter_range(&KeyRange::half_open(b.clone(), d.clone()))
+            .map(LexKey::as_bytes)
+            .collect();
+
+        assert_eq!(hits, vec![&b"b"[..], b"c"]);
+        assert_eq!(set.iter_range(&KeyRange::half_open(d, b)).count(), 0);
+    }
+
+    #[test]
+    fn should_insert_get_and_remove_map_entries() {
+        let mut map = LexMap::new();
+        let k = composite(&[b"acme", b"alice"]);
+
+        assert_eq!(map.insert(k.clone(), 1), None);
+        assert_eq!(map.insert(k.clone(), 2), Some(1));
+        assert_eq!(map.get(&k), Some(&2));
+        *map.get_mut(&k).unwrap() += 1;
+        assert!(map.contains_key(&k));
+        assert_eq!(map.remove(&k), Some(3));
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn should_scan_prefix_and_pick_first_and_last() {
+        let map: LexMap<u32> = [
+            (composite(&[b"acme", b"b"]), 2),
+            (composite(&[b"acme", b"a"]), 1),
+            (composite(&[b"acme", b"c"]), 3),
+            (composite(&[b"globex", b"a"]), 9),
+            (composite(&[b"acme2", b"a"]), 7),
+            (composite(&[b"acme-eu", b"a"]), 8),
+        ]
+        .into_iter()
+        .collect();
+
+        let values: Vec<u32> = map.scan_prefix(&[b"acme"]).map(|(_, v)| *v).collect();
+
+        assert_eq!(values, vec![1, 2, 3]);
+        assert_eq!(map.first_in_prefix(&[b"acme"]).map(|(_, v)| *v), Some(1));
+        assert_eq!(map.last_in_prefix(&[b"acme"]).map(|(_, v)| *v), Some(3));
+        assert_eq!(map.first_in_prefix(&[b"initech"]), None);
+    }
+
+    #[test]
+    fn should_scan_map_range() {
+        let map: LexMap<u32> = [(&b"a"[..], 1), (b"b", 2), (b"c", 3)]
+            .into_iter()
+            .map(|(k, v)| (LexKey::from(k), v))
+            .collect();
+
+        let values: Vec<u32> = map
+            .scan_range(&KeyRange::prefix(b"b"))
+            .map(|(_, v)| *v)
+            .collect();
+
+        assert_eq!(values, vec![2]);
+    }
+}
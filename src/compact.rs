@@ -0,0 +1,133 @@
+//! Keys stored relative to a base key as `(shared prefix length, suffix)`.
+//!
+//! In-process caches often hold thousands of keys that share a long tenant or table prefix.
+//! `LexKey::compact_against` keeps only the bytes after the prefix shared with a base key, and
+//! `CompactKey::restore` rebuilds the full key from the same base.
+
+use bytes::Bytes;
+
+use crate::LexKey;
+
+/// A key stored as the length of its common prefix with a base key plus the remaining bytes.
+///
+/// A `CompactKey` carries no reference to its base; restoring against a different base yields a
+/// different key. The suffix is copied into its own allocation, so a cached `CompactKey` does not
+/// keep the original key's buffer alive.
+///
+/// ```rust
+/// use lexkey::LexKey;
+///
+/// let base = LexKey::encode_composite(&[b"tenant-0042", b"orders"]);
+/// let key = LexKey::encode_composite(&[b"tenant-0042", b"orders", b"2024-06-01"]);
+///
+/// let compact = key.compact_against(&base);
+/// assert_eq!(compact.shared_len(), base.as_bytes().len());
+/// assert_eq!(compact.suffix(), b"\x002024-06-01");
+/// assert_eq!(compact.restore(&base), key);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CompactKey {
+    shared: usize,
+    suffix: Bytes,
+}
+
+impl CompactKey {
+    /// Number of leading bytes taken from the base key.
+    #[inline]
+    #[must_use]
+    pub fn shared_len(&self) -> usize {
+        self.shared
+    }
+
+    /// Bytes that follow the shared prefix.
+    #[inline]
+    #[must_use]
+    pub fn suffix(&self) -> &[u8] {
+        &self.suffix
+    }
+
+    /// Rebuild the full key from the base it was compacted against.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `base` is shorter than `shared_len()`, which means it is not the base the key
+    /// was compacted against.
+    #[must_use]
+    pub fn restore(&self, base: &LexKey) -> LexKey {
+        let prefix = base
+            .as_bytes()
+            .get(..self.shared)
+            .expect("base key is shorter than the compacted shared prefix");
+        if self.suffix.is_empty() && self.shared == base.as_bytes().len() {
+            return base.clone();
+        }
+        let mut buf = Vec::with_capacity(self.shared + self.suffix.len());
+        buf.extend_from_slice(prefix);
+        buf.extend_from_slice(&self.suffix);
+        LexKey::from_bytes(buf)
+    }
+}
+
+impl LexKey {
+    /// Store this key as its common prefix length with `base` plus the remaining suffix.
+    #[must_use]
+    pub fn compact_against(&self, base: &Self) -> CompactKey {
+        let shared = self.common_prefix_len(base);
+        CompactKey {
+            shared,
+            suffix: Bytes::copy_from_slice(&self.bytes[shared..]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_round_trip_against_any_base() {
+        let base = LexKey::encode_composite(&[b"acme", b"users", b"alice"]);
+        for key in [
+            LexKey::empty(),
+            LexKey::encode_string("acme"),
+            LexKey::encode_composite(&[b"acme", b"users", b"bob"]),
+            LexKey::encode_composite(&[b"acme", b"users", b"alice", b"profile"]),
+            LexKey::encode_string("zeta"),
+            base.clone(),
+        ] {
+            let compact = key.compact_against(&base);
+            assert_eq!(
+                compact.shared_len() + compact.suffix().len(),
+                key.as_bytes().len()
+            );
+            assert_eq!(compact.restore(&base), key);
+        }
+    }
+
+    #[test]
+    fn should_share_nothing_with_unrelated_base() {
+        let key = LexKey::encode_string("beta");
+        let compact = key.compact_against(&LexKey::encode_string("alpha"));
+        assert_eq!(compact.shared_len(), 0);
+        assert_eq!(compact.suffix(), b"beta");
+    }
+
+    #[test]
+    fn should_not_borrow_the_original_allocation() {
+        let base = LexKey::encode_composite(&[b"acme", b"users"]);
+        let key = LexKey::encode_composite(&[b"acme", b"users", b"alice"]);
+        let compact = key.compact_against(&base);
+        let range = key.as_bytes().as_ptr_range();
+        assert!(!range.contains(&compact.suffix().as_ptr()));
+        drop(key);
+        assert_eq!(compact.suffix(), b"\x00alice");
+    }
+
+    #[test]
+    #[should_panic(expected = "shorter than the compacted shared prefix")]
+    fn should_reject_restoring_against_short_base() {
+        let compact =
+            LexKey::encode_string("abcdef").compact_against(&LexKey::encode_string("abcd"));
+        let _ = compact.restore(&LexKey::encode_string("ab"));
+    }
+}
@@ -0,0 +1,177 @@
+use crate::{Encoder, LexKey};
+
+/// Length in bytes of every supported content digest.
+pub const DIGEST_LEN: usize = 32;
+
+/// Digest algorithm used to derive content-addressed keys.
+///
+/// Each variant is only available when the matching cargo feature is enabled. The algorithm is
+/// always chosen explicitly so that enabling another feature elsewhere in a dependency graph can
+/// never change the bytes of existing keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ContentHash {
+    /// BLAKE3 with the default 32-byte output (`blake3` feature).
+    #[cfg(feature = "blake3")]
+    Blake3,
+    /// SHA-256 (`sha2` feature).
+    #[cfg(feature = "sha2")]
+    Sha256,
+}
+
+impl ContentHash {
+    /// Compute the 32-byte digest of `content`.
+    #[inline]
+    #[must_use]
+    pub fn digest(self, content: &[u8]) -> [u8; DIGEST_LEN] {
+        match self {
+            #[cfg(feature = "blake3")]
+            Self::Blake3 => *blake3::hash(content).as_bytes(),
+            #[cfg(feature = "sha2")]
+            Self::Sha256 => {
+                use sha2::Digest;
+                sha2::Sha256::digest(content).into()
+            }
+        }
+    }
+}
+
+impl LexKey {
+    /// Encode the digest of `content` as a 32-byte key.
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "blake3")] {
+    /// use lexkey::{ContentHash, LexKey};
+    /// let k = LexKey::encode_content_hash(ContentHash::Blake3, b"blob");
+    /// assert_eq!(k.as_bytes().len(), 32);
+    /// # }
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn encode_content_hash(algorithm: ContentHash, content: &[u8]) -> Self {
+        Self::from_bytes(algorithm.digest(content).to_vec())
+    }
+}
+
+impl Encoder {
+    /// Append the 32-byte digest of `content` and return 32.
+    #[inline]
+    pub fn encode_content_hash_into(&mut self, algorithm: ContentHash, content: &[u8]) -> usize {
+        self.encode_bytes_into(&algorithm.digest(content))
+    }
+}
+
+/// Builder for `(namespace, hash)` keys used by content-addressed blob indexes.
+///
+/// Keys are `namespace || 0x00 || digest`, so all blobs of one namespace share the structured
+/// partition range returned by `range_bounds`.
+///
+/// ```rust
+/// # #[cfg(feature = "sha2")] {
+/// use lexkey::{ContentHash, ContentKey};
+/// let blobs = ContentKey::new(b"blobs", ContentHash::Sha256);
+/// let key = blobs.key(b"hello");
+/// let (lower, upper) = blobs.range_bounds();
+/// assert!(lower < key && key < upper);
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ContentKey<'a> {
+    namespace: &'a [u8],
+    algorithm: ContentHash,
+}
+
+impl<'a> ContentKey<'a> {
+    /// Create a builder for `namespace` hashing content with `algorithm`.
+    #[inline]
+    #[must_use]
+    pub fn new(namespace: &'a [u8], algorithm: ContentHash) -> Self {
+        Self {
+            namespace,
+            algorithm,
+        }
+    }
+
+    /// The namespace prefix of every key built by this builder.
+    #[inline]
+    #[must_use]
+    pub fn namespace(&self) -> &'a [u8] {
+        self.namespace
+    }
+
+    /// The digest algorithm of this builder.
+    #[inline]
+    #[must_use]
+    pub fn algorithm(&self) -> ContentHash {
+        self.algorithm
+    }
+
+    /// Build the key for `content` by hashing it.
+    #[inline]
+    #[must_use]
+    pub fn key(&self, content: &[u8]) -> LexKey {
+        self.key_for_digest(&self.algorithm.digest(content))
+    }
+
+    /// Build the key for an already computed digest.
+    #[inline]
+    #[must_use]
+    pub fn key_for_digest(&self, digest: &[u8; DIGEST_LEN]) -> LexKey {
+        LexKey::encode_composite(&[self.namespace, digest])
+    }
+
+    /// Return `(namespace || 0x00, namespace || 0xff)` covering every key of the namespace.
+    #[inline]
+    #[must_use]
+    pub fn range_bounds(&self) -> (LexKey, LexKey) {
+        LexKey::encode_range_bounds(self.namespace)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "blake3")]
+    #[test]
+    fn should_encode_blake3_digest_of_content() {
+        let k = LexKey::encode_content_hash(ContentHash::Blake3, b"abc");
+        assert_eq!(
+            k.to_hex_string(),
+            "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"
+        );
+    }
+
+    #[cfg(feature = "sha2")]
+    #[test]
+    fn should_encode_sha256_digest_of_content() {
+        let k = LexKey::encode_content_hash(ContentHash::Sha256, b"abc");
+        assert_eq!(
+            k.to_hex_string(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[cfg(feature = "sha2")]
+    #[test]
+    fn should_build_namespaced_content_key_within_range_bounds() {
+        let blobs = ContentKey::new(b"blobs", ContentHash::Sha256);
+        let digest = ContentHash::Sha256.digest(b"abc");
+
+        let key = blobs.key(b"abc");
+        let (lower, upper) = blobs.range_bounds();
+
+        assert_eq!(key, blobs.key_for_digest(&digest));
+        assert_eq!(&key.as_bytes()[..6], b"blobs\x00");
+        assert_eq!(&key.as_bytes()[6..], &digest[..]);
+        assert!(lower < key && key < upper);
+
+        let mut enc = Encoder::with_capacity(64);
+        enc.encode_bytes_into(b"blobs");
+        enc.push_separator();
+        assert_eq!(
+            enc.encode_content_hash_into(ContentHash::Sha256, b"abc"),
+            32
+        );
+        assert_eq!(enc.as_slice(), key.as_bytes());
+    }
+}
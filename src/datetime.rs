@@ -0,0 +1,489 @@
+//! Encoders for `time` and `jiff` date and time values.
+//!
+//! Instants (`time::OffsetDateTime`, `jiff::Timestamp`, `jiff::Zoned`) are normalized to UTC
+//! and written as Unix nanoseconds in the signed 8-byte form of `encode_time_unix_nanos`, so
+//! the offset or time zone never affects the key and every value for the same instant encodes
+//! identically, whichever crate produced it. That form covers 1677-09-21 to 2262-04-11; instants
+//! outside it panic in the `Encodable` impls and return `EncodeError::OutOfRange` through the
+//! `try_` APIs. `time::Date` is written as days since 1970-01-01 in the signed 4-byte form of
+//! `encode_i32`.
+
+#[cfg(feature = "jiff")]
+use jiff::{Timestamp, Zoned};
+#[cfg(feature = "time")]
+use time::{Date, OffsetDateTime};
+
+use crate::{DecodeError, Decoder, Encodable, EncodeError, Encoder, LexKey, TryEncodable};
+
+/// Julian day number of 1970-01-01.
+#[cfg(feature = "time")]
+const UNIX_EPOCH_JULIAN_DAY: i32 = 2_440_588;
+
+/// Narrow Unix nanoseconds to `i64`, or `OutOfRange` if they do not fit.
+fn fit_nanos(nanos: i128) -> Result<i64, EncodeError> {
+    i64::try_from(nanos).map_err(|_| EncodeError::OutOfRange)
+}
+
+/// Unix nanoseconds of `dt`, or `OutOfRange` if they do not fit in an `i64`.
+#[cfg(feature = "time")]
+fn unix_nanos(dt: &OffsetDateTime) -> Result<i64, EncodeError> {
+    fit_nanos(dt.unix_timestamp_nanos())
+}
+
+/// Days between 1970-01-01 and `date`, negative before the epoch.
+#[cfg(feature = "time")]
+fn epoch_days(date: Date) -> i32 {
+    date.to_julian_day() - UNIX_EPOCH_JULIAN_DAY
+}
+
+#[cfg(feature = "time")]
+impl LexKey {
+    /// Encode an `OffsetDateTime` as UTC Unix nanoseconds, the `encode_time_unix_nanos` bytes.
+    ///
+    /// ```rust
+    /// use lexkey::LexKey;
+    /// use time::{Date, Month, UtcOffset};
+    ///
+    /// let utc = Date::from_calendar_date(2024, Month::March, 1)
+    ///     .unwrap()
+    ///     .with_hms(12, 0, 0)
+    ///     .unwrap()
+    ///     .assume_utc();
+    /// let local = utc.to_offset(UtcOffset::from_hms(-5, 0, 0).unwrap());
+    /// assert_eq!(LexKey::encode_offset_datetime(&utc), LexKey::encode_offset_datetime(&local));
+    /// assert_eq!(
+    ///     LexKey::encode_offset_datetime(&utc),
+    ///     LexKey::encode_time_unix_nanos(1_709_294_400_000_000_000)
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `dt` is before 1677-09-21 or after 2262-04-11 (UTC).
+    #[inline]
+    #[must_use]
+    pub fn encode_offset_datetime(dt: &OffsetDateTime) -> Self {
+        Self::try_encode_offset_datetime(dt).expect("timestamp outside the i64 nanosecond range")
+    }
+
+    /// Encode an `OffsetDateTime` like `encode_offset_datetime`, without panicking.
+    ///
+    /// # Errors
+    ///
+    /// Returns `EncodeError::OutOfRange` if the UTC Unix nanoseconds do not fit in an `i64`.
+    #[inline]
+    pub fn try_encode_offset_datetime(dt: &OffsetDateTime) -> Result<Self, EncodeError> {
+        unix_nanos(dt).map(Self::encode_time_unix_nanos)
+    }
+
+    /// Encode an `OffsetDateTime` so the most recent instant sorts first; see `encode_time_desc`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `dt` is before 1677-09-21 or after 2262-04-11 (UTC).
+    #[inline]
+    #[must_use]
+    pub fn encode_offset_datetime_desc(dt: &OffsetDateTime) -> Self {
+        Self::encode_time_desc(unix_nanos(dt).expect("timestamp outside the i64 nanosecond range"))
+    }
+
+    /// Encode a `Date` as days since 1970-01-01 in the sortable 4-byte `i32` form.
+    ///
+    /// ```rust
+    /// use lexkey::LexKey;
+    /// use time::{Date, Month};
+    ///
+    /// let epoch = Date::from_calendar_date(1970, Month::January, 1).unwrap();
+    /// assert_eq!(LexKey::encode_date(epoch), LexKey::encode_i32(0));
+    /// assert!(LexKey::encode_date(epoch.previous_day().unwrap()) < LexKey::encode_date(epoch));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn encode_date(date: Date) -> Self {
+        Self::encode_i32(epoch_days(date))
+    }
+}
+
+#[cfg(feature = "time")]
+impl Encoder {
+    /// Append an `OffsetDateTime` as 8 bytes of UTC Unix nanoseconds.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `dt` is before 1677-09-21 or after 2262-04-11 (UTC).
+    #[inline]
+    pub fn encode_offset_datetime_into(&mut self, dt: &OffsetDateTime) -> usize {
+        dt.encode_into(&mut self.buf)
+    }
+
+    /// Append an `OffsetDateTime` so the most recent instant sorts first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `dt` is before 1677-09-21 or after 2262-04-11 (UTC).
+    #[inline]
+    pub fn encode_offset_datetime_desc_into(&mut self, dt: &OffsetDateTime) -> usize {
+        let nanos = unix_nanos(dt).expect("timestamp outside the i64 nanosecond range");
+        self.encode_time_desc_into(nanos)
+    }
+
+    /// Append a `Date` as 4 bytes of days since 1970-01-01.
+    #[inline]
+    pub fn encode_date_into(&mut self, date: Date) -> usize {
+        date.encode_into(&mut self.buf)
+    }
+}
+
+#[cfg(feature = "time")]
+impl Decoder<'_> {
+    /// Decode an `OffsetDateTime` written by `encode_offset_datetime`; the result is in UTC.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DecodeError::UnexpectedEnd` if fewer than 8 bytes remain.
+    pub fn decode_offset_datetime(&mut self) -> Result<OffsetDateTime, DecodeError> {
+        let offset = self.position();
+        let nanos = self.decode_i64()?;
+        OffsetDateTime::from_unix_timestamp_nanos(i128::from(nanos)).map_err(|_| {
+            self.rewind(offset);
+            DecodeError::Invalid {
+                offset,
+                expected: "timestamp",
+            }
+        })
+    }
+
+    /// Decode a `Date` written by `encode_date`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DecodeError::UnexpectedEnd` if fewer than 4 bytes remain and
+    /// `DecodeError::Invalid` if the day count is outside the range `Date` supports.
+    pub fn decode_date(&mut self) -> Result<Date, DecodeError> {
+        let offset = self.position();
+        let days = self.decode_i32()?;
+        days.checked_add(UNIX_EPOCH_JULIAN_DAY)
+            .and_then(|jd| Date::from_julian_day(jd).ok())
+            .ok_or_else(|| {
+                self.rewind(offset);
+                DecodeError::Invalid {
+                    offset,
+                    expected: "date",
+                }
+            })
+    }
+}
+
+#[cfg(feature = "time")]
+/// UTC Unix nanoseconds, 8 bytes.
+///
+/// # Panics
+///
+/// `encode_into` panics outside 1677-09-21 to 2262-04-11 (UTC); use `TryEncodable` to get
+/// `EncodeError::OutOfRange` instead.
+impl Encodable for OffsetDateTime {
+    #[inline]
+    fn encoded_len(&self) -> usize {
+        8
+    }
+
+    #[inline]
+    fn encode_into(&self, dst: &mut Vec<u8>) -> usize {
+        let nanos = unix_nanos(self).expect("timestamp outside the i64 nanosecond range");
+        LexKey::encode_i64_into(dst, nanos)
+    }
+}
+
+#[cfg(feature = "time")]
+impl TryEncodable for OffsetDateTime {
+    #[inline]
+    fn try_encode_into(&self, dst: &mut Vec<u8>) -> Result<usize, EncodeError> {
+        unix_nanos(self).map(|nanos| LexKey::encode_i64_into(dst, nanos))
+    }
+}
+
+#[cfg(feature = "time")]
+/// Days since 1970-01-01, 4 bytes.
+impl Encodable for Date {
+    #[inline]
+    fn encoded_len(&self) -> usize {
+        4
+    }
+
+    #[inline]
+    fn encode_into(&self, dst: &mut Vec<u8>) -> usize {
+        LexKey::encode_i32_into(dst, epoch_days(*self))
+    }
+}
+
+#[cfg(feature = "time")]
+impl TryEncodable for Date {
+    #[inline]
+    fn try_encode_into(&self, dst: &mut Vec<u8>) -> Result<usize, EncodeError> {
+        Ok(self.encode_into(dst))
+    }
+}
+
+#[cfg(feature = "jiff")]
+impl LexKey {
+    /// Encode a `jiff::Timestamp` as Unix nanoseconds, the `encode_time_unix_nanos` bytes.
+    ///
+    /// ```rust
+    /// use jiff::Timestamp;
+    /// use lexkey::LexKey;
+    ///
+    /// let ts = Timestamp::from_second(1_700_000_000).unwrap();
+    /// assert_eq!(LexKey::encode_timestamp(&ts).to_hex_string(), "97979cfe362a0000");
+    /// assert!(LexKey::encode_timestamp(&Timestamp::UNIX_EPOCH) < LexKey::encode_timestamp(&ts));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ts` is before 1677-09-21 or after 2262-04-11.
+    #[inline]
+    #[must_use]
+    pub fn encode_timestamp(ts: &Timestamp) -> Self {
+        Self::try_encode_timestamp(ts).expect("timestamp outside the i64 nanosecond range")
+    }
+
+    /// Encode a `jiff::Timestamp` so the most recent instant sorts first; see `encode_time_desc`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ts` is before 1677-09-21 or after 2262-04-11.
+    #[inline]
+    #[must_use]
+    pub fn encode_timestamp_desc(ts: &Timestamp) -> Self {
+        let nanos =
+            fit_nanos(ts.as_nanosecond()).expect("timestamp outside the i64 nanosecond range");
+        Self::encode_time_desc(nanos)
+    }
+
+    /// Encode a `jiff::Timestamp` like `encode_timestamp`, without panicking.
+    ///
+    /// # Errors
+    ///
+    /// Returns `EncodeError::OutOfRange` if the Unix nanoseconds do not fit in an `i64`.
+    #[inline]
+    pub fn try_encode_timestamp(ts: &Timestamp) -> Result<Self, EncodeError> {
+        fit_nanos(ts.as_nanosecond()).map(Self::encode_time_unix_nanos)
+    }
+}
+
+#[cfg(feature = "jiff")]
+impl Encoder {
+    /// Append a `jiff::Timestamp` as 8 bytes of Unix nanoseconds.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ts` is before 1677-09-21 or after 2262-04-11.
+    #[inline]
+    pub fn encode_timestamp_into(&mut self, ts: &Timestamp) -> usize {
+        ts.encode_into(&mut self.buf)
+    }
+
+    /// Append a `jiff::Timestamp` so the most recent instant sorts first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ts` is before 1677-09-21 or after 2262-04-11.
+    #[inline]
+    pub fn encode_timestamp_desc_into(&mut self, ts: &Timestamp) -> usize {
+        let nanos =
+            fit_nanos(ts.as_nanosecond()).expect("timestamp outside the i64 nanosecond range");
+        self.encode_time_desc_into(nanos)
+    }
+}
+
+#[cfg(feature = "jiff")]
+impl Decoder<'_> {
+    /// Decode a `jiff::Timestamp` written by `encode_timestamp` or any other instant encoder.
+    ///
+    /// A `Zoned` key decodes to its instant; the time zone is not stored.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DecodeError::UnexpectedEnd` if fewer than 8 bytes remain.
+    pub fn decode_timestamp(&mut self) -> Result<Timestamp, DecodeError> {
+        let offset = self.position();
+        let nanos = self.decode_i64()?;
+        Timestamp::from_nanosecond(i128::from(nanos)).map_err(|_| {
+            self.rewind(offset);
+            DecodeError::Invalid {
+                offset,
+                expected: "timestamp",
+            }
+        })
+    }
+}
+
+/// Unix nanoseconds, 8 bytes.
+///
+/// # Panics
+///
+/// `encode_into` panics outside 1677-09-21 to 2262-04-11; use `TryEncodable` to get
+/// `EncodeError::OutOfRange` instead.
+#[cfg(feature = "jiff")]
+impl Encodable for Timestamp {
+    #[inline]
+    fn encoded_len(&self) -> usize {
+        8
+    }
+
+    #[inline]
+    fn encode_into(&self, dst: &mut Vec<u8>) -> usize {
+        let nanos =
+            fit_nanos(self.as_nanosecond()).expect("timestamp outside the i64 nanosecond range");
+        LexKey::encode_i64_into(dst, nanos)
+    }
+}
+
+#[cfg(feature = "jiff")]
+impl TryEncodable for Timestamp {
+    #[inline]
+    fn try_encode_into(&self, dst: &mut Vec<u8>) -> Result<usize, EncodeError> {
+        fit_nanos(self.as_nanosecond()).map(|nanos| LexKey::encode_i64_into(dst, nanos))
+    }
+}
+
+/// The instant only, as for `Timestamp`; the time zone is dropped so equal instants in
+/// different zones share a key.
+///
+/// # Panics
+///
+/// `encode_into` panics outside 1677-09-21 to 2262-04-11; use `TryEncodable` to get
+/// `EncodeError::OutOfRange` instead.
+#[cfg(feature = "jiff")]
+impl Encodable for Zoned {
+    #[inline]
+    fn encoded_len(&self) -> usize {
+        8
+    }
+
+    #[inline]
+    fn encode_into(&self, dst: &mut Vec<u8>) -> usize {
+        self.timestamp().encode_into(dst)
+    }
+}
+
+#[cfg(feature = "jiff")]
+impl TryEncodable for Zoned {
+    #[inline]
+    fn try_encode_into(&self, dst: &mut Vec<u8>) -> Result<usize, EncodeError> {
+        self.timestamp().try_encode_into(dst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "time")]
+    use time::{Duration, Month, UtcOffset};
+
+    use super::*;
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn should_order_instants_regardless_of_offset() {
+        let base = Date::from_calendar_date(2024, Month::March, 1)
+            .unwrap()
+            .midnight()
+            .assume_utc();
+        let east = UtcOffset::from_hms(9, 0, 0).unwrap();
+        let west = UtcOffset::from_hms(-7, 0, 0).unwrap();
+        let instants = [
+            (base - Duration::nanoseconds(1)).to_offset(east),
+            base.to_offset(west),
+            (base + Duration::nanoseconds(1)).to_offset(east),
+            (base + Duration::hours(1)).to_offset(west),
+        ];
+        let keys: Vec<_> = instants
+            .iter()
+            .map(LexKey::encode_offset_datetime)
+            .collect();
+        assert!(keys.windows(2).all(|w| w[0] < w[1]));
+
+        let mut enc = Encoder::with_capacity(16);
+        enc.encode_offset_datetime_into(&instants[1]);
+        let key = enc.freeze();
+        let decoded = Decoder::new(&key).decode_offset_datetime().unwrap();
+        assert_eq!(decoded, base);
+        assert_eq!(decoded.offset(), UtcOffset::UTC);
+
+        let newest_first: Vec<_> = instants
+            .iter()
+            .map(LexKey::encode_offset_datetime_desc)
+            .collect();
+        assert!(newest_first.windows(2).all(|w| w[0] > w[1]));
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn should_reject_instants_outside_i64_nanos() {
+        let far = Date::from_calendar_date(2300, Month::January, 1)
+            .unwrap()
+            .midnight()
+            .assume_utc();
+        assert_eq!(
+            LexKey::try_encode_offset_datetime(&far),
+            Err(EncodeError::OutOfRange)
+        );
+        let mut enc = Encoder::with_capacity(8);
+        assert_eq!(enc.try_encode_into(&far), Err(EncodeError::OutOfRange));
+        assert!(enc.as_slice().is_empty());
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn should_round_trip_dates_in_calendar_order() {
+        let dates = [
+            Date::MIN,
+            Date::from_calendar_date(1969, Month::December, 31).unwrap(),
+            Date::from_calendar_date(1970, Month::January, 1).unwrap(),
+            Date::from_calendar_date(2024, Month::February, 29).unwrap(),
+            Date::MAX,
+        ];
+        let keys: Vec<_> = dates.iter().map(|&d| LexKey::encode_date(d)).collect();
+        assert!(keys.windows(2).all(|w| w[0] < w[1]));
+
+        for (date, key) in dates.iter().zip(&keys) {
+            assert_eq!(Decoder::new(key.as_bytes()).decode_date(), Ok(*date));
+        }
+        let beyond = LexKey::encode_i32(i32::MAX);
+        assert!(Decoder::new(beyond.as_bytes()).decode_date().is_err());
+    }
+
+    #[cfg(feature = "jiff")]
+    #[test]
+    fn should_key_jiff_instants_by_unix_nanos() {
+        let ts = Timestamp::from_nanosecond(-1).unwrap();
+        let zoned = ts.to_zoned(jiff::tz::TimeZone::fixed(jiff::tz::offset(9)));
+        assert_eq!(
+            LexKey::encode_timestamp(&ts),
+            LexKey::encode_time_unix_nanos(-1)
+        );
+        assert_eq!(
+            crate::encode_composite!(zoned),
+            LexKey::encode_timestamp(&ts)
+        );
+
+        let mut enc = Encoder::with_capacity(16);
+        enc.encode_timestamp_into(&ts);
+        let key = enc.freeze();
+        assert_eq!(Decoder::new(&key).decode_timestamp(), Ok(ts));
+        assert!(
+            LexKey::encode_timestamp_desc(&ts)
+                > LexKey::encode_timestamp_desc(&Timestamp::UNIX_EPOCH)
+        );
+
+        assert_eq!(
+            LexKey::try_encode_timestamp(&Timestamp::MAX),
+            Err(EncodeError::OutOfRange)
+        );
+        let mut enc = Encoder::with_capacity(8);
+        assert_eq!(
+            enc.try_encode_into(&Timestamp::MIN.to_zoned(jiff::tz::TimeZone::UTC)),
+            Err(EncodeError::OutOfRange)
+        );
+        assert!(enc.as_slice().is_empty());
+    }
+}
@@ -0,0 +1,334 @@
+use std::fmt;
+
+use crate::{Encoder, LexKey};
+
+const NEGATIVE: u8 = 0x01;
+const ZERO: u8 = 0x02;
+const POSITIVE: u8 = 0x03;
+const DIGITS_END: u8 = 0x00;
+const SIGN_BIT_32: u32 = 0x8000_0000;
+
+/// Error returned when a string is not a finite decimal number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseDecimalError {
+    input: String,
+}
+
+impl fmt::Display for ParseDecimalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid decimal number: {:?}", self.input)
+    }
+}
+
+impl std::error::Error for ParseDecimalError {}
+
+/// A finite decimal `0.d1 d2 d3 ... x 10^exponent` with no leading or trailing zero digits.
+struct Decimal {
+    negative: bool,
+    exponent: i32,
+    digits: Vec<u8>,
+}
+
+fn parse(s: &str) -> Option<Decimal> {
+    let (negative, rest) = match s.as_bytes().first()? {
+        b'-' => (true, &s[1..]),
+        b'+' => (false, &s[1..]),
+        _ => (false, s),
+    };
+    let (mantissa, exp) = match rest.find(['e', 'E']) {
+        Some(i) => (&rest[..i], rest[i + 1..].parse::<i64>().ok()?),
+        None => (rest, 0),
+    };
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    if int.is_empty() && frac.is_empty() {
+        return None;
+    }
+    if !int.bytes().chain(frac.bytes()).all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let all: Vec<u8> = int.bytes().chain(frac.bytes()).map(|b| b - b'0').collect();
+    let Some(first) = all.iter().position(|&d| d != 0) else {
+        return Some(Decimal {
+            negative: false,
+            exponent: 0,
+            digits: Vec::new(),
+        });
+    };
+    let last = all.iter().rposition(|&d| d != 0).unwrap_or(first);
+    let point = i64::try_from(int.len()).ok()?;
+    let lead = i64::try_from(first).ok()?;
+    let exponent = i32::try_from(exp.checked_add(point)?.checked_sub(lead)?).ok()?;
+    Some(Decimal {
+        negative,
+        exponent,
+        digits: all[first..=last].to_vec(),
+    })
+}
+
+fn encode_decimal_into(dst: &mut Vec<u8>, d: &Decimal) -> usize {
+    if d.digits.is_empty() {
+        dst.push(ZERO);
+        return 1;
+    }
+    let start = dst.len();
+    dst.push(if d.negative { NEGATIVE } else { POSITIVE });
+    let body = dst.len();
+    dst.extend_from_slice(&(d.exponent.cast_unsigned() ^ SIGN_BIT_32).to_be_bytes());
+    // Two digits per byte, offset by one so 0x00 stays free for the terminator. A trailing odd
+    // digit is padded with 0, which is unambiguous because trailing zeros are stripped.
+    for pair in d.digits.chunks(2) {
+        let hi = pair[0];
+        let lo = pair.get(1).copied().unwrap_or(0);
+        dst.push(hi * 10 + lo + 1);
+    }
+    dst.push(DIGITS_END);
+    if d.negative {
+        // Larger magnitudes must sort first for negative numbers.
+        for b in &mut dst[body..] {
+            *b = !*b;
+        }
+    }
+    dst.len() - start
+}
+
+/// Normalize a `rust_decimal::Decimal` (`mantissa * 10^-scale`) into digits and exponent.
+#[cfg(feature = "decimal")]
+fn from_rust_decimal(value: &rust_decimal::Decimal) -> Decimal {
+    let mantissa = value.mantissa();
+    let all: Vec<u8> = mantissa
+        .unsigned_abs()
+        .to_string()
+        .bytes()
+        .map(|b| b - b'0')
+        .collect();
+    let Some(last) = all.iter().rposition(|&d| d != 0) else {
+        return Decimal {
+            negative: false,
+            exponent: 0,
+            digits: Vec::new(),
+        };
+    };
+    // At most 29 digits and a scale of at most 28, so both fit in i32.
+    let len = i32::try_from(all.len()).unwrap_or(i32::MAX);
+    let scale = i32::try_from(value.scale()).unwrap_or(i32::MAX);
+    Decimal {
+        negative: mantissa < 0,
+        exponent: len - scale,
+        digits: all[..=last].to_vec(),
+    }
+}
+
+#[cfg(feature = "decimal")]
+fn rust_decimal_len(d: &Decimal) -> usize {
+    if d.digits.is_empty() {
+        1
+    } else {
+        1 + 4 + d.digits.len().div_ceil(2) + 1
+    }
+}
+
+/// Uses the exact decimal encoding, so it matches `LexKey::encode_f64_from_str` for the same
+/// value: `Decimal` `1.50` and the string `"1.5"` produce identical bytes.
+#[cfg(feature = "decimal")]
+impl crate::Encodable for rust_decimal::Decimal {
+    fn encoded_len(&self) -> usize {
+        rust_decimal_len(&from_rust_decimal(self))
+    }
+
+    fn encode_into(&self, dst: &mut Vec<u8>) -> usize {
+        encode_decimal_into(dst, &from_rust_decimal(self))
+    }
+}
+
+impl LexKey {
+    /// Encode a `rust_decimal::Decimal` so keys order by its exact value.
+    ///
+    /// Trailing zeros do not matter (`1.50` and `1.5` are one key), and the bytes match
+    /// `encode_f64_from_str` for the same number, so both can feed one key position.
+    ///
+    /// ```rust
+    /// use lexkey::LexKey;
+    /// use rust_decimal::Decimal;
+    ///
+    /// let price = |s: &str| LexKey::encode_decimal(&s.parse::<Decimal>().unwrap());
+    /// assert!(price("-0.01") < price("0") && price("0") < price("19.99"));
+    /// assert_eq!(price("1.50"), price("1.5"));
+    /// ```
+    #[cfg(feature = "decimal")]
+    #[must_use]
+    pub fn encode_decimal(value: &rust_decimal::Decimal) -> Self {
+        let d = from_rust_decimal(value);
+        let mut buf = Vec::with_capacity(rust_decimal_len(&d));
+        encode_decimal_into(&mut buf, &d);
+        Self::from_bytes(buf)
+    }
+
+    /// Encode a decimal number string exactly, so keys order by the number's true value.
+    ///
+    /// Parsing text into `f64` first rounds values such as `0.1` or
+    /// `9007199254740993`, and two different prices can collapse to the same key or sort by their
+    /// rounded neighbours. This encoder never rounds: every input, whether or not `f64` could
+    /// represent it exactly, uses one variable-length sortable decimal form (sign byte, 4-byte
+    /// exponent, base-100 digits, terminator). Equal values such as `1.50`, `1.5` and `15e-1`
+    /// encode identically.
+    ///
+    /// The bytes differ from `encode_f64`; do not mix the two encodings in one key position.
+    ///
+    /// ```rust
+    /// use lexkey::LexKey;
+    ///
+    /// let a = LexKey::encode_f64_from_str("9007199254740992").unwrap();
+    /// let b = LexKey::encode_f64_from_str("9007199254740993").unwrap();
+    /// assert!(a < b); // both parse to the same f64
+    /// assert_eq!(LexKey::encode_f64_from_str("1.50"), LexKey::encode_f64_from_str("15e-1"));
+    /// assert!(LexKey::encode_f64_from_str("NaN").is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `ParseDecimalError` unless `s` is `[+-]digits[.digits][e[+-]digits]` with at least
+    /// one digit in the mantissa and an exponent that fits in `i32`. `inf` and `NaN` are rejected.
+    pub fn encode_f64_from_str(s: &str) -> Result<Self, ParseDecimalError> {
+        let d = parse(s).ok_or_else(|| ParseDecimalError {
+            input: s.to_owned(),
+        })?;
+        let mut buf = Vec::with_capacity(6 + d.digits.len().div_ceil(2));
+        encode_decimal_into(&mut buf, &d);
+        Ok(Self::from_bytes(buf))
+    }
+}
+
+impl Encoder {
+    /// Append the exact encoding of a `rust_decimal::Decimal`; see `LexKey::encode_decimal`.
+    #[cfg(feature = "decimal")]
+    #[inline]
+    pub fn encode_decimal_into(&mut self, value: &rust_decimal::Decimal) -> usize {
+        encode_decimal_into(&mut self.buf, &from_rust_decimal(value))
+    }
+
+    /// Append the exact decimal encoding of `s` and return bytes written.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ParseDecimalError` for the same inputs as `LexKey::encode_f64_from_str`; nothing
+    /// is written in that case.
+    pub fn encode_f64_from_str_into(&mut self, s: &str) -> Result<usize, ParseDecimalError> {
+        let d = parse(s).ok_or_else(|| ParseDecimalError {
+            input: s.to_owned(),
+        })?;
+        Ok(encode_decimal_into(&mut self.buf, &d))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(s: &str) -> LexKey {
+        LexKey::encode_f64_from_str(s).unwrap()
+    }
+
+    #[test]
+    fn should_order_by_exact_decimal_value() {
+        let ordered = [
+            "-1e300",
+            "-123.456",
+            "-123.45",
+            "-1",
+            "-0.1000000000000000055511151231257827",
+            "-0.1",
+            "-1e-300",
+            "0",
+            "1e-300",
+            "0.1",
+            "0.1000000000000000055511151231257827",
+            "0.12",
+            "0.125",
+            "1",
+            "9.99",
+            "10",
+            "100.01",
+            "9007199254740992",
+            "9007199254740993",
+            "1e300",
+        ];
+        for pair in ordered.windows(2) {
+            assert!(key(pair[0]) < key(pair[1]), "{} < {}", pair[0], pair[1]);
+        }
+    }
+
+    #[test]
+    fn should_normalize_equal_values() {
+        assert_eq!(key("1.5"), key("+001.500"));
+        assert_eq!(key("1.5"), key("0.15E1"));
+        assert_eq!(key("0"), key("-0.000e5"));
+        assert_eq!(key("5."), key("5"));
+        assert_ne!(key(".5"), key("5"));
+    }
+
+    #[test]
+    fn should_reject_non_decimal_input() {
+        for bad in [
+            "",
+            "-",
+            ".",
+            "1e",
+            "1e+",
+            "abc",
+            "1.2.3",
+            "NaN",
+            "inf",
+            "1e99999999999",
+        ] {
+            assert!(LexKey::encode_f64_from_str(bad).is_err(), "{bad:?}");
+        }
+    }
+
+    #[cfg(feature = "decimal")]
+    #[test]
+    fn should_match_string_encoding_for_rust_decimal() {
+        use crate::Encodable;
+        use rust_decimal::Decimal;
+
+        let values = [
+            "-79228162514264337593543950335",
+            "-1.5",
+            "-0.0000000000000000000000000001",
+            "0",
+            "0.0000000000000000000000000001",
+            "0.1",
+            "1.50",
+            "100",
+            "79228162514264337593543950335",
+        ];
+        let keys: Vec<_> = values
+            .iter()
+            .map(|s| {
+                let d: Decimal = s.parse().unwrap();
+                let key = LexKey::encode_decimal(&d);
+                assert_eq!(key, LexKey::encode_f64_from_str(s).unwrap(), "{s}");
+                assert_eq!(d.encoded_len(), key.as_bytes().len());
+                key
+            })
+            .collect();
+        assert!(keys.windows(2).all(|w| w[0] < w[1]));
+
+        let mut enc = Encoder::with_capacity(16);
+        enc.encode_decimal_into(&Decimal::new(-250, 2));
+        assert_eq!(enc.as_slice(), key("-2.5").as_bytes());
+        assert_eq!(LexKey::encode_decimal(&Decimal::new(0, 5)), key("0"));
+    }
+
+    #[test]
+    fn should_leave_encoder_untouched_on_error() {
+        let mut enc = Encoder::with_capacity(16);
+        assert_eq!(
+            enc.encode_f64_from_str_into("0.5"),
+            Ok(key("0.5").as_bytes().len())
+        );
+        let before = enc.as_slice().to_vec();
+        assert!(enc.encode_f64_from_str_into("x").is_err());
+        assert_eq!(enc.as_slice(), before.as_slice());
+    }
+}
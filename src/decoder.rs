@@ -0,0 +1,903 @@
+//! Decoding keys produced by the sortable encoders back into values.
+//!
+//! Keys carry no type information, so the caller decodes fields in the order they were written,
+//! mirroring the `Encoder` calls that built the key. Every method checks the remaining length and
+//! leaves the cursor where it was when it returns an error.
+
+use std::fmt;
+
+use uuid::Uuid;
+
+use crate::{escape, Decodable, F64Mode, LexKey};
+
+const SIGN_BIT: u64 = 0x8000_0000_0000_0000;
+const SIGN_BIT_8: u8 = 0x80;
+const SIGN_BIT_16: u16 = 0x8000;
+const SIGN_BIT_32: u32 = 0x8000_0000;
+const SIGN_BIT_128: u128 = 1 << 127;
+
+/// Error returned when bytes cannot be decoded as the requested value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The input ended before a value of `needed` bytes could be read at `offset`.
+    UnexpectedEnd {
+        offset: usize,
+        needed: usize,
+        available: usize,
+    },
+    /// The bytes at `offset` are not a valid encoding of `expected`.
+    Invalid {
+        offset: usize,
+        expected: &'static str,
+    },
+    /// Decoding finished at `offset` but `remaining` bytes were left over.
+    TrailingBytes { offset: usize, remaining: usize },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedEnd {
+                offset,
+                needed,
+                available,
+            } => write!(
+                f,
+                "key truncated at offset {offset}: needed {needed} bytes, {available} available"
+            ),
+            Self::Invalid { offset, expected } => {
+                write!(f, "invalid {expected} encoding at offset {offset}")
+            }
+            Self::TrailingBytes { offset, remaining } => {
+                write!(f, "{remaining} unexpected bytes after offset {offset}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// A cursor over encoded key bytes, the mirror of `Encoder`.
+///
+/// ```rust
+/// use lexkey::{Decoder, Encoder};
+/// use uuid::Uuid;
+///
+/// let id = Uuid::from_u128(7);
+/// let mut enc = Encoder::with_capacity(64);
+/// enc.encode_string_into("orders");
+/// enc.push_separator();
+/// enc.encode_i64_into(-42);
+/// enc.encode_uuid_into_buf(&id);
+/// let key = enc.freeze();
+///
+/// let mut dec = Decoder::new(&key);
+/// assert_eq!(dec.decode_part()?, b"orders");
+/// assert_eq!(dec.decode_i64()?, -42);
+/// assert_eq!(dec.decode_uuid()?, id);
+/// assert!(dec.is_empty());
+/// # Ok::<(), lexkey::DecodeError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Decoder<'a> {
+    src: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Decoder<'a> {
+    /// Start decoding at the beginning of `src`.
+    #[inline]
+    #[must_use]
+    pub fn new(src: &'a [u8]) -> Self {
+        Self { src, pos: 0 }
+    }
+
+    /// Offset of the next byte to decode.
+    #[inline]
+    #[must_use]
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Bytes not yet decoded.
+    #[inline]
+    #[must_use]
+    pub fn remaining(&self) -> &'a [u8] {
+        &self.src[self.pos..]
+    }
+
+    /// Whether every byte has been decoded.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.pos == self.src.len()
+    }
+
+    /// Check that every byte has been decoded.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DecodeError::TrailingBytes` if input remains.
+    pub fn finish(self) -> Result<(), DecodeError> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(DecodeError::TrailingBytes {
+                offset: self.pos,
+                remaining: self.src.len() - self.pos,
+            })
+        }
+    }
+
+    /// Move the cursor back to `pos`, undoing a partial decode.
+    pub(crate) fn rewind(&mut self, pos: usize) {
+        self.pos = pos;
+    }
+
+    /// Advance past `n` bytes already inspected through `remaining`.
+    pub(crate) fn skip(&mut self, n: usize) {
+        self.pos += n;
+    }
+
+    pub(crate) fn take<const N: usize>(&mut self) -> Result<[u8; N], DecodeError> {
+        let rest = self.remaining();
+        let Some(bytes) = rest.first_chunk::<N>() else {
+            return Err(DecodeError::UnexpectedEnd {
+                offset: self.pos,
+                needed: N,
+                available: rest.len(),
+            });
+        };
+        self.pos += N;
+        Ok(*bytes)
+    }
+
+    fn invalid(&self, expected: &'static str) -> DecodeError {
+        DecodeError::Invalid {
+            offset: self.pos,
+            expected,
+        }
+    }
+
+    /// Decode a 1-byte unsigned integer.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DecodeError::UnexpectedEnd` if no bytes remain.
+    #[inline]
+    pub fn decode_u8(&mut self) -> Result<u8, DecodeError> {
+        self.take::<1>().map(|[b]| b)
+    }
+
+    /// Decode a 2-byte big-endian unsigned integer.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DecodeError::UnexpectedEnd` if fewer than 2 bytes remain.
+    #[inline]
+    pub fn decode_u16(&mut self) -> Result<u16, DecodeError> {
+        self.take().map(u16::from_be_bytes)
+    }
+
+    /// Decode a 4-byte big-endian unsigned integer.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DecodeError::UnexpectedEnd` if fewer than 4 bytes remain.
+    #[inline]
+    pub fn decode_u32(&mut self) -> Result<u32, DecodeError> {
+        self.take().map(u32::from_be_bytes)
+    }
+
+    /// Decode an 8-byte big-endian unsigned integer.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DecodeError::UnexpectedEnd` if fewer than 8 bytes remain.
+    #[inline]
+    pub fn decode_u64(&mut self) -> Result<u64, DecodeError> {
+        self.take().map(u64::from_be_bytes)
+    }
+
+    /// Decode a 1-byte signed integer written with its sign bit flipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DecodeError::UnexpectedEnd` if no bytes remain.
+    #[inline]
+    pub fn decode_i8(&mut self) -> Result<i8, DecodeError> {
+        self.decode_u8().map(|n| (n ^ SIGN_BIT_8).cast_signed())
+    }
+
+    /// Decode a 2-byte signed integer written with its sign bit flipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DecodeError::UnexpectedEnd` if fewer than 2 bytes remain.
+    #[inline]
+    pub fn decode_i16(&mut self) -> Result<i16, DecodeError> {
+        self.decode_u16().map(|n| (n ^ SIGN_BIT_16).cast_signed())
+    }
+
+    /// Decode a 4-byte signed integer written with its sign bit flipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DecodeError::UnexpectedEnd` if fewer than 4 bytes remain.
+    #[inline]
+    pub fn decode_i32(&mut self) -> Result<i32, DecodeError> {
+        self.decode_u32().map(|n| (n ^ SIGN_BIT_32).cast_signed())
+    }
+
+    /// Decode an 8-byte signed integer written with its sign bit flipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DecodeError::UnexpectedEnd` if fewer than 8 bytes remain.
+    #[inline]
+    pub fn decode_i64(&mut self) -> Result<i64, DecodeError> {
+        self.decode_u64().map(|n| (n ^ SIGN_BIT).cast_signed())
+    }
+
+    /// Decode a 16-byte big-endian unsigned integer.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DecodeError::UnexpectedEnd` if fewer than 16 bytes remain.
+    #[inline]
+    pub fn decode_u128(&mut self) -> Result<u128, DecodeError> {
+        self.take().map(u128::from_be_bytes)
+    }
+
+    /// Decode a 16-byte signed integer written with its sign bit flipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DecodeError::UnexpectedEnd` if fewer than 16 bytes remain.
+    #[inline]
+    pub fn decode_i128(&mut self) -> Result<i128, DecodeError> {
+        self.decode_u128().map(|n| (n ^ SIGN_BIT_128).cast_signed())
+    }
+
+    /// Decode a sortable 4-byte `f32`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DecodeError::UnexpectedEnd` if fewer than 4 bytes remain and
+    /// `DecodeError::Invalid` if the bytes decode to NaN, which the encoders never write.
+    pub fn decode_f32(&mut self) -> Result<f32, DecodeError> {
+        let start = self.pos;
+        let t = self.decode_u32()?;
+        let bits = if t & SIGN_BIT_32 == 0 {
+            !t
+        } else {
+            t ^ SIGN_BIT_32
+        };
+        let x = f32::from_bits(bits);
+        if x.is_nan() {
+            self.pos = start;
+            return Err(self.invalid("f32"));
+        }
+        Ok(x)
+    }
+
+    /// Decode a sortable 8-byte `f64`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DecodeError::UnexpectedEnd` if fewer than 8 bytes remain and
+    /// `DecodeError::Invalid` if the bytes decode to NaN, which the encoders never write.
+    #[inline]
+    pub fn decode_f64(&mut self) -> Result<f64, DecodeError> {
+        self.decode_f64_with(F64Mode::RejectNaN)
+    }
+
+    /// Decode a sortable 8-byte `f64` written under `mode`; `F64Mode::TotalOrder` accepts NaN.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DecodeError::UnexpectedEnd` if fewer than 8 bytes remain and
+    /// `DecodeError::Invalid` if the bytes decode to NaN under `F64Mode::RejectNaN`.
+    pub fn decode_f64_with(&mut self, mode: F64Mode) -> Result<f64, DecodeError> {
+        let start = self.pos;
+        let t = self.decode_u64()?;
+        let bits = if t & SIGN_BIT == 0 { !t } else { t ^ SIGN_BIT };
+        let x = f64::from_bits(bits);
+        if x.is_nan() && mode == F64Mode::RejectNaN {
+            self.pos = start;
+            return Err(self.invalid("f64"));
+        }
+        Ok(x)
+    }
+
+    /// Decode a one-byte boolean (`00` or `01`).
+    ///
+    /// # Errors
+    ///
+    /// Returns `DecodeError::UnexpectedEnd` if no bytes remain and `DecodeError::Invalid` for any
+    /// other byte value.
+    pub fn decode_bool(&mut self) -> Result<bool, DecodeError> {
+        match self.remaining().first() {
+            Some(0x00 | 0x01) => self.decode_u8().map(|b| b == 0x01),
+            Some(_) => Err(self.invalid("bool")),
+            None => Err(DecodeError::UnexpectedEnd {
+                offset: self.pos,
+                needed: 1,
+                available: 0,
+            }),
+        }
+    }
+
+    /// Decode a UUID from its 16 raw bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DecodeError::UnexpectedEnd` if fewer than 16 bytes remain.
+    #[inline]
+    pub fn decode_uuid(&mut self) -> Result<Uuid, DecodeError> {
+        self.take().map(Uuid::from_bytes)
+    }
+
+    /// Decode a variable-length part: the bytes up to the next `SEPARATOR` or the end of input.
+    ///
+    /// The separator, if present, is consumed but not returned. Only use this for parts that
+    /// cannot contain `0x00` themselves, such as strings written with `encode_string_into`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DecodeError::UnexpectedEnd` if no bytes remain.
+    pub fn decode_part(&mut self) -> Result<&'a [u8], DecodeError> {
+        let rest = self.remaining();
+        if rest.is_empty() {
+            return Err(DecodeError::UnexpectedEnd {
+                offset: self.pos,
+                needed: 1,
+                available: 0,
+            });
+        }
+        let len = rest
+            .iter()
+            .position(|&b| b == LexKey::SEPARATOR)
+            .unwrap_or(rest.len());
+        self.pos += (len + 1).min(rest.len());
+        Ok(&rest[..len])
+    }
+
+    /// Decode a variable-length part as UTF-8; see `decode_part`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DecodeError::UnexpectedEnd` if no bytes remain and `DecodeError::Invalid` if the
+    /// part is not valid UTF-8.
+    pub fn decode_str(&mut self) -> Result<&'a str, DecodeError> {
+        let start = self.pos;
+        let part = self.decode_part()?;
+        std::str::from_utf8(part).map_err(|_| {
+            self.pos = start;
+            self.invalid("UTF-8 string")
+        })
+    }
+
+    /// Consume a `SEPARATOR` byte.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DecodeError::UnexpectedEnd` if no bytes remain and `DecodeError::Invalid` if the
+    /// next byte is not `0x00`.
+    pub fn expect_separator(&mut self) -> Result<(), DecodeError> {
+        match self.remaining().first() {
+            Some(&LexKey::SEPARATOR) => {
+                self.pos += 1;
+                Ok(())
+            }
+            Some(_) => Err(self.invalid("separator")),
+            None => Err(DecodeError::UnexpectedEnd {
+                offset: self.pos,
+                needed: 1,
+                available: 0,
+            }),
+        }
+    }
+
+    /// Decode any `Decodable` value, leaving a following separator unread.
+    ///
+    /// ```rust
+    /// use lexkey::{encode_composite, Decoder};
+    ///
+    /// let key = encode_composite!("acme", 42u32);
+    /// let mut dec = Decoder::new(key.as_bytes());
+    /// assert_eq!(dec.decode_value::<String>()?, "acme");
+    /// dec.expect_separator()?;
+    /// assert_eq!(dec.decode_value::<u32>()?, 42);
+    /// # Ok::<(), lexkey::DecodeError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns the `DecodeError` from `T::decode_from`, with offsets relative to the whole input.
+    pub fn decode_value<T: Decodable>(&mut self) -> Result<T, DecodeError> {
+        self.decode_with(T::decode_from)
+    }
+
+    /// Decode a value written in descending order, the inverse of `SortOrder::Desc`.
+    ///
+    /// Reads `T::decode_desc_from`: fixed-width values are complemented back, and strings are
+    /// read in the escaped layout of `LexKey::encode_string_desc` up to their terminator.
+    ///
+    /// ```rust
+    /// use lexkey::{Decoder, LexKey};
+    ///
+    /// let key = LexKey::encode_i64_desc(-7);
+    /// assert_eq!(Decoder::new(key.as_bytes()).decode_value_desc::<i64>()?, -7);
+    ///
+    /// let key = LexKey::encode_string_desc("acme");
+    /// assert_eq!(Decoder::new(key.as_bytes()).decode_value_desc::<String>()?, "acme");
+    /// # Ok::<(), lexkey::DecodeError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns the `DecodeError` from `T::decode_desc_from`, with offsets relative to the whole
+    /// input.
+    pub fn decode_value_desc<T: Decodable>(&mut self) -> Result<T, DecodeError> {
+        self.decode_with(T::decode_desc_from)
+    }
+
+    fn decode_with<T>(
+        &mut self,
+        decode: impl FnOnce(&mut &[u8]) -> Result<T, DecodeError>,
+    ) -> Result<T, DecodeError> {
+        let mut rest = self.remaining();
+        let len = rest.len();
+        let value = decode(&mut rest).map_err(|e| shift(e, self.pos))?;
+        self.pos += len - rest.len();
+        Ok(value)
+    }
+}
+
+/// Run `decode` over `src` and advance it by the bytes consumed.
+fn decode_slice<'a, T>(
+    src: &mut &'a [u8],
+    decode: impl FnOnce(&mut Decoder<'a>) -> Result<T, DecodeError>,
+) -> Result<T, DecodeError> {
+    let mut dec = Decoder::new(src);
+    let value = decode(&mut dec)?;
+    *src = &src[dec.position()..];
+    Ok(value)
+}
+
+macro_rules! impl_decodable {
+    ($($ty:ty => $method:ident),+ $(,)?) => {
+        $(
+            impl Decodable for $ty {
+                #[inline]
+                fn decode_from(src: &mut &[u8]) -> Result<Self, DecodeError> {
+                    decode_slice(src, Decoder::$method)
+                }
+
+                #[inline]
+                fn decode_desc_from(src: &mut &[u8]) -> Result<Self, DecodeError> {
+                    decode_flipped::<{ std::mem::size_of::<$ty>() }, _>(src, Self::decode_from)
+                }
+            }
+        )+
+    };
+}
+
+/// Complement the first `N` bytes of `src` and read them with `decode`: the descending form of
+/// a fixed-width value, without touching the bytes after it.
+fn decode_flipped<const N: usize, T>(
+    src: &mut &[u8],
+    decode: fn(&mut &[u8]) -> Result<T, DecodeError>,
+) -> Result<T, DecodeError> {
+    let n = N.min(src.len());
+    let mut flipped = [0u8; N];
+    for (d, s) in flipped.iter_mut().zip(&src[..n]) {
+        *d = !s;
+    }
+    let mut rest = &flipped[..n];
+    let value = decode(&mut rest)?;
+    *src = &src[n - rest.len()..];
+    Ok(value)
+}
+
+impl_decodable! {
+    u8 => decode_u8,
+    u16 => decode_u16,
+    u32 => decode_u32,
+    u64 => decode_u64,
+    i8 => decode_i8,
+    i16 => decode_i16,
+    i32 => decode_i32,
+    i64 => decode_i64,
+    u128 => decode_u128,
+    i128 => decode_i128,
+    f32 => decode_f32,
+    f64 => decode_f64,
+    bool => decode_bool,
+    Uuid => decode_uuid,
+}
+
+/// Reads the bytes up to the next `SEPARATOR` (or the end) and leaves the separator in `src`.
+/// Descending, reads the escaped layout of `LexKey::encode_string_desc` up to its terminator.
+impl Decodable for String {
+    fn decode_from(src: &mut &[u8]) -> Result<Self, DecodeError> {
+        let len = src
+            .iter()
+            .position(|&b| b == LexKey::SEPARATOR)
+            .unwrap_or(src.len());
+        let s = std::str::from_utf8(&src[..len]).map_err(|e| DecodeError::Invalid {
+            offset: e.valid_up_to(),
+            expected: "UTF-8 string",
+        })?;
+        *src = &src[len..];
+        Ok(s.to_owned())
+    }
+
+    fn decode_desc_from(src: &mut &[u8]) -> Result<Self, DecodeError> {
+        let (bytes, len) = escape::unescape(src, 0xff)?;
+        let s = String::from_utf8(bytes).map_err(|e| DecodeError::Invalid {
+            offset: e.utf8_error().valid_up_to(),
+            expected: "UTF-8 string",
+        })?;
+        *src = &src[len..];
+        Ok(s)
+    }
+}
+
+/// Reads a presence marker written by `Option<T>`'s `Encodable` impl or `encode_opt_f64`;
+/// both the nulls-first and nulls-last markers decode to `None`.
+impl<T: Decodable> Decodable for Option<T> {
+    fn decode_from(src: &mut &[u8]) -> Result<Self, DecodeError> {
+        decode_option(src, 0x00, T::decode_from)
+    }
+
+    fn decode_desc_from(src: &mut &[u8]) -> Result<Self, DecodeError> {
+        decode_option(src, 0xff, T::decode_desc_from)
+    }
+}
+
+impl<T: Decodable> Decodable for crate::NullsLast<T> {
+    fn decode_from(src: &mut &[u8]) -> Result<Self, DecodeError> {
+        Option::decode_from(src).map(Self)
+    }
+
+    fn decode_desc_from(src: &mut &[u8]) -> Result<Self, DecodeError> {
+        Option::decode_desc_from(src).map(Self)
+    }
+}
+
+/// Read a presence marker xor-ed with `flip`, then the value with `decode` if it is present.
+fn decode_option<T>(
+    src: &mut &[u8],
+    flip: u8,
+    decode: fn(&mut &[u8]) -> Result<T, DecodeError>,
+) -> Result<Option<T>, DecodeError> {
+    let mut rest = *src;
+    let value = match u8::decode_from(&mut rest)? ^ flip {
+        0x00 | 0x02 => None,
+        0x01 => Some(decode(&mut rest).map_err(|e| shift(e, 1))?),
+        _ => {
+            return Err(DecodeError::Invalid {
+                offset: 0,
+                expected: "option marker",
+            });
+        }
+    };
+    *src = rest;
+    Ok(value)
+}
+
+/// Move an error's offset `by` bytes later, for errors from a nested slice.
+pub(crate) fn shift(err: DecodeError, by: usize) -> DecodeError {
+    match err {
+        DecodeError::UnexpectedEnd {
+            offset,
+            needed,
+            available,
+        } => DecodeError::UnexpectedEnd {
+            offset: offset + by,
+            needed,
+            available,
+        },
+        DecodeError::Invalid { offset, expected } => DecodeError::Invalid {
+            offset: offset + by,
+            expected,
+        },
+        DecodeError::TrailingBytes { offset, remaining } => DecodeError::TrailingBytes {
+            offset: offset + by,
+            remaining,
+        },
+    }
+}
+
+/// Decode one value that must span all of `bytes`.
+fn decode_exact<'a, T>(
+    bytes: &'a [u8],
+    decode: impl FnOnce(&mut Decoder<'a>) -> Result<T, DecodeError>,
+) -> Result<T, DecodeError> {
+    let mut dec = Decoder::new(bytes);
+    let value = decode(&mut dec)?;
+    dec.finish()?;
+    Ok(value)
+}
+
+impl LexKey {
+    /// Decode a key written by `encode_u64`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DecodeError::UnexpectedEnd` or `DecodeError::TrailingBytes` unless `bytes` is
+    /// exactly 8 bytes long.
+    pub fn decode_u64(bytes: &[u8]) -> Result<u64, DecodeError> {
+        decode_exact(bytes, Decoder::decode_u64)
+    }
+
+    /// Decode a key written by `encode_i64` or `encode_time_unix_nanos`.
+    ///
+    /// ```rust
+    /// use lexkey::LexKey;
+    /// let key = LexKey::encode_i64(-7);
+    /// assert_eq!(LexKey::decode_i64(key.as_bytes()), Ok(-7));
+    /// assert!(LexKey::decode_i64(&key.as_bytes()[..4]).is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `DecodeError::UnexpectedEnd` or `DecodeError::TrailingBytes` unless `bytes` is
+    /// exactly 8 bytes long.
+    pub fn decode_i64(bytes: &[u8]) -> Result<i64, DecodeError> {
+        decode_exact(bytes, Decoder::decode_i64)
+    }
+
+    /// Decode a key written by `encode_f64`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DecodeError::UnexpectedEnd` or `DecodeError::TrailingBytes` unless `bytes` is
+    /// exactly 8 bytes long, and `DecodeError::Invalid` if they decode to NaN.
+    pub fn decode_f64(bytes: &[u8]) -> Result<f64, DecodeError> {
+        decode_exact(bytes, Decoder::decode_f64)
+    }
+
+    /// Decode a key written by `encode_bool`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DecodeError::UnexpectedEnd` or `DecodeError::TrailingBytes` unless `bytes` is
+    /// exactly one byte, and `DecodeError::Invalid` unless that byte is `00` or `01`.
+    pub fn decode_bool(bytes: &[u8]) -> Result<bool, DecodeError> {
+        decode_exact(bytes, Decoder::decode_bool)
+    }
+
+    /// Decode a key written by `encode_uuid`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DecodeError::UnexpectedEnd` or `DecodeError::TrailingBytes` unless `bytes` is
+    /// exactly 16 bytes long.
+    pub fn decode_uuid(bytes: &[u8]) -> Result<Uuid, DecodeError> {
+        decode_exact(bytes, Decoder::decode_uuid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Encoder;
+
+    #[test]
+    fn should_round_trip_fixed_width_values() {
+        let mut enc = Encoder::with_capacity(64);
+        enc.encode_u8_into(200);
+        enc.encode_u16_into(60_000);
+        enc.encode_u32_into(4_000_000_000);
+        enc.encode_u64_into(u64::MAX);
+        enc.encode_i8_into(i8::MIN);
+        enc.encode_i16_into(-2);
+        enc.encode_i32_into(i32::MAX);
+        enc.encode_i64_into(-1);
+        enc.encode_f32_into(-0.5);
+        enc.encode_f64_into(f64::NEG_INFINITY);
+        enc.encode_f64_into(-0.0);
+        enc.encode_f64_into(1e-300);
+        let key = enc.freeze();
+
+        let mut dec = Decoder::new(&key);
+        assert_eq!(dec.decode_u8(), Ok(200));
+        assert_eq!(dec.decode_u16(), Ok(60_000));
+        assert_eq!(dec.decode_u32(), Ok(4_000_000_000));
+        assert_eq!(dec.decode_u64(), Ok(u64::MAX));
+        assert_eq!(dec.decode_i8(), Ok(i8::MIN));
+        assert_eq!(dec.decode_i16(), Ok(-2));
+        assert_eq!(dec.decode_i32(), Ok(i32::MAX));
+        assert_eq!(dec.decode_i64(), Ok(-1));
+        assert_eq!(dec.decode_f32(), Ok(-0.5));
+        assert_eq!(dec.decode_f64(), Ok(f64::NEG_INFINITY));
+        assert!(dec.decode_f64().unwrap().is_sign_negative());
+        assert_eq!(dec.decode_f64(), Ok(1e-300));
+        assert!(dec.is_empty());
+    }
+
+    #[test]
+    fn should_decode_parts_bools_and_uuids() {
+        let id = Uuid::from_u128(0x0123_4567_89ab_cdef);
+        let key = crate::encode_composite!("tenant", true, id, "tail");
+
+        let mut dec = Decoder::new(key.as_bytes());
+        assert_eq!(dec.decode_str(), Ok("tenant"));
+        assert_eq!(dec.decode_bool(), Ok(true));
+        assert_eq!(dec.expect_separator(), Ok(()));
+        assert_eq!(dec.decode_uuid(), Ok(id));
+        assert_eq!(dec.expect_separator(), Ok(()));
+        assert_eq!(dec.decode_part(), Ok(&b"tail"[..]));
+        assert!(dec.is_empty());
+    }
+
+    #[test]
+    fn should_report_truncation_without_advancing() {
+        let mut dec = Decoder::new(&[0x80, 0x00, 0x00]);
+        assert_eq!(
+            dec.decode_i64(),
+            Err(DecodeError::UnexpectedEnd {
+                offset: 0,
+                needed: 8,
+                available: 3
+            })
+        );
+        assert_eq!(dec.position(), 0);
+        assert_eq!(dec.decode_i16(), Ok(0));
+        assert_eq!(dec.remaining(), &[0x00]);
+    }
+
+    #[test]
+    fn should_decode_whole_keys_with_lexkey_helpers() {
+        let id = Uuid::from_u128(99);
+        assert_eq!(LexKey::decode_u64(LexKey::encode_u64(5).as_bytes()), Ok(5));
+        assert_eq!(
+            LexKey::decode_i64(LexKey::encode_time_unix_nanos(i64::MIN).as_bytes()),
+            Ok(i64::MIN)
+        );
+        assert_eq!(
+            LexKey::decode_f64(LexKey::encode_f64(-2.5).as_bytes()),
+            Ok(-2.5)
+        );
+        assert_eq!(
+            LexKey::decode_bool(LexKey::encode_bool(false).as_bytes()),
+            Ok(false)
+        );
+        assert_eq!(
+            LexKey::decode_uuid(LexKey::encode_uuid(&id).as_bytes()),
+            Ok(id)
+        );
+        assert_eq!(
+            LexKey::decode_bool(&[0x01, 0x00]),
+            Err(DecodeError::TrailingBytes {
+                offset: 1,
+                remaining: 1
+            })
+        );
+    }
+
+    #[test]
+    fn should_round_trip_encodables_through_decodable() {
+        fn round_trip<T: crate::Encodable + Decodable + PartialEq + std::fmt::Debug>(value: T) {
+            let mut buf = Vec::new();
+            value.encode_into(&mut buf);
+            buf.push(0xAA);
+            let mut src = buf.as_slice();
+            assert_eq!(T::decode_from(&mut src), Ok(value));
+            assert_eq!(src, &[0xAA]);
+        }
+
+        round_trip(7u8);
+        round_trip(7u16);
+        round_trip(7u32);
+        round_trip(7u64);
+        round_trip(-7i8);
+        round_trip(-7i16);
+        round_trip(-7i32);
+        round_trip(-7i64);
+        round_trip(u128::MAX);
+        round_trip(i128::MIN);
+        round_trip(1.5f64);
+        round_trip(true);
+        round_trip(Uuid::from_u128(7));
+        round_trip(Some(-7i64));
+        round_trip(None::<u32>);
+    }
+
+    #[test]
+    fn should_decode_strings_up_to_separator() {
+        let key = crate::encode_composite!("alice", Some(3u8));
+        let mut src = key.as_bytes();
+        assert_eq!(String::decode_from(&mut src).as_deref(), Ok("alice"));
+        assert_eq!(src, &[0x00, 0x01, 0x03]);
+        src = &src[1..];
+        assert_eq!(Option::<u8>::decode_from(&mut src), Ok(Some(3)));
+
+        let mut truncated: &[u8] = &[0x01, 0x00];
+        assert_eq!(
+            Option::<u16>::decode_from(&mut truncated),
+            Err(DecodeError::UnexpectedEnd {
+                offset: 1,
+                needed: 2,
+                available: 1
+            })
+        );
+        assert_eq!(truncated, &[0x01, 0x00]);
+    }
+
+    #[test]
+    fn should_reject_invalid_bool_and_nan() {
+        let mut dec = Decoder::new(&[0x02]);
+        assert_eq!(
+            dec.decode_bool(),
+            Err(DecodeError::Invalid {
+                offset: 0,
+                expected: "bool"
+            })
+        );
+        let nan = [0xff; 8];
+        assert!(Decoder::new(&nan).decode_f64().is_err());
+        let canonical = LexKey::encode_f64_with(-f64::NAN, F64Mode::TotalOrder);
+        let x = Decoder::new(canonical.as_bytes())
+            .decode_f64_with(F64Mode::TotalOrder)
+            .unwrap();
+        assert!(x.is_nan() && x.is_sign_negative());
+        assert_eq!(
+            DecodeError::Invalid {
+                offset: 3,
+                expected: "f64"
+            }
+            .to_string(),
+            "invalid f64 encoding at offset 3"
+        );
+    }
+
+    #[test]
+    fn should_decode_descending_values_reading_only_their_bytes() {
+        use crate::desc::Desc;
+
+        let key = crate::encode_composite!(
+            Desc("a\x00b"),
+            Desc(-7i64),
+            Desc(Some("ab")),
+            Desc(None::<u32>),
+            "tail"
+        );
+        let mut dec = Decoder::new(key.as_bytes());
+        assert_eq!(dec.decode_value_desc::<String>(), Ok("a\x00b".to_owned()));
+        dec.expect_separator().unwrap();
+        assert_eq!(dec.decode_value_desc::<i64>(), Ok(-7));
+        dec.expect_separator().unwrap();
+        assert_eq!(
+            dec.decode_value_desc::<Option<String>>(),
+            Ok(Some("ab".to_owned()))
+        );
+        dec.expect_separator().unwrap();
+        assert_eq!(dec.decode_value_desc::<Option<u32>>(), Ok(None));
+        dec.expect_separator().unwrap();
+        assert_eq!(dec.decode_value::<String>(), Ok("tail".to_owned()));
+        assert!(dec.is_empty());
+
+        let truncated = LexKey::encode_string_desc("ab");
+        let bytes = &truncated.as_bytes()[..3];
+        assert_eq!(
+            Decoder::new(bytes).decode_value_desc::<String>(),
+            Err(DecodeError::UnexpectedEnd {
+                offset: 2,
+                needed: 2,
+                available: 1
+            })
+        );
+        assert_eq!(
+            Decoder::new(&[0xfe]).decode_value_desc::<u16>(),
+            Err(DecodeError::UnexpectedEnd {
+                offset: 0,
+                needed: 2,
+                available: 1
+            })
+        );
+    }
+}
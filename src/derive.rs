@@ -0,0 +1,58 @@
+//! Derived composite keys for structs.
+//!
+//! `#[derive(LexKeyEncode)]` encodes a struct as a composite key: each field in declaration order
+//! through its `Encodable` impl, joined by `SEPARATOR`, exactly like `encode_composite!` over
+//! the same values. The derive implements `Encodable` (so derived keys nest and mix with other
+//! parts) and adds two inherent methods:
+//!
+//! - `to_lexkey(&self) -> LexKey` allocates the key.
+//! - `encode_into(&self, &mut Encoder) -> usize` appends it to a reusable encoder.
+//!
+//! Field attributes:
+//!
+//! - `#[lexkey(desc)]` encodes the field in descending order, like wrapping it in `Desc`:
+//!   fixed-width fields are complemented, and strings and byte strings use the escaped,
+//!   terminated layout of `LexKey::encode_string_desc`.
+//! - `#[lexkey(nulls_last)]` on an `Option` field writes `None` as `02`, after every present
+//!   value, like wrapping it in `NullsLast`; the default is `00`, before them (`NullOrder`).
+//!
+//! Derived types also implement the descending methods of `Encodable`, so `Desc(key)` reverses
+//! the whole value: each field is written in the opposite of its own direction, joined by plain
+//! separators, as `Desc` does for tuples.
+//!
+//! Enums encode like `LexKey::encode_enum`: the variant's ordinal as a 2-byte `u16`, followed by
+//! its fields as above. Ordinals count from 0 in declaration order, so keys sort by variant
+//! position rather than name; `#[lexkey(ordinal = N)]` on a variant pins its ordinal (later
+//! variants continue from `N + 1`), which keeps stored keys valid when variants are reordered.
+//!
+//! `#[derive(LexKeyDecode)]` is the inverse for structs whose fields are all `Decodable`: it
+//! implements `Decodable` and adds `from_lexkey(&[u8]) -> Result<Self, DecodeError>`, which
+//! rejects trailing bytes. Ascending strings and byte parts are read up to the next separator, so
+//! only the last of them may contain `0x00`; `#[lexkey(desc)]` strings are self-terminating. The
+//! derive also implements `decode_desc_from`, reading back what `Desc` over the type writes.
+//!
+//! ```rust
+//! use lexkey::{encode_composite, LexKeyDecode, LexKeyEncode};
+//! use uuid::Uuid;
+//!
+//! #[derive(Debug, PartialEq, LexKeyEncode, LexKeyDecode)]
+//! struct OrderKey {
+//!     tenant: String,
+//!     #[lexkey(desc)]
+//!     created_at: i64,
+//!     id: Uuid,
+//! }
+//!
+//! let key = |created_at| OrderKey { tenant: "acme".into(), created_at, id: Uuid::nil() };
+//! assert!(key(200).to_lexkey() < key(100).to_lexkey());
+//! assert_eq!(
+//!     key(100).to_lexkey(),
+//!     encode_composite!("acme", lexkey::desc::Desc(100i64), Uuid::nil()),
+//! );
+//!
+//! let stored = key(100).to_lexkey();
+//! assert_eq!(OrderKey::from_lexkey(stored.as_bytes())?, key(100));
+//! # Ok::<(), lexkey::DecodeError>(())
+//! ```
+
+pub use lexkey_derive::{LexKeyDecode, LexKeyEncode};
@@ -0,0 +1,426 @@
+//! Descending-order encoders.
+//!
+//! Each `_desc` encoder writes the bitwise complement of the ascending encoding, so larger values
+//! produce smaller keys and a forward scan returns them largest first. Fixed-width values keep
+//! their width, so they can sit anywhere inside an ascending composite such as
+//! `(tenant ASC, timestamp DESC)`.
+//!
+//! For mixed-direction composites, pick the direction per field with `SortOrder` or wrap values
+//! in `Desc`, which works anywhere an `Encodable` does, including `encode_composite!`.
+
+use bytes::BufMut;
+
+use crate::{escape, Encodable, Encoder, LexKey};
+
+/// Complement every byte written to `dst` from `start` on.
+#[inline]
+pub(crate) fn complement_from(dst: &mut [u8], start: usize) {
+    for b in &mut dst[start..] {
+        *b = !*b;
+    }
+}
+
+/// Append the complemented escaped form of `bytes`, the descending layout of strings and byte
+/// strings.
+#[inline]
+pub(crate) fn escaped_desc_into<B: BufMut + ?Sized>(dst: &mut B, bytes: &[u8]) -> usize {
+    escape::escape_flipped_into(dst, bytes, 0xff)
+}
+
+/// Write the complement of the `N`-byte encoding `encode` produces.
+#[inline]
+fn put_desc<const N: usize, B: BufMut + ?Sized>(
+    dst: &mut B,
+    encode: impl FnOnce(&mut [u8]) -> usize,
+) -> usize {
+    let mut bytes = [0u8; N];
+    let n = encode(&mut bytes[..]);
+    complement_from(&mut bytes, 0);
+    dst.put_slice(&bytes[..n]);
+    n
+}
+
+/// Direction of one field in a composite key.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum SortOrder {
+    /// Smaller values sort first; the plain encoding.
+    #[default]
+    Asc,
+    /// Larger values sort first; the byte complement of the plain encoding.
+    Desc,
+}
+
+impl SortOrder {
+    /// Append `value` in this direction and return bytes written.
+    #[inline]
+    pub fn encode_into<T: Encodable + ?Sized>(self, value: &T, dst: &mut Vec<u8>) -> usize {
+        match self {
+            Self::Asc => value.encode_into(dst),
+            Self::Desc => value.encode_desc_into(dst),
+        }
+    }
+}
+
+/// Encodes the wrapped value in descending order.
+///
+/// Writes `Encodable::encode_desc_into`: the byte complement of the plain encoding for
+/// fixed-width values such as integers, floats, bools and UUIDs. Complementing alone would put
+/// `"b"` before `"ba"` (a shorter value stays smaller), so strings and byte strings are escaped
+/// and terminated first, the layout of `LexKey::encode_string_desc`, and options and tuples
+/// apply the rule to each element.
+///
+/// ```rust
+/// use lexkey::desc::Desc;
+/// use lexkey::encode_composite;
+///
+/// let newer = encode_composite!("acme", Desc(1_700_000_000_000i64));
+/// let older = encode_composite!("acme", Desc(1_600_000_000_000i64));
+/// assert!(newer < older);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Desc<T>(pub T);
+
+impl<T: Encodable> Encodable for Desc<T> {
+    #[inline]
+    fn encoded_len(&self) -> usize {
+        self.0.encoded_desc_len()
+    }
+
+    #[inline]
+    fn encode_into(&self, dst: &mut Vec<u8>) -> usize {
+        SortOrder::Desc.encode_into(&self.0, dst)
+    }
+}
+
+impl LexKey {
+    /// Encode a composite whose parts each carry their own `SortOrder`.
+    ///
+    /// Parts are joined by `SEPARATOR` as in `encode_composite_encodables`; separators are never
+    /// complemented.
+    ///
+    /// ```rust
+    /// use lexkey::desc::SortOrder;
+    /// use lexkey::LexKey;
+    ///
+    /// let key = |score: u64, id: &str| {
+    ///     LexKey::encode_composite_ordered(&[(&score, SortOrder::Desc), (&id, SortOrder::Asc)])
+    /// };
+    /// assert!(key(90, "b") < key(80, "a"));
+    /// assert!(key(90, "a") < key(90, "b"));
+    /// ```
+    #[must_use]
+    pub fn encode_composite_ordered(parts: &[(&dyn Encodable, SortOrder)]) -> Self {
+        let total_len = parts.iter().map(|(p, _)| p.encoded_len()).sum::<usize>()
+            + parts.len().saturating_sub(1);
+        let mut buf = Vec::with_capacity(total_len);
+        for (i, (part, order)) in parts.iter().enumerate() {
+            if i > 0 {
+                buf.push(Self::SEPARATOR);
+            }
+            order.encode_into(*part, &mut buf);
+        }
+        #[cfg(feature = "metrics")]
+        crate::metrics::record(buf.len(), parts.len());
+        Self::from_bytes(buf)
+    }
+
+    /// Encode a signed integer so larger values sort first (8 bytes).
+    ///
+    /// ```rust
+    /// use lexkey::LexKey;
+    /// assert!(LexKey::encode_i64_desc(10) < LexKey::encode_i64_desc(-10));
+    /// assert_eq!(LexKey::encode_i64_desc(0).to_hex_string(), "7fffffffffffffff");
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn encode_i64_desc(n: i64) -> Self {
+        let mut buf = Vec::with_capacity(8);
+        Self::encode_i64_desc_into(&mut buf, n);
+        Self::from_bytes(buf)
+    }
+
+    /// Encode a UTC timestamp in UNIX nanoseconds so the most recent sorts first (8 bytes).
+    ///
+    /// This is the complement of `encode_time_unix_nanos`. Put it after the entity in a
+    /// composite and a forward prefix scan yields that entity's latest events first, so
+    /// "latest N" is a scan that stops after N keys.
+    ///
+    /// ```rust
+    /// use lexkey::{Encoder, LexKey};
+    ///
+    /// let event = |entity: &str, nanos: i64| {
+    ///     let mut enc = Encoder::with_capacity(16);
+    ///     enc.encode_string_into(entity);
+    ///     enc.push_separator();
+    ///     enc.encode_time_desc_into(nanos);
+    ///     enc.freeze()
+    /// };
+    /// let mut keys = [event("a", 1), event("a", 3), event("b", 2), event("a", 2)];
+    /// keys.sort();
+    /// assert_eq!(keys, [event("a", 3), event("a", 2), event("a", 1), event("b", 2)]);
+    /// assert_eq!(LexKey::encode_time_desc(0), LexKey::encode_i64_desc(0));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn encode_time_desc(nanos: i64) -> Self {
+        Self::encode_i64_desc(nanos)
+    }
+
+    /// Encode an unsigned integer so larger values sort first (8 bytes).
+    #[inline]
+    #[must_use]
+    pub fn encode_u64_desc(n: u64) -> Self {
+        let mut buf = Vec::with_capacity(8);
+        Self::encode_u64_desc_into(&mut buf, n);
+        Self::from_bytes(buf)
+    }
+
+    /// Encode an `f64` so larger values sort first (8 bytes).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is NaN.
+    #[inline]
+    #[must_use]
+    pub fn encode_f64_desc(x: f64) -> Self {
+        let mut buf = Vec::with_capacity(8);
+        Self::encode_f64_desc_into(&mut buf, x);
+        Self::from_bytes(buf)
+    }
+
+    /// Encode a string so it sorts in reverse order, correctly even inside composites.
+    ///
+    /// Complementing raw string bytes is not enough: `"ab"` would still sort before `"abc"`
+    /// because it is shorter. This complements the escaped, terminated form instead (each `0x00`
+    /// becomes `00 ff`, the end is `00 01`), giving `ff 00` per embedded NUL and `ff fe` at the
+    /// end. Longer strings with an equal prefix therefore sort first, and later composite parts
+    /// cannot change the order.
+    ///
+    /// ```rust
+    /// use lexkey::LexKey;
+    ///
+    /// let mut names = ["ab", "abc", "b", ""].map(LexKey::encode_string_desc);
+    /// names.sort();
+    /// assert_eq!(names, ["b", "abc", "ab", ""].map(LexKey::encode_string_desc));
+    /// assert_eq!(LexKey::encode_string_desc("a").to_hex_string(), "9efffe");
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn encode_string_desc(s: &str) -> Self {
+        let mut buf = Vec::with_capacity(escape::escaped_len(s.as_bytes()));
+        Self::encode_string_desc_into(&mut buf, s);
+        Self::from_bytes(buf)
+    }
+
+    /// Append the descending string encoding into `dst` and return bytes written.
+    #[inline]
+    pub fn encode_string_desc_into<B: BufMut + ?Sized>(dst: &mut B, s: &str) -> usize {
+        escaped_desc_into(dst, s.as_bytes())
+    }
+
+    /// Append the descending `i64` encoding into `dst` and return 8.
+    #[inline]
+    pub fn encode_i64_desc_into<B: BufMut + ?Sized>(dst: &mut B, n: i64) -> usize {
+        put_desc::<8, _>(dst, |mut d| Self::encode_i64_into(&mut d, n))
+    }
+
+    /// Append the descending timestamp encoding into `dst` and return 8.
+    #[inline]
+    pub fn encode_time_desc_into<B: BufMut + ?Sized>(dst: &mut B, nanos: i64) -> usize {
+        Self::encode_i64_desc_into(dst, nanos)
+    }
+
+    /// Append the descending `u64` encoding into `dst` and return 8.
+    #[inline]
+    pub fn encode_u64_desc_into<B: BufMut + ?Sized>(dst: &mut B, n: u64) -> usize {
+        put_desc::<8, _>(dst, |mut d| Self::encode_u64_into(&mut d, n))
+    }
+
+    /// Append the descending `f64` encoding into `dst` and return 8.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is NaN.
+    #[inline]
+    pub fn encode_f64_desc_into<B: BufMut + ?Sized>(dst: &mut B, x: f64) -> usize {
+        put_desc::<8, _>(dst, |mut d| Self::encode_f64_into(&mut d, x))
+    }
+}
+
+impl Encoder {
+    /// Append any `Encodable` value in the given direction.
+    #[inline]
+    pub fn encode_ordered_into<T: Encodable + ?Sized>(
+        &mut self,
+        value: &T,
+        order: SortOrder,
+    ) -> usize {
+        order.encode_into(value, &mut self.buf)
+    }
+
+    /// Append the descending string encoding; see `LexKey::encode_string_desc`.
+    #[inline]
+    pub fn encode_string_desc_into(&mut self, s: &str) -> usize {
+        LexKey::encode_string_desc_into(&mut self.buf, s)
+    }
+
+    /// Append the descending `i64` encoding.
+    #[inline]
+    pub fn encode_i64_desc_into(&mut self, n: i64) -> usize {
+        LexKey::encode_i64_desc_into(&mut self.buf, n)
+    }
+
+    /// Append the descending timestamp encoding; see `LexKey::encode_time_desc`.
+    #[inline]
+    pub fn encode_time_desc_into(&mut self, nanos: i64) -> usize {
+        LexKey::encode_time_desc_into(&mut self.buf, nanos)
+    }
+
+    /// Append the descending `u64` encoding.
+    #[inline]
+    pub fn encode_u64_desc_into(&mut self, n: u64) -> usize {
+        LexKey::encode_u64_desc_into(&mut self.buf, n)
+    }
+
+    /// Append the descending `f64` encoding.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is NaN.
+    #[inline]
+    pub fn encode_f64_desc_into(&mut self, x: f64) -> usize {
+        LexKey::encode_f64_desc_into(&mut self.buf, x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_reverse_numeric_order() {
+        let ints = [i64::MIN, -1, 0, 1, i64::MAX];
+        let uints = [0, 1, u64::MAX / 2, u64::MAX];
+        let floats = [f64::NEG_INFINITY, -1.5, -0.0, 0.0, 2.5, f64::INFINITY];
+
+        let i: Vec<_> = ints.iter().map(|&n| LexKey::encode_i64_desc(n)).collect();
+        let u: Vec<_> = uints.iter().map(|&n| LexKey::encode_u64_desc(n)).collect();
+        let f: Vec<_> = floats.iter().map(|&x| LexKey::encode_f64_desc(x)).collect();
+
+        assert!(i.windows(2).all(|w| w[0] > w[1]));
+        assert!(u.windows(2).all(|w| w[0] > w[1]));
+        assert!(f.windows(2).all(|w| w[0] > w[1]));
+    }
+
+    #[test]
+    fn should_order_descending_strings_inside_composites() {
+        let key = |name: &str, id: u8| {
+            let mut enc = Encoder::with_capacity(16);
+            enc.encode_string_desc_into(name);
+            enc.push_separator();
+            enc.encode_u8_into(id);
+            enc.freeze()
+        };
+        let ordered = [
+            key("b", 0),
+            key("a\u{0}b", 0),
+            key("a\u{0}", 9),
+            key("a", 0),
+            key("a", 1),
+            key("", 0),
+        ];
+        assert!(ordered.windows(2).all(|w| w[0] < w[1]), "{ordered:?}");
+        assert_eq!(
+            LexKey::encode_string_desc("a\u{0}").to_hex_string(),
+            "9eff00fffe"
+        );
+        let hex = |s| LexKey::encode_string_desc(s).to_hex_string();
+        assert_eq!(
+            [hex("a"), hex("b"), hex("ba")],
+            ["9efffe", "9dfffe", "9d9efffe"]
+        );
+    }
+
+    #[test]
+    fn should_agree_across_sort_order_apis() {
+        let via_macro = crate::encode_composite!("t", Desc(7u32), true);
+        let via_ordered = LexKey::encode_composite_ordered(&[
+            (&"t", SortOrder::Asc),
+            (&7u32, SortOrder::Desc),
+            (&true, SortOrder::Asc),
+        ]);
+        let mut enc = Encoder::with_capacity(16);
+        enc.encode_ordered_into(&"t", SortOrder::Asc);
+        enc.push_separator();
+        enc.encode_ordered_into(&7u32, SortOrder::Desc);
+        enc.push_separator();
+        enc.encode_ordered_into(&true, SortOrder::default());
+
+        assert_eq!(via_macro, via_ordered);
+        assert_eq!(enc.as_slice(), via_macro.as_bytes());
+        assert_eq!(via_macro.to_hex_string(), "7400fffffff80001");
+        assert_eq!(
+            LexKey::encode_composite_ordered(&[(&-3i64, SortOrder::Desc)]),
+            LexKey::encode_i64_desc(-3)
+        );
+    }
+
+    #[test]
+    fn should_order_prefix_related_strings_in_mixed_direction_composites() {
+        let key = |tenant: &str, name: &str, id: u8| {
+            LexKey::encode_composite_ordered(&[
+                (&tenant, SortOrder::Asc),
+                (&name, SortOrder::Desc),
+                (&id, SortOrder::Asc),
+            ])
+        };
+        let ordered = [
+            key("a", "ba", 0),
+            key("a", "b", 0),
+            key("a", "b", 1),
+            key("a", "", 9),
+            key("b", "z", 0),
+        ];
+        assert!(ordered.windows(2).all(|w| w[0] < w[1]), "{ordered:?}");
+        assert_eq!(
+            crate::encode_composite!("a", Desc("b"), 0u8),
+            key("a", "b", 0)
+        );
+        assert_eq!(
+            key("a", "b", 0).to_hex_string(),
+            format!(
+                "6100{}0000",
+                LexKey::encode_string_desc("b").to_hex_string()
+            )
+        );
+
+        let bytes = |b: &[u8], tail: &[u8]| crate::encode_composite!(Desc(b), Desc(Some(b)), tail);
+        assert!(bytes(b"ba", b"") < bytes(b"b", b""));
+        assert!(bytes(b"b\0", b"") < bytes(b"b", b"\xff"));
+        let tuples = [Desc(("b", "a")), Desc(("b", "")), Desc(("a", "z"))];
+        let keys: Vec<LexKey> = tuples.iter().map(|t| crate::encode_composite!(t)).collect();
+        assert!(keys.windows(2).all(|w| w[0] < w[1]), "{keys:?}");
+        assert_eq!(
+            Desc("b").encoded_len(),
+            LexKey::encode_string_desc("b").len()
+        );
+    }
+
+    #[test]
+    fn should_build_mixed_direction_composite() {
+        let key = |tenant: &str, ts: i64| {
+            let mut enc = Encoder::with_capacity(32);
+            enc.encode_string_into(tenant);
+            enc.push_separator();
+            enc.encode_i64_desc_into(ts);
+            enc.freeze()
+        };
+
+        assert!(key("acme", 200) < key("acme", 100));
+        assert!(key("acme", 100) < key("beta", 200));
+        assert_eq!(
+            key("a", 1).as_ref(),
+            [&b"a\x00"[..], LexKey::encode_i64_desc(1).as_bytes()].concat()
+        );
+    }
+}
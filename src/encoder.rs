@@ -1,10 +1,13 @@
-use bytes::Bytes;
+use bytes::{Bytes, BytesMut};
+use std::cmp::Ordering;
 use uuid::Uuid;
 
 const SIGN_BIT: u64 = 0x8000_0000_0000_0000;
 const SIGN_BIT_8: u8 = 0x80;
 const SIGN_BIT_16: u16 = 0x8000;
 const SIGN_BIT_32: u32 = 0x8000_0000;
+/// Minimum size of each shared allocation `split_key` copies keys into.
+const SPLIT_CHUNK: usize = 4096;
 
 /// A fast, one-way, lexicographically sortable key encoder.
 ///
@@ -18,8 +21,35 @@ const SIGN_BIT_32: u32 = 0x8000_0000;
 /// - floats: IEEE-754 sortable transform at declared width (error on NaN)
 ///
 /// `Encoder` is reusable; call `clear()` between uses.
+///
+/// To emit several keys from one buffer, call `finish_key()` after each key and `freeze_all()`
+/// at the end; the returned keys share one allocation. To hand keys out one at a time, call
+/// `split_key()` after each key instead.
+///
+/// Every `LexKey::encode_x(args) -> LexKey` constructor has an `Encoder::encode_x_into(args)`
+/// that appends the same bytes and returns the number written (`Option`/`Result` constructors
+/// return the count inside the same wrapper), except for the rows below. The baseline names
+/// `encode_uuid_into_buf`, `encode_composite_into_buf` and `push_end_marker` stay as aliases.
+///
+/// | `LexKey`                  | `Encoder`                                               |
+/// |---------------------------|---------------------------------------------------------|
+/// | `encode_uuid_v1_sortable` | `encode_uuid_v1_time_ordered_into`                      |
+/// | `encode_range_bounds`     | `encode_range_lower_into` and `encode_range_upper_into` |
 pub struct Encoder {
-    buf: Vec<u8>,
+    pub(crate) buf: Vec<u8>,
+    ends: Vec<usize>,
+    split: BytesMut,
+    prefix_len: usize,
+}
+
+/// A saved position in an `Encoder`, taken with `Encoder::snapshot`.
+///
+/// A snapshot is two offsets into the encoder's buffer, so taking one never copies. It stays
+/// valid until the encoder is cleared, frozen or consumed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EncoderSnapshot {
+    start: usize,
+    end: usize,
 }
 
 impl Encoder {
@@ -28,17 +58,265 @@ impl Encoder {
     pub fn with_capacity(cap: usize) -> Self {
         Self {
             buf: Vec::with_capacity(cap),
+            ends: Vec::new(),
+            split: BytesMut::new(),
+            prefix_len: 0,
         }
     }
 
-    /// Reset the internal buffer so the encoder can be reused.
+    /// Create an encoder whose buffer always starts with `prefix`.
+    ///
+    /// The prefix is written once and pinned: `clear()` truncates back to it instead of to
+    /// empty, and `split_key()` keeps it for the next key, so a hot loop over
+    /// `tenant || 00 || suffix` keys only encodes the suffix. Build the prefix with any encoder,
+    /// typically ending in `SEPARATOR`.
+    ///
+    /// ```rust
+    /// use lexkey::{encode_composite, Encoder, LexKey};
+    ///
+    /// let prefix = LexKey::encode_first(&[b"acme"]);
+    /// let mut enc = Encoder::with_prefix(prefix.as_bytes(), 32);
+    /// for id in [7u64, 8] {
+    ///     enc.clear();
+    ///     enc.encode_u64_into(id);
+    ///     assert_eq!(enc.as_slice(), encode_composite!("acme", id).as_bytes());
+    /// }
+    /// assert_eq!(enc.prefix(), b"acme\x00");
+    /// ```
+    #[must_use]
+    pub fn with_prefix(prefix: &[u8], cap: usize) -> Self {
+        let mut enc = Self::with_capacity(prefix.len() + cap);
+        enc.buf.extend_from_slice(prefix);
+        enc.prefix_len = prefix.len();
+        enc
+    }
+
+    /// The pinned prefix set by `with_prefix`, empty for other encoders.
+    #[inline]
+    #[must_use]
+    pub fn prefix(&self) -> &[u8] {
+        &self.buf[..self.prefix_len]
+    }
+
+    /// Reset the internal buffer and any finished keys so the encoder can be reused.
+    ///
+    /// An encoder made with `with_prefix` keeps its prefix.
     pub fn clear(&mut self) {
-        self.buf.clear();
+        self.buf.truncate(self.prefix_len);
+        self.ends.clear();
+    }
+
+    /// Clear the encoder and drop its pinned prefix, keeping the buffer's capacity.
+    pub(crate) fn reset(&mut self) {
+        self.prefix_len = 0;
+        self.clear();
+    }
+
+    /// Mark the end of the current key; bytes written afterwards start the next key.
+    ///
+    /// An encoder made with `with_prefix` starts the next key with a copy of its pinned prefix,
+    /// so every finished key carries it. Finished keys are only split apart by `freeze_all`.
+    /// `freeze`, `into_vec` and `as_slice` still see the whole buffer.
+    #[inline]
+    pub fn finish_key(&mut self) {
+        self.ends.push(self.buf.len());
+        if self.prefix_len > 0 {
+            self.buf.extend_from_within(..self.prefix_len);
+        }
+    }
+
+    /// Offset in the buffer where the key being written began.
+    #[inline]
+    pub(crate) fn current_key_start(&self) -> usize {
+        self.ends.last().copied().unwrap_or(0)
+    }
+
+    /// Save the key written so far so several sibling keys can branch from it.
+    ///
+    /// ```rust
+    /// use lexkey::Encoder;
+    ///
+    /// let mut enc = Encoder::with_capacity(64);
+    /// enc.encode_string_into("users");
+    /// enc.push_separator();
+    /// enc.encode_u64_into(42);
+    /// enc.push_separator();
+    /// let row = enc.snapshot();
+    ///
+    /// for index in ["by_email", "by_name"] {
+    ///     enc.resume(row);
+    ///     enc.encode_string_into(index);
+    ///     enc.finish_key();
+    /// }
+    /// let keys = enc.freeze_all();
+    /// assert!(keys[0].ends_with(b"by_email") && keys[1].ends_with(b"by_name"));
+    /// assert_eq!(keys[0][..15], keys[1][..15]);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn snapshot(&self) -> EncoderSnapshot {
+        EncoderSnapshot {
+            start: self.current_key_start(),
+            end: self.buf.len(),
+        }
+    }
+
+    /// Continue the current key from a snapshot, discarding anything written after it.
+    ///
+    /// If keys were finished since the snapshot, the saved prefix is copied from earlier in the
+    /// buffer to start a new key; unfinished bytes of the current key are dropped either way.
+    /// The shared prefix is copied as raw bytes and never re-encoded.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the snapshot's bytes are no longer in the buffer, e.g. after `clear`.
+    pub fn resume(&mut self, snapshot: EncoderSnapshot) {
+        let start = self.current_key_start();
+        let valid = if start == snapshot.start {
+            snapshot.end <= self.buf.len()
+        } else {
+            snapshot.end <= start
+        };
+        assert!(valid, "snapshot does not belong to this encoder state");
+        if start == snapshot.start {
+            self.buf.truncate(snapshot.end);
+        } else {
+            self.buf.truncate(start);
+            self.buf.extend_from_within(snapshot.start..snapshot.end);
+        }
+    }
+
+    /// Take the key being written as an independent `Bytes`, keeping the encoder's capacity.
+    ///
+    /// The key's bytes are copied into a shared chunk of at least 4 KiB and split off it with
+    /// `BytesMut::split`, so producing many keys costs one copy each and an allocation only per
+    /// chunk, while the encoder's buffer is reused for the next key. Keys already marked with
+    /// `finish_key` stay in the buffer, and so does a prefix pinned with `with_prefix`, which
+    /// every split key includes.
+    ///
+    /// ```rust
+    /// use lexkey::Encoder;
+    ///
+    /// let mut enc = Encoder::with_capacity(32);
+    /// let keys: Vec<_> = (0..3u64)
+    ///     .map(|id| {
+    ///         enc.encode_string_into("user");
+    ///         enc.push_separator();
+    ///         enc.encode_u64_into(id);
+    ///         enc.split_key()
+    ///     })
+    ///     .collect();
+    /// assert!(enc.is_empty());
+    /// assert!(keys[0] < keys[1] && keys[1] < keys[2]);
+    /// assert_eq!(keys[2].len(), 13);
+    /// ```
+    pub fn split_key(&mut self) -> Bytes {
+        let start = self.current_key_start();
+        self.split_off_key(start, start + self.prefix_len)
+    }
+
+    /// Copy `buf[start..]` into the shared split chunk, truncate the buffer to `keep` and
+    /// return the copy.
+    fn split_off_key(&mut self, start: usize, keep: usize) -> Bytes {
+        let key = &self.buf[start..];
+        if self.split.capacity() < key.len() {
+            self.split.reserve(key.len().max(SPLIT_CHUNK));
+        }
+        self.split.extend_from_slice(key);
+        self.buf.truncate(keep);
+        let key = self.split.split().freeze();
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_bytes(&key);
+        key
+    }
+
+    /// Build one key per item, all starting with the key written so far.
+    ///
+    /// For each item, `f` appends the rest of its key; the key is then taken as with
+    /// `split_key` and the buffer rewound to the shared head, so a bulk load reuses one buffer
+    /// and allocates only per 4 KiB chunk of output. After `clear`, the head is empty, or the
+    /// pinned prefix of an encoder made with `with_prefix`.
+    ///
+    /// ```rust
+    /// use lexkey::{encode_composite, Encoder};
+    ///
+    /// let mut enc = Encoder::with_capacity(64);
+    /// enc.encode(&"orders").sep();
+    /// let keys = enc.encode_batch([3u64, 1, 2], |enc, id| {
+    ///     enc.encode(&id);
+    /// });
+    /// assert_eq!(keys.len(), 3);
+    /// assert_eq!(keys[1], encode_composite!("orders", 1u64).as_bytes());
+    /// assert_eq!(enc.as_slice(), b"orders\x00");
+    /// ```
+    pub fn encode_batch<I, F>(&mut self, items: I, mut f: F) -> Vec<Bytes>
+    where
+        I: IntoIterator,
+        F: FnMut(&mut Self, I::Item),
+    {
+        let items = items.into_iter();
+        let start = self.current_key_start();
+        let head = self.buf.len();
+        let mut keys = Vec::with_capacity(items.size_hint().0);
+        for item in items {
+            f(self, item);
+            keys.push(self.split_off_key(start, head));
+        }
+        keys
+    }
+
+    /// Number of keys marked with `finish_key` since the last `clear`.
+    #[inline]
+    #[must_use]
+    pub fn finished_keys(&self) -> usize {
+        self.ends.len()
+    }
+
+    /// Split the buffer into the finished keys, all sharing one allocation.
+    ///
+    /// Bytes written after the last `finish_key` form a final key when non-empty, or for an
+    /// encoder made with `with_prefix`, when anything follows the pinned prefix. Finishing a key
+    /// without writing anything yields an empty key, or just the prefix.
+    ///
+    /// ```rust
+    /// use lexkey::Encoder;
+    ///
+    /// let mut enc = Encoder::with_capacity(64);
+    /// for index in ["by_name", "by_email"] {
+    ///     enc.encode_string_into(index);
+    ///     enc.push_separator();
+    ///     enc.encode_u64_into(42);
+    ///     enc.finish_key();
+    /// }
+    /// let keys = enc.freeze_all();
+    /// assert_eq!(keys.len(), 2);
+    /// assert!(keys[0].starts_with(b"by_name\x00"));
+    /// ```
+    #[must_use]
+    pub fn freeze_all(mut self) -> Vec<Bytes> {
+        if self.current_key_start() + self.prefix_len < self.buf.len() {
+            self.ends.push(self.buf.len());
+        }
+        self.buf.truncate(self.current_key_start());
+        let all = Bytes::from(self.buf);
+        let mut start = 0;
+        self.ends
+            .into_iter()
+            .map(|end| {
+                let key = all.slice(start..end);
+                start = end;
+                #[cfg(feature = "metrics")]
+                crate::metrics::record_bytes(&key);
+                key
+            })
+            .collect()
     }
 
     /// Convert the accumulated buffer into an immutable `Bytes`.
     #[must_use]
     pub fn freeze(self) -> Bytes {
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_bytes(&self.buf);
         Bytes::from(self.buf)
     }
 
@@ -49,6 +327,8 @@ impl Encoder {
     /// out to a `Vec`.
     #[must_use]
     pub fn into_vec(self) -> Vec<u8> {
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_bytes(&self.buf);
         self.buf
     }
 
@@ -58,6 +338,36 @@ impl Encoder {
         &self.buf
     }
 
+    /// Compare the key being written against `target` as far as the written bytes decide it.
+    ///
+    /// Returns `Some(ordering)` once the final key's order relative to `target` can no longer
+    /// change: a byte already differs, or the key has grown past a `target` it starts with (the
+    /// key is then `Greater`). Returns `None` while the written bytes are a prefix of `target`,
+    /// including when they equal it, because later writes still decide the order. Only bytes
+    /// since the last `finish_key` count.
+    ///
+    /// ```rust
+    /// use lexkey::Encoder;
+    /// use std::cmp::Ordering;
+    ///
+    /// let cursor = b"tenant\x00\x80\x00\x00\x00\x00\x00\x00\x2a";
+    /// let mut enc = Encoder::with_capacity(32);
+    /// enc.encode_string_into("tenant");
+    /// enc.push_separator();
+    /// assert_eq!(enc.cmp_partial(cursor), None);
+    /// enc.encode_i64_into(7);
+    /// assert_eq!(enc.cmp_partial(cursor), Some(Ordering::Less));
+    /// ```
+    #[must_use]
+    pub fn cmp_partial(&self, target: &[u8]) -> Option<Ordering> {
+        let written = &self.buf[self.current_key_start()..];
+        match written.iter().zip(target).find(|(a, b)| a != b) {
+            Some((a, b)) => Some(a.cmp(b)),
+            None if written.len() > target.len() => Some(Ordering::Greater),
+            None => None,
+        }
+    }
+
     /// Return current buffer length.
     #[inline]
     #[must_use]
@@ -112,6 +422,12 @@ impl Encoder {
         8
     }
 
+    /// Append the 16-byte big-endian encoding of a `u128`.
+    #[inline]
+    pub fn encode_u128_into(&mut self, n: u128) -> usize {
+        crate::LexKey::encode_u128_into(&mut self.buf, n)
+    }
+
     /// Append the native 1-byte encoding of a `u8`.
     #[inline]
     pub fn encode_u8_into(&mut self, n: u8) -> usize {
@@ -145,6 +461,12 @@ impl Encoder {
         8
     }
 
+    /// Append the 16-byte sortable encoding of an `i128`.
+    #[inline]
+    pub fn encode_i128_into(&mut self, n: i128) -> usize {
+        crate::LexKey::encode_i128_into(&mut self.buf, n)
+    }
+
     /// Append the native 1-byte sortable encoding of an `i8`.
     #[inline]
     pub fn encode_i8_into(&mut self, n: i8) -> usize {
@@ -190,6 +512,36 @@ impl Encoder {
         8
     }
 
+    /// Append an `f64` under an explicit NaN policy; see `LexKey::encode_f64_with`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is NaN and `mode` is `F64Mode::RejectNaN`.
+    #[inline]
+    pub fn encode_f64_with_into(&mut self, x: f64, mode: crate::F64Mode) -> usize {
+        crate::LexKey::encode_f64_with_into(&mut self.buf, x, mode)
+    }
+
+    /// Append an optional `f64` behind a presence marker; see `LexKey::encode_opt_f64`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is `Some(NaN)`.
+    #[inline]
+    pub fn encode_opt_f64_into(&mut self, x: Option<f64>, nulls: crate::NullOrder) -> usize {
+        crate::LexKey::encode_opt_f64_into(&mut self.buf, x, nulls)
+    }
+
+    /// Append any optional value behind a presence marker; see `LexKey::encode_opt`.
+    #[inline]
+    pub fn encode_opt_into<T: crate::Encodable + ?Sized>(
+        &mut self,
+        value: Option<&T>,
+        nulls: crate::NullOrder,
+    ) -> usize {
+        crate::lexkey::encode_opt_vec_into(&mut self.buf, value, nulls)
+    }
+
     /// Append the native 4-byte sortable IEEE-754 encoding of an `f32`.
     ///
     /// # Panics
@@ -214,6 +566,81 @@ impl Encoder {
         16
     }
 
+    /// Append the 16-byte RFC-4122 UUID representation; the same as `encode_uuid_into_buf`.
+    #[inline]
+    pub fn encode_uuid_into(&mut self, u: &Uuid) -> usize {
+        self.encode_uuid_into_buf(u)
+    }
+
+    /// Append a boolean as one byte: `false -> 0x00`, `true -> 0x01`.
+    #[inline]
+    pub fn encode_bool_into(&mut self, b: bool) -> usize {
+        crate::LexKey::encode_bool_into(&mut self.buf, b)
+    }
+
+    /// Append a UTC timestamp in UNIX nanoseconds; see `LexKey::encode_time_unix_nanos`.
+    #[inline]
+    pub fn encode_time_unix_nanos_into(&mut self, nanos: i64) -> usize {
+        crate::LexKey::encode_i64_into(&mut self.buf, nanos)
+    }
+
+    /// Append the time-ordered form of a v1 UUID (see `LexKey::encode_uuid_v1_time_ordered`).
+    #[inline]
+    pub fn encode_uuid_v1_time_ordered_into(&mut self, u: &Uuid) -> usize {
+        crate::LexKey::encode_uuid_v1_time_ordered_into(&mut self.buf, u)
+    }
+
+    /// Append any `Encodable` value, returning bytes written.
+    ///
+    /// ```rust
+    /// use lexkey::{Encoder, LexKey};
+    ///
+    /// let mut enc = Encoder::with_capacity(16);
+    /// enc.encode_value_into(&42u32);
+    /// assert_eq!(enc.as_slice(), LexKey::encode_u32(42).as_bytes());
+    /// ```
+    #[inline]
+    pub fn encode_value_into<T: crate::Encodable + ?Sized>(&mut self, value: &T) -> usize {
+        self.buf.reserve(value.encoded_len());
+        value.encode_into(&mut self.buf)
+    }
+
+    /// Append any `Encodable` value and return `self` for chaining.
+    ///
+    /// ```rust
+    /// use lexkey::{encode_composite, Encoder};
+    /// use uuid::Uuid;
+    ///
+    /// let (tenant, id, ts) = ("acme", Uuid::nil(), 1_700_000_000i64);
+    /// let mut enc = Encoder::with_capacity(64);
+    /// enc.encode(&tenant).sep().encode(&id).sep().encode(&ts);
+    /// assert_eq!(enc.as_slice(), encode_composite!(tenant, id, ts).as_bytes());
+    /// ```
+    #[inline]
+    pub fn encode<T: crate::Encodable + ?Sized>(&mut self, value: &T) -> &mut Self {
+        self.encode_value_into(value);
+        self
+    }
+
+    /// Append the composite part separator (`0x00`) and return `self` for chaining.
+    #[inline]
+    pub fn sep(&mut self) -> &mut Self {
+        self.push_separator();
+        self
+    }
+
+    /// Append typed parts from an iterator joined by separators; see `LexKey::encode_parts`.
+    ///
+    /// Returns the number of parts written.
+    #[inline]
+    pub fn encode_parts_into<I>(&mut self, parts: I) -> usize
+    where
+        I: IntoIterator,
+        I::Item: crate::Encodable,
+    {
+        crate::lexkey::encode_parts_vec_into(&mut self.buf, parts)
+    }
+
     /// Append a composite multi-part key separated by `0x00`.
     ///
     /// Parts are copied as-is. Empty parts are allowed and can produce adjacent
@@ -228,6 +655,94 @@ impl Encoder {
         self.buf.reserve(total);
         crate::encode_parts_into(&mut self.buf, parts)
     }
+
+    /// Append a composite multi-part key; the same as `encode_composite_into_buf`.
+    #[inline]
+    pub fn encode_composite_into(&mut self, parts: &[&[u8]]) -> usize {
+        self.encode_composite_into_buf(parts)
+    }
+
+    /// Append `Encodable` parts joined by separators; see `LexKey::encode_composite_encodables`.
+    #[inline]
+    pub fn encode_composite_encodables_into(&mut self, parts: &[&dyn crate::Encodable]) -> usize {
+        let start = self.buf.len();
+        self.encode_parts_into(parts);
+        self.buf.len() - start
+    }
+
+    /// Append the end sentinel (`0xFF`) and return 1; see `LexKey::encode_end_marker`.
+    #[inline]
+    pub fn encode_end_marker_into(&mut self) -> usize {
+        self.push_end_marker();
+        1
+    }
+
+    /// Append `parts` followed by `SEPARATOR`; see `LexKey::encode_first`.
+    #[inline]
+    pub fn encode_first_into(&mut self, parts: &[&[u8]]) -> usize {
+        let n = self.encode_composite_into_buf(parts);
+        self.push_separator();
+        n + 1
+    }
+
+    /// Append `parts` followed by `END_MARKER`; see `LexKey::encode_last`.
+    #[inline]
+    pub fn encode_last_into(&mut self, parts: &[&[u8]]) -> usize {
+        let n = self.encode_composite_into_buf(parts);
+        self.push_end_marker();
+        n + 1
+    }
+
+    /// Append a range lower bound, `P || 00 [|| L]`; see `LexKey::encode_range_lower`.
+    #[inline]
+    pub fn encode_range_lower_into(&mut self, partition: &[u8], row_lower: Option<&[u8]>) -> usize {
+        let start = self.buf.len();
+        self.buf.extend_from_slice(partition);
+        self.push_separator();
+        if let Some(row) = row_lower {
+            self.buf.extend_from_slice(row);
+        }
+        self.buf.len() - start
+    }
+
+    /// Append a range upper bound, `P || 00 || U || ff` or `P || ff`; see
+    /// `LexKey::encode_range_upper`.
+    #[inline]
+    pub fn encode_range_upper_into(&mut self, partition: &[u8], row_upper: Option<&[u8]>) -> usize {
+        let start = self.buf.len();
+        self.buf.extend_from_slice(partition);
+        if let Some(row) = row_upper {
+            self.push_separator();
+            self.buf.extend_from_slice(row);
+        }
+        self.push_end_marker();
+        self.buf.len() - start
+    }
+}
+
+/// Append bytes from an iterator, e.g. an escaping adapter, without an intermediate buffer.
+impl Extend<u8> for Encoder {
+    #[inline]
+    fn extend<I: IntoIterator<Item = u8>>(&mut self, iter: I) {
+        self.buf.extend(iter);
+    }
+}
+
+impl<'a> Extend<&'a u8> for Encoder {
+    #[inline]
+    fn extend<I: IntoIterator<Item = &'a u8>>(&mut self, iter: I) {
+        self.buf.extend(iter);
+    }
+}
+
+/// Append raw byte chunks back to back; no separators are inserted.
+impl<'a> Extend<&'a [u8]> for Encoder {
+    #[inline]
+    fn extend<I: IntoIterator<Item = &'a [u8]>>(&mut self, iter: I) {
+        for chunk in iter {
+            self.buf.extend_from_slice(chunk);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -264,6 +779,85 @@ mod tests {
         assert_eq!(out, b"realm\x00kv\x00");
     }
 
+    #[test]
+    fn should_split_finished_keys_over_one_allocation() {
+        let mut enc = Encoder::with_capacity(32);
+        enc.encode_string_into("a");
+        enc.finish_key();
+        enc.finish_key();
+        enc.encode_u16_into(0x0102);
+        enc.push_separator();
+        enc.encode_string_into("bc");
+        assert_eq!(enc.finished_keys(), 2);
+
+        let keys = enc.freeze_all();
+
+        assert_eq!(keys.len(), 3);
+        assert_eq!(keys[0].as_ref(), b"a");
+        assert!(keys[1].is_empty());
+        assert_eq!(keys[2].as_ref(), b"\x01\x02\x00bc");
+        let base = keys[0].as_ptr();
+        assert_eq!(keys[2].as_ptr(), base.wrapping_add(1));
+    }
+
+    #[test]
+    fn should_forget_finished_keys_on_clear() {
+        let mut enc = Encoder::with_capacity(8);
+        enc.encode_string_into("old");
+        enc.finish_key();
+        enc.clear();
+        assert_eq!(enc.finished_keys(), 0);
+        assert!(enc.freeze_all().is_empty());
+    }
+
+    #[test]
+    fn should_decide_order_against_target_as_soon_as_possible() {
+        let mut enc = Encoder::with_capacity(16);
+        assert_eq!(enc.cmp_partial(b"abc"), None);
+        enc.encode_bytes_into(b"ab");
+        assert_eq!(enc.cmp_partial(b"abc"), None);
+        enc.push_byte(b'c');
+        assert_eq!(enc.cmp_partial(b"abc"), None);
+        enc.push_byte(0);
+        assert_eq!(enc.cmp_partial(b"abc"), Some(Ordering::Greater));
+        assert_eq!(enc.cmp_partial(b"abd"), Some(Ordering::Less));
+        assert_eq!(enc.cmp_partial(b""), Some(Ordering::Greater));
+
+        enc.finish_key();
+        assert_eq!(enc.cmp_partial(b"abd"), None);
+        enc.push_byte(b'b');
+        assert_eq!(enc.cmp_partial(b"abd"), Some(Ordering::Greater));
+    }
+
+    #[test]
+    fn should_branch_sibling_keys_from_snapshot() {
+        let mut enc = Encoder::with_capacity(32);
+        enc.encode_string_into("p");
+        let snap = enc.snapshot();
+
+        enc.encode_string_into("-junk");
+        enc.resume(snap);
+        enc.encode_string_into("1");
+        enc.finish_key();
+        enc.resume(snap);
+        enc.encode_string_into("2");
+        enc.finish_key();
+        enc.resume(snap);
+
+        let keys = enc.freeze_all();
+        assert_eq!(keys, [&b"p1"[..], b"p2", b"p"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "snapshot does not belong to this encoder state")]
+    fn should_reject_snapshot_after_clear() {
+        let mut enc = Encoder::with_capacity(8);
+        enc.encode_string_into("prefix");
+        let snap = enc.snapshot();
+        enc.clear();
+        enc.resume(snap);
+    }
+
     #[test]
     fn should_return_eight_when_encoding_u64() {
         let mut enc = Encoder::with_capacity(64);
@@ -333,6 +927,15 @@ mod tests {
         assert!(enc.as_slice().contains(&0x00));
     }
 
+    #[test]
+    fn should_extend_from_byte_iterators() {
+        let mut enc = Encoder::with_capacity(16);
+        enc.extend(b"ab".iter().map(u8::to_ascii_uppercase));
+        enc.extend(&[0x00u8]);
+        enc.extend([b"cd".as_ref(), b"ef".as_ref()]);
+        assert_eq!(enc.as_slice(), b"AB\x00cdef");
+    }
+
     #[test]
     fn should_yield_bytes_after_push_and_freeze() {
         let mut enc = Encoder::with_capacity(16);
@@ -340,4 +943,196 @@ mod tests {
         let out = enc.freeze();
         assert!(!out.is_empty());
     }
+
+    #[test]
+    fn should_split_keys_from_shared_chunks_and_keep_capacity() {
+        let mut enc = Encoder::with_capacity(16);
+        let cap = enc.buf.capacity();
+        let keys: Vec<Bytes> = (0..100u32)
+            .map(|n| {
+                enc.encode_u32_into(n);
+                enc.split_key()
+            })
+            .collect();
+        assert_eq!(enc.buf.capacity(), cap);
+        assert_eq!(keys[42], 42u32.to_be_bytes()[..]);
+        // Consecutive keys sit next to each other in one allocation.
+        assert_eq!(keys[1].as_ptr(), keys[0].as_ptr().wrapping_add(4));
+
+        // Finished keys stay put; only the key being written is taken.
+        enc.encode_u8_into(1);
+        enc.finish_key();
+        enc.encode_u8_into(2);
+        assert_eq!(enc.split_key(), &[2][..]);
+        assert_eq!(enc.split_key(), Bytes::new());
+        let big = vec![7u8; SPLIT_CHUNK * 2];
+        enc.encode_bytes_into(&big);
+        assert_eq!(enc.split_key(), big);
+        assert_eq!(enc.freeze_all(), vec![Bytes::from_static(&[1])]);
+    }
+
+    #[test]
+    fn should_keep_pinned_prefix_across_clear_and_split() {
+        let mut enc = Encoder::with_prefix(b"t\x00", 8);
+        assert_eq!(enc.as_slice(), b"t\x00");
+        enc.encode_u8_into(1);
+        assert_eq!(enc.split_key(), &b"t\x00\x01"[..]);
+        assert_eq!(enc.as_slice(), b"t\x00");
+        enc.encode_u8_into(2);
+        enc.finish_key();
+        enc.clear();
+        assert_eq!(enc.finished_keys(), 0);
+        assert_eq!(enc.prefix(), b"t\x00");
+        assert_eq!(Encoder::with_capacity(4).prefix(), b"");
+    }
+
+    #[test]
+    fn should_chain_generic_encodes_and_separators() {
+        let mut enc = Encoder::with_capacity(16);
+        enc.encode("a")
+            .sep()
+            .encode(&Some(1u8))
+            .sep()
+            .sep()
+            .encode(&(2u16, true));
+        assert_eq!(enc.as_slice(), b"a\x00\x01\x01\x00\x00\x00\x02\x00\x01");
+    }
+
+    #[test]
+    fn should_encode_parts_from_iterators() {
+        let mut enc = Encoder::with_capacity(16);
+        let tags = vec![String::from("b"), String::from("a")];
+        assert_eq!(enc.encode_parts_into(&tags), 2);
+        enc.push_separator();
+        assert_eq!(enc.encode_parts_into((1u8..=3).map(|n| n * 2)), 3);
+        assert_eq!(enc.as_slice(), b"b\x00a\x00\x02\x00\x04\x00\x06");
+        assert_eq!(enc.encode_parts_into(std::iter::empty::<u8>()), 0);
+    }
+
+    #[test]
+    fn should_encode_batches_from_the_current_head() {
+        let mut enc = Encoder::with_prefix(b"p\x00", 8);
+        let keys = enc.encode_batch(0..1000u16, |enc, n| {
+            enc.encode_u16_into(n);
+        });
+        assert_eq!(keys.len(), 1000);
+        assert_eq!(keys[258], &b"p\x00\x01\x02"[..]);
+        assert!(keys.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(enc.as_slice(), b"p\x00");
+
+        enc.encode_u8_into(9);
+        enc.finish_key();
+        let keys = enc.encode_batch(["x"], |enc, s| {
+            enc.encode_string_into(s);
+        });
+        assert_eq!(keys, [Bytes::from_static(b"p\x00x")]);
+        assert_eq!(enc.finished_keys(), 1);
+    }
+
+    #[test]
+    fn should_start_every_finished_key_with_the_pinned_prefix() {
+        let mut enc = Encoder::with_prefix(b"acme\x00", 32);
+        for id in 1..=3u8 {
+            enc.encode_u8_into(id);
+            enc.finish_key();
+        }
+        assert_eq!(enc.finished_keys(), 3);
+        assert_eq!(
+            enc.as_slice(),
+            b"acme\x00\x01acme\x00\x02acme\x00\x03acme\x00"
+        );
+        let row = enc.snapshot();
+        enc.encode_u8_into(4);
+        assert_eq!(enc.split_key(), &b"acme\x00\x04"[..]);
+        enc.resume(row);
+        enc.encode_u8_into(5);
+
+        let keys = enc.freeze_all();
+        assert_eq!(
+            keys,
+            [
+                &b"acme\x00\x01"[..],
+                &b"acme\x00\x02"[..],
+                &b"acme\x00\x03"[..],
+                &b"acme\x00\x05"[..]
+            ]
+        );
+
+        let mut enc = Encoder::with_prefix(b"acme\x00", 8);
+        enc.encode_u8_into(1);
+        enc.finish_key();
+        assert_eq!(enc.freeze_all(), [&b"acme\x00\x01"[..]]);
+        assert!(Encoder::with_prefix(b"acme\x00", 8).freeze_all().is_empty());
+    }
+
+    #[test]
+    fn should_match_lexkey_for_every_named_counterpart() {
+        use crate::{F64Mode, LexKey, StringMode};
+        use std::net::{Ipv4Addr, Ipv6Addr};
+
+        let u = Uuid::from_u128(5);
+        let parts: [&[u8]; 2] = [b"a", b"b"];
+        let typed: [&dyn crate::Encodable; 2] = [&"t", &7u32];
+        let values = [crate::Value::from("v"), crate::Value::U64(1)];
+        let mut enc = Encoder::with_capacity(64);
+        let n = enc.encode_bool_into(true)
+            + enc.encode_time_unix_nanos_into(-9)
+            + enc.encode_uuid_into(&u)
+            + enc.encode_uuid_into_buf(&u)
+            + enc.encode_uuid_v1_time_ordered_into(&u)
+            + enc.encode_f64_with_into(f64::NAN, F64Mode::TotalOrder)
+            + enc.encode_f64_with_into(-1.0, F64Mode::TotalOrder)
+            + enc.encode_string_with_into("ab", StringMode::Escaped)
+            + enc.encode_string_with_into("ab", StringMode::Chunked)
+            + enc.encode_ipv4_into(Ipv4Addr::LOCALHOST)
+            + enc.encode_ipv6_into(Ipv6Addr::LOCALHOST)
+            + enc.encode_geohash_into(42.6, -5.6, 5)
+            + enc.encode_composite_into(&parts)
+            + enc.encode_composite_encodables_into(&typed)
+            + enc.encode_first_into(&parts)
+            + enc.encode_last_into(&parts)
+            + enc.encode_range_lower_into(b"p", Some(b"l"))
+            + enc.encode_range_lower_into(b"p", None)
+            + enc.encode_range_upper_into(b"p", Some(b"u"))
+            + enc.encode_range_upper_into(b"p", None)
+            + enc.encode_values_into(&values)
+            + enc.encode_end_marker_into();
+
+        let expected = [
+            LexKey::encode_bool(true),
+            LexKey::encode_time_unix_nanos(-9),
+            LexKey::encode_uuid(&u),
+            LexKey::encode_uuid(&u),
+            LexKey::encode_uuid_v1_sortable(&u),
+            LexKey::encode_f64_with(f64::NAN, F64Mode::TotalOrder),
+            LexKey::encode_f64_with(-1.0, F64Mode::TotalOrder),
+            LexKey::encode_string_with("ab", StringMode::Escaped),
+            LexKey::encode_string_with("ab", StringMode::Chunked),
+            LexKey::encode_ipv4(Ipv4Addr::LOCALHOST),
+            LexKey::encode_ipv6(Ipv6Addr::LOCALHOST),
+            LexKey::encode_geohash(42.6, -5.6, 5),
+            LexKey::encode_composite(&parts),
+            LexKey::encode_composite_encodables(&typed),
+            LexKey::encode_first(&parts),
+            LexKey::encode_last(&parts),
+            LexKey::encode_range_lower(b"p", Some(b"l")),
+            LexKey::encode_range_lower(b"p", None),
+            LexKey::encode_range_upper(b"p", Some(b"u")),
+            LexKey::encode_range_upper(b"p", None),
+            LexKey::encode_values(&values),
+            LexKey::encode_end_marker(),
+        ]
+        .iter()
+        .flat_map(|k| k.as_bytes().to_vec())
+        .collect::<Vec<u8>>();
+        assert_eq!(enc.as_slice(), expected);
+        assert_eq!(n, expected.len());
+
+        let (lower, upper) = LexKey::encode_range_bounds(b"p");
+        let mut enc = Encoder::with_capacity(8);
+        enc.encode_range_lower_into(b"p", None);
+        assert_eq!(enc.split_key(), lower.as_bytes());
+        enc.encode_range_upper_into(b"p", None);
+        assert_eq!(enc.split_key(), upper.as_bytes());
+    }
 }
@@ -0,0 +1,238 @@
+//! Self-terminating encoding for byte strings that may contain `0x00`.
+//!
+//! Every `0x00` in the input is written as `00 ff` and the value ends with `00 01`, the scheme
+//! the `FoundationDB` tuple layer uses. The result sorts like the raw bytes and no encoded value
+//! is a prefix of another, so it stays order-correct inside composites and under complementing.
+
+use bytes::BufMut;
+
+use crate::decoder::shift;
+use crate::{DecodeError, Decoder, Encoder, LexKey};
+
+/// Escape byte that follows an embedded `0x00`.
+const ESCAPED_NUL: u8 = 0xFF;
+/// The two-byte terminator; sorts below an escaped `0x00` (`00 ff`) and any other byte.
+const TERMINATOR: [u8; 2] = [0x00, 0x01];
+
+/// Append the escaped and terminated form of `bytes` into `dst` and return bytes written.
+pub(crate) fn escape_into<B: BufMut + ?Sized>(dst: &mut B, bytes: &[u8]) -> usize {
+    escape_flipped_into(dst, bytes, 0x00)
+}
+
+/// Append the escaped and terminated form of `bytes` with every byte xor-ed with `flip` (`0xff`
+/// for the complemented descending layout) and return bytes written.
+pub(crate) fn escape_flipped_into<B: BufMut + ?Sized>(
+    dst: &mut B,
+    bytes: &[u8],
+    flip: u8,
+) -> usize {
+    let mut n = 0;
+    for chunk in bytes.split_inclusive(|&b| b == 0x00) {
+        put_flipped(dst, chunk, flip);
+        n += chunk.len();
+        if chunk.last() == Some(&0x00) {
+            dst.put_u8(ESCAPED_NUL ^ flip);
+            n += 1;
+        }
+    }
+    put_flipped(dst, &TERMINATOR, flip);
+    n + TERMINATOR.len()
+}
+
+fn put_flipped<B: BufMut + ?Sized>(dst: &mut B, bytes: &[u8], flip: u8) {
+    if flip == 0x00 {
+        dst.put_slice(bytes);
+    } else {
+        for &b in bytes {
+            dst.put_u8(b ^ flip);
+        }
+    }
+}
+
+/// Read the escaped, terminated bytes at the front of `src` with every byte xor-ed with `flip`
+/// (`0xff` for the complemented descending layout). Returns the bytes and the length consumed;
+/// error offsets are relative to `src`.
+pub(crate) fn unescape(src: &[u8], flip: u8) -> Result<(Vec<u8>, usize), DecodeError> {
+    let mut out = Vec::with_capacity(src.len());
+    let mut i = 0;
+    loop {
+        let Some(nul) = src[i..].iter().position(|&b| b == flip) else {
+            return Err(DecodeError::UnexpectedEnd {
+                offset: src.len(),
+                needed: TERMINATOR.len(),
+                available: 0,
+            });
+        };
+        out.extend(src[i..i + nul].iter().map(|&b| b ^ flip));
+        i += nul;
+        match src.get(i + 1).map(|&b| b ^ flip) {
+            Some(ESCAPED_NUL) => out.push(0x00),
+            Some(b) if b == TERMINATOR[1] => return Ok((out, i + TERMINATOR.len())),
+            Some(_) => {
+                return Err(DecodeError::Invalid {
+                    offset: i,
+                    expected: "escaped bytes",
+                });
+            }
+            None => {
+                return Err(DecodeError::UnexpectedEnd {
+                    offset: i,
+                    needed: TERMINATOR.len(),
+                    available: 1,
+                });
+            }
+        }
+        i += 2;
+    }
+}
+
+/// Length of the escaped and terminated form of `bytes`.
+pub(crate) fn escaped_len(bytes: &[u8]) -> usize {
+    bytes.len() + bytes.iter().map(|&b| usize::from(b == 0x00)).sum::<usize>() + TERMINATOR.len()
+}
+
+impl LexKey {
+    /// Encode arbitrary bytes so they can be a composite part even when they contain `0x00`.
+    ///
+    /// Each `0x00` becomes `00 ff` and the value is terminated by `00 01`. Escaped values sort
+    /// like the raw bytes and a shorter value always sorts before longer values it is a prefix
+    /// of, whatever follows in the composite. `Decoder::decode_bytes_escaped` reverses it.
+    ///
+    /// ```rust
+    /// use lexkey::LexKey;
+    ///
+    /// assert_eq!(LexKey::encode_bytes_escaped(b"a\x00b").to_hex_string(), "6100ff620001");
+    ///
+    /// // As raw parts, ("a", 1) sorts after ("a\0", 0) because the NUL looks like a separator.
+    /// let raw_short = LexKey::encode_composite(&[b"a", &[1]]);
+    /// let raw_long = LexKey::encode_composite(&[b"a\x00", &[0]]);
+    /// assert!(raw_short > raw_long);
+    ///
+    /// let key = |blob: &[u8], n: u8| {
+    ///     let mut buf = LexKey::encode_bytes_escaped(blob).as_bytes().to_vec();
+    ///     buf.extend([LexKey::SEPARATOR, n]);
+    ///     LexKey::from_bytes(buf)
+    /// };
+    /// assert!(key(b"a", 1) < key(b"a\x00", 0));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn encode_bytes_escaped(bytes: &[u8]) -> Self {
+        let mut buf = Vec::with_capacity(escaped_len(bytes));
+        escape_into(&mut buf, bytes);
+        Self::from_bytes(buf)
+    }
+
+    /// Append the escaped, terminated form of `bytes` into `dst` and return bytes written.
+    #[inline]
+    pub fn encode_bytes_escaped_into<B: BufMut + ?Sized>(dst: &mut B, bytes: &[u8]) -> usize {
+        escape_into(dst, bytes)
+    }
+}
+
+impl Encoder {
+    /// Append the escaped, terminated form of `bytes`; see `LexKey::encode_bytes_escaped`.
+    #[inline]
+    pub fn encode_bytes_escaped_into(&mut self, bytes: &[u8]) -> usize {
+        escape_into(&mut self.buf, bytes)
+    }
+}
+
+impl Decoder<'_> {
+    /// Decode a value written by `encode_bytes_escaped`, consuming its terminator.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DecodeError::UnexpectedEnd` if the input ends before the `00 01` terminator and
+    /// `DecodeError::Invalid` if a `0x00` is followed by anything other than `ff` or `01`.
+    pub fn decode_bytes_escaped(&mut self) -> Result<Vec<u8>, DecodeError> {
+        let (out, n) = unescape(self.remaining(), 0x00).map_err(|e| shift(e, self.position()))?;
+        self.skip(n);
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_preserve_order_and_prefix_freedom() {
+        let mut raw: Vec<&[u8]> = vec![
+            b"",
+            b"\x00",
+            b"\x00\x00",
+            b"\x00\x01",
+            b"\x01",
+            b"a",
+            b"a\x00",
+            b"a\x00\xff",
+            b"a\x01",
+            b"ab",
+            b"\xff",
+        ];
+        raw.sort();
+        let encoded: Vec<_> = raw
+            .iter()
+            .map(|b| LexKey::encode_bytes_escaped(b))
+            .collect();
+
+        for (i, a) in encoded.iter().enumerate() {
+            for b in &encoded[i + 1..] {
+                assert!(a < b);
+                assert!(!b.as_bytes().starts_with(a.as_bytes()));
+            }
+        }
+    }
+
+    #[test]
+    fn should_round_trip_through_decoder_inside_composite() {
+        let blob = b"\x00k\x00\x00\xff";
+        let mut enc = Encoder::with_capacity(32);
+        enc.encode_bytes_escaped_into(blob);
+        enc.push_separator();
+        enc.encode_u8_into(5);
+        let key = enc.freeze();
+
+        let mut dec = Decoder::new(&key);
+        assert_eq!(dec.decode_bytes_escaped().as_deref(), Ok(&blob[..]));
+        assert_eq!(dec.expect_separator(), Ok(()));
+        assert_eq!(dec.decode_u8(), Ok(5));
+        assert!(dec.is_empty());
+        assert_eq!(escaped_len(blob), key.len() - 2);
+    }
+
+    #[test]
+    fn should_reject_malformed_escapes() {
+        let cases: [(&[u8], DecodeError); 3] = [
+            (
+                b"ab",
+                DecodeError::UnexpectedEnd {
+                    offset: 2,
+                    needed: 2,
+                    available: 0,
+                },
+            ),
+            (
+                b"a\x00",
+                DecodeError::UnexpectedEnd {
+                    offset: 1,
+                    needed: 2,
+                    available: 1,
+                },
+            ),
+            (
+                b"a\x00\x02",
+                DecodeError::Invalid {
+                    offset: 1,
+                    expected: "escaped bytes",
+                },
+            ),
+        ];
+        for (input, err) in cases {
+            let mut dec = Decoder::new(input);
+            assert_eq!(dec.decode_bytes_escaped(), Err(err));
+            assert_eq!(dec.position(), 0);
+        }
+    }
+}
@@ -0,0 +1,241 @@
+//! Allocation-free encoding of fixed-width values into caller-owned buffers.
+
+use std::fmt;
+
+use crate::LexKey;
+
+/// Error returned when a destination slice cannot hold an encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BufferTooSmall {
+    /// Bytes the encoding needs.
+    pub needed: usize,
+    /// Bytes the destination had.
+    pub available: usize,
+}
+
+impl fmt::Display for BufferTooSmall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "buffer too small: need {} bytes, have {}",
+            self.needed, self.available
+        )
+    }
+}
+
+impl std::error::Error for BufferTooSmall {}
+
+fn write_slice(dst: &mut [u8], bytes: &[u8]) -> Result<usize, BufferTooSmall> {
+    let Some(dst) = dst.get_mut(..bytes.len()) else {
+        return Err(BufferTooSmall {
+            needed: bytes.len(),
+            available: dst.len(),
+        });
+    };
+    dst.copy_from_slice(bytes);
+    Ok(bytes.len())
+}
+
+macro_rules! impl_key_bytes {
+    ($($ty:ident, $len:literal, $bytes:ident, $to_slice:ident, |$n:ident| $transform:expr;)+) => {
+        impl LexKey {
+            $(
+                #[doc = concat!("The ", stringify!($len), "-byte key encoding of a `", stringify!($ty), "`, as an array.")]
+                #[inline]
+                #[must_use]
+                pub const fn $bytes($n: $ty) -> [u8; $len] {
+                    $transform.to_be_bytes()
+                }
+
+                #[doc = concat!("Write the ", stringify!($len), "-byte key encoding of a `", stringify!($ty), "` to the front of `dst` and return ", stringify!($len), ".")]
+                ///
+                /// # Errors
+                ///
+                #[doc = concat!("Returns `BufferTooSmall` and leaves `dst` untouched if it is shorter than ", stringify!($len), " bytes.")]
+                #[inline]
+                pub fn $to_slice(dst: &mut [u8], $n: $ty) -> Result<usize, BufferTooSmall> {
+                    write_slice(dst, &Self::$bytes($n))
+                }
+            )+
+        }
+    };
+}
+
+impl_key_bytes! {
+    u8, 1, u8_key_bytes, encode_u8_to_slice, |n| n;
+    u16, 2, u16_key_bytes, encode_u16_to_slice, |n| n;
+    u32, 4, u32_key_bytes, encode_u32_to_slice, |n| n;
+    u64, 8, u64_key_bytes, encode_u64_to_slice, |n| n;
+    u128, 16, u128_key_bytes, encode_u128_to_slice, |n| n;
+    i8, 1, i8_key_bytes, encode_i8_to_slice, |n| (n ^ i8::MIN).cast_unsigned();
+    i16, 2, i16_key_bytes, encode_i16_to_slice, |n| (n ^ i16::MIN).cast_unsigned();
+    i32, 4, i32_key_bytes, encode_i32_to_slice, |n| (n ^ i32::MIN).cast_unsigned();
+    i64, 8, i64_key_bytes, encode_i64_to_slice, |n| (n ^ i64::MIN).cast_unsigned();
+    i128, 16, i128_key_bytes, encode_i128_to_slice, |n| (n ^ i128::MIN).cast_unsigned();
+}
+
+impl LexKey {
+    /// The 8-byte sortable encoding of an `f64`, as an array.
+    ///
+    /// ```rust
+    /// use lexkey::LexKey;
+    ///
+    /// const ZERO: [u8; 8] = LexKey::f64_key_bytes(0.0);
+    /// assert_eq!(ZERO, *LexKey::encode_f64(0.0).as_bytes());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is NaN.
+    #[inline]
+    #[must_use]
+    pub const fn f64_key_bytes(x: f64) -> [u8; 8] {
+        assert!(!x.is_nan(), "NaN is not encodable");
+        let b = x.to_bits();
+        let mask = 0u64.wrapping_sub(b >> 63);
+        ((!b & mask) | ((b ^ (1 << 63)) & !mask)).to_be_bytes()
+    }
+
+    /// The 4-byte sortable encoding of an `f32`, as an array.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is NaN.
+    #[inline]
+    #[must_use]
+    pub const fn f32_key_bytes(x: f32) -> [u8; 4] {
+        assert!(!x.is_nan(), "NaN is not encodable");
+        let b = x.to_bits();
+        let mask = 0u32.wrapping_sub(b >> 31);
+        ((!b & mask) | ((b ^ (1 << 31)) & !mask)).to_be_bytes()
+    }
+
+    /// Write the 8-byte sortable encoding of an `f64` to the front of `dst` and return 8.
+    ///
+    /// ```rust
+    /// use lexkey::LexKey;
+    ///
+    /// let mut key = [0u8; 17];
+    /// let mut n = LexKey::encode_u8_to_slice(&mut key, 3)?;
+    /// n += LexKey::encode_i64_to_slice(&mut key[n..], -5)?;
+    /// n += LexKey::encode_f64_to_slice(&mut key[n..], 1.5)?;
+    /// assert_eq!(n, 17);
+    /// assert_eq!(&key[1..9], LexKey::encode_i64(-5).as_bytes());
+    /// assert!(LexKey::encode_u64_to_slice(&mut key[n..], 1).is_err());
+    /// # Ok::<(), lexkey::BufferTooSmall>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `BufferTooSmall` and leaves `dst` untouched if it is shorter than 8 bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is NaN.
+    #[inline]
+    pub fn encode_f64_to_slice(dst: &mut [u8], x: f64) -> Result<usize, BufferTooSmall> {
+        write_slice(dst, &Self::f64_key_bytes(x))
+    }
+
+    /// Write the 4-byte sortable encoding of an `f32` to the front of `dst` and return 4.
+    ///
+    /// # Errors
+    ///
+    /// Returns `BufferTooSmall` and leaves `dst` untouched if it is shorter than 4 bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is NaN.
+    #[inline]
+    pub fn encode_f32_to_slice(dst: &mut [u8], x: f32) -> Result<usize, BufferTooSmall> {
+        write_slice(dst, &Self::f32_key_bytes(x))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_match_allocating_encoders() {
+        assert_eq!(
+            LexKey::u8_key_bytes(7)[..],
+            *LexKey::encode_u8(7).as_bytes()
+        );
+        assert_eq!(
+            LexKey::u16_key_bytes(7)[..],
+            *LexKey::encode_u16(7).as_bytes()
+        );
+        assert_eq!(
+            LexKey::u32_key_bytes(7)[..],
+            *LexKey::encode_u32(7).as_bytes()
+        );
+        assert_eq!(
+            LexKey::u64_key_bytes(7)[..],
+            *LexKey::encode_u64(7).as_bytes()
+        );
+        assert_eq!(
+            LexKey::u128_key_bytes(7)[..],
+            *LexKey::encode_u128(7).as_bytes()
+        );
+        for n in [i64::MIN, -1, 0, 1, i64::MAX] {
+            assert_eq!(
+                LexKey::i64_key_bytes(n)[..],
+                *LexKey::encode_i64(n).as_bytes()
+            );
+            assert_eq!(
+                LexKey::i128_key_bytes(n.into())[..],
+                *LexKey::encode_i128(n.into()).as_bytes()
+            );
+        }
+        for n in [i8::MIN, -1, 0, i8::MAX] {
+            assert_eq!(
+                LexKey::i8_key_bytes(n)[..],
+                *LexKey::encode_i8(n).as_bytes()
+            );
+            assert_eq!(
+                LexKey::i16_key_bytes(n.into())[..],
+                *LexKey::encode_i16(n.into()).as_bytes()
+            );
+            assert_eq!(
+                LexKey::i32_key_bytes(n.into())[..],
+                *LexKey::encode_i32(n.into()).as_bytes()
+            );
+        }
+        for x in [f64::NEG_INFINITY, -1.5, -0.0, 0.0, 2.0, f64::INFINITY] {
+            assert_eq!(
+                LexKey::f64_key_bytes(x)[..],
+                *LexKey::encode_f64(x).as_bytes()
+            );
+        }
+        for x in [f32::NEG_INFINITY, -1.5, -0.0, 0.0, 2.0, f32::INFINITY] {
+            assert_eq!(
+                LexKey::f32_key_bytes(x)[..],
+                *LexKey::encode_f32(x).as_bytes()
+            );
+        }
+    }
+
+    #[test]
+    fn should_reject_short_slices_without_writing() {
+        let mut buf = [0xaa; 3];
+        assert_eq!(
+            LexKey::encode_u32_to_slice(&mut buf, 1),
+            Err(BufferTooSmall {
+                needed: 4,
+                available: 3
+            })
+        );
+        assert_eq!(buf, [0xaa; 3]);
+        assert_eq!(LexKey::encode_i16_to_slice(&mut buf, 0), Ok(2));
+        assert_eq!(buf, [0x80, 0x00, 0xaa]);
+        assert_eq!(
+            BufferTooSmall {
+                needed: 8,
+                available: 0
+            }
+            .to_string(),
+            "buffer too small: need 8 bytes, have 0"
+        );
+    }
+}
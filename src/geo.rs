@@ -0,0 +1,746 @@
+//! Geohash keys and prefix-range covers for latitude/longitude points, and Morton (Z-order)
+//! keys for integer grid coordinates.
+//!
+//! A geohash interleaves longitude and latitude bisection bits and writes them 5 at a time in the
+//! base32 alphabet `0123456789bcdefghjkmnpqrstuvwxyz`. The alphabet is in ASCII order, so every
+//! geohash is a prefix of the geohashes of the smaller cells inside it, and a cell maps to one
+//! raw-prefix scan. Nearby points usually, but not always, share a long prefix; use `neighbors`
+//! or `cover` to query an area instead of a single prefix.
+//!
+//! ```rust
+//! use lexkey::geo::{self, GeoBox};
+//!
+//! assert_eq!(geo::geohash(57.64911, 10.40744, 11), "u4pruydqqvj");
+//!
+//! let area = GeoBox::new(57.6, 10.3, 57.7, 10.5);
+//! let cells = geo::cover(&area, 5);
+//! assert!(cells.iter().any(|c| geo::geohash(57.64911, 10.40744, 11).starts_with(c.as_str())));
+//! ```
+
+use std::ops::Bound;
+
+use bytes::BufMut;
+
+use crate::{Encoder, KeyRange, LexKey};
+
+const ALPHABET: &[u8; 32] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+
+/// Longest supported geohash, 60 bits.
+pub const MAX_PRECISION: usize = 12;
+
+/// Most interleaved bits an `encode_latlon` key can carry.
+pub const MAX_LATLON_BITS: u8 = 64;
+
+/// A latitude/longitude rectangle in degrees.
+///
+/// A box whose `min_lon` is greater than its `max_lon` crosses the antimeridian.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GeoBox {
+    pub min_lat: f64,
+    pub min_lon: f64,
+    pub max_lat: f64,
+    pub max_lon: f64,
+}
+
+impl GeoBox {
+    /// Create a box from its south-west and north-east corners.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a coordinate is out of range or NaN, or if `min_lat > max_lat`.
+    #[must_use]
+    pub fn new(min_lat: f64, min_lon: f64, max_lat: f64, max_lon: f64) -> Self {
+        check_point(min_lat, min_lon);
+        check_point(max_lat, max_lon);
+        assert!(min_lat <= max_lat, "min_lat must not exceed max_lat");
+        Self {
+            min_lat,
+            min_lon,
+            max_lat,
+            max_lon,
+        }
+    }
+}
+
+fn check_point(lat: f64, lon: f64) {
+    assert!(
+        (-90.0..=90.0).contains(&lat),
+        "latitude must be within [-90, 90]"
+    );
+    assert!(
+        (-180.0..=180.0).contains(&lon),
+        "longitude must be within [-180, 180]"
+    );
+}
+
+fn check_precision(precision: usize) {
+    assert!(
+        (1..=MAX_PRECISION).contains(&precision),
+        "geohash precision must be between 1 and 12"
+    );
+}
+
+/// Number of `(lat, lon)` bits at `precision` characters; longitude takes the extra odd bit.
+#[inline]
+fn bit_split(precision: usize) -> (u32, u32) {
+    let total = u32::try_from(5 * precision).expect("precision is at most 12");
+    (total / 2, total.div_ceil(2))
+}
+
+/// Standard geohash bisection: each step keeps the upper half when `v >= mid`.
+fn bisect(v: f64, mut lo: f64, mut hi: f64, bits: u32) -> u64 {
+    let mut idx = 0u64;
+    for _ in 0..bits {
+        let mid = f64::midpoint(lo, hi);
+        idx <<= 1;
+        if v >= mid {
+            idx |= 1;
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    idx
+}
+
+fn cell(lat: f64, lon: f64, precision: usize) -> (u64, u64) {
+    let (lat_bits, lon_bits) = bit_split(precision);
+    (
+        bisect(lat, -90.0, 90.0, lat_bits),
+        bisect(lon, -180.0, 180.0, lon_bits),
+    )
+}
+
+fn interleave(lat_idx: u64, lon_idx: u64, precision: usize) -> String {
+    let (mut lat_left, mut lon_left) = bit_split(precision);
+    let mut out = String::with_capacity(precision);
+    let mut ch = 0usize;
+    for i in 0..5 * precision {
+        let bit = if i % 2 == 0 {
+            lon_left -= 1;
+            (lon_idx >> lon_left) & 1
+        } else {
+            lat_left -= 1;
+            (lat_idx >> lat_left) & 1
+        };
+        ch = (ch << 1) | usize::from(bit == 1);
+        if i % 5 == 4 {
+            out.push(char::from(ALPHABET[ch]));
+            ch = 0;
+        }
+    }
+    out
+}
+
+fn deinterleave(hash: &str) -> Option<(u64, u64)> {
+    let (mut lat_idx, mut lon_idx) = (0u64, 0u64);
+    let mut i = 0;
+    for c in hash.bytes() {
+        let v = ALPHABET.iter().position(|&a| a == c)?;
+        for shift in (0..5).rev() {
+            let bit = u64::from((v >> shift) & 1 == 1);
+            if i % 2 == 0 {
+                lon_idx = (lon_idx << 1) | bit;
+            } else {
+                lat_idx = (lat_idx << 1) | bit;
+            }
+            i += 1;
+        }
+    }
+    Some((lat_idx, lon_idx))
+}
+
+/// Encode a point as a geohash string of `precision` characters.
+///
+/// # Panics
+///
+/// Panics if a coordinate is out of range or NaN, or if `precision` is not in `1..=12`.
+#[must_use]
+pub fn geohash(lat: f64, lon: f64, precision: usize) -> String {
+    check_point(lat, lon);
+    check_precision(precision);
+    let (lat_idx, lon_idx) = cell(lat, lon, precision);
+    interleave(lat_idx, lon_idx, precision)
+}
+
+/// Return the cell of `hash` as a box, or `None` if it is empty, too long or not base32.
+#[must_use]
+pub fn bounds(hash: &str) -> Option<GeoBox> {
+    if !(1..=MAX_PRECISION).contains(&hash.len()) {
+        return None;
+    }
+    let (lat_idx, lon_idx) = deinterleave(hash)?;
+    let (lat_bits, lon_bits) = bit_split(hash.len());
+    let h = 180.0 / f64::from(1u32 << lat_bits);
+    let w = 360.0 / f64::from(1u32 << lon_bits);
+    // At most 30 bits per axis, so both indices convert to `f64` exactly.
+    let (lat_idx, lon_idx) = (u32::try_from(lat_idx).ok()?, u32::try_from(lon_idx).ok()?);
+    let (lat0, lon0) = (
+        -90.0 + f64::from(lat_idx) * h,
+        -180.0 + f64::from(lon_idx) * w,
+    );
+    Some(GeoBox {
+        min_lat: lat0,
+        min_lon: lon0,
+        max_lat: lat0 + h,
+        max_lon: lon0 + w,
+    })
+}
+
+/// Return the up to eight cells adjacent to `hash`, in ascending order.
+///
+/// Longitude wraps around the antimeridian; there are no neighbours beyond the poles. Returns an
+/// empty list for an invalid geohash.
+#[must_use]
+pub fn neighbors(hash: &str) -> Vec<String> {
+    let precision = hash.len();
+    if !(1..=MAX_PRECISION).contains(&precision) {
+        return Vec::new();
+    }
+    let Some((lat_idx, lon_idx)) = deinterleave(hash) else {
+        return Vec::new();
+    };
+    let (lat_bits, lon_bits) = bit_split(precision);
+    let (lat_cells, lon_cells) = (1u64 << lat_bits, 1u64 << lon_bits);
+
+    let mut out = Vec::with_capacity(8);
+    for dlat in [-1i64, 0, 1] {
+        let Some(lat) = lat_idx.checked_add_signed(dlat).filter(|&l| l < lat_cells) else {
+            continue;
+        };
+        for dlon in [-1i64, 0, 1] {
+            if dlat == 0 && dlon == 0 {
+                continue;
+            }
+            let lon = (lon_idx + lon_cells).wrapping_add_signed(dlon) % lon_cells;
+            out.push(interleave(lat, lon, precision));
+        }
+    }
+    out.sort();
+    out.dedup();
+    out.retain(|h| h != hash);
+    out
+}
+
+/// Return every cell of `precision` characters that intersects `area`, in ascending order.
+///
+/// The number of cells grows quickly with precision; pick the precision whose cell size is close
+/// to the box size to keep a cover to a handful of scans.
+///
+/// # Panics
+///
+/// Panics if `precision` is not in `1..=12`.
+#[must_use]
+pub fn cover(area: &GeoBox, precision: usize) -> Vec<String> {
+    check_precision(precision);
+    let (lat_lo, lon_lo) = cell(area.min_lat, area.min_lon, precision);
+    let (lat_hi, lon_hi) = cell(area.max_lat, area.max_lon, precision);
+    let lon_cells = 1u64 << bit_split(precision).1;
+    let lon_span = if lon_lo <= lon_hi {
+        lon_hi - lon_lo
+    } else {
+        lon_cells - lon_lo + lon_hi
+    };
+
+    let mut out = Vec::new();
+    for lat in lat_lo..=lat_hi {
+        for step in 0..=lon_span {
+            out.push(interleave(lat, (lon_lo + step) % lon_cells, precision));
+        }
+    }
+    out.sort();
+    out
+}
+
+/// Return the raw-prefix ranges of `key_prefix || cell` for every cell in `cover(area, precision)`.
+///
+/// Use an empty `key_prefix` when the geohash starts the key.
+///
+/// # Panics
+///
+/// Panics if `precision` is not in `1..=12`.
+#[must_use]
+pub fn cover_ranges(key_prefix: &[u8], area: &GeoBox, precision: usize) -> Vec<KeyRange> {
+    cover(area, precision)
+        .into_iter()
+        .map(|cell| {
+            let mut prefix = Vec::with_capacity(key_prefix.len() + cell.len());
+            prefix.extend_from_slice(key_prefix);
+            prefix.extend_from_slice(cell.as_bytes());
+            KeyRange::prefix(&prefix)
+        })
+        .collect()
+}
+
+fn check_latlon_bits(precision_bits: u8) {
+    assert!(
+        (1..=MAX_LATLON_BITS).contains(&precision_bits),
+        "lat/lon precision must be between 1 and 64 bits"
+    );
+}
+
+/// Cell indices of a point with `bits` interleaved bits; longitude takes the extra odd bit.
+fn latlon_cell(lat: f64, lon: f64, bits: u8) -> (u64, u64) {
+    let bits = u32::from(bits);
+    (
+        bisect(lat, -90.0, 90.0, bits / 2),
+        bisect(lon, -180.0, 180.0, bits.div_ceil(2)),
+    )
+}
+
+/// Interleave cell indices longitude first, like a geohash, and left-align them in a `u64`.
+fn latlon_code(lat_idx: u64, lon_idx: u64, bits: u8) -> u64 {
+    let (mut lat_left, mut lon_left) = (bits / 2, bits.div_ceil(2));
+    let mut code = 0u64;
+    for i in 0..bits {
+        let bit = if i % 2 == 0 {
+            lon_left -= 1;
+            (lon_idx >> lon_left) & 1
+        } else {
+            lat_left -= 1;
+            (lat_idx >> lat_left) & 1
+        };
+        code = (code << 1) | bit;
+    }
+    code << (64 - u32::from(bits))
+}
+
+/// Interleave the bisection bits of a point into a left-aligned `u64`.
+///
+/// The top `precision_bits` bits are the same bits a geohash carries, five per character, and
+/// the rest are zero. Codes sort like geohashes, so every cell is one contiguous code range.
+///
+/// # Panics
+///
+/// Panics if a coordinate is out of range or NaN, or if `precision_bits` is not in `1..=64`.
+#[must_use]
+pub fn latlon(lat: f64, lon: f64, precision_bits: u8) -> u64 {
+    check_point(lat, lon);
+    check_latlon_bits(precision_bits);
+    let (lat_idx, lon_idx) = latlon_cell(lat, lon, precision_bits);
+    latlon_code(lat_idx, lon_idx, precision_bits)
+}
+
+/// Return key ranges covering every `encode_latlon` key under `key_prefix` that lies in `area`.
+///
+/// The ranges cover each `precision_bits` cell that intersects `area`, with adjacent cells
+/// merged, in ascending order. Keys written at a finer precision than `precision_bits` fall in
+/// the range of the coarser cell that contains them. As with `cover`, choose a precision whose
+/// cell size is close to the box size; each extra bit can double the number of cells.
+///
+/// ```rust
+/// use std::ops::RangeBounds;
+/// use lexkey::geo::{self, GeoBox};
+/// use lexkey::LexKey;
+///
+/// let area = GeoBox::new(57.6, 10.3, 57.7, 10.5);
+/// let ranges = geo::latlon_cover_ranges(b"", &area, 20);
+/// let point = LexKey::encode_latlon(57.64911, 10.40744, 64);
+/// assert!(ranges.len() <= 4);
+/// assert!(ranges.iter().any(|r| r.contains(&point)));
+/// ```
+///
+/// # Panics
+///
+/// Panics if `precision_bits` is not in `1..=64`.
+#[must_use]
+pub fn latlon_cover_ranges(key_prefix: &[u8], area: &GeoBox, precision_bits: u8) -> Vec<KeyRange> {
+    check_latlon_bits(precision_bits);
+    let (lat_lo, lon_lo) = latlon_cell(area.min_lat, area.min_lon, precision_bits);
+    let (lat_hi, lon_hi) = latlon_cell(area.max_lat, area.max_lon, precision_bits);
+    let lon_cells = 1u128 << u32::from(precision_bits).div_ceil(2);
+    let lon_span = if lon_lo <= lon_hi {
+        u128::from(lon_hi - lon_lo)
+    } else {
+        lon_cells - u128::from(lon_lo) + u128::from(lon_hi)
+    };
+
+    let mut codes = Vec::new();
+    for lat in lat_lo..=lat_hi {
+        for step in 0..=lon_span {
+            let lon = (u128::from(lon_lo) + step) % lon_cells;
+            let lon = u64::try_from(lon).expect("longitude index fits in 32 bits");
+            codes.push(latlon_code(lat, lon, precision_bits));
+        }
+    }
+    codes.sort_unstable();
+
+    // Half-open `[start, end)` code ranges; an `end` of 2^64 is past the last code.
+    let cell = 1u128 << (64 - u32::from(precision_bits));
+    let mut merged: Vec<(u128, u128)> = Vec::new();
+    for code in codes {
+        let start = u128::from(code);
+        match merged.last_mut() {
+            Some((_, end)) if *end == start => *end = start + cell,
+            _ => merged.push((start, start + cell)),
+        }
+    }
+
+    let key = |code: u64| {
+        let mut buf = Vec::with_capacity(key_prefix.len() + 8);
+        buf.extend_from_slice(key_prefix);
+        LexKey::encode_u64_into(&mut buf, code);
+        LexKey::from(buf)
+    };
+    merged
+        .into_iter()
+        .map(|(start, end)| {
+            let start = Bound::Included(key(u64::try_from(start).expect("codes fit in u64")));
+            let end = match u64::try_from(end) {
+                Ok(end) => Bound::Excluded(key(end)),
+                Err(_) => match LexKey::prefix_successor(key_prefix) {
+                    Some(upper) => Bound::Excluded(LexKey::from(upper)),
+                    None => Bound::Unbounded,
+                },
+            };
+            KeyRange::new(start, end)
+        })
+        .collect()
+}
+
+/// Spread the bits of `v` so bit `i` moves to bit `2i`.
+fn spread32(v: u32) -> u64 {
+    let mut x = u64::from(v);
+    x = (x | (x << 16)) & 0x0000_FFFF_0000_FFFF;
+    x = (x | (x << 8)) & 0x00FF_00FF_00FF_00FF;
+    x = (x | (x << 4)) & 0x0F0F_0F0F_0F0F_0F0F;
+    x = (x | (x << 2)) & 0x3333_3333_3333_3333;
+    (x | (x << 1)) & 0x5555_5555_5555_5555
+}
+
+/// Inverse of `spread32`: gather the even bits of `z`.
+fn compact32(z: u64) -> u32 {
+    let mut x = z & 0x5555_5555_5555_5555;
+    x = (x | (x >> 1)) & 0x3333_3333_3333_3333;
+    x = (x | (x >> 2)) & 0x0F0F_0F0F_0F0F_0F0F;
+    x = (x | (x >> 4)) & 0x00FF_00FF_00FF_00FF;
+    x = (x | (x >> 8)) & 0x0000_FFFF_0000_FFFF;
+    ((x | (x >> 16)) & 0xFFFF_FFFF) as u32
+}
+
+/// Spread the bits of `v` so bit `i` moves to bit `2i`.
+fn spread64(v: u64) -> u128 {
+    let mut x = u128::from(v);
+    x = (x | (x << 32)) & 0x0000_0000_FFFF_FFFF_0000_0000_FFFF_FFFF;
+    x = (x | (x << 16)) & 0x0000_FFFF_0000_FFFF_0000_FFFF_0000_FFFF;
+    x = (x | (x << 8)) & 0x00FF_00FF_00FF_00FF_00FF_00FF_00FF_00FF;
+    x = (x | (x << 4)) & 0x0F0F_0F0F_0F0F_0F0F_0F0F_0F0F_0F0F_0F0F;
+    x = (x | (x << 2)) & 0x3333_3333_3333_3333_3333_3333_3333_3333;
+    (x | (x << 1)) & 0x5555_5555_5555_5555_5555_5555_5555_5555
+}
+
+/// Inverse of `spread64`: gather the even bits of `z`.
+fn compact64(z: u128) -> u64 {
+    let mut x = z & 0x5555_5555_5555_5555_5555_5555_5555_5555;
+    x = (x | (x >> 1)) & 0x3333_3333_3333_3333_3333_3333_3333_3333;
+    x = (x | (x >> 2)) & 0x0F0F_0F0F_0F0F_0F0F_0F0F_0F0F_0F0F_0F0F;
+    x = (x | (x >> 4)) & 0x00FF_00FF_00FF_00FF_00FF_00FF_00FF_00FF;
+    x = (x | (x >> 8)) & 0x0000_FFFF_0000_FFFF_0000_FFFF_0000_FFFF;
+    x = (x | (x >> 16)) & 0x0000_0000_FFFF_FFFF_0000_0000_FFFF_FFFF;
+    ((x | (x >> 32)) & 0xFFFF_FFFF_FFFF_FFFF) as u64
+}
+
+/// Interleave the bits of `x` and `y` into a Morton (Z-order) code.
+///
+/// Bit `i` of `x` becomes bit `2i` and bit `i` of `y` becomes bit `2i + 1`, so `y` supplies the
+/// most significant bit. Points in the same aligned `2^k x 2^k` tile share the top `2k` bits.
+#[inline]
+#[must_use]
+pub fn morton2(x: u32, y: u32) -> u64 {
+    spread32(x) | (spread32(y) << 1)
+}
+
+/// Split a Morton code from `morton2` back into `(x, y)`.
+#[inline]
+#[must_use]
+pub fn morton2_decode(z: u64) -> (u32, u32) {
+    (compact32(z), compact32(z >> 1))
+}
+
+/// Interleave two 64-bit coordinates into a 128-bit Morton code; see `morton2`.
+#[inline]
+#[must_use]
+pub fn morton2_u64(x: u64, y: u64) -> u128 {
+    spread64(x) | (spread64(y) << 1)
+}
+
+/// Split a Morton code from `morton2_u64` back into `(x, y)`.
+#[inline]
+#[must_use]
+pub fn morton2_u64_decode(z: u128) -> (u64, u64) {
+    (compact64(z), compact64(z >> 1))
+}
+
+impl LexKey {
+    /// Encode a point as a geohash of `precision` base32 characters.
+    ///
+    /// ```rust
+    /// use lexkey::LexKey;
+    /// assert_eq!(LexKey::encode_geohash(42.6, -5.6, 5).as_bytes(), b"ezs42");
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if a coordinate is out of range or NaN, or if `precision` is not in `1..=12`.
+    #[inline]
+    #[must_use]
+    pub fn encode_geohash(lat: f64, lon: f64, precision: usize) -> Self {
+        Self::from(geohash(lat, lon, precision).into_bytes())
+    }
+
+    /// Encode a point as its 8-byte big-endian interleaved code at `precision_bits`.
+    ///
+    /// This is the binary form of a geohash: the same bits, packed eight per byte instead of
+    /// five per character, so keys sort like geohashes and can be as fine as 64 bits (about a
+    /// centimetre). Bits beyond `precision_bits` are zero, so coarser keys sit at the start of
+    /// their cell. `latlon_cover_ranges` turns a bounding box into a few range scans.
+    ///
+    /// ```rust
+    /// use lexkey::LexKey;
+    ///
+    /// // "ezs42" is 25 bits: 01101 11111 11000 00100 00010.
+    /// assert_eq!(
+    ///     LexKey::encode_latlon(42.6, -5.6, 25).to_hex_string(),
+    ///     "6ff0410000000000"
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if a coordinate is out of range or NaN, or if `precision_bits` is not in `1..=64`.
+    #[inline]
+    #[must_use]
+    pub fn encode_latlon(lat: f64, lon: f64, precision_bits: u8) -> Self {
+        Self::encode_u64(latlon(lat, lon, precision_bits))
+    }
+
+    /// Append the 8-byte `encode_latlon` key into `dst` and return 8.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a coordinate is out of range or NaN, or if `precision_bits` is not in `1..=64`.
+    #[inline]
+    pub fn encode_latlon_into<B: BufMut + ?Sized>(
+        dst: &mut B,
+        lat: f64,
+        lon: f64,
+        precision_bits: u8,
+    ) -> usize {
+        Self::encode_u64_into(dst, latlon(lat, lon, precision_bits))
+    }
+
+    /// Encode grid coordinates as their 8-byte big-endian Morton (Z-order) code.
+    ///
+    /// Keys for one aligned tile form a contiguous range, so tile and grid lookups become
+    /// prefix scans: every cell with the same top bits of `x` and `y` shares a key prefix.
+    ///
+    /// ```rust
+    /// use lexkey::LexKey;
+    ///
+    /// assert_eq!(LexKey::encode_morton2(0b11, 0b01).to_hex_string(), "0000000000000007");
+    /// // (2, 0) is in the next 2x2 tile, so it sorts after (1, 1) despite its smaller y.
+    /// assert!(LexKey::encode_morton2(1, 1) < LexKey::encode_morton2(2, 0));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn encode_morton2(x: u32, y: u32) -> Self {
+        Self::encode_u64(morton2(x, y))
+    }
+
+    /// Encode 64-bit grid coordinates as their 16-byte big-endian Morton code.
+    #[inline]
+    #[must_use]
+    pub fn encode_morton2_u64(x: u64, y: u64) -> Self {
+        Self::encode_u128(morton2_u64(x, y))
+    }
+
+    /// Append the 8-byte Morton code of `(x, y)` into `dst` and return 8.
+    #[inline]
+    pub fn encode_morton2_into<B: BufMut + ?Sized>(dst: &mut B, x: u32, y: u32) -> usize {
+        Self::encode_u64_into(dst, morton2(x, y))
+    }
+
+    /// Append the 16-byte Morton code of `(x, y)` into `dst` and return 16.
+    #[inline]
+    pub fn encode_morton2_u64_into<B: BufMut + ?Sized>(dst: &mut B, x: u64, y: u64) -> usize {
+        Self::encode_u128_into(dst, morton2_u64(x, y))
+    }
+}
+
+impl Encoder {
+    /// Append the geohash of a point as ASCII bytes; see `LexKey::encode_geohash`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a coordinate is out of range or NaN, or if `precision` is not in `1..=12`.
+    #[inline]
+    pub fn encode_geohash_into(&mut self, lat: f64, lon: f64, precision: usize) -> usize {
+        let hash = geohash(lat, lon, precision);
+        self.buf.extend_from_slice(hash.as_bytes());
+        hash.len()
+    }
+
+    /// Append the 8-byte interleaved code of a point; see `LexKey::encode_latlon`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a coordinate is out of range or NaN, or if `precision_bits` is not in `1..=64`.
+    #[inline]
+    pub fn encode_latlon_into(&mut self, lat: f64, lon: f64, precision_bits: u8) -> usize {
+        LexKey::encode_latlon_into(&mut self.buf, lat, lon, precision_bits)
+    }
+
+    /// Append the 8-byte Morton code of `(x, y)`; see `LexKey::encode_morton2`.
+    #[inline]
+    pub fn encode_morton2_into(&mut self, x: u32, y: u32) -> usize {
+        LexKey::encode_morton2_into(&mut self.buf, x, y)
+    }
+
+    /// Append the 16-byte Morton code of `(x, y)`; see `LexKey::encode_morton2_u64`.
+    #[inline]
+    pub fn encode_morton2_u64_into(&mut self, x: u64, y: u64) -> usize {
+        LexKey::encode_morton2_u64_into(&mut self.buf, x, y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_match_reference_geohashes() {
+        assert_eq!(geohash(57.64911, 10.40744, 11), "u4pruydqqvj");
+        assert_eq!(geohash(42.6, -5.6, 5), "ezs42");
+        assert_eq!(geohash(-90.0, -180.0, 4), "0000");
+        assert_eq!(geohash(90.0, 180.0, 4), "zzzz");
+    }
+
+    #[test]
+    fn should_nest_cells_as_prefixes() {
+        let fine = geohash(37.7749, -122.4194, 9);
+        for p in 1..9 {
+            assert_eq!(geohash(37.7749, -122.4194, p), fine[..p]);
+        }
+        let cell = bounds(&fine).unwrap();
+        assert!(cell.min_lat <= 37.7749 && 37.7749 < cell.max_lat);
+        assert!(cell.min_lon <= -122.4194 && -122.4194 < cell.max_lon);
+    }
+
+    #[test]
+    fn should_find_neighbors_with_wrap_and_poles() {
+        assert_eq!(
+            neighbors("ezs42"),
+            vec!["ezefp", "ezefr", "ezefx", "ezs40", "ezs41", "ezs43", "ezs48", "ezs49"]
+        );
+        // Top row of the grid: only five neighbours remain.
+        assert_eq!(neighbors("z").len(), 5);
+        assert!(neighbors("0").contains(&"p".to_owned()));
+        assert!(neighbors("a").is_empty());
+    }
+
+    #[test]
+    fn should_cover_box_with_intersecting_cells() {
+        let area = GeoBox::new(10.0, 10.0, 20.0, 30.0);
+        let cells = cover(&area, 2);
+        for (lat, lon) in [(10.0, 10.0), (15.0, 20.0), (20.0, 30.0), (19.9, 10.1)] {
+            assert!(cells.contains(&geohash(lat, lon, 2)));
+        }
+        assert!(!cells.contains(&geohash(0.0, 0.0, 2)));
+
+        let across = GeoBox::new(0.0, 170.0, 1.0, -170.0);
+        let wrapped = cover(&across, 2);
+        assert!(wrapped.contains(&geohash(0.5, 175.0, 2)));
+        assert!(wrapped.contains(&geohash(0.5, -175.0, 2)));
+        assert!(!wrapped.contains(&geohash(0.5, 0.0, 2)));
+    }
+
+    #[test]
+    fn should_build_prefix_ranges_for_cover() {
+        let area = GeoBox::new(57.64, 10.40, 57.65, 10.41);
+        let ranges = cover_ranges(b"poi\x00", &area, 6);
+        let point = LexKey::encode_composite(&[b"poi", geohash(57.645, 10.405, 9).as_bytes()]);
+        assert!(ranges
+            .iter()
+            .any(|r| std::ops::RangeBounds::contains(r, &point)));
+    }
+
+    #[test]
+    fn should_interleave_and_round_trip_morton_codes() {
+        assert_eq!(morton2(u32::MAX, 0), 0x5555_5555_5555_5555);
+        assert_eq!(morton2(0, u32::MAX), 0xAAAA_AAAA_AAAA_AAAA);
+        assert_eq!(morton2_u64(u64::MAX, u64::MAX), u128::MAX);
+        for (x, y) in [(0, 0), (1, 2), (0xDEAD_BEEF, 0x1234_5678), (u32::MAX, 7)] {
+            assert_eq!(morton2_decode(morton2(x, y)), (x, y));
+            let (wx, wy) = (u64::from(x) << 31 | 5, u64::from(y) << 32);
+            assert_eq!(morton2_u64_decode(morton2_u64(wx, wy)), (wx, wy));
+        }
+    }
+
+    #[test]
+    fn should_keep_aligned_tiles_contiguous() {
+        // Every cell of the 4x4 tile at (8, 4) sorts between the tile's first and last cell.
+        let first = LexKey::encode_morton2(8, 4);
+        let last = LexKey::encode_morton2(11, 7);
+        for x in 0..16 {
+            for y in 0..16 {
+                let key = LexKey::encode_morton2(x, y);
+                let inside = (8..12).contains(&x) && (4..8).contains(&y);
+                assert_eq!(first <= key && key <= last, inside, "({x}, {y})");
+            }
+        }
+
+        let mut enc = Encoder::with_capacity(24);
+        enc.encode_morton2_into(3, 1);
+        enc.encode_morton2_u64_into(3, 1);
+        assert_eq!(
+            &enc.as_slice()[..8],
+            LexKey::encode_morton2(3, 1).as_bytes()
+        );
+        assert_eq!(
+            &enc.as_slice()[8..],
+            LexKey::encode_morton2_u64(3, 1).as_bytes()
+        );
+    }
+
+    #[test]
+    fn should_carry_geohash_bits_in_latlon_codes() {
+        let hash = geohash(57.64911, 10.40744, 12);
+        let bits = hash.bytes().fold(0u64, |acc, c| {
+            let v = ALPHABET.iter().position(|&a| a == c).unwrap() as u64;
+            (acc << 5) | v
+        });
+        assert_eq!(latlon(57.64911, 10.40744, 60), bits << 4);
+        assert_eq!(latlon(57.64911, 10.40744, 64) >> 4, bits);
+        assert_eq!(latlon(-90.0, -180.0, 64), 0);
+        assert_eq!(latlon(90.0, 180.0, 64), u64::MAX);
+    }
+
+    #[test]
+    fn should_cover_box_with_merged_latlon_ranges() {
+        use std::ops::RangeBounds;
+
+        // Crosses the antimeridian.
+        let area = GeoBox::new(-10.0, 170.0, 10.0, -170.0);
+        let ranges = latlon_cover_ranges(b"p", &area, 12);
+        for (lat, lon, inside) in [
+            (0.0, 179.9, true),
+            (-9.9, -175.0, true),
+            (9.9, 171.0, true),
+            (0.0, 0.0, false),
+            (20.0, 179.0, false),
+        ] {
+            let mut key = b"p".to_vec();
+            LexKey::encode_latlon_into(&mut key, lat, lon, 64);
+            let key = LexKey::from(key);
+            assert_eq!(
+                ranges.iter().any(|r| r.contains(&key)),
+                inside,
+                "({lat}, {lon})"
+            );
+        }
+
+        let whole = latlon_cover_ranges(b"p", &GeoBox::new(-90.0, -180.0, 90.0, 180.0), 4);
+        assert_eq!(whole.len(), 1);
+        assert_eq!(whole[0].end(), Bound::Excluded(&LexKey::from(&b"q"[..])));
+    }
+}
@@ -0,0 +1,562 @@
+use bytes::BufMut;
+use serde_json::Value;
+
+use crate::escape::escape_into;
+use crate::schema::{self, KeySchema};
+use crate::{DecodeError, Encoder, LexKey};
+
+/// Type tag for JSON `null`.
+pub const TAG_NULL: u8 = 0x01;
+/// Type tag for JSON `false`.
+pub const TAG_FALSE: u8 = 0x02;
+/// Type tag for JSON `true`.
+pub const TAG_TRUE: u8 = 0x03;
+/// Type tag for JSON numbers, followed by the 8-byte sortable `f64` and a 2-byte remainder.
+pub const TAG_NUMBER: u8 = 0x04;
+/// Type tag for JSON strings.
+pub const TAG_STRING: u8 = 0x05;
+/// Type tag for JSON arrays in `encode_json_value`.
+pub const TAG_ARRAY: u8 = 0x06;
+/// Type tag for JSON objects in `encode_json_value`.
+pub const TAG_OBJECT: u8 = 0x07;
+
+/// Ends an array or object; sorts below every element tag and `ENTRY`.
+const END: u8 = 0x00;
+/// Starts each object entry, so `{}` sorts before any non-empty object.
+const ENTRY: u8 = 0x01;
+
+/// Append the tagged encoding of a JSON scalar into `dst` and return the bytes written.
+///
+/// Every value starts with a one-byte type tag, so values of different types order as
+/// `null < false < true < number < string`. Numbers follow as the 8-byte sortable `f64`
+/// encoding (`-0` is normalized to `0`) and then the sortable `i16` difference between the exact
+/// value and that `f64`. The difference is zero except for integers beyond 2^53, which round
+/// when converted; it keeps every `i64` and `u64` distinct and ordered among the floats, while
+/// `3` and `3.0` still encode identically. Strings follow escaped like
+/// `LexKey::encode_bytes_escaped`, the same layout `encode_json_value_into` writes, so a string
+/// part sorts correctly whatever follows it.
+///
+/// Returns `None` without writing anything for arrays and objects.
+pub fn encode_json_scalar_into<B: BufMut + ?Sized>(dst: &mut B, value: &Value) -> Option<usize> {
+    match value {
+        Value::Null => dst.put_u8(TAG_NULL),
+        Value::Bool(false) => dst.put_u8(TAG_FALSE),
+        Value::Bool(true) => dst.put_u8(TAG_TRUE),
+        Value::Number(n) => {
+            let x = n.as_f64()?;
+            dst.put_u8(TAG_NUMBER);
+            // JSON has no NaN; `+ 0.0` folds `-0.0` into `0.0`.
+            return Some(
+                1 + LexKey::encode_f64_into(dst, x + 0.0)
+                    + LexKey::encode_i16_into(dst, rounding_error(n)),
+            );
+        }
+        Value::String(s) => {
+            dst.put_u8(TAG_STRING);
+            return Some(1 + escape_into(dst, s.as_bytes()));
+        }
+        Value::Array(_) | Value::Object(_) => return None,
+    }
+    Some(1)
+}
+
+/// How far an integer is from its nearest `f64`, the value `as_f64` rounds it to.
+///
+/// Integers up to 2^53 convert exactly. Above that, `f64` keeps 53 significant bits and rounds
+/// half to even, so the error is at most half the spacing between adjacent `f64`s at that
+/// magnitude: 1024 for 64-bit integers.
+fn rounding_error(n: &serde_json::Number) -> i16 {
+    let Some(exact) = n
+        .as_i64()
+        .map(i128::from)
+        .or_else(|| n.as_u64().map(i128::from))
+    else {
+        return 0;
+    };
+    let magnitude = exact.unsigned_abs();
+    let width = u128::BITS - magnitude.leading_zeros();
+    if width <= f64::MANTISSA_DIGITS {
+        return 0;
+    }
+    let dropped = width - f64::MANTISSA_DIGITS;
+    let step = 1u128 << dropped;
+    let low = magnitude & (step - 1);
+    let rounds_up = 2 * low > step || (2 * low == step && (magnitude >> dropped) & 1 == 1);
+    let error = i16::try_from(if rounds_up { step - low } else { low })
+        .expect("a 64-bit integer drops at most 11 bits");
+    // Rounding up leaves the integer below its `f64`; the sign flips again for negatives.
+    if rounds_up == (exact < 0) {
+        error
+    } else {
+        -error
+    }
+}
+
+/// Append the self-terminating encoding of any JSON value into `dst` and return bytes written.
+///
+/// Types order as `null < false < true < number < string < array < object`. Null, booleans and
+/// strings are written as in `encode_json_scalar_into`, so the encoding never depends on what
+/// follows it. An array is `TAG_ARRAY`, each element in turn, then `00`, so arrays compare
+/// element by element and a prefix sorts first. An object is `TAG_OBJECT`, then `01`, the escaped key and
+/// the value for each entry in key order, then `00`; objects with equal contents encode
+/// identically whatever their insertion order.
+pub fn encode_json_value_into<B: BufMut + ?Sized>(dst: &mut B, value: &Value) -> usize {
+    match value {
+        Value::Array(items) => {
+            dst.put_u8(TAG_ARRAY);
+            let mut n = 2;
+            for item in items {
+                n += encode_json_value_into(dst, item);
+            }
+            dst.put_u8(END);
+            n
+        }
+        Value::Object(map) => {
+            dst.put_u8(TAG_OBJECT);
+            let mut n = 2;
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+            for (key, item) in entries {
+                dst.put_u8(ENTRY);
+                n += 1 + escape_into(dst, key.as_bytes()) + encode_json_value_into(dst, item);
+            }
+            dst.put_u8(END);
+            n
+        }
+        // Every JSON number converts to `f64`, so scalars always encode.
+        _ => encode_json_scalar_into(dst, value).unwrap_or(0),
+    }
+}
+
+fn encode_json_array_into(dst: &mut Vec<u8>, items: &[Value]) -> Option<usize> {
+    let start = dst.len();
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            dst.push(LexKey::SEPARATOR);
+        }
+        if encode_json_scalar_into(dst, item).is_none() {
+            dst.truncate(start);
+            return None;
+        }
+    }
+    Some(dst.len() - start)
+}
+
+impl LexKey {
+    /// Encode a JSON scalar with a type tag so mixed-type values order deterministically.
+    ///
+    /// See `json::encode_json_scalar_into` for the exact layout. Returns `None` for arrays and
+    /// objects.
+    ///
+    /// ```rust
+    /// use lexkey::LexKey;
+    /// use serde_json::json;
+    ///
+    /// let null = LexKey::encode_json_scalar(&json!(null)).unwrap();
+    /// let num = LexKey::encode_json_scalar(&json!(-3)).unwrap();
+    /// let text = LexKey::encode_json_scalar(&json!("a")).unwrap();
+    /// assert!(null < num && num < text);
+    /// assert!(LexKey::encode_json_scalar(&json!({"a": 1})).is_none());
+    /// ```
+    #[must_use]
+    pub fn encode_json_scalar(value: &Value) -> Option<Self> {
+        let mut buf = Vec::new();
+        encode_json_scalar_into(&mut buf, value)?;
+        Some(Self::from_bytes(buf))
+    }
+
+    /// Encode a JSON array of scalars as a composite key, one tagged part per element.
+    ///
+    /// Returns `None` if `value` is not an array or if any element is an array or object.
+    ///
+    /// ```rust
+    /// use lexkey::LexKey;
+    /// use serde_json::json;
+    ///
+    /// let a = LexKey::encode_json_array(&json!(["acme", 1])).unwrap();
+    /// let b = LexKey::encode_json_array(&json!(["acme", 2])).unwrap();
+    /// assert!(a < b);
+    /// ```
+    #[must_use]
+    pub fn encode_json_array(value: &Value) -> Option<Self> {
+        let items = value.as_array()?;
+        let mut buf = Vec::new();
+        encode_json_array_into(&mut buf, items)?;
+        Some(Self::from_bytes(buf))
+    }
+
+    /// Encode any JSON value, including arrays and objects, with a canonical cross-type order.
+    ///
+    /// See `json::encode_json_value_into` for the layout. The result is self-terminating, so it
+    /// can be followed by further composite parts such as a primary key.
+    ///
+    /// ```rust
+    /// use lexkey::LexKey;
+    /// use serde_json::json;
+    ///
+    /// let ordered = [json!(null), json!(7), json!("a"), json!([1]), json!([1, 2]), json!({"a": 1})];
+    /// for pair in ordered.windows(2) {
+    ///     assert!(LexKey::encode_json_value(&pair[0]) < LexKey::encode_json_value(&pair[1]));
+    /// }
+    /// ```
+    #[must_use]
+    pub fn encode_json_value(value: &Value) -> Self {
+        let mut buf = Vec::new();
+        encode_json_value_into(&mut buf, value);
+        Self::from_bytes(buf)
+    }
+
+    /// Encode the value at a JSON Pointer (RFC 6901) inside `value`, or `None` if it is missing.
+    ///
+    /// ```rust
+    /// use lexkey::LexKey;
+    /// use serde_json::json;
+    ///
+    /// let doc = json!({"user": {"age": 41, "tags": ["a"]}});
+    /// assert_eq!(
+    ///     LexKey::encode_json_pointer(&doc, "/user/age"),
+    ///     Some(LexKey::encode_json_value(&json!(41)))
+    /// );
+    /// assert!(LexKey::encode_json_pointer(&doc, "/user/name").is_none());
+    /// ```
+    #[must_use]
+    pub fn encode_json_pointer(value: &Value, pointer: &str) -> Option<Self> {
+        value.pointer(pointer).map(Self::encode_json_value)
+    }
+
+    /// Encode the values at several JSON Pointers as a composite, one part per pointer.
+    ///
+    /// A missing path encodes as JSON `null`, as an expression index over documents without
+    /// the field would store it.
+    ///
+    /// ```rust
+    /// use lexkey::LexKey;
+    /// use serde_json::json;
+    ///
+    /// let key = |doc| LexKey::encode_json_pointers(&doc, &["/tenant", "/score"]);
+    /// assert!(key(json!({"tenant": "a"})) < key(json!({"tenant": "a", "score": -1})));
+    /// assert!(key(json!({"tenant": "a", "score": 9})) < key(json!({"tenant": "b"})));
+    /// ```
+    #[must_use]
+    pub fn encode_json_pointers(value: &Value, pointers: &[&str]) -> Self {
+        let mut buf = Vec::new();
+        encode_json_pointers_into(&mut buf, value, pointers);
+        Self::from_bytes(buf)
+    }
+}
+
+fn encode_json_pointers_into(dst: &mut Vec<u8>, value: &Value, pointers: &[&str]) -> usize {
+    let start = dst.len();
+    for (i, pointer) in pointers.iter().enumerate() {
+        if i > 0 {
+            dst.push(LexKey::SEPARATOR);
+        }
+        encode_json_value_into(dst, value.pointer(pointer).unwrap_or(&Value::Null));
+    }
+    dst.len() - start
+}
+
+impl KeySchema {
+    /// Decode `key` into a JSON object with one member per field, for log pipelines and APIs.
+    ///
+    /// Members are keyed by field name, or by the field's index for schemas made with
+    /// `KeySchema::new`. Integers and finite floats become numbers, booleans stay booleans, and
+    /// strings, bytes (`0x`-prefixed hex), UUIDs and non-finite floats become strings as rendered
+    /// by `schema::Value`'s `Display`.
+    ///
+    /// ```rust
+    /// use lexkey::encode_composite;
+    /// use lexkey::schema::{FieldType, KeySchema};
+    /// use serde_json::json;
+    ///
+    /// let schema = KeySchema::named([("tenant", FieldType::Str), ("ts", FieldType::I64)]);
+    /// let key = encode_composite!("acme", 1_700_000_000i64);
+    /// assert_eq!(
+    ///     schema.decode_to_json(key.as_bytes())?,
+    ///     json!({"tenant": "acme", "ts": 1_700_000_000})
+    /// );
+    /// # Ok::<(), lexkey::DecodeError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns the `DecodeError` from `KeySchema::decode`.
+    pub fn decode_to_json(&self, key: &[u8]) -> Result<Value, DecodeError> {
+        let object = self
+            .decode(key)?
+            .iter()
+            .enumerate()
+            .map(|(i, value)| (self.label(i), field_to_json(value)))
+            .collect();
+        Ok(Value::Object(object))
+    }
+}
+
+fn field_to_json(value: &schema::Value) -> Value {
+    use schema::Value as V;
+    match value {
+        V::Str(s) => Value::from(s.as_str()),
+        V::U8(n) => Value::from(*n),
+        V::U16(n) => Value::from(*n),
+        V::U32(n) => Value::from(*n),
+        V::U64(n) => Value::from(*n),
+        V::I8(n) => Value::from(*n),
+        V::I16(n) => Value::from(*n),
+        V::I32(n) => Value::from(*n),
+        V::I64(n) => Value::from(*n),
+        V::F32(x) => serde_json::Number::from_f64(f64::from(*x))
+            .map_or_else(|| Value::String(value.to_string()), Value::Number),
+        V::F64(x) => serde_json::Number::from_f64(*x)
+            .map_or_else(|| Value::String(value.to_string()), Value::Number),
+        V::Bool(b) => Value::Bool(*b),
+        V::Bytes(_) | V::Uuid(_) => Value::String(value.to_string()),
+    }
+}
+
+impl Encoder {
+    /// Append the self-terminating encoding of any JSON value; see `LexKey::encode_json_value`.
+    #[inline]
+    pub fn encode_json_value_into(&mut self, value: &Value) -> usize {
+        encode_json_value_into(&mut self.buf, value)
+    }
+
+    /// Append the tagged encoding of a JSON scalar, or return `None` for arrays and objects.
+    #[inline]
+    pub fn encode_json_scalar_into(&mut self, value: &Value) -> Option<usize> {
+        encode_json_scalar_into(&mut self.buf, value)
+    }
+
+    /// Append a JSON array of scalars as separator-joined tagged parts.
+    ///
+    /// Returns `None` and leaves the buffer unchanged if `value` is not an array of scalars.
+    #[inline]
+    pub fn encode_json_array_into(&mut self, value: &Value) -> Option<usize> {
+        encode_json_array_into(&mut self.buf, value.as_array()?)
+    }
+
+    /// Append the value at a JSON Pointer, or return `None` and write nothing if it is missing;
+    /// see `LexKey::encode_json_pointer`.
+    #[inline]
+    pub fn encode_json_pointer_into(&mut self, value: &Value, pointer: &str) -> Option<usize> {
+        Some(encode_json_value_into(
+            &mut self.buf,
+            value.pointer(pointer)?,
+        ))
+    }
+
+    /// Append the values at several JSON Pointers as separator-joined parts; see
+    /// `LexKey::encode_json_pointers`.
+    #[inline]
+    pub fn encode_json_pointers_into(&mut self, value: &Value, pointers: &[&str]) -> usize {
+        encode_json_pointers_into(&mut self.buf, value, pointers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn key(v: &Value) -> LexKey {
+        LexKey::encode_json_scalar(v).unwrap()
+    }
+
+    #[test]
+    fn should_order_scalars_by_type_then_value() {
+        let ordered = [
+            json!(null),
+            json!(false),
+            json!(true),
+            json!(-1.5),
+            json!(0),
+            json!(2),
+            json!(10),
+            json!(""),
+            json!("a"),
+            json!("b"),
+        ];
+        for pair in ordered.windows(2) {
+            assert!(key(&pair[0]) < key(&pair[1]), "{} < {}", pair[0], pair[1]);
+        }
+    }
+
+    #[test]
+    fn should_encode_integer_and_float_forms_equally() {
+        assert_eq!(key(&json!(3)), key(&json!(3.0)));
+        assert_eq!(key(&json!(0)), key(&json!(-0.0)));
+    }
+
+    #[test]
+    fn should_keep_large_integers_exact_and_ordered() {
+        let two53 = 1u64 << 53;
+        let ordered = [
+            json!(i64::MIN),
+            json!(i64::MIN + 1),
+            json!(-1.5),
+            json!(two53 - 1),
+            json!(two53),
+            json!(two53 + 1),
+            json!(two53 + 2),
+            json!(two53 + 3),
+            json!(9.1e15),
+            json!(i64::MAX - 1),
+            json!(i64::MAX),
+            json!(1u64 << 63),
+            json!(u64::MAX - 1),
+            json!(u64::MAX),
+            json!(1e300),
+        ];
+        for pair in ordered.windows(2) {
+            assert!(key(&pair[0]) < key(&pair[1]), "{} < {}", pair[0], pair[1]);
+        }
+        assert_eq!(key(&json!(two53)), key(&json!(9_007_199_254_740_992.0)));
+        assert_eq!(
+            key(&json!(two53 + 1)).as_bytes(),
+            [
+                &[TAG_NUMBER][..],
+                LexKey::encode_f64(9_007_199_254_740_992.0).as_bytes(),
+                LexKey::encode_i16(1).as_bytes(),
+            ]
+            .concat()
+        );
+    }
+
+    #[test]
+    fn should_encode_arrays_as_composites() {
+        let k = LexKey::encode_json_array(&json!(["x", true])).unwrap();
+        assert_eq!(
+            k.as_bytes(),
+            &[TAG_STRING, b'x', 0x00, 0x01, 0x00, TAG_TRUE]
+        );
+
+        let mut enc = Encoder::with_capacity(8);
+        enc.push_byte(0xaa);
+        assert!(enc.encode_json_array_into(&json!([1, [2]])).is_none());
+        assert!(enc.encode_json_array_into(&json!("x")).is_none());
+        assert_eq!(enc.as_slice(), &[0xaa]);
+    }
+
+    #[test]
+    fn should_encode_json_pointers_through_encoder() {
+        let doc = json!({"tenant": "a", "score": 9});
+        let mut enc = Encoder::with_capacity(32);
+        assert_eq!(enc.encode_json_pointer_into(&doc, "/missing"), None);
+        assert!(enc.is_empty());
+        let n = enc.encode_json_pointer_into(&doc, "/score").unwrap();
+        assert_eq!(
+            enc.split_key(),
+            LexKey::encode_json_pointer(&doc, "/score").unwrap()
+        );
+        assert_eq!(n, LexKey::encode_json_value(&json!(9)).len());
+
+        let pointers = ["/tenant", "/missing", "/score"];
+        let n = enc.encode_json_pointers_into(&doc, &pointers);
+        let key = LexKey::encode_json_pointers(&doc, &pointers);
+        assert_eq!((enc.as_slice(), n), (key.as_bytes(), key.len()));
+    }
+
+    #[test]
+    fn should_render_decoded_keys_as_json_objects() {
+        use crate::schema::FieldType;
+        use uuid::Uuid;
+
+        let named = KeySchema::named([
+            ("tenant", FieldType::Str),
+            ("blob", FieldType::Bytes),
+            ("shard", FieldType::U8),
+            ("delta", FieldType::I32),
+            ("score", FieldType::F64),
+            ("ratio", FieldType::F32),
+            ("live", FieldType::Bool),
+            ("id", FieldType::Uuid),
+        ]);
+        let key = crate::encode_composite!(
+            "acme",
+            b"\x01\xff",
+            3u8,
+            -7i32,
+            f64::INFINITY,
+            0.5f32,
+            true,
+            Uuid::from_u128(1)
+        );
+        assert_eq!(
+            named.decode_to_json(key.as_bytes()),
+            Ok(json!({
+                "tenant": "acme",
+                "blob": "0x01ff",
+                "shard": 3,
+                "delta": -7,
+                "score": "inf",
+                "ratio": 0.5,
+                "live": true,
+                "id": "00000000-0000-0000-0000-000000000001",
+            }))
+        );
+
+        let unnamed = KeySchema::new([FieldType::Str, FieldType::U64]);
+        let key = crate::encode_composite!("a", u64::MAX);
+        assert_eq!(
+            unnamed.decode_to_json(key.as_bytes()),
+            Ok(json!({"0": "a", "1": u64::MAX}))
+        );
+        assert!(unnamed.decode_to_json(b"a").is_err());
+    }
+
+    #[test]
+    fn should_order_nested_values_canonically() {
+        let value = |v: &Value| LexKey::encode_json_value(v);
+        let ordered = [
+            json!(null),
+            json!(true),
+            json!(-1),
+            json!(""),
+            json!("a"),
+            json!("a\u{0}"),
+            json!("ab"),
+            json!([]),
+            json!([null]),
+            json!([1, "x"]),
+            json!([1, "x", null]),
+            json!([2]),
+            json!({}),
+            json!({"": 5}),
+            json!({"a": 1}),
+            json!({"a": 1, "b": 0}),
+            json!({"a": 2}),
+            json!({"b": []}),
+        ];
+        for pair in ordered.windows(2) {
+            assert!(
+                value(&pair[0]) < value(&pair[1]),
+                "{} < {}",
+                pair[0],
+                pair[1]
+            );
+        }
+
+        assert_eq!(
+            value(&json!({"a": 1, "b": [true]})).as_bytes(),
+            [
+                &[TAG_OBJECT, ENTRY, b'a', 0x00, 0x01, TAG_NUMBER][..],
+                LexKey::encode_f64(1.0).as_bytes(),
+                LexKey::encode_i16(0).as_bytes(),
+                &[ENTRY, b'b', 0x00, 0x01, TAG_ARRAY, TAG_TRUE, END, END],
+            ]
+            .concat()
+        );
+        assert_eq!(
+            value(&json!("a\u{0}")),
+            LexKey::encode_json_scalar(&json!("a\u{0}")).unwrap()
+        );
+        // A string part stays ordered when more parts follow it.
+        let with_id = |s: &str, id: u8| {
+            let mut enc = Encoder::with_capacity(16);
+            enc.encode_json_value_into(&json!(s));
+            enc.push_separator();
+            enc.encode_u8_into(id);
+            enc.freeze()
+        };
+        assert!(with_id("a", 9) < with_id("a\u{0}", 0));
+    }
+}
@@ -0,0 +1,171 @@
+use bytes::BufMut;
+use siphasher::sip128::SipHasher24;
+
+use crate::{Encoder, LexKey};
+
+/// Replaces identifier components with a keyed SipHash-2-4 digest.
+///
+/// Equal identifiers always map to equal digests under the same secret, so point lookups and
+/// prefix scans *within* a hashed scope keep working, while raw tenant or user identifiers never
+/// appear in the keyspace. Digests do not preserve the order of the original identifiers.
+///
+/// The digest is the 128-bit SipHash-2-4 output in big-endian order, truncated to `width` bytes.
+/// Narrow widths save space at the cost of a higher collision probability.
+///
+/// ```rust
+/// use lexkey::{KeyedHasher, LexKey};
+///
+/// let hasher = KeyedHasher::new([7u8; 16], 8);
+/// let tenant = hasher.encode(b"acme");
+/// assert_eq!(tenant.as_bytes().len(), 8);
+///
+/// let row = hasher.encode_scoped(b"acme", &[b"orders", b"42"]);
+/// let (lower, upper) = hasher.range_bounds(b"acme");
+/// assert!(lower < row && row < upper);
+/// ```
+#[derive(Clone)]
+pub struct KeyedHasher {
+    hasher: SipHasher24,
+    width: usize,
+}
+
+impl KeyedHasher {
+    /// The widest supported digest in bytes.
+    pub const MAX_WIDTH: usize = 16;
+
+    /// Create a hasher from a 16-byte secret and a digest width in bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` is zero or greater than `MAX_WIDTH`.
+    #[must_use]
+    pub fn new(secret: [u8; 16], width: usize) -> Self {
+        assert!(
+            (1..=Self::MAX_WIDTH).contains(&width),
+            "keyed digest width must be between 1 and 16 bytes"
+        );
+        Self {
+            hasher: SipHasher24::new_with_key(&secret),
+            width,
+        }
+    }
+
+    /// Digest width in bytes.
+    #[inline]
+    #[must_use]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[inline]
+    fn digest(&self, id: &[u8]) -> [u8; 16] {
+        self.hasher.hash(id).as_u128().to_be_bytes()
+    }
+
+    /// Append the truncated digest of `id` into `dst` and return the width.
+    #[inline]
+    pub fn encode_into<B: BufMut + ?Sized>(&self, dst: &mut B, id: &[u8]) -> usize {
+        dst.put_slice(&self.digest(id)[..self.width]);
+        self.width
+    }
+
+    /// Encode the truncated digest of `id` as a key.
+    #[inline]
+    #[must_use]
+    pub fn encode(&self, id: &[u8]) -> LexKey {
+        let mut buf = Vec::with_capacity(self.width);
+        self.encode_into(&mut buf, id);
+        LexKey::from_bytes(buf)
+    }
+
+    /// Encode `digest(id) || 0x00 || rest[0] || 0x00 || ...` as a composite key.
+    #[must_use]
+    pub fn encode_scoped(&self, id: &[u8], rest: &[&[u8]]) -> LexKey {
+        let mut buf = Vec::with_capacity(self.width + 1 + crate::encode_len(rest));
+        self.encode_into(&mut buf, id);
+        if !rest.is_empty() {
+            buf.push(LexKey::SEPARATOR);
+            crate::encode_parts_into(&mut buf, rest);
+        }
+        LexKey::from_bytes(buf)
+    }
+
+    /// Return the structured range bounds covering every key scoped under `id`.
+    #[must_use]
+    pub fn range_bounds(&self, id: &[u8]) -> (LexKey, LexKey) {
+        LexKey::encode_range_bounds(self.encode(id).as_bytes())
+    }
+}
+
+impl std::fmt::Debug for KeyedHasher {
+    // The secret is deliberately omitted.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeyedHasher")
+            .field("width", &self.width)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Encoder {
+    /// Append the keyed digest of `id` produced by `hasher` and return its width.
+    #[inline]
+    pub fn encode_keyed_into(&mut self, hasher: &KeyedHasher, id: &[u8]) -> usize {
+        self.encode_bytes_into(&hasher.digest(id)[..hasher.width])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_map_equal_ids_to_equal_digests_of_configured_width() {
+        let hasher = KeyedHasher::new([1u8; 16], 8);
+
+        let a = hasher.encode(b"tenant-a");
+        let again = hasher.encode(b"tenant-a");
+        let b = hasher.encode(b"tenant-b");
+
+        assert_eq!(a, again);
+        assert_ne!(a, b);
+        assert_eq!(a.as_bytes().len(), 8);
+        assert!(!a.as_bytes().windows(8).any(|w| w == b"tenant-a"));
+    }
+
+    #[test]
+    fn should_depend_on_secret() {
+        let one = KeyedHasher::new([1u8; 16], 16).encode(b"acme");
+        let two = KeyedHasher::new([2u8; 16], 16).encode(b"acme");
+        assert_ne!(one, two);
+    }
+
+    #[test]
+    fn should_truncate_full_digest_to_width() {
+        let full = KeyedHasher::new([3u8; 16], 16).encode(b"acme");
+        let short = KeyedHasher::new([3u8; 16], 4).encode(b"acme");
+        assert_eq!(short.as_bytes(), &full.as_bytes()[..4]);
+    }
+
+    #[test]
+    fn should_scope_children_under_hashed_prefix() {
+        let hasher = KeyedHasher::new([4u8; 16], 8);
+        let key = hasher.encode_scoped(b"acme", &[b"orders"]);
+        let (lower, upper) = hasher.range_bounds(b"acme");
+        let (other_lower, other_upper) = hasher.range_bounds(b"globex");
+
+        assert!(lower < key && key < upper);
+        assert!(!(other_lower < key && key < other_upper));
+
+        let mut enc = Encoder::with_capacity(32);
+        enc.encode_keyed_into(&hasher, b"acme");
+        enc.push_separator();
+        enc.encode_bytes_into(b"orders");
+        assert_eq!(enc.as_slice(), key.as_bytes());
+    }
+
+    #[test]
+    #[should_panic(expected = "keyed digest width must be between 1 and 16 bytes")]
+    fn should_panic_on_zero_width() {
+        let _ = KeyedHasher::new([0u8; 16], 0);
+    }
+}
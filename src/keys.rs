@@ -0,0 +1,93 @@
+//! Binary-search helpers over sorted key slices.
+//!
+//! These helpers assume the slice is sorted in ascending byte order, for example a `Vec<LexKey>`
+//! after `sort()`. They return indices so in-memory indexes can slice their own storage.
+
+use std::ops::{Bound, Range};
+
+use crate::{KeyRange, LexKey};
+
+/// Return the index of the first key that is greater than or equal to `prefix`.
+///
+/// Every key starting with `prefix` sits at or after this index.
+///
+/// ```rust
+/// use lexkey::{keys, LexKey};
+/// let sorted: Vec<LexKey> = [&b"a"[..], b"ab", b"b"].into_iter().map(LexKey::from).collect();
+/// assert_eq!(keys::partition_point_for_prefix(&sorted, b"ab"), 1);
+/// ```
+#[inline]
+#[must_use]
+pub fn partition_point_for_prefix(keys: &[LexKey], prefix: &[u8]) -> usize {
+    keys.partition_point(|k| k.as_bytes() < prefix)
+}
+
+/// Return the index range of the keys inside `range`.
+///
+/// Returns an empty range positioned at the lower bound when nothing matches.
+///
+/// ```rust
+/// use lexkey::{keys, KeyRange, LexKey};
+/// let sorted: Vec<LexKey> = [&b"a"[..], b"ab", b"abc", b"b"].into_iter().map(LexKey::from).collect();
+/// assert_eq!(keys::range_indices(&sorted, &KeyRange::prefix(b"ab")), 1..3);
+/// ```
+#[must_use]
+pub fn range_indices(keys: &[LexKey], range: &KeyRange) -> Range<usize> {
+    let lo = match range.start() {
+        Bound::Included(s) => keys.partition_point(|k| k < s),
+        Bound::Excluded(s) => keys.partition_point(|k| k <= s),
+        Bound::Unbounded => 0,
+    };
+    let hi = match range.end() {
+        Bound::Included(e) => keys.partition_point(|k| k <= e),
+        Bound::Excluded(e) => keys.partition_point(|k| k < e),
+        Bound::Unbounded => keys.len(),
+    };
+    lo..hi.max(lo)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(raw: &[&[u8]]) -> Vec<LexKey> {
+        let mut keys: Vec<LexKey> = raw.iter().copied().map(LexKey::from).collect();
+        keys.sort();
+        keys
+    }
+
+    #[test]
+    fn should_find_partition_point_for_prefix() {
+        let keys = sorted(&[b"a", b"b\x00x", b"b\x00y", b"c"]);
+        assert_eq!(partition_point_for_prefix(&keys, b"b"), 1);
+        assert_eq!(partition_point_for_prefix(&keys, b"0"), 0);
+        assert_eq!(partition_point_for_prefix(&keys, b"z"), 4);
+    }
+
+    #[test]
+    fn should_return_indices_for_partition_range() {
+        let keys = sorted(&[b"a", b"b\x00x", b"b\x00y", b"c"]);
+        assert_eq!(range_indices(&keys, &KeyRange::partition(b"b")), 1..3);
+        assert_eq!(range_indices(&keys, &KeyRange::all()), 0..4);
+    }
+
+    #[test]
+    fn should_respect_inclusive_and_exclusive_bounds() {
+        let keys = sorted(&[b"a", b"b", b"c", b"d"]);
+        let b = LexKey::from(&b"b"[..]);
+        let d = LexKey::from(&b"d"[..]);
+
+        let inclusive = KeyRange::new(Bound::Included(b.clone()), Bound::Included(d.clone()));
+        let exclusive = KeyRange::new(Bound::Excluded(b), Bound::Excluded(d));
+
+        assert_eq!(range_indices(&keys, &inclusive), 1..4);
+        assert_eq!(range_indices(&keys, &exclusive), 2..3);
+    }
+
+    #[test]
+    fn should_return_empty_range_for_inverted_bounds() {
+        let keys = sorted(&[b"a", b"b", b"c"]);
+        let inverted = KeyRange::half_open(LexKey::from(&b"c"[..]), LexKey::from(&b"a"[..]));
+        assert!(range_indices(&keys, &inverted).is_empty());
+    }
+}
@@ -0,0 +1,232 @@
+//! Declarative key layouts with `define_keyspace!`.
+//!
+//! Each keyspace is declared once with its name and typed fields:
+//!
+//! ```rust
+//! use lexkey::define_keyspace;
+//! use uuid::Uuid;
+//!
+//! define_keyspace! {
+//!     /// Orders by tenant.
+//!     pub Orders => (tenant: &str, order_id: Uuid);
+//!     pub Users => (user_id: u64);
+//! }
+//!
+//! let id = Uuid::from_u128(7);
+//! let key = Orders::encode("acme", id);
+//! assert_eq!(Orders::decode(key.as_bytes())?, ("acme", id));
+//! assert!(Orders::range().contains_key(key.as_bytes()));
+//! assert!(!Users::range().contains_key(key.as_bytes()));
+//! # Ok::<(), lexkey::DecodeError>(())
+//! ```
+//!
+//! The macro defines an uninhabited marker type per keyspace with four associated functions:
+//!
+//! - `encode(fields..) -> TypedKey<Self>` writes the composite `(name, fields..)`.
+//! - `prefix() -> LexKey` is the leading name part shared by every key of the keyspace.
+//! - `range() -> KeyRange` is the child range of that prefix (`name || 00` up to `name || 01`),
+//!   covering every key of the keyspace and none of a keyspace whose name extends it.
+//! - `decode(&[u8]) -> Result<(fields..), DecodeError>` checks the name and decodes the fields.
+//!
+//! The name part is the keyspace identifier as written, so renaming a keyspace changes its keys.
+//! Fields use their `Encodable` impls; `&str` and `&[u8]` fields decode borrowed from the key,
+//! other fields through `Decodable`. Variable-length fields are read up to the next separator,
+//! so only the last field may contain `0x00`.
+
+use crate::{Decodable, DecodeError, Decoder, LexKey};
+
+/// Decode one keyspace field, borrowing from the key where the field type allows it.
+#[doc(hidden)]
+pub trait DecodeField<'a>: Sized {
+    fn decode_field(dec: &mut Decoder<'a>) -> Result<Self, DecodeError>;
+}
+
+impl<'a, T: Decodable> DecodeField<'a> for T {
+    #[inline]
+    fn decode_field(dec: &mut Decoder<'a>) -> Result<Self, DecodeError> {
+        dec.decode_value()
+    }
+}
+
+impl<'a> DecodeField<'a> for &'a [u8] {
+    #[inline]
+    fn decode_field(dec: &mut Decoder<'a>) -> Result<Self, DecodeError> {
+        let rest = dec.remaining();
+        let len = rest
+            .iter()
+            .position(|&b| b == LexKey::SEPARATOR)
+            .unwrap_or(rest.len());
+        dec.skip(len);
+        Ok(&rest[..len])
+    }
+}
+
+impl<'a> DecodeField<'a> for &'a str {
+    #[inline]
+    fn decode_field(dec: &mut Decoder<'a>) -> Result<Self, DecodeError> {
+        let start = dec.position();
+        let part = <&[u8]>::decode_field(dec)?;
+        std::str::from_utf8(part).map_err(|e| {
+            dec.rewind(start);
+            DecodeError::Invalid {
+                offset: start + e.valid_up_to(),
+                expected: "UTF-8 string",
+            }
+        })
+    }
+}
+
+/// Consume the keyspace name part, failing if the key belongs to another keyspace.
+#[doc(hidden)]
+pub fn expect_name(dec: &mut Decoder<'_>, name: &str) -> Result<(), DecodeError> {
+    let start = dec.position();
+    if <&[u8]>::decode_field(dec)? == name.as_bytes() {
+        Ok(())
+    } else {
+        dec.rewind(start);
+        Err(DecodeError::Invalid {
+            offset: start,
+            expected: "keyspace name",
+        })
+    }
+}
+
+/// Declare strongly typed keyspaces; see the `keyspace` module.
+///
+/// ```rust
+/// lexkey::define_keyspace! {
+///     Events => (stream: &str, seq: u64);
+///     EventsArchive => (stream: &str, seq: u64);
+/// }
+///
+/// let key = Events::encode("clicks", 3);
+/// assert_eq!(Events::range(), lexkey::KeyRange::children(Events::prefix().as_bytes()));
+/// assert!(Events::range().contains_key(key.as_bytes()));
+/// assert!(!Events::range().contains_key(EventsArchive::encode("clicks", 3).as_bytes()));
+/// ```
+#[macro_export]
+macro_rules! define_keyspace {
+    ($(
+        $(#[$meta:meta])*
+        $vis:vis $name:ident => ($($field:ident: $ty:ty),+ $(,)?)
+    );+ $(;)?) => {
+        $(
+            $(#[$meta])*
+            $vis enum $name {}
+
+            impl $name {
+                /// Encode a key of this keyspace.
+                #[must_use]
+                $vis fn encode($($field: $ty),+) -> $crate::TypedKey<Self> {
+                    $crate::TypedKey::from_lexkey($crate::encode_composite!(
+                        stringify!($name) $(, $field)+
+                    ))
+                }
+
+                /// The name part that starts every key of this keyspace.
+                #[must_use]
+                $vis fn prefix() -> $crate::LexKey {
+                    $crate::LexKey::encode_string(stringify!($name))
+                }
+
+                /// The range covering every key of this keyspace.
+                #[must_use]
+                $vis fn range() -> $crate::KeyRange {
+                    $crate::KeyRange::children(stringify!($name).as_bytes())
+                }
+
+                /// Decode the fields of a key of this keyspace.
+                ///
+                /// # Errors
+                ///
+                /// Returns a `DecodeError` if the key belongs to another keyspace, a field is
+                /// truncated or invalid, or bytes remain after the last field.
+                $vis fn decode(key: &[u8]) -> ::std::result::Result<($($ty,)+), $crate::DecodeError> {
+                    let mut dec = $crate::Decoder::new(key);
+                    $crate::keyspace::expect_name(&mut dec, stringify!($name))?;
+                    $(
+                        dec.expect_separator()?;
+                        let $field = <$ty as $crate::keyspace::DecodeField<'_>>::decode_field(&mut dec)?;
+                    )+
+                    dec.finish()?;
+                    ::std::result::Result::Ok(($($field,)+))
+                }
+            }
+        )+
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use crate::{encode_composite, DecodeError, TypedKey};
+
+    crate::define_keyspace! {
+        Orders => (tenant: &str, order_id: Uuid);
+        pub(crate) Blobs => (owner: String, hash: &[u8]);
+        OrdersArchive => (order_id: u64);
+    }
+
+    #[test]
+    fn should_encode_name_then_fields() {
+        let id = Uuid::from_u128(1);
+        let key: TypedKey<Orders> = Orders::encode("acme", id);
+        assert_eq!(
+            key.as_lexkey(),
+            &encode_composite!("Orders", "acme", Uuid::from_u128(1))
+        );
+        assert_eq!(Orders::prefix().as_bytes(), b"Orders");
+        assert!(Orders::range().contains_key(key.as_bytes()));
+        assert!(Orders::encode("a", id) < Orders::encode("b", Uuid::nil()));
+    }
+
+    #[test]
+    fn should_decode_borrowed_and_owned_fields() {
+        let key = Blobs::encode("alice".to_owned(), b"\x01\x02");
+        assert_eq!(
+            Blobs::decode(key.as_bytes()),
+            Ok(("alice".to_owned(), &b"\x01\x02"[..]))
+        );
+    }
+
+    #[test]
+    fn should_reject_keys_of_other_keyspaces() {
+        let key = Blobs::encode("alice".to_owned(), b"");
+        assert_eq!(
+            Orders::decode(key.as_bytes()),
+            Err(DecodeError::Invalid {
+                offset: 0,
+                expected: "keyspace name",
+            })
+        );
+
+        let key = Orders::encode("acme", Uuid::nil());
+        let bytes = key.as_bytes();
+        assert_eq!(
+            Orders::decode(&bytes[..bytes.len() - 1]),
+            Err(DecodeError::UnexpectedEnd {
+                offset: 12,
+                needed: 16,
+                available: 15,
+            })
+        );
+    }
+
+    #[test]
+    fn should_keep_keyspace_ranges_disjoint() {
+        let blob = Blobs::encode("alice".to_owned(), b"h");
+        let order = Orders::encode("alice", Uuid::nil());
+        assert_eq!(Blobs::prefix().as_bytes(), b"Blobs");
+        assert!(Blobs::range().contains_key(blob.as_bytes()));
+        assert!(!Blobs::range().contains_key(order.as_bytes()));
+        assert!(!Orders::range().contains_key(blob.as_bytes()));
+
+        let archived = OrdersArchive::encode(1);
+        assert!(OrdersArchive::prefix().as_bytes().starts_with(b"Orders"));
+        assert_eq!(OrdersArchive::decode(archived.as_bytes()), Ok((1,)));
+        assert!(OrdersArchive::range().contains_key(archived.as_bytes()));
+        assert!(!Orders::range().contains_key(archived.as_bytes()));
+        assert!(Orders::decode(archived.as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,195 @@
+use std::ops::Bound;
+
+use bytes::Bytes;
+use rusty_leveldb::{Cmp, LdbIterator};
+
+use crate::{KeyRange, LexKey};
+
+impl LexKey {
+    /// Wrap a key returned by `rusty_leveldb` without copying.
+    #[inline]
+    #[must_use]
+    pub fn from_leveldb(key: Bytes) -> Self {
+        Self::from_bytes(key)
+    }
+}
+
+/// Position `iter` on the first entry inside `range`, returning false if there is none.
+///
+/// Only the lower bound is applied; use `scan` to also stop at the upper bound.
+pub fn seek_range<I: LdbIterator + ?Sized>(iter: &mut I, range: &KeyRange) -> bool {
+    match range.start() {
+        Bound::Included(start) => iter.seek(start.as_bytes()),
+        Bound::Excluded(start) => {
+            iter.seek(start.as_bytes());
+            if iter
+                .current()
+                .is_some_and(|(k, _)| k.as_ref() == start.as_bytes())
+            {
+                iter.advance();
+            }
+        }
+        Bound::Unbounded => iter.seek_to_first(),
+    }
+    iter.valid()
+}
+
+/// Iterate the entries of `iter` that fall inside `range`, in ascending key order.
+///
+/// ```rust
+/// use lexkey::{leveldb, KeyRange, LexKey};
+/// use rusty_leveldb::{in_memory, DB};
+///
+/// let mut db = DB::open("scan-doc", in_memory()).unwrap();
+/// for id in [&b"a"[..], b"b", b"c"] {
+///     db.put(LexKey::encode_composite(&[b"t1", id]).as_bytes(), id).unwrap();
+/// }
+/// db.put(b"t2\x00a", b"other").unwrap();
+///
+/// let mut iter = db.new_iter().unwrap();
+/// let values: Vec<_> = leveldb::scan(&mut iter, &KeyRange::partition(b"t1"))
+///     .map(|(_, v)| v)
+///     .collect();
+/// assert_eq!(values, [&b"a"[..], b"b", b"c"]);
+/// ```
+pub fn scan<'a, I: LdbIterator + ?Sized>(iter: &'a mut I, range: &KeyRange) -> Scan<'a, I> {
+    let positioned = seek_range(iter, range);
+    Scan {
+        iter,
+        end: range.end().cloned(),
+        positioned,
+        done: !positioned,
+    }
+}
+
+/// Iterator returned by `scan`.
+pub struct Scan<'a, I: LdbIterator + ?Sized> {
+    iter: &'a mut I,
+    end: Bound<LexKey>,
+    positioned: bool,
+    done: bool,
+}
+
+impl<I: LdbIterator + ?Sized> Iterator for Scan<'_, I> {
+    type Item = (LexKey, Bytes);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        if !std::mem::take(&mut self.positioned) && !self.iter.advance() {
+            self.done = true;
+            return None;
+        }
+        let Some((key, value)) = self.iter.current() else {
+            self.done = true;
+            return None;
+        };
+        let in_range = match &self.end {
+            Bound::Included(end) => key.as_ref() <= end.as_bytes(),
+            Bound::Excluded(end) => key.as_ref() < end.as_bytes(),
+            Bound::Unbounded => true,
+        };
+        if !in_range {
+            self.done = true;
+            return None;
+        }
+        Some((LexKey::from_leveldb(key), value))
+    }
+}
+
+/// Check that `cmp` orders representative lexkeys exactly like raw byte comparison.
+///
+/// Lexkey ranges and prefix scans assume a bytewise comparator, which is the `LevelDB` default. Run
+/// this at startup when the comparator is configurable.
+///
+/// ```rust
+/// use rusty_leveldb::DefaultCmp;
+/// assert!(lexkey::leveldb::is_bytewise_comparator(&DefaultCmp));
+/// ```
+#[must_use]
+pub fn is_bytewise_comparator(cmp: &dyn Cmp) -> bool {
+    let samples: [&[u8]; 9] = [
+        b"",
+        b"\x00",
+        b"\x00\x00",
+        b"\x00\xff",
+        b"\x01",
+        b"a",
+        b"a\x00b",
+        b"ab",
+        b"\xff",
+    ];
+    samples
+        .iter()
+        .all(|a| samples.iter().all(|b| cmp.cmp(a, b) == Ord::cmp(a, b)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use super::*;
+    use rusty_leveldb::{in_memory, DB};
+
+    fn db_with(keys: &[&[u8]]) -> DB {
+        let mut db = DB::open("lexkey-test", in_memory()).unwrap();
+        for k in keys {
+            db.put(k, b"").unwrap();
+        }
+        db
+    }
+
+    fn scan_keys(db: &mut DB, range: &KeyRange) -> Vec<Vec<u8>> {
+        let mut iter = db.new_iter().unwrap();
+        scan(&mut iter, range)
+            .map(|(k, _)| k.as_bytes().to_vec())
+            .collect()
+    }
+
+    #[test]
+    fn should_scan_with_inclusive_and_exclusive_bounds() {
+        let mut db = db_with(&[b"a", b"b", b"c", b"d"]);
+        let b = LexKey::from(&b"b"[..]);
+        let d = LexKey::from(&b"d"[..]);
+
+        let inclusive = KeyRange::new(Bound::Included(b.clone()), Bound::Included(d.clone()));
+        let exclusive = KeyRange::new(Bound::Excluded(b), Bound::Excluded(d));
+
+        assert_eq!(
+            scan_keys(&mut db, &inclusive),
+            vec![b"b".to_vec(), b"c".to_vec(), b"d".to_vec()]
+        );
+        assert_eq!(scan_keys(&mut db, &exclusive), vec![b"c".to_vec()]);
+        assert_eq!(scan_keys(&mut db, &KeyRange::all()).len(), 4);
+    }
+
+    #[test]
+    fn should_yield_nothing_past_last_key() {
+        let mut db = db_with(&[b"a", b"b"]);
+        assert!(scan_keys(&mut db, &KeyRange::prefix(b"z")).is_empty());
+    }
+
+    struct Reversed;
+
+    impl Cmp for Reversed {
+        fn cmp(&self, a: &[u8], b: &[u8]) -> Ordering {
+            b.cmp(a)
+        }
+        fn find_shortest_sep(&self, from: &[u8], _to: &[u8]) -> Vec<u8> {
+            from.to_vec()
+        }
+        fn find_short_succ(&self, key: &[u8]) -> Vec<u8> {
+            key.to_vec()
+        }
+        fn id(&self) -> &'static str {
+            "reversed"
+        }
+    }
+
+    #[test]
+    fn should_reject_non_bytewise_comparator() {
+        assert!(is_bytewise_comparator(&rusty_leveldb::DefaultCmp));
+        assert!(!is_bytewise_comparator(&Reversed));
+    }
+}
@@ -1,13 +1,17 @@
-use bytes::Bytes;
+use bytes::{BufMut, Bytes};
 use std::cmp::Ordering;
+use std::ops::Bound;
 use uuid::Uuid;
 
+use crate::desc::escaped_desc_into;
+use crate::escape::escaped_len;
 use crate::Encodable;
 
 const SIGN_BIT: u64 = 0x8000_0000_0000_0000;
 const SIGN_BIT_8: u8 = 0x80;
 const SIGN_BIT_16: u16 = 0x8000;
 const SIGN_BIT_32: u32 = 0x8000_0000;
+const SIGN_BIT_128: u128 = 1 << 127;
 
 // Small static byte buffers used to avoid allocating tiny Vecs for common single-byte
 // encodings (false/true/end-marker). Using `Bytes::from_static` avoids a heap
@@ -16,6 +20,29 @@ static END_MARKER_BYTE: [u8; 1] = [0xFFu8];
 static BOOL_FALSE: [u8; 1] = [0x00u8];
 static BOOL_TRUE: [u8; 1] = [0x01u8];
 
+// Quiet NaN with an empty payload; `F64Mode::TotalOrder` maps every NaN to this (plus its sign).
+const CANONICAL_NAN_BITS: u64 = 0x7FF8_0000_0000_0000;
+
+// Presence markers for optional values: `None` sorts before or after every `Some`.
+pub(crate) const NONE_FIRST_MARKER: u8 = 0x00;
+pub(crate) const SOME_MARKER: u8 = 0x01;
+const NONE_LAST_MARKER: u8 = 0x02;
+
+/// Reorder v1 timestamp fields (`time_low`, `time_mid`, `version|time_hi`) into v6 order.
+fn v1_time_ordered_bytes(u: &Uuid) -> [u8; 16] {
+    let (time_low, time_mid, time_hi_and_version, _) = u.as_fields();
+    let ts = (u64::from(time_hi_and_version & 0x0FFF) << 48)
+        | (u64::from(time_mid) << 32)
+        | u64::from(time_low);
+
+    let mut out = *u.as_bytes();
+    out[0..4].copy_from_slice(&(((ts >> 28) & 0xFFFF_FFFF) as u32).to_be_bytes());
+    out[4..6].copy_from_slice(&(((ts >> 12) & 0xFFFF) as u16).to_be_bytes());
+    out[6..8]
+        .copy_from_slice(&((time_hi_and_version & 0xF000) | (ts & 0x0FFF) as u16).to_be_bytes());
+    out
+}
+
 /// A lexicographically sortable key.
 ///
 /// Keys are compared by their raw bytes. Use the provided encoders to ensure that numeric and
@@ -28,9 +55,9 @@ static BOOL_TRUE: [u8; 1] = [0x01u8];
 /// let b = LexKey::encode_i64(7);
 /// assert!(a < b); // numeric order preserved via encoding transform
 /// ```
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct LexKey {
-    bytes: Bytes,
+    pub(crate) bytes: Bytes,
 }
 
 impl LexKey {
@@ -62,6 +89,30 @@ impl LexKey {
         &self.bytes
     }
 
+    /// Consume the key and return its backing `Bytes` without copying.
+    #[inline]
+    #[must_use]
+    pub fn into_bytes(self) -> Bytes {
+        self.bytes
+    }
+
+    /// Consume the key and return it as a `Vec<u8>`.
+    ///
+    /// Reuses the allocation when this key is its sole owner (keys built by `LexKey`
+    /// constructors, `Encoder::freeze` or `from_bytes(Vec)`); otherwise copies.
+    ///
+    /// ```rust
+    /// use lexkey::LexKey;
+    ///
+    /// let key = LexKey::encode_u16(0x0102);
+    /// assert_eq!(key.into_vec(), vec![1, 2]);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn into_vec(self) -> Vec<u8> {
+        Vec::from(self.bytes)
+    }
+
     /// Check if key is empty.
     #[inline]
     pub fn is_empty(&self) -> bool {
@@ -124,30 +175,54 @@ impl LexKey {
 
     /// Append the 8-byte big-endian encoding of `n` into `dst`.
     /// Returns the number of bytes written (always 8).
+    ///
+    /// Like every `_into` encoder that takes `dst`, this writes through `bytes::BufMut`, so `dst`
+    /// can be a `Vec<u8>`, a `BytesMut` network frame or a stack array (as `&mut [u8]`) as well
+    /// as an `Encoder`'s buffer.
+    ///
+    /// ```rust
+    /// use bytes::BytesMut;
+    /// use lexkey::LexKey;
+    ///
+    /// let mut frame = BytesMut::from(&b"hdr"[..]);
+    /// LexKey::encode_u64_into(&mut frame, 7);
+    /// LexKey::encode_i32_into(&mut frame, -1);
+    /// assert_eq!(&frame[3..11], 7u64.to_be_bytes());
+    ///
+    /// let mut stack = [0u8; 12];
+    /// let mut dst = &mut stack[..];
+    /// let n = LexKey::encode_u64_into(&mut dst, 7) + LexKey::encode_i32_into(&mut dst, -1);
+    /// assert_eq!(&stack[..n], &frame[3..]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `dst` cannot grow and has fewer than 8 bytes of space left, as with
+    /// `BufMut::put_slice`.
     #[inline]
-    pub fn encode_u64_into(dst: &mut Vec<u8>, n: u64) -> usize {
-        dst.extend_from_slice(&n.to_be_bytes());
+    pub fn encode_u64_into<B: BufMut + ?Sized>(dst: &mut B, n: u64) -> usize {
+        dst.put_slice(&n.to_be_bytes());
         8
     }
 
     /// Append the 1-byte encoding of `n` into `dst`.
     #[inline]
-    pub fn encode_u8_into(dst: &mut Vec<u8>, n: u8) -> usize {
-        dst.push(n);
+    pub fn encode_u8_into<B: BufMut + ?Sized>(dst: &mut B, n: u8) -> usize {
+        dst.put_u8(n);
         1
     }
 
     /// Append the 2-byte big-endian encoding of `n` into `dst`.
     #[inline]
-    pub fn encode_u16_into(dst: &mut Vec<u8>, n: u16) -> usize {
-        dst.extend_from_slice(&n.to_be_bytes());
+    pub fn encode_u16_into<B: BufMut + ?Sized>(dst: &mut B, n: u16) -> usize {
+        dst.put_slice(&n.to_be_bytes());
         2
     }
 
     /// Append the 4-byte big-endian encoding of `n` into `dst`.
     #[inline]
-    pub fn encode_u32_into(dst: &mut Vec<u8>, n: u32) -> usize {
-        dst.extend_from_slice(&n.to_be_bytes());
+    pub fn encode_u32_into<B: BufMut + ?Sized>(dst: &mut B, n: u32) -> usize {
+        dst.put_slice(&n.to_be_bytes());
         4
     }
 
@@ -185,34 +260,72 @@ impl LexKey {
         Self::from_bytes(Bytes::copy_from_slice(&transformed.to_be_bytes()))
     }
 
+    /// Encode an unsigned 128-bit integer as 16-byte big-endian.
+    #[inline]
+    #[must_use]
+    pub fn encode_u128(n: u128) -> Self {
+        Self::from_bytes(Bytes::copy_from_slice(&n.to_be_bytes()))
+    }
+
+    /// Encode a signed 128-bit integer so lexicographic order matches numeric order.
+    ///
+    /// Transform: sign bit flipped, then 16 bytes big-endian.
+    ///
+    /// ```rust
+    /// use lexkey::LexKey;
+    /// assert!(LexKey::encode_i128(i128::MIN) < LexKey::encode_i128(-1));
+    /// assert_eq!(LexKey::encode_i128(0).to_hex_string(), format!("80{}", "00".repeat(15)));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn encode_i128(n: i128) -> Self {
+        let transformed = n.cast_unsigned() ^ SIGN_BIT_128;
+        Self::from_bytes(Bytes::copy_from_slice(&transformed.to_be_bytes()))
+    }
+
+    /// Append the 16-byte big-endian encoding of a `u128` into `dst`.
+    #[inline]
+    pub fn encode_u128_into<B: BufMut + ?Sized>(dst: &mut B, n: u128) -> usize {
+        dst.put_slice(&n.to_be_bytes());
+        16
+    }
+
+    /// Append the transformed 16-byte big-endian encoding of an `i128` into `dst`.
+    #[inline]
+    pub fn encode_i128_into<B: BufMut + ?Sized>(dst: &mut B, n: i128) -> usize {
+        let t = n.cast_unsigned() ^ SIGN_BIT_128;
+        dst.put_slice(&t.to_be_bytes());
+        16
+    }
+
     /// Append the transformed 8-byte encoding of an `i64` into `dst` (always 8 bytes).
     #[inline]
-    pub fn encode_i64_into(dst: &mut Vec<u8>, n: i64) -> usize {
+    pub fn encode_i64_into<B: BufMut + ?Sized>(dst: &mut B, n: i64) -> usize {
         let t = n.cast_unsigned() ^ SIGN_BIT;
-        dst.extend_from_slice(&t.to_be_bytes());
+        dst.put_slice(&t.to_be_bytes());
         8
     }
 
     /// Append the transformed 1-byte encoding of an `i8` into `dst`.
     #[inline]
-    pub fn encode_i8_into(dst: &mut Vec<u8>, n: i8) -> usize {
-        dst.push(n.cast_unsigned() ^ SIGN_BIT_8);
+    pub fn encode_i8_into<B: BufMut + ?Sized>(dst: &mut B, n: i8) -> usize {
+        dst.put_u8(n.cast_unsigned() ^ SIGN_BIT_8);
         1
     }
 
     /// Append the transformed 2-byte big-endian encoding of an `i16` into `dst`.
     #[inline]
-    pub fn encode_i16_into(dst: &mut Vec<u8>, n: i16) -> usize {
+    pub fn encode_i16_into<B: BufMut + ?Sized>(dst: &mut B, n: i16) -> usize {
         let t = n.cast_unsigned() ^ SIGN_BIT_16;
-        dst.extend_from_slice(&t.to_be_bytes());
+        dst.put_slice(&t.to_be_bytes());
         2
     }
 
     /// Append the transformed 4-byte big-endian encoding of an `i32` into `dst`.
     #[inline]
-    pub fn encode_i32_into(dst: &mut Vec<u8>, n: i32) -> usize {
+    pub fn encode_i32_into<B: BufMut + ?Sized>(dst: &mut B, n: i32) -> usize {
         let t = n.cast_unsigned() ^ SIGN_BIT_32;
-        dst.extend_from_slice(&t.to_be_bytes());
+        dst.put_slice(&t.to_be_bytes());
         4
     }
 
@@ -233,15 +346,15 @@ impl LexKey {
 
     /// Append the boolean encoding into `dst` and return 1.
     #[inline]
-    pub fn encode_bool_into(dst: &mut Vec<u8>, b: bool) -> usize {
-        dst.push(u8::from(b));
+    pub fn encode_bool_into<B: BufMut + ?Sized>(dst: &mut B, b: bool) -> usize {
+        dst.put_u8(u8::from(b));
         1
     }
 
     /// Encode an IEEE-754 `f64` using a transform so that lexicographic order matches numeric order.
     ///
-    /// NaN values are not supported and will cause a panic. Use a schema-level marker for
-    /// optional/absent floating-point values if you need to express missingness.
+    /// NaN values are not supported and will cause a panic. Use `encode_opt_f64` (or
+    /// `Option<f64>` in composites) for optional/absent floating-point values.
     /// Negative values are bitwise-not of their IEEE representation; non-negative values are
     /// XOR'd with the sign bit.
     ///
@@ -292,7 +405,7 @@ impl LexKey {
     ///
     /// Panics if `x` is NaN.
     #[inline]
-    pub fn encode_f64_into(dst: &mut Vec<u8>, x: f64) -> usize {
+    pub fn encode_f64_into<B: BufMut + ?Sized>(dst: &mut B, x: f64) -> usize {
         assert!(!x.is_nan(), "NaN not encodable");
 
         let b = x.to_bits();
@@ -301,7 +414,7 @@ impl LexKey {
         let pos = b ^ SIGN_BIT;
         let transformed = (neg & mask) | (pos & !mask);
 
-        dst.extend_from_slice(&transformed.to_be_bytes());
+        dst.put_slice(&transformed.to_be_bytes());
         8
     }
 
@@ -311,7 +424,7 @@ impl LexKey {
     ///
     /// Panics if `x` is NaN.
     #[inline]
-    pub fn encode_f32_into(dst: &mut Vec<u8>, x: f32) -> usize {
+    pub fn encode_f32_into<B: BufMut + ?Sized>(dst: &mut B, x: f32) -> usize {
         assert!(!x.is_nan(), "NaN not encodable");
 
         let b = x.to_bits();
@@ -320,10 +433,148 @@ impl LexKey {
         let pos = b ^ SIGN_BIT_32;
         let transformed = (neg & mask) | (pos & !mask);
 
-        dst.extend_from_slice(&transformed.to_be_bytes());
+        dst.put_slice(&transformed.to_be_bytes());
         4
     }
 
+    /// Encode an `f64` under an explicit NaN policy.
+    ///
+    /// `F64Mode::RejectNaN` is `encode_f64`. `F64Mode::TotalOrder` also accepts NaN: every
+    /// positive NaN becomes the canonical quiet NaN and sorts after `+inf`, every negative NaN
+    /// sorts before `-inf`, and all other values encode exactly as with `encode_f64`. The result
+    /// follows `f64::total_cmp` with NaN payloads collapsed to one slot per sign.
+    ///
+    /// ```rust
+    /// use lexkey::{F64Mode, LexKey};
+    ///
+    /// let nan = LexKey::encode_f64_with(f64::NAN, F64Mode::TotalOrder);
+    /// let neg_nan = LexKey::encode_f64_with(-f64::NAN, F64Mode::TotalOrder);
+    /// assert!(LexKey::encode_f64(f64::INFINITY) < nan);
+    /// assert!(neg_nan < LexKey::encode_f64(f64::NEG_INFINITY));
+    /// assert_eq!(LexKey::encode_f64_with(1.0, F64Mode::TotalOrder), LexKey::encode_f64(1.0));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is NaN and `mode` is `F64Mode::RejectNaN`.
+    #[inline]
+    #[must_use]
+    pub fn encode_f64_with(x: f64, mode: F64Mode) -> Self {
+        let mut buf = Vec::with_capacity(8);
+        Self::encode_f64_with_into(&mut buf, x, mode);
+        Self::from_bytes(buf)
+    }
+
+    /// Append the 8-byte `encode_f64_with` form into `dst` and return 8.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is NaN and `mode` is `F64Mode::RejectNaN`.
+    #[inline]
+    pub fn encode_f64_with_into<B: BufMut + ?Sized>(dst: &mut B, x: f64, mode: F64Mode) -> usize {
+        if mode == F64Mode::RejectNaN || !x.is_nan() {
+            return Self::encode_f64_into(dst, x);
+        }
+        // The sortable transform already orders NaN bit patterns by sign like `total_cmp`;
+        // only the payload needs canonicalizing.
+        let bits = CANONICAL_NAN_BITS | (x.to_bits() & SIGN_BIT);
+        let mask = 0u64.wrapping_sub(bits >> 63);
+        let transformed = (!bits & mask) | ((bits ^ SIGN_BIT) & !mask);
+        dst.put_slice(&transformed.to_be_bytes());
+        8
+    }
+
+    /// Encode an optional `f64` behind a one-byte presence marker.
+    ///
+    /// `Some(x)` is `01` followed by the 8-byte `encode_f64` form. `None` is the single byte `00`
+    /// with `NullOrder::First` or `02` with `NullOrder::Last`, so missing values sort before or
+    /// after every number. `NullOrder::First` matches the `Encodable` impl for `Option<f64>`.
+    ///
+    /// ```rust
+    /// use lexkey::{LexKey, NullOrder};
+    ///
+    /// let none = LexKey::encode_opt_f64(None, NullOrder::Last);
+    /// let big = LexKey::encode_opt_f64(Some(f64::INFINITY), NullOrder::Last);
+    /// assert!(big < none);
+    ///
+    /// let first = LexKey::encode_opt_f64(None, NullOrder::First);
+    /// assert!(first < LexKey::encode_opt_f64(Some(f64::NEG_INFINITY), NullOrder::First));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is `Some(NaN)`.
+    #[inline]
+    #[must_use]
+    pub fn encode_opt_f64(x: Option<f64>, nulls: NullOrder) -> Self {
+        let mut buf = Vec::with_capacity(9);
+        Self::encode_opt_f64_into(&mut buf, x, nulls);
+        Self::from_bytes(buf)
+    }
+
+    /// Append the optional `f64` encoding into `dst` and return bytes written (1 or 9).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is `Some(NaN)`.
+    #[inline]
+    pub fn encode_opt_f64_into<B: BufMut + ?Sized>(
+        dst: &mut B,
+        x: Option<f64>,
+        nulls: NullOrder,
+    ) -> usize {
+        if let Some(x) = x {
+            dst.put_u8(SOME_MARKER);
+            1 + Self::encode_f64_into(dst, x)
+        } else {
+            dst.put_u8(nulls.none_marker());
+            1
+        }
+    }
+
+    /// Encode any optional value behind a one-byte presence marker.
+    ///
+    /// `Some(v)` is `01` followed by `v`; `None` is `00` with `NullOrder::First` or `02` with
+    /// `NullOrder::Last`. `NullOrder::First` matches the `Encodable` impl for `Option<T>` and
+    /// `NullOrder::Last` matches `NullsLast`.
+    ///
+    /// ```rust
+    /// use lexkey::{LexKey, NullOrder};
+    ///
+    /// let last = |v: Option<&String>| LexKey::encode_opt(v, NullOrder::Last);
+    /// assert!(last(Some(&"zzz".to_owned())) < last(None));
+    /// assert_eq!(last(None).to_hex_string(), "02");
+    /// assert_eq!(LexKey::encode_opt(Some(&7u8), NullOrder::First).to_hex_string(), "0107");
+    /// ```
+    #[must_use]
+    pub fn encode_opt<T: Encodable + ?Sized>(value: Option<&T>, nulls: NullOrder) -> Self {
+        let mut buf = Vec::with_capacity(1 + value.map_or(0, Encodable::encoded_len));
+        encode_opt_vec_into(&mut buf, value, nulls);
+        Self::from_bytes(buf)
+    }
+
+    /// Append the optional value encoding into `dst` and return bytes written.
+    ///
+    /// A present value is encoded through `Encodable` into a `Vec<u8>` first, like
+    /// `encode_parts_into`.
+    #[inline]
+    pub fn encode_opt_into<B: BufMut + ?Sized, T: Encodable + ?Sized>(
+        dst: &mut B,
+        value: Option<&T>,
+        nulls: NullOrder,
+    ) -> usize {
+        if let Some(v) = value {
+            let mut bytes = Vec::with_capacity(1 + v.encoded_len());
+            bytes.push(SOME_MARKER);
+            v.encode_into(&mut bytes);
+            dst.put_slice(&bytes);
+            bytes.len()
+        } else {
+            dst.put_u8(nulls.none_marker());
+            1
+        }
+    }
+
     /// Encode a UUID as its 16 raw RFC4122 bytes.
     #[inline]
     #[must_use]
@@ -331,13 +582,93 @@ impl LexKey {
         Self::from_bytes(Bytes::copy_from_slice(u.as_bytes()))
     }
 
+    /// Generate a version 7 UUID for the current time and encode it as its 16 raw bytes.
+    ///
+    /// v7 UUIDs start with a big-endian Unix millisecond timestamp, so the raw bytes already
+    /// sort by creation time, and the `uuid` crate guarantees that UUIDs generated in one
+    /// process increase. Requires the `uuid-v7` feature.
+    ///
+    /// ```rust
+    /// use lexkey::LexKey;
+    ///
+    /// let first = LexKey::new_uuid_v7();
+    /// let second = LexKey::new_uuid_v7();
+    /// assert!(first < second);
+    /// ```
+    #[cfg(feature = "uuid-v7")]
+    #[inline]
+    #[must_use]
+    pub fn new_uuid_v7() -> Self {
+        Self::encode_uuid(&Uuid::now_v7())
+    }
+
     /// Append a UUID's 16 bytes into `dst` and return 16.
     #[inline]
-    pub fn encode_uuid_into(dst: &mut Vec<u8>, u: &Uuid) -> usize {
-        dst.extend_from_slice(u.as_bytes());
+    pub fn encode_uuid_into<B: BufMut + ?Sized>(dst: &mut B, u: &Uuid) -> usize {
+        dst.put_slice(u.as_bytes());
+        16
+    }
+
+    /// Encode a version 1 UUID with its timestamp fields reordered so keys sort chronologically.
+    ///
+    /// RFC 4122 v1 stores the 60-bit Gregorian timestamp low bits first, so raw v1 bytes do not
+    /// sort by time. This writes the RFC 9562 v6 field order instead: the top 48 timestamp bits,
+    /// then the version nibble and the low 12 bits, then clock sequence and node unchanged. The
+    /// version nibble is kept as-is so `uuid_from_v1_time_ordered` restores the original exactly.
+    /// Other UUID versions are rearranged the same way, which is lossless but not meaningful.
+    ///
+    /// ```rust
+    /// use lexkey::LexKey;
+    /// use uuid::Uuid;
+    ///
+    /// // Same clock sequence and node, timestamps one tick apart across a time_low rollover.
+    /// let earlier = Uuid::from_fields(0xffff_ffff, 0x0001, 0x11d0, &[0x80, 0, 0, 0, 0, 0, 0, 1]);
+    /// let later = Uuid::from_fields(0x0000_0000, 0x0002, 0x11d0, &[0x80, 0, 0, 0, 0, 0, 0, 1]);
+    /// assert!(LexKey::encode_uuid(&earlier) > LexKey::encode_uuid(&later));
+    ///
+    /// let key = LexKey::encode_uuid_v1_time_ordered(&earlier);
+    /// assert!(key < LexKey::encode_uuid_v1_time_ordered(&later));
+    /// assert_eq!(LexKey::uuid_from_v1_time_ordered(key.as_bytes()), Some(earlier));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn encode_uuid_v1_time_ordered(u: &Uuid) -> Self {
+        Self::from_bytes(Bytes::copy_from_slice(&v1_time_ordered_bytes(u)))
+    }
+
+    /// Encode a v1 UUID so keys sort chronologically; the same bytes as
+    /// `encode_uuid_v1_time_ordered`.
+    #[inline]
+    #[must_use]
+    pub fn encode_uuid_v1_sortable(u: &Uuid) -> Self {
+        Self::encode_uuid_v1_time_ordered(u)
+    }
+
+    /// Append the time-ordered form of a v1 UUID into `dst` and return 16.
+    #[inline]
+    pub fn encode_uuid_v1_time_ordered_into<B: BufMut + ?Sized>(dst: &mut B, u: &Uuid) -> usize {
+        dst.put_slice(&v1_time_ordered_bytes(u));
         16
     }
 
+    /// Restore the original UUID from 16 bytes written by `encode_uuid_v1_time_ordered`.
+    ///
+    /// Returns `None` unless `bytes` is exactly 16 bytes long.
+    #[must_use]
+    pub fn uuid_from_v1_time_ordered(bytes: &[u8]) -> Option<Uuid> {
+        let bytes: &[u8; 16] = bytes.try_into().ok()?;
+        let high = u64::from(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]));
+        let mid = u64::from(u16::from_be_bytes([bytes[4], bytes[5]]));
+        let low = u16::from_be_bytes([bytes[6], bytes[7]]);
+        let ts = (high << 28) | (mid << 12) | u64::from(low & 0x0FFF);
+
+        let mut out = *bytes;
+        out[0..4].copy_from_slice(&((ts & 0xFFFF_FFFF) as u32).to_be_bytes());
+        out[4..6].copy_from_slice(&(((ts >> 32) & 0xFFFF) as u16).to_be_bytes());
+        out[6..8].copy_from_slice(&((low & 0xF000) | (ts >> 48) as u16).to_be_bytes());
+        Some(Uuid::from_bytes(out))
+    }
+
     /// Encode a UTC timestamp represented as UNIX nanoseconds.
     #[inline]
     #[must_use]
@@ -436,26 +767,14 @@ impl LexKey {
     /// No trailing separator is added after the last part.
     ///
     /// Example: representing an optional value in a composite
+    ///
+    /// `Option<T>` is `Encodable`: `None` encodes as `00` and `Some(v)` as `01` followed by `v`.
     /// ```rust
-    /// use lexkey::{Encoder, LexKey};
-    ///
-    /// let tenant = "tenant";
-    /// let optional_user: Option<&str> = Some("alice");
-    /// let mut enc = Encoder::with_capacity(64);
-    ///
-    /// enc.encode_string_into(tenant);
-    /// enc.push_byte(LexKey::SEPARATOR);
-    /// match optional_user {
-    ///     Some(name) => {
-    ///         enc.push_byte(0x01); // presence marker
-    ///         enc.encode_string_into(name);
-    ///     }
-    ///     None => {
-    ///         enc.push_byte(0x00); // absence marker
-    ///     }
-    /// }
-    /// let key = enc.freeze();
-    /// assert!(!key.is_empty());
+    /// use lexkey::encode_composite;
+    ///
+    /// let with_user = encode_composite!("tenant", Some("alice"));
+    /// let without_user = encode_composite!("tenant", None::<&str>);
+    /// assert!(without_user < with_user);
     /// ```
     #[inline]
     fn composite_capacity(parts: &[&[u8]]) -> usize {
@@ -472,18 +791,14 @@ impl LexKey {
         let cap = Self::composite_capacity(parts);
         let mut buf = Vec::with_capacity(cap);
         crate::encode_parts_into(&mut buf, parts);
+        #[cfg(feature = "metrics")]
+        crate::metrics::record(buf.len(), parts.len());
         Self::from_bytes(buf)
     }
 
     /// Append composite parts into `dst` without a trailing separator and return bytes written.
     #[inline]
-    pub fn encode_composite_into(dst: &mut Vec<u8>, parts: &[&[u8]]) -> usize {
-        if parts.is_empty() {
-            return 0;
-        }
-
-        let additional = Self::composite_capacity(parts);
-        dst.reserve(additional);
+    pub fn encode_composite_into<B: BufMut + ?Sized>(dst: &mut B, parts: &[&[u8]]) -> usize {
         crate::encode_parts_into(dst, parts)
     }
 
@@ -584,6 +899,24 @@ impl LexKey {
         (Self::from_bytes(lower), Self::from_bytes(upper))
     }
 
+    /// Encode any `Encodable` value as a key.
+    ///
+    /// Tuples encode as the composite of their elements, so this is `encode_composite!` without
+    /// the macro.
+    ///
+    /// ```rust
+    /// use lexkey::{encode_composite, LexKey};
+    /// use uuid::Uuid;
+    ///
+    /// let key = LexKey::encode(&("tenant", 42i64, Uuid::nil()));
+    /// assert_eq!(key, encode_composite!("tenant", 42i64, Uuid::nil()));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn encode<T: Encodable + ?Sized>(value: &T) -> Self {
+        crate::__private::encode_composite_tuple(&value)
+    }
+
     #[inline]
     pub fn encode_composite_encodables(parts: &[&dyn Encodable]) -> Self {
         if parts.is_empty() {
@@ -602,14 +935,486 @@ impl LexKey {
                 buf.push(Self::SEPARATOR);
             }
         }
+        #[cfg(feature = "metrics")]
+        crate::metrics::record(buf.len(), parts.len());
+        Self::from_bytes(buf)
+    }
+
+    /// Encode typed parts from an iterator as a composite, joined by `SEPARATOR`.
+    ///
+    /// Each part is encoded straight into the key's buffer, so runtime-length lists of typed
+    /// values need no pre-encoded `&[u8]` temporaries. Parts must share one type; use
+    /// `encode_composite!`, a tuple or `&dyn Encodable` items to mix types.
+    ///
+    /// ```rust
+    /// use lexkey::{encode_composite, Encodable, LexKey};
+    ///
+    /// let ids = [3u32, 1, 2];
+    /// assert_eq!(LexKey::encode_parts(ids), encode_composite!(3u32, 1u32, 2u32));
+    ///
+    /// let mixed: [&dyn Encodable; 2] = [&"tenant", &42i64];
+    /// assert_eq!(LexKey::encode_parts(mixed), encode_composite!("tenant", 42i64));
+    /// assert!(LexKey::encode_parts(Vec::<u8>::new()).is_empty());
+    /// ```
+    #[must_use]
+    pub fn encode_parts<I>(parts: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Encodable,
+    {
+        let mut buf = Vec::new();
+        let count = encode_parts_vec_into(&mut buf, parts);
+        #[cfg(feature = "metrics")]
+        crate::metrics::record(buf.len(), count);
+        #[cfg(not(feature = "metrics"))]
+        let _ = count;
+        Self::from_bytes(buf)
+    }
+
+    /// Append typed parts from an iterator into `dst`, joined by `SEPARATOR`, and return the
+    /// number of parts written.
+    ///
+    /// `Encodable` writes into a `Vec<u8>`, so each part goes through one reused scratch buffer
+    /// before it is copied into `dst`.
+    pub fn encode_parts_into<B, I>(dst: &mut B, parts: I) -> usize
+    where
+        B: BufMut + ?Sized,
+        I: IntoIterator,
+        I::Item: Encodable,
+    {
+        let mut scratch = Vec::new();
+        let mut count = 0;
+        for part in parts {
+            if count > 0 {
+                dst.put_u8(Self::SEPARATOR);
+            }
+            scratch.clear();
+            part.encode_into(&mut scratch);
+            dst.put_slice(&scratch);
+            count += 1;
+        }
+        count
+    }
+
+    /// Split a composite key into its parts at each `SEPARATOR` byte.
+    ///
+    /// An empty key has no parts. Like `to_debug_string`, this has no schema, so parts that
+    /// contain `0x00` themselves (fixed-width numbers, UUIDs) are split too; use a `Decoder` for
+    /// keys with binary fields.
+    ///
+    /// ```rust
+    /// use lexkey::LexKey;
+    /// let key = LexKey::encode_composite(&[b"acme", b"users", b"alice"]);
+    /// let parts: Vec<&[u8]> = key.parts().collect();
+    /// assert_eq!(parts, [&b"acme"[..], b"users", b"alice"]);
+    /// assert_eq!(key.nth_part(1), Some(&b"users"[..]));
+    /// assert_eq!(key.nth_part(3), None);
+    /// ```
+    pub fn parts(&self) -> impl Iterator<Item = &[u8]> {
+        let bytes = if self.bytes.is_empty() {
+            None
+        } else {
+            Some(self.as_bytes())
+        };
+        bytes
+            .into_iter()
+            .flat_map(|b| b.split(|&byte| byte == Self::SEPARATOR))
+    }
+
+    /// Split like `parts`, returning reference-counted slices of this key's buffer.
+    ///
+    /// No part is copied; each `Bytes` keeps the key's allocation alive.
+    ///
+    /// ```rust
+    /// use lexkey::LexKey;
+    /// let key = LexKey::encode_composite(&[b"acme", b"alice"]);
+    /// let parts = key.split_parts();
+    /// assert_eq!(parts, [&b"acme"[..], b"alice"]);
+    /// assert_eq!(parts[1].as_ptr(), key.as_bytes()[5..].as_ptr());
+    /// ```
+    #[must_use]
+    pub fn split_parts(&self) -> Vec<Bytes> {
+        self.parts()
+            .map(|part| self.bytes.slice_ref(part))
+            .collect()
+    }
+
+    /// Return the part at `index` (zero-based), or `None` if the key has fewer parts.
+    #[must_use]
+    pub fn nth_part(&self, index: usize) -> Option<&[u8]> {
+        self.parts().nth(index)
+    }
+
+    /// Return exactly `n` bytes for bloom filters and prefix partitioning.
+    ///
+    /// Longer keys are truncated to their first `n` bytes; shorter keys are padded with `0x00`.
+    /// Keys that agree on their first `n` bytes always map to the same filter key, and the
+    /// mapping never reverses order (`a <= b` implies `a.fixed_prefix(n) <= b.fixed_prefix(n)`).
+    /// Padding means a short key collides with longer keys that continue with zero bytes, which
+    /// only adds false positives to a bloom filter.
+    ///
+    /// ```rust
+    /// use lexkey::LexKey;
+    ///
+    /// let a = LexKey::encode_composite(&[b"acme", b"orders", b"1"]);
+    /// let b = LexKey::encode_composite(&[b"acme", b"orders", b"2"]);
+    /// assert_eq!(a.fixed_prefix(11), b.fixed_prefix(11));
+    /// assert_eq!(LexKey::encode_string("ab").fixed_prefix(4).as_bytes(), b"ab\x00\x00");
+    /// ```
+    #[must_use]
+    pub fn fixed_prefix(&self, n: usize) -> Self {
+        if self.bytes.len() >= n {
+            return Self::from_bytes(self.bytes.slice(..n));
+        }
+        let mut buf = Vec::with_capacity(n);
+        buf.extend_from_slice(&self.bytes);
+        buf.resize(n, 0x00);
+        Self::from_bytes(buf)
+    }
+
+    /// Return the smallest key strictly greater than every key that starts with this one.
+    ///
+    /// The method form of `prefix_successor`: the last non-`0xff` byte is incremented and any
+    /// trailing `0xff` bytes are dropped. Unlike `encode_last`'s `0xff` sentinel it is a true
+    /// exclusive upper bound for raw-byte prefixes, including keys that continue with `0xff`.
+    /// Returns `None` for the empty key and for all-`0xff` keys, which have no finite bound.
+    ///
+    /// ```rust
+    /// use lexkey::LexKey;
+    ///
+    /// let key = LexKey::from(&b"ab\xff"[..]);
+    /// assert_eq!(key.prefix_successor_key(), Some(LexKey::from(&b"ac"[..])));
+    /// assert!(LexKey::from(&b"ab\xff\xff"[..]) < LexKey::from(&b"ac"[..]));
+    /// assert_eq!(LexKey::from(&b"\xff\xff"[..]).prefix_successor_key(), None);
+    /// ```
+    #[must_use]
+    pub fn prefix_successor_key(&self) -> Option<Self> {
+        let end = self.bytes.iter().rposition(|&b| b != u8::MAX)?;
+        let mut buf = self.bytes[..=end].to_vec();
+        buf[end] += 1;
+        Some(Self::from_bytes(buf))
+    }
+
+    /// Return `self || 0x00`, the `encode_first` bound of this key's parts.
+    ///
+    /// Sorts before every key that extends this one with further parts, so it is the inclusive
+    /// lower bound of a child scan built from a key you already have.
+    ///
+    /// ```rust
+    /// use lexkey::LexKey;
+    ///
+    /// let tenant = LexKey::encode_composite(&[b"acme", b"orders"]);
+    /// assert_eq!(tenant.first_child(), LexKey::encode_first(&[b"acme", b"orders"]));
+    /// assert_eq!(tenant.last_child(), LexKey::encode_last(&[b"acme", b"orders"]));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn first_child(&self) -> Self {
+        self.with_suffix_byte(Self::SEPARATOR)
+    }
+
+    /// Return `self || 0xff`, the `encode_last` bound of this key's parts.
+    ///
+    /// Sorts after every key that extends this one with a separator and further parts; see
+    /// `encode_last` for when raw-byte extensions need `prefix_successor_key` instead.
+    #[inline]
+    #[must_use]
+    pub fn last_child(&self) -> Self {
+        self.with_suffix_byte(Self::END_MARKER)
+    }
+
+    fn with_suffix_byte(&self, byte: u8) -> Self {
+        let mut buf = Vec::with_capacity(self.bytes.len() + 1);
+        buf.extend_from_slice(&self.bytes);
+        buf.push(byte);
         Self::from_bytes(buf)
     }
 
+    /// Return the scan bounds `[self || 0x00, self || 0xff)` of every composite extending this key.
+    ///
+    /// These are the `encode_first`/`encode_last` bounds for this key's parts, ready for
+    /// `BTreeMap::range` and other `RangeBounds` APIs. The key itself is not in the range; only
+    /// keys that continue with a separator and further parts are. Like `encode_last`, the upper
+    /// bound relies on children continuing with a separator: a raw key such as `acmex` also sorts
+    /// inside the range of `acme`. `KeyRange::partition` builds the same range as a `KeyRange`.
+    ///
+    /// ```rust
+    /// use std::collections::BTreeSet;
+    /// use lexkey::LexKey;
+    ///
+    /// let tenant = LexKey::encode_composite(&[b"acme"]);
+    /// let keys: BTreeSet<_> = [
+    ///     LexKey::encode_composite(&[b"acme"]),
+    ///     LexKey::encode_composite(&[b"acme", b"1"]),
+    ///     LexKey::encode_composite(&[b"acme", b"2", b"x"]),
+    ///     LexKey::encode_composite(&[b"acmf", b"1"]),
+    /// ]
+    /// .into_iter()
+    /// .collect();
+    /// assert_eq!(keys.range(tenant.prefix_range()).count(), 2);
+    /// ```
+    #[must_use]
+    pub fn prefix_range(&self) -> (Bound<Self>, Bound<Self>) {
+        (
+            Bound::Included(self.first_child()),
+            Bound::Excluded(self.last_child()),
+        )
+    }
+
+    /// Length of the match if `prefix` is this key or a whole-part prefix of it.
+    fn part_prefix_len(&self, prefix: &[u8]) -> Option<usize> {
+        let rest = self.bytes.strip_prefix(prefix)?;
+        match (prefix.last(), rest.first()) {
+            (None | Some(&Self::SEPARATOR), _) | (_, None) => Some(prefix.len()),
+            (_, Some(&Self::SEPARATOR)) => Some(prefix.len() + 1),
+            _ => None,
+        }
+    }
+
+    /// Return true if `prefix` is this key or covers whole leading parts of it.
+    ///
+    /// Matching stops at part boundaries, so `ten` is not a prefix of `tenant`; it is only a
+    /// prefix of `ten` itself and of keys continuing `ten || 0x00 ...`. A prefix that already
+    /// ends with a separator (such as an `encode_first` bound) and the empty key match any key
+    /// that starts with their bytes.
+    ///
+    /// ```rust
+    /// use lexkey::LexKey;
+    ///
+    /// let key = LexKey::encode_composite(&[b"tenant", b"orders"]);
+    /// assert!(key.starts_with(&LexKey::encode_string("tenant")));
+    /// assert!(!key.starts_with(&LexKey::encode_string("ten")));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn starts_with(&self, prefix: &Self) -> bool {
+        self.part_prefix_len(&prefix.bytes).is_some()
+    }
+
+    /// Remove `prefix` and the separator after it, returning the remaining parts.
+    ///
+    /// Returns `None` unless `starts_with(prefix)`. Stripping a key from itself returns the
+    /// empty key. The result shares this key's buffer.
+    ///
+    /// ```rust
+    /// use lexkey::LexKey;
+    ///
+    /// let key = LexKey::encode_composite(&[b"tenant", b"orders", b"42"]);
+    /// let rest = key.strip_prefix(&LexKey::encode_string("tenant")).unwrap();
+    /// assert_eq!(rest, LexKey::encode_composite(&[b"orders", b"42"]));
+    /// assert_eq!(key.strip_prefix(&LexKey::encode_string("ten")), None);
+    /// ```
+    #[must_use]
+    pub fn strip_prefix(&self, prefix: &Self) -> Option<Self> {
+        let n = self.part_prefix_len(&prefix.bytes)?;
+        Some(Self::from_bytes(self.bytes.slice(n..)))
+    }
+
+    /// Return the number of leading bytes this key shares with `other`.
+    ///
+    /// Use it to pick restart points for prefix-compressed blocks, or to choose a short split
+    /// key between two neighbours when partitioning a keyspace: any key that extends the common
+    /// prefix by one byte between theirs separates them.
+    ///
+    /// ```rust
+    /// use lexkey::LexKey;
+    ///
+    /// let a = LexKey::encode_composite(&[b"acme", b"alice"]);
+    /// let b = LexKey::encode_composite(&[b"acme", b"bob"]);
+    /// assert_eq!(a.common_prefix_len(&b), 5);
+    /// assert_eq!(a.common_prefix_len(&a), a.as_bytes().len());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn common_prefix_len(&self, other: &Self) -> usize {
+        crate::common_prefix_len(&self.bytes, &other.bytes)
+    }
+
     /// Convert to a lowercase hex string, useful for debugging.
     #[inline]
     pub fn to_hex_string(&self) -> String {
         hex::encode(&self.bytes)
     }
+
+    /// Render the key as lowercase hex groups split at `SEPARATOR` bytes.
+    ///
+    /// Separators are shown as ` | ` rather than `00`, which makes composite keys legible in
+    /// test failures. Parts that contain `0x00` themselves (e.g. fixed-width numbers) are split
+    /// too, since the key carries no schema.
+    ///
+    /// ```rust
+    /// use lexkey::LexKey;
+    /// let k = LexKey::encode_composite(&[b"foo", b"bar", &[0x01]]);
+    /// assert_eq!(k.to_debug_string(), "666f6f | 626172 | 01");
+    /// ```
+    #[must_use]
+    pub fn to_debug_string(&self) -> String {
+        let mut out = String::with_capacity(self.bytes.len() * 2 + 8);
+        for (i, part) in self.bytes.split(|&b| b == Self::SEPARATOR).enumerate() {
+            if i > 0 {
+                out.push_str(" | ");
+            }
+            out.push_str(&hex::encode(part));
+        }
+        out
+    }
+
+    /// Report where this key first differs from `other`.
+    ///
+    /// Useful for debugging keys that land in an unexpected partition. The part index counts
+    /// `SEPARATOR` bytes before the differing offset, so it is only meaningful when parts do
+    /// not contain `0x00` themselves.
+    ///
+    /// ```rust
+    /// use lexkey::LexKey;
+    /// let a = LexKey::encode_composite(&[b"acme", b"alice"]);
+    /// let b = LexKey::encode_composite(&[b"acme", b"bob"]);
+    /// let diff = a.diff(&b);
+    /// assert_eq!(diff.offset, Some(5));
+    /// assert_eq!(diff.part, 1);
+    /// assert_eq!((diff.left, diff.right), (Some(b'a'), Some(b'b')));
+    /// ```
+    #[must_use]
+    pub fn diff(&self, other: &Self) -> KeyDiff {
+        let (left, right) = (self.as_bytes(), other.as_bytes());
+        let common = crate::common_prefix_len(left, right);
+        let part = left[..common].split(|&b| b == Self::SEPARATOR).count() - 1;
+        let offset = (left.len() != right.len() || common < left.len()).then_some(common);
+        KeyDiff {
+            offset,
+            part,
+            left: left.get(common).copied(),
+            right: right.get(common).copied(),
+        }
+    }
+}
+
+/// How `f64` encoders treat NaN.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum F64Mode {
+    /// NaN is not encodable and panics, as with `encode_f64`.
+    #[default]
+    RejectNaN,
+    /// NaN sorts after `+inf` (or before `-inf` when its sign bit is set), like `f64::total_cmp`
+    /// with one canonical NaN per sign.
+    TotalOrder,
+}
+
+/// Where `None` sorts relative to present values in optional encodings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum NullOrder {
+    /// `None` encodes as `00` and sorts before every present value.
+    #[default]
+    First,
+    /// `None` encodes as `02` and sorts after every present value.
+    Last,
+}
+
+impl NullOrder {
+    /// The single byte that encodes `None` under this order.
+    #[inline]
+    #[must_use]
+    pub const fn none_marker(self) -> u8 {
+        match self {
+            Self::First => NONE_FIRST_MARKER,
+            Self::Last => NONE_LAST_MARKER,
+        }
+    }
+}
+
+/// Encodes the wrapped `Option` with `NullOrder::Last`, so `None` sorts after every value.
+///
+/// A plain `Option<T>` sorts `None` first; wrap it to pick the other policy anywhere an
+/// `Encodable` is accepted, including `encode_composite!`. Both forms decode as `Option<T>`.
+///
+/// ```rust
+/// use lexkey::{encode_composite, NullsLast};
+///
+/// let due = |d: Option<u32>| encode_composite!("task", NullsLast(d));
+/// assert!(due(Some(u32::MAX)) < due(None));
+/// assert!(encode_composite!("task", None::<u32>) < encode_composite!("task", Some(0u32)));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct NullsLast<T>(pub Option<T>);
+
+/// The first difference between two keys, as returned by `LexKey::diff`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyDiff {
+    /// Offset of the first differing byte, or `None` when the keys are equal.
+    ///
+    /// When one key is a prefix of the other, this is the length of the shorter key.
+    pub offset: Option<usize>,
+    /// Number of `SEPARATOR` bytes before `offset`, i.e. the composite part index.
+    pub part: usize,
+    /// Byte of the left key at `offset`, or `None` if it ends there.
+    pub left: Option<u8>,
+    /// Byte of the right key at `offset`, or `None` if it ends there.
+    pub right: Option<u8>,
+}
+
+impl KeyDiff {
+    /// Check if the compared keys were equal.
+    #[inline]
+    #[must_use]
+    pub fn is_equal(&self) -> bool {
+        self.offset.is_none()
+    }
+}
+
+impl std::fmt::Display for KeyDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Some(offset) = self.offset else {
+            return f.write_str("keys are equal");
+        };
+        write!(f, "keys differ at byte {offset} (part {}): ", self.part)?;
+        match (self.left, self.right) {
+            (Some(l), Some(r)) => write!(f, "{l:02x} vs {r:02x}"),
+            (None, _) => f.write_str("left key ends"),
+            (_, None) => f.write_str("right key ends"),
+        }
+    }
+}
+
+/// Formats as `LexKey(<to_debug_string>)`.
+impl std::fmt::Debug for LexKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "LexKey({})", self.to_debug_string())
+    }
+}
+
+/// Printable ASCII as-is, everything else as a Rust byte-string escape (`\x00`, `\n`, `\\`), so
+/// textual parts stay legible in logs.
+///
+/// ```rust
+/// use lexkey::LexKey;
+///
+/// let key = LexKey::encode_composite(&[b"tenant", &[0xff, b'"']]);
+/// assert_eq!(key.to_string(), r#"tenant\x00\xff\""#);
+/// ```
+impl std::fmt::Display for LexKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.bytes.escape_ascii())
+    }
+}
+
+/// Lowercase hex digits, `0x`-prefixed with `{:#x}`; parse with `LexKey::from_hex`.
+impl std::fmt::LowerHex for LexKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if f.alternate() {
+            f.write_str("0x")?;
+        }
+        f.write_str(&hex::encode(&self.bytes))
+    }
+}
+
+/// Uppercase hex digits, `0x`-prefixed with `{:#X}`; parse with `LexKey::from_hex`.
+impl std::fmt::UpperHex for LexKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if f.alternate() {
+            f.write_str("0x")?;
+        }
+        f.write_str(&hex::encode_upper(&self.bytes))
+    }
 }
 
 impl PartialOrd for LexKey {
@@ -626,6 +1431,96 @@ impl Ord for LexKey {
     }
 }
 
+/// Compare keys with raw byte containers in either direction, using the same byte order as
+/// `Ord`, so `key == stored` and `key < upper` need no `.as_bytes()`.
+macro_rules! impl_cmp_bytes {
+    ($($ty:ty),+ $(,)?) => {
+        $(
+            impl PartialEq<$ty> for LexKey {
+                #[inline]
+                fn eq(&self, other: &$ty) -> bool {
+                    self.as_bytes() == &other[..]
+                }
+            }
+
+            impl PartialEq<LexKey> for $ty {
+                #[inline]
+                fn eq(&self, other: &LexKey) -> bool {
+                    &self[..] == other.as_bytes()
+                }
+            }
+
+            impl PartialOrd<$ty> for LexKey {
+                #[inline]
+                fn partial_cmp(&self, other: &$ty) -> Option<Ordering> {
+                    Some(self.as_bytes().cmp(&other[..]))
+                }
+            }
+
+            impl PartialOrd<LexKey> for $ty {
+                #[inline]
+                fn partial_cmp(&self, other: &LexKey) -> Option<Ordering> {
+                    Some(self[..].cmp(other.as_bytes()))
+                }
+            }
+        )+
+    };
+}
+
+impl_cmp_bytes!([u8], &[u8], Vec<u8>, Bytes);
+
+impl AsRef<[u8]> for LexKey {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+/// Slice methods such as `len`, `iter` and `ends_with` work on keys directly.
+impl std::ops::Deref for LexKey {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+/// Hashing, equality and ordering agree with `[u8]`, so maps and sets keyed by `LexKey` can be
+/// queried with a borrowed `&[u8]`.
+///
+/// ```rust
+/// use lexkey::LexKey;
+/// use std::collections::{BTreeMap, HashMap};
+///
+/// let key = LexKey::encode_u32(7);
+/// let hashed = HashMap::from([(key.clone(), "seven")]);
+/// let sorted = BTreeMap::from([(key, "seven")]);
+/// let raw: &[u8] = &[0, 0, 0, 7];
+/// assert_eq!(hashed.get(raw), Some(&"seven"));
+/// assert_eq!(sorted.get(raw), Some(&"seven"));
+/// ```
+impl std::borrow::Borrow<[u8]> for LexKey {
+    #[inline]
+    fn borrow(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl From<LexKey> for Bytes {
+    #[inline]
+    fn from(key: LexKey) -> Self {
+        key.into_bytes()
+    }
+}
+
+impl From<LexKey> for Vec<u8> {
+    #[inline]
+    fn from(key: LexKey) -> Self {
+        key.into_vec()
+    }
+}
+
 impl From<&[u8]> for LexKey {
     #[inline]
     fn from(bytes: &[u8]) -> Self {
@@ -649,6 +1544,42 @@ impl From<&str> for LexKey {
     }
 }
 
+/// Join parts into a composite key, equivalent to `encode_composite`.
+///
+/// ```rust
+/// use lexkey::LexKey;
+/// let parts: Vec<&[u8]> = vec![b"tenant", b"user"];
+/// let key: LexKey = parts.iter().copied().collect();
+/// assert_eq!(key.as_bytes(), b"tenant\x00user");
+/// ```
+impl<'a> FromIterator<&'a [u8]> for LexKey {
+    fn from_iter<I: IntoIterator<Item = &'a [u8]>>(iter: I) -> Self {
+        let mut buf = Vec::new();
+        for (i, part) in iter.into_iter().enumerate() {
+            if i > 0 {
+                buf.push(Self::SEPARATOR);
+            }
+            buf.extend_from_slice(part);
+        }
+        Self::from_bytes(buf)
+    }
+}
+
+/// Join existing keys as composite parts separated by `SEPARATOR`.
+impl FromIterator<LexKey> for LexKey {
+    fn from_iter<I: IntoIterator<Item = LexKey>>(iter: I) -> Self {
+        let mut buf = Vec::new();
+        for (i, part) in iter.into_iter().enumerate() {
+            if i > 0 {
+                buf.push(Self::SEPARATOR);
+            }
+            buf.extend_from_slice(&part.bytes);
+        }
+        Self::from_bytes(buf)
+    }
+}
+
+/// References encode as their target, so `&str`, `&[u8]`, `&[u8; N]` and `&&T` are parts too.
 impl<T: Encodable + ?Sized> Encodable for &T {
     #[inline]
     fn encoded_len(&self) -> usize {
@@ -659,9 +1590,19 @@ impl<T: Encodable + ?Sized> Encodable for &T {
     fn encode_into(&self, dst: &mut Vec<u8>) -> usize {
         (**self).encode_into(dst)
     }
+
+    #[inline]
+    fn encoded_desc_len(&self) -> usize {
+        (**self).encoded_desc_len()
+    }
+
+    #[inline]
+    fn encode_desc_into(&self, dst: &mut Vec<u8>) -> usize {
+        (**self).encode_desc_into(dst)
+    }
 }
 
-impl Encodable for &str {
+impl Encodable for str {
     #[inline]
     fn encoded_len(&self) -> usize {
         self.len()
@@ -672,9 +1613,19 @@ impl Encodable for &str {
         dst.extend_from_slice(self.as_bytes());
         self.len()
     }
+
+    #[inline]
+    fn encoded_desc_len(&self) -> usize {
+        escaped_len(self.as_bytes())
+    }
+
+    #[inline]
+    fn encode_desc_into(&self, dst: &mut Vec<u8>) -> usize {
+        escaped_desc_into(dst, self.as_bytes())
+    }
 }
 
-impl Encodable for &[u8] {
+impl Encodable for [u8] {
     #[inline]
     fn encoded_len(&self) -> usize {
         self.len()
@@ -685,9 +1636,19 @@ impl Encodable for &[u8] {
         dst.extend_from_slice(self);
         self.len()
     }
+
+    #[inline]
+    fn encoded_desc_len(&self) -> usize {
+        escaped_len(self)
+    }
+
+    #[inline]
+    fn encode_desc_into(&self, dst: &mut Vec<u8>) -> usize {
+        escaped_desc_into(dst, self)
+    }
 }
 
-impl<const N: usize> Encodable for &[u8; N] {
+impl<const N: usize> Encodable for [u8; N] {
     #[inline]
     fn encoded_len(&self) -> usize {
         N
@@ -711,42 +1672,86 @@ impl Encodable for LexKey {
         dst.extend_from_slice(&self.bytes);
         self.bytes.len()
     }
+
+    #[inline]
+    fn encoded_desc_len(&self) -> usize {
+        escaped_len(&self.bytes)
+    }
+
+    #[inline]
+    fn encode_desc_into(&self, dst: &mut Vec<u8>) -> usize {
+        escaped_desc_into(dst, &self.bytes)
+    }
+}
+
+impl Encodable for Bytes {
+    #[inline]
+    fn encoded_len(&self) -> usize {
+        self.len()
+    }
+
+    #[inline]
+    fn encode_into(&self, dst: &mut Vec<u8>) -> usize {
+        dst.extend_from_slice(self);
+        self.len()
+    }
+
+    #[inline]
+    fn encoded_desc_len(&self) -> usize {
+        escaped_len(self)
+    }
+
+    #[inline]
+    fn encode_desc_into(&self, dst: &mut Vec<u8>) -> usize {
+        escaped_desc_into(dst, self)
+    }
+}
+
+impl Encodable for i64 {
+    #[inline]
+    fn encoded_len(&self) -> usize {
+        8
+    }
+
+    #[inline]
+    fn encode_into(&self, dst: &mut Vec<u8>) -> usize {
+        LexKey::encode_i64_into(dst, *self)
+    }
 }
 
-impl Encodable for Bytes {
+impl Encodable for u64 {
     #[inline]
     fn encoded_len(&self) -> usize {
-        self.len()
+        8
     }
 
     #[inline]
     fn encode_into(&self, dst: &mut Vec<u8>) -> usize {
-        dst.extend_from_slice(self);
-        self.len()
+        LexKey::encode_u64_into(dst, *self)
     }
 }
 
-impl Encodable for i64 {
+impl Encodable for u128 {
     #[inline]
     fn encoded_len(&self) -> usize {
-        8
+        16
     }
 
     #[inline]
     fn encode_into(&self, dst: &mut Vec<u8>) -> usize {
-        LexKey::encode_i64_into(dst, *self)
+        LexKey::encode_u128_into(dst, *self)
     }
 }
 
-impl Encodable for u64 {
+impl Encodable for i128 {
     #[inline]
     fn encoded_len(&self) -> usize {
-        8
+        16
     }
 
     #[inline]
     fn encode_into(&self, dst: &mut Vec<u8>) -> usize {
-        LexKey::encode_u64_into(dst, *self)
+        LexKey::encode_i128_into(dst, *self)
     }
 }
 
@@ -882,6 +1887,16 @@ impl Encodable for String {
         dst.extend_from_slice(bytes);
         bytes.len()
     }
+
+    #[inline]
+    fn encoded_desc_len(&self) -> usize {
+        escaped_len(self.as_bytes())
+    }
+
+    #[inline]
+    fn encode_desc_into(&self, dst: &mut Vec<u8>) -> usize {
+        escaped_desc_into(dst, self.as_bytes())
+    }
 }
 
 impl Encodable for Vec<u8> {
@@ -895,8 +1910,197 @@ impl Encodable for Vec<u8> {
         dst.extend_from_slice(self);
         self.len()
     }
+
+    #[inline]
+    fn encoded_desc_len(&self) -> usize {
+        escaped_len(self)
+    }
+
+    #[inline]
+    fn encode_desc_into(&self, dst: &mut Vec<u8>) -> usize {
+        escaped_desc_into(dst, self)
+    }
+}
+
+/// `None` is `00`; `Some(v)` is `01` followed by `v`, so `None` sorts first (`NullOrder::First`).
+impl<T: Encodable> Encodable for Option<T> {
+    #[inline]
+    fn encoded_len(&self) -> usize {
+        self.as_ref().map_or(1, |v| 1 + v.encoded_len())
+    }
+
+    #[inline]
+    fn encode_into(&self, dst: &mut Vec<u8>) -> usize {
+        encode_opt_vec_into(dst, self.as_ref(), NullOrder::First)
+    }
+
+    #[inline]
+    fn encoded_desc_len(&self) -> usize {
+        self.as_ref().map_or(1, |v| 1 + v.encoded_desc_len())
+    }
+
+    #[inline]
+    fn encode_desc_into(&self, dst: &mut Vec<u8>) -> usize {
+        encode_opt_desc_into(dst, self.as_ref(), NullOrder::First)
+    }
+}
+
+/// `None` is `02`; `Some(v)` is `01` followed by `v`, so `None` sorts last (`NullOrder::Last`).
+impl<T: Encodable> Encodable for NullsLast<T> {
+    #[inline]
+    fn encoded_len(&self) -> usize {
+        self.0.encoded_len()
+    }
+
+    #[inline]
+    fn encode_into(&self, dst: &mut Vec<u8>) -> usize {
+        encode_opt_vec_into(dst, self.0.as_ref(), NullOrder::Last)
+    }
+
+    #[inline]
+    fn encoded_desc_len(&self) -> usize {
+        self.0.encoded_desc_len()
+    }
+
+    #[inline]
+    fn encode_desc_into(&self, dst: &mut Vec<u8>) -> usize {
+        encode_opt_desc_into(dst, self.0.as_ref(), NullOrder::Last)
+    }
+}
+
+/// The marker followed by the value's encoding, written straight into a `Vec`.
+pub(crate) fn encode_opt_vec_into<T: Encodable + ?Sized>(
+    dst: &mut Vec<u8>,
+    value: Option<&T>,
+    nulls: NullOrder,
+) -> usize {
+    match value {
+        None => {
+            dst.push(nulls.none_marker());
+            1
+        }
+        Some(v) => {
+            dst.push(SOME_MARKER);
+            1 + v.encode_into(dst)
+        }
+    }
+}
+
+/// Typed parts joined by `SEPARATOR`, written straight into a `Vec`; returns the part count.
+pub(crate) fn encode_parts_vec_into<I>(dst: &mut Vec<u8>, parts: I) -> usize
+where
+    I: IntoIterator,
+    I::Item: Encodable,
+{
+    let mut count = 0;
+    for part in parts {
+        if count > 0 {
+            dst.push(LexKey::SEPARATOR);
+        }
+        dst.reserve(part.encoded_len());
+        part.encode_into(dst);
+        count += 1;
+    }
+    count
+}
+
+/// The complemented marker followed by the value's descending encoding.
+fn encode_opt_desc_into<T: Encodable>(
+    dst: &mut Vec<u8>,
+    value: Option<&T>,
+    nulls: NullOrder,
+) -> usize {
+    match value {
+        None => {
+            dst.push(!nulls.none_marker());
+            1
+        }
+        Some(v) => {
+            dst.push(!SOME_MARKER);
+            1 + v.encode_desc_into(dst)
+        }
+    }
+}
+
+// A tuple encodes as the composite of its elements joined by `SEPARATOR`, the same bytes as
+// `encode_composite!` over the elements. Descending, each element is written descending and the
+// separators are left as they are, like `encode_composite_ordered`. Implemented up to arity 16.
+macro_rules! impl_encodable_tuple {
+    ($($ty:ident:$value:ident),+) => {
+        impl<$($ty),+> Encodable for ($($ty,)+)
+        where
+            $($ty: Encodable,)+
+        {
+            #[inline]
+            fn encoded_len(&self) -> usize {
+                let ($($value,)+) = self;
+                0 $(+ $value.encoded_len())+ + impl_encodable_tuple!(@separators $($ty),+)
+            }
+
+            #[inline]
+            fn encode_into(&self, dst: &mut Vec<u8>) -> usize {
+                let start = dst.len();
+                let ($($value,)+) = self;
+                impl_encodable_tuple!(@encode encode_into dst; $($value),+);
+                dst.len() - start
+            }
+
+            #[inline]
+            fn encoded_desc_len(&self) -> usize {
+                let ($($value,)+) = self;
+                0 $(+ $value.encoded_desc_len())+ + impl_encodable_tuple!(@separators $($ty),+)
+            }
+
+            #[inline]
+            fn encode_desc_into(&self, dst: &mut Vec<u8>) -> usize {
+                let start = dst.len();
+                let ($($value,)+) = self;
+                impl_encodable_tuple!(@encode encode_desc_into dst; $($value),+);
+                dst.len() - start
+            }
+        }
+    };
+    (@separators $single:ident) => {
+        0
+    };
+    (@separators $first:ident, $($rest:ident),+) => {
+        impl_encodable_tuple!(@count $($rest),+)
+    };
+    (@count $single:ident) => {
+        1
+    };
+    (@count $first:ident, $($rest:ident),+) => {
+        1 + impl_encodable_tuple!(@count $($rest),+)
+    };
+    (@encode $method:ident $dst:ident; $first:ident) => {
+        $first.$method($dst);
+    };
+    (@encode $method:ident $dst:ident; $first:ident, $($rest:ident),+) => {
+        $first.$method($dst);
+        $(
+            $dst.push(LexKey::SEPARATOR);
+            $rest.$method($dst);
+        )+
+    };
 }
 
+impl_encodable_tuple!(A:a);
+impl_encodable_tuple!(A:a, B:b);
+impl_encodable_tuple!(A:a, B:b, C:c);
+impl_encodable_tuple!(A:a, B:b, C:c, D:d);
+impl_encodable_tuple!(A:a, B:b, C:c, D:d, E:e);
+impl_encodable_tuple!(A:a, B:b, C:c, D:d, E:e, F:f);
+impl_encodable_tuple!(A:a, B:b, C:c, D:d, E:e, F:f, G:g);
+impl_encodable_tuple!(A:a, B:b, C:c, D:d, E:e, F:f, G:g, H:h);
+impl_encodable_tuple!(A:a, B:b, C:c, D:d, E:e, F:f, G:g, H:h, I:i);
+impl_encodable_tuple!(A:a, B:b, C:c, D:d, E:e, F:f, G:g, H:h, I:i, J:j);
+impl_encodable_tuple!(A:a, B:b, C:c, D:d, E:e, F:f, G:g, H:h, I:i, J:j, K:k);
+impl_encodable_tuple!(A:a, B:b, C:c, D:d, E:e, F:f, G:g, H:h, I:i, J:j, K:k, L:l);
+impl_encodable_tuple!(A:a, B:b, C:c, D:d, E:e, F:f, G:g, H:h, I:i, J:j, K:k, L:l, M:m);
+impl_encodable_tuple!(A:a, B:b, C:c, D:d, E:e, F:f, G:g, H:h, I:i, J:j, K:k, L:l, M:m, N:n);
+impl_encodable_tuple!(A:a, B:b, C:c, D:d, E:e, F:f, G:g, H:h, I:i, J:j, K:k, L:l, M:m, N:n, O:o);
+impl_encodable_tuple!(A:a, B:b, C:c, D:d, E:e, F:f, G:g, H:h, I:i, J:j, K:k, L:l, M:m, N:n, O:o, P:p);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -911,6 +2115,69 @@ mod tests {
         assert_eq!(k.to_hex_string(), "68656c6c6f");
     }
 
+    #[test]
+    fn should_collect_slices_into_composite() {
+        let n = LexKey::encode_i64(42);
+        let parts: Vec<&[u8]> = vec![b"foo", n.as_bytes(), &[0x01]];
+
+        let collected: LexKey = parts.iter().copied().collect();
+
+        assert_eq!(collected, LexKey::encode_composite(&parts));
+        assert_eq!(collected.to_hex_string(), "666f6f00800000000000002a0001");
+    }
+
+    #[test]
+    fn should_collect_keys_into_composite() {
+        let keys = vec![LexKey::encode_string("tenant"), LexKey::encode_u8(7)];
+
+        let collected: LexKey = keys.into_iter().collect();
+
+        assert_eq!(collected.as_bytes(), b"tenant\x00\x07");
+        assert!(std::iter::empty::<LexKey>().collect::<LexKey>().is_empty());
+    }
+
+    #[test]
+    fn should_render_debug_string_split_at_separators() {
+        let k = LexKey::encode_composite(&[b"foo", &[0x80, 0x2a], &[0x01]]);
+        assert_eq!(k.to_debug_string(), "666f6f | 802a | 01");
+        assert_eq!(format!("{k:?}"), "LexKey(666f6f | 802a | 01)");
+        assert_eq!(format!("{:?}", LexKey::empty()), "LexKey()");
+        assert_eq!(LexKey::encode_first(&[b"a"]).to_debug_string(), "61 | ");
+    }
+
+    #[test]
+    fn should_diff_keys_at_first_differing_byte() {
+        let a = LexKey::encode_composite(&[b"acme", b"orders", b"\x01"]);
+        let b = LexKey::encode_composite(&[b"acme", b"orders", b"\x02"]);
+
+        let diff = a.diff(&b);
+
+        assert_eq!(diff.offset, Some(12));
+        assert_eq!(diff.part, 2);
+        assert_eq!((diff.left, diff.right), (Some(0x01), Some(0x02)));
+        assert_eq!(
+            diff.to_string(),
+            "keys differ at byte 12 (part 2): 01 vs 02"
+        );
+    }
+
+    #[test]
+    fn should_diff_equal_and_prefix_keys() {
+        let a = LexKey::encode_string("acme");
+        let longer = LexKey::encode_composite(&[b"acme", b"x"]);
+
+        assert!(a.diff(&a.clone()).is_equal());
+        assert_eq!(a.diff(&a.clone()).to_string(), "keys are equal");
+
+        let diff = a.diff(&longer);
+        assert_eq!(diff.offset, Some(4));
+        assert_eq!((diff.left, diff.right), (None, Some(0x00)));
+        assert_eq!(
+            diff.to_string(),
+            "keys differ at byte 4 (part 0): left key ends"
+        );
+    }
+
     #[test]
     fn should_return_original_bytes_given_vec_when_from_vec() {
         let b = vec![0x00u8, 0x01, 0xff];
@@ -950,6 +2217,117 @@ mod tests {
         assert!(n < p);
     }
 
+    #[test]
+    fn should_order_optional_floats_by_null_order() {
+        let values = [
+            Some(f64::NEG_INFINITY),
+            Some(-1.5),
+            Some(0.0),
+            Some(f64::MAX),
+        ];
+
+        for nulls in [NullOrder::First, NullOrder::Last] {
+            let none = LexKey::encode_opt_f64(None, nulls);
+            let keys: Vec<_> = values
+                .iter()
+                .map(|&x| LexKey::encode_opt_f64(x, nulls))
+                .collect();
+            assert!(keys.windows(2).all(|w| w[0] < w[1]));
+            match nulls {
+                NullOrder::First => assert!(keys.iter().all(|k| none < *k)),
+                NullOrder::Last => assert!(keys.iter().all(|k| *k < none)),
+            }
+        }
+        assert_eq!(
+            LexKey::encode_opt_f64(Some(2.5), NullOrder::Last).to_hex_string(),
+            "01c004000000000000"
+        );
+        assert_eq!(
+            LexKey::encode_opt_f64(None, NullOrder::Last).to_hex_string(),
+            "02"
+        );
+    }
+
+    #[test]
+    fn should_encode_generic_option_with_nulls_first() {
+        let some = crate::encode_composite!(Some(2.5f64));
+        let none = crate::encode_composite!(None::<f64>);
+
+        assert_eq!(some, LexKey::encode_opt_f64(Some(2.5), NullOrder::First));
+        assert_eq!(none, LexKey::encode_opt_f64(None, NullOrder::First));
+        assert_eq!(Some("ab").encoded_len(), 3);
+    }
+
+    #[test]
+    fn should_order_generic_options_by_null_order() {
+        let values = [Some(0u32), Some(7), Some(u32::MAX)];
+        for nulls in [NullOrder::First, NullOrder::Last] {
+            let mut keys: Vec<LexKey> = values
+                .iter()
+                .map(|v| LexKey::encode_opt(Some(v), nulls))
+                .collect();
+            let none = LexKey::encode_opt(None::<&u32>, nulls);
+            match nulls {
+                NullOrder::First => keys.insert(0, none),
+                NullOrder::Last => keys.push(none),
+            }
+            for pair in keys.windows(2) {
+                assert!(pair[0] < pair[1]);
+            }
+        }
+
+        assert_eq!(
+            crate::encode_composite!(NullsLast(None::<u32>)),
+            LexKey::encode_opt(None::<&u32>, NullOrder::Last)
+        );
+        assert_eq!(NullsLast(Some(7u32)).encoded_len(), 5);
+        // Nested options keep each level's marker: Some(None) < Some(Some(_)) < None.
+        let nested = |v: Option<Option<u8>>| crate::encode_composite!(NullsLast(v));
+        assert!(nested(Some(None)) < nested(Some(Some(0))));
+        assert!(nested(Some(Some(u8::MAX))) < nested(None));
+        assert_eq!(nested(Some(None)).to_hex_string(), "0100");
+    }
+
+    #[test]
+    fn should_encode_tuples_like_composites() {
+        let id = Uuid::from_u128(7);
+        assert_eq!(
+            LexKey::encode(&("tenant", 42i64, id)),
+            crate::encode_composite!("tenant", 42i64, id)
+        );
+        assert_eq!(LexKey::encode(&(1u8,)), LexKey::encode(&1u8));
+        let wide = (
+            1u8, 2u8, 3u8, 4u8, 5u8, 6u8, 7u8, 8u8, 9u8, 10u8, 11u8, 12u8,
+        );
+        assert_eq!(wide.encoded_len(), 23);
+        assert_eq!(LexKey::encode(&wide).as_bytes()[22], 12);
+
+        // Tuples nest as single parts and order element by element.
+        let nested = (("a", 1u16), Some(2u8));
+        assert_eq!(LexKey::encode(&nested).to_hex_string(), "61000001000102");
+        assert!(LexKey::encode(&("a", 1u16)) < LexKey::encode(&("a", 2u16)));
+        assert!(LexKey::encode(&("a", u16::MAX)) < LexKey::encode(&("b", 0u16)));
+    }
+
+    #[test]
+    fn should_round_trip_generic_options_under_both_policies() {
+        use crate::Decodable;
+
+        let key = crate::encode_composite!(NullsLast(None::<i32>), Some(-1i32));
+        let mut rest = key.as_bytes();
+        assert_eq!(
+            NullsLast::<i32>::decode_from(&mut rest),
+            Ok(NullsLast(None))
+        );
+        let mut dec = crate::Decoder::new(rest);
+        dec.expect_separator().unwrap();
+        assert_eq!(dec.decode_value::<Option<i32>>(), Ok(Some(-1)));
+
+        let mut enc = crate::Encoder::with_capacity(8);
+        assert_eq!(enc.encode_opt_into(Some(&"x"), NullOrder::Last), 2);
+        assert_eq!(enc.as_slice(), b"\x01x");
+    }
+
     #[test]
     fn should_encode_uuid_and_time_and_duration_examples_from_spec() {
         // Arrange
@@ -966,6 +2344,152 @@ mod tests {
         assert_eq!(dur.to_hex_string(), "800000000000002a");
     }
 
+    #[test]
+    fn should_reorder_v1_uuid_like_rfc9562_v6_vector() {
+        // Arrange: the RFC 9562 appendix A example, v1 and its v6 equivalent.
+        let v1 = Uuid::parse_str("c232ab00-9414-11ec-b3c8-9f6bdeced846").unwrap();
+
+        // Act
+        let key = LexKey::encode_uuid_v1_time_ordered(&v1);
+        let mut buf = Vec::new();
+        LexKey::encode_uuid_v1_time_ordered_into(&mut buf, &v1);
+
+        // Assert: v6 field order, original version nibble kept.
+        assert_eq!(key.to_hex_string(), "1ec9414c232a1b00b3c89f6bdeced846");
+        assert_eq!(buf, key.as_bytes());
+        assert_eq!(LexKey::uuid_from_v1_time_ordered(key.as_bytes()), Some(v1));
+        assert_eq!(LexKey::uuid_from_v1_time_ordered(&buf[..15]), None);
+    }
+
+    #[test]
+    fn should_order_v1_uuids_by_timestamp() {
+        let node = [0x80, 0, 0, 0, 0, 0, 0, 1];
+        let ticks = [0u64, 1, 0xFFFF_FFFF, 0x1_0000_0000, 0x0FFF_FFFF_FFFF_FFFF];
+        let keys: Vec<_> = ticks
+            .iter()
+            .map(|&ts| {
+                let u = Uuid::from_fields(
+                    (ts & 0xFFFF_FFFF) as u32,
+                    ((ts >> 32) & 0xFFFF) as u16,
+                    0x1000 | (ts >> 48) as u16,
+                    &node,
+                );
+                let key = LexKey::encode_uuid_v1_sortable(&u);
+                assert_eq!(key, LexKey::encode_uuid_v1_time_ordered(&u));
+                key
+            })
+            .collect();
+        assert!(keys.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[cfg(feature = "uuid-v7")]
+    #[test]
+    fn should_generate_increasing_v7_uuid_keys() {
+        let keys: Vec<_> = (0..1000).map(|_| LexKey::new_uuid_v7()).collect();
+        assert!(keys.windows(2).all(|w| w[0] < w[1]));
+        let uuid = Uuid::from_slice(keys[0].as_bytes()).unwrap();
+        assert_eq!(uuid.get_version_num(), 7);
+    }
+
+    #[test]
+    fn should_truncate_or_pad_fixed_prefix_preserving_order() {
+        // Arrange
+        let keys = [
+            LexKey::empty(),
+            LexKey::encode_string("a"),
+            LexKey::encode_string("ab"),
+            LexKey::encode_composite(&[b"ab", b"c"]),
+            LexKey::encode_string("abcd"),
+            LexKey::encode_string("b"),
+        ];
+
+        // Act
+        let prefixes: Vec<_> = keys.iter().map(|k| k.fixed_prefix(3)).collect();
+
+        // Assert
+        assert!(prefixes.iter().all(|p| p.as_bytes().len() == 3));
+        assert!(prefixes.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(prefixes[4].as_bytes(), b"abc");
+        assert_eq!(prefixes[0].as_bytes(), b"\x00\x00\x00");
+        assert_eq!(keys[4].fixed_prefix(0), LexKey::empty());
+    }
+
+    #[test]
+    fn should_split_parts_including_empty_segments() {
+        // Arrange
+        let key = LexKey::encode_composite(&[b"a", b"", b"c"]);
+        let trailing = LexKey::encode_first(&[b"a".as_ref()]);
+
+        // Act
+        let parts: Vec<&[u8]> = key.parts().collect();
+
+        // Assert
+        assert_eq!(parts, [&b"a"[..], b"", b"c"]);
+        assert_eq!(trailing.parts().count(), 2);
+        assert_eq!(LexKey::empty().parts().count(), 0);
+        assert_eq!(
+            LexKey::encode_string("solo").nth_part(0),
+            Some(&b"solo"[..])
+        );
+    }
+
+    #[test]
+    fn should_place_canonical_nans_at_both_ends_in_total_order_mode() {
+        // Arrange
+        let payload_nan = f64::from_bits(0x7FF0_0000_0000_0001);
+        let neg_payload_nan = f64::from_bits(0xFFFF_FFFF_FFFF_FFFF);
+        let ordered = [
+            -f64::NAN,
+            f64::NEG_INFINITY,
+            -1.0,
+            -0.0,
+            0.0,
+            f64::MIN_POSITIVE,
+            f64::INFINITY,
+            f64::NAN,
+        ];
+
+        // Act
+        let keys: Vec<_> = ordered
+            .iter()
+            .map(|&x| LexKey::encode_f64_with(x, F64Mode::TotalOrder))
+            .collect();
+
+        // Assert
+        assert!(keys.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(
+            LexKey::encode_f64_with(payload_nan, F64Mode::TotalOrder),
+            keys[7]
+        );
+        assert_eq!(
+            LexKey::encode_f64_with(neg_payload_nan, F64Mode::TotalOrder),
+            keys[0]
+        );
+        assert_eq!(keys[7].to_hex_string(), "fff8000000000000");
+    }
+
+    #[test]
+    fn should_order_128_bit_integers_across_boundaries() {
+        // Arrange
+        let signed = [i128::MIN, i128::MIN + 1, -1, 0, 1, i128::MAX - 1, i128::MAX];
+        let unsigned = [0, 1, u128::from(u64::MAX), u128::MAX - 1, u128::MAX];
+
+        // Act
+        let s: Vec<_> = signed.iter().map(|&n| LexKey::encode_i128(n)).collect();
+        let u: Vec<_> = unsigned.iter().map(|&n| LexKey::encode_u128(n)).collect();
+        let mut buf = Vec::new();
+        LexKey::encode_i128_into(&mut buf, i128::MIN);
+        LexKey::encode_u128_into(&mut buf, u128::MAX);
+
+        // Assert
+        assert!(s.windows(2).all(|w| w[0] < w[1]));
+        assert!(u.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(s[0].to_hex_string(), "00".repeat(16));
+        assert_eq!(s[6].to_hex_string(), "ff".repeat(16));
+        assert_eq!(buf, [[0u8; 16], [0xff; 16]].concat());
+        assert_eq!(crate::encode_composite!(-1i128), s[2]);
+    }
+
     #[test]
     fn should_concatenate_parts_with_separator_and_encode_first_last() {
         // Arrange
@@ -1058,6 +2582,39 @@ mod tests {
         assert!(LexKey::encode_f32(-1.0).as_bytes() < LexKey::encode_f32(1.0).as_bytes());
     }
 
+    #[test]
+    fn should_order_float32_across_full_range_on_every_path() {
+        // Arrange
+        let values = [
+            f32::NEG_INFINITY,
+            f32::MIN,
+            -1.0,
+            -f32::MIN_POSITIVE,
+            -1e-45,
+            -0.0,
+            0.0,
+            1e-45,
+            f32::MIN_POSITIVE,
+            1.0,
+            f32::MAX,
+            f32::INFINITY,
+        ];
+        let mut enc = Encoder::with_capacity(4);
+
+        // Act & Assert
+        let keys: Vec<_> = values.iter().map(|&x| LexKey::encode_f32(x)).collect();
+        assert!(keys.windows(2).all(|w| w[0] < w[1]));
+        for (&x, key) in values.iter().zip(&keys) {
+            let mut buf = Vec::new();
+            assert_eq!(LexKey::encode_f32_into(&mut buf, x), 4);
+            assert_eq!(x.encode_into(&mut Vec::new()), 4);
+            enc.clear();
+            enc.encode_f32_into(x);
+            assert_eq!(buf, key.as_bytes());
+            assert_eq!(enc.as_slice(), key.as_bytes());
+        }
+    }
+
     #[test]
     fn should_order_negative_zero_before_positive_zero_for_float32() {
         let neg_zero = LexKey::encode_f32(-0.0_f32);
@@ -1088,6 +2645,73 @@ mod tests {
         assert_eq!(empty.to_hex_string(), "");
     }
 
+    #[test]
+    fn should_format_as_escaped_text_and_hex() {
+        let key = LexKey::encode_composite(&[b"a\\b", &[0x01, 0xab]]);
+        assert_eq!(format!("{key}"), "a\\\\b\\x00\\x01\\xab");
+        assert_eq!(format!("{key:x}"), "615c620001ab");
+        assert_eq!(format!("{key:X}"), "615C620001AB");
+        assert_eq!(format!("{key:#x}"), "0x615c620001ab");
+        assert_eq!(format!("{}", LexKey::empty()), "");
+    }
+
+    #[test]
+    fn should_convert_into_owned_bytes_without_copying() {
+        let key = LexKey::encode_composite(&[b"tenant", b"alice"]);
+        let ptr = key.as_bytes().as_ptr();
+        let vec = key.clone().into_vec();
+        assert_eq!(vec, key.as_bytes());
+        assert_ne!(vec.as_ptr(), ptr);
+
+        let bytes = Bytes::from(key.clone());
+        assert_eq!(bytes.as_ptr(), ptr);
+        drop(bytes);
+        let vec = Vec::<u8>::from(key);
+        assert_eq!(vec.as_ptr(), ptr);
+        assert_eq!(vec, b"tenant\x00alice");
+        assert_eq!(LexKey::empty().into_bytes(), Bytes::new());
+    }
+
+    #[test]
+    fn should_compare_with_byte_containers() {
+        let key = LexKey::encode_u16(0x0102);
+        let raw: &[u8] = &[1, 2];
+        assert_eq!(key, raw);
+        assert_eq!(raw, key);
+        assert!(key == *raw);
+        assert!(key == vec![1, 2] && vec![1, 2] == key);
+        assert!(key == Bytes::from_static(&[1, 2]) && Bytes::from_static(&[1, 2]) == key);
+        assert!(key != [1u8][..]);
+
+        let upper: &[u8] = &[1, 3];
+        assert!(key < upper);
+        assert!(upper > key);
+        assert!(key > [1u8][..] && key >= raw);
+        assert!(key < Bytes::from_static(&[2]) && vec![0xff] > key);
+        assert_eq!(key.partial_cmp(raw), Some(Ordering::Equal));
+    }
+
+    #[test]
+    fn should_deref_and_borrow_as_byte_slice() {
+        use std::collections::{BTreeSet, HashSet};
+
+        fn takes_ref(k: impl AsRef<[u8]>) -> usize {
+            k.as_ref().len()
+        }
+
+        let key = crate::encode_composite!("ab", 1u8);
+        assert_eq!(key.len(), 4);
+        assert!(key.ends_with(&[0, 1]));
+        assert_eq!(key.first(), Some(&b'a'));
+        assert_eq!(takes_ref(&key), 4);
+
+        let hashed: HashSet<LexKey> = [key.clone()].into();
+        let sorted: BTreeSet<LexKey> = [key].into();
+        assert!(hashed.contains(&b"ab\x00\x01"[..]));
+        assert!(sorted.contains(&b"ab\x00\x01"[..]));
+        assert!(!sorted.contains(&b"ab"[..]));
+    }
+
     #[test]
     fn should_compare_using_ord_and_partialord() {
         let a = LexKey::encode_string("a");
@@ -1137,6 +2761,74 @@ mod tests {
         assert_eq!(dst1, dst2);
     }
 
+    #[test]
+    fn should_write_same_bytes_into_any_buf_mut() {
+        let id = Uuid::from_u128(9);
+        let write = |dst: &mut dyn BufMut| {
+            LexKey::encode_i16_into(dst, -2)
+                + LexKey::encode_u128_into(dst, 3)
+                + LexKey::encode_f32_into(dst, 0.5)
+                + LexKey::encode_opt_f64_into(dst, None, NullOrder::Last)
+                + LexKey::encode_uuid_into(dst, &id)
+        };
+
+        let mut vec = Vec::new();
+        let n = write(&mut vec);
+        let mut frame = bytes::BytesMut::new();
+        assert_eq!(write(&mut frame), n);
+        let mut stack = [0u8; 39];
+        let mut slice = &mut stack[..];
+        assert_eq!(write(&mut slice), n);
+
+        assert_eq!(n, 39);
+        assert_eq!(frame, vec);
+        assert_eq!(stack[..], vec[..]);
+    }
+
+    #[test]
+    fn should_write_variable_width_encoders_into_any_buf_mut() {
+        let addr: std::net::SocketAddr = "10.0.0.1:80".parse().unwrap();
+        let write = |dst: &mut dyn BufMut| {
+            assert_eq!(LexKey::encode_parts_into(dst, ["x", "yz"]), 2);
+            LexKey::encode_composite_into(dst, &[b"a", b"bc"])
+                + LexKey::encode_opt_into(dst, Some(&7u16), NullOrder::First)
+                + LexKey::encode_string_desc_into(dst, "a\0b")
+                + LexKey::encode_i64_desc_into(dst, -3)
+                + LexKey::encode_bytes_escaped_into(dst, b"\0")
+                + LexKey::encode_socket_addr_into(dst, addr)
+                + LexKey::encode_time_of_day_into(dst, 1)
+                + LexKey::encode_morton2_into(dst, 1, 2)
+        };
+
+        let mut vec = Vec::new();
+        let n = write(&mut vec);
+        let mut frame = bytes::BytesMut::new();
+        write(&mut frame);
+        let mut stack = [0u8; 64];
+        let mut slice = &mut stack[..];
+        write(&mut slice);
+
+        let expected: Vec<u8> = [
+            LexKey::encode_parts(["x", "yz"]),
+            LexKey::encode_composite(&[b"a", b"bc"]),
+            LexKey::encode_opt(Some(&7u16), NullOrder::First),
+            LexKey::encode_string_desc("a\0b"),
+            LexKey::encode_i64_desc(-3),
+            LexKey::encode_bytes_escaped(b"\0"),
+            LexKey::encode_socket_addr(addr),
+            LexKey::encode_time_of_day(1),
+            LexKey::encode_morton2(1, 2),
+        ]
+        .iter()
+        .flat_map(|k| k.as_bytes().iter().copied())
+        .collect();
+        assert_eq!(vec, expected);
+        // `encode_parts_into` returns its part count, not its 4 bytes.
+        assert_eq!(n + 4, expected.len());
+        assert_eq!(frame, vec);
+        assert_eq!(stack[..vec.len()], vec[..]);
+    }
+
     #[test]
     fn should_encode_smaller_types_via_encodable_trait() {
         // Arrange
@@ -1361,6 +3053,28 @@ mod tests {
         assert_eq!(key, expected);
     }
 
+    #[test]
+    fn should_encode_owned_and_borrowed_parts_alike() {
+        use crate::encode_composite;
+
+        let array = *b"ab";
+        let vec = b"cd".to_vec();
+        let string = String::from("ef");
+        let name: &&str = &"gh";
+        let key = encode_composite!(array, &vec, vec.as_slice(), &string, name, true);
+        assert_eq!(
+            key,
+            encode_composite!(&b"ab"[..], b"cd", &b"cd"[..], "ef", "gh", true)
+        );
+        assert_eq!(array.encoded_len(), 2);
+        assert_eq!(
+            LexKey::encode(&(array, "x")),
+            LexKey::encode(&(&array, "x"))
+        );
+        assert_eq!(LexKey::encode("str").as_bytes(), b"str");
+        assert_eq!(LexKey::encode(&b"raw"[..]).as_bytes(), b"raw");
+    }
+
     #[test]
     fn should_encode_range_lower_with_row() {
         let partition = b"part";
@@ -1426,6 +3140,64 @@ mod tests {
         assert_eq!(LexKey::prefix_successor(b""), None);
     }
 
+    #[test]
+    fn should_match_prefixes_only_at_part_boundaries() {
+        let key = LexKey::encode_composite(&[b"ten", b"ant"]);
+        let cases: [(&[u8], Option<&[u8]>); 7] = [
+            (b"", Some(b"ten\x00ant")),
+            (b"ten", Some(b"ant")),
+            (b"ten\x00", Some(b"ant")),
+            (b"ten\x00ant", Some(b"")),
+            (b"te", None),
+            (b"ten\x00an", None),
+            (b"ten\x00ant\x00", None),
+        ];
+        for (prefix, rest) in cases {
+            let prefix = LexKey::from(prefix);
+            assert_eq!(key.starts_with(&prefix), rest.is_some(), "{prefix:?}");
+            assert_eq!(
+                key.strip_prefix(&prefix).as_ref().map(LexKey::as_bytes),
+                rest,
+                "{prefix:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn should_match_partition_range_with_prefix_range() {
+        let key = LexKey::encode_composite(&[b"acme", b"orders"]);
+        let (start, end) = key.prefix_range();
+        let range = crate::KeyRange::partition(key.as_bytes());
+        assert_eq!(start.as_ref(), range.start());
+        assert_eq!(end.as_ref(), range.end());
+        assert_eq!(
+            start,
+            Bound::Included(LexKey::encode_first(&[b"acme", b"orders"]))
+        );
+        assert_eq!(
+            end,
+            Bound::Excluded(LexKey::encode_last(&[b"acme", b"orders"]))
+        );
+    }
+
+    #[test]
+    fn should_bound_every_extension_with_prefix_successor_key() {
+        let key = LexKey::encode_composite(&[b"t", b"a\xff"]);
+        let upper = key.prefix_successor_key().unwrap();
+        assert_eq!(upper.as_bytes(), b"t\x00b");
+        assert_eq!(
+            Some(upper.as_bytes().to_vec()),
+            LexKey::prefix_successor(key.as_bytes())
+        );
+        for tail in [&b""[..], b"\x00", b"\xff", b"\xff\xff\xff"] {
+            let mut child = key.as_bytes().to_vec();
+            child.extend_from_slice(tail);
+            assert!(child < upper);
+        }
+        assert!(LexKey::encode_last(&[b"t", b"a\xff"]) < upper);
+        assert_eq!(LexKey::empty().prefix_successor_key(), None);
+    }
+
     #[test]
     fn should_build_prefix_scan_bounds_for_arbitrary_bytes() {
         let prefix = b"acme\0kv\0";
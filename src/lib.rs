@@ -9,7 +9,7 @@
 //! `Encodable` numeric types keep their native width (`u8` is 1 byte, `u16` is 2, `u32`/`f32`
 //! are 4). Use explicit 64-bit values when a schema needs cross-width canonicalization. Note:
 //! NaN values are not encodable by this crate's encoders and will cause a panic; represent missing
-//! or invalid floats with a schema-level presence/marker value instead.
+//! or invalid floats as `None` with `LexKey::encode_opt_f64` or `Option<f64>` instead.
 //!
 //! Quick start
 //!
@@ -41,12 +41,82 @@
 //! ```
 //!
 //! See `LexKey` and `Encoder` for detailed APIs and more examples.
+pub mod anonymize;
+pub mod arena;
+pub mod calendar;
+pub mod checksum;
+pub mod collections;
+pub mod compact;
+#[cfg(any(feature = "blake3", feature = "sha2"))]
+pub mod content;
+#[cfg(any(feature = "time", feature = "jiff"))]
+pub mod datetime;
+pub mod decimal;
+pub mod decoder;
+#[cfg(feature = "derive")]
+pub mod derive;
+pub mod desc;
 pub mod encoder;
+mod escape;
+mod fixed;
+pub mod geo;
+#[cfg(feature = "json")]
+pub mod json;
+#[cfg(feature = "keyed-hash")]
+pub mod keyed;
+pub mod keys;
+pub mod keyspace;
+#[cfg(feature = "leveldb")]
+pub mod leveldb;
 pub mod lexkey;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod money;
+pub mod net;
+pub mod patterns;
+pub mod pool;
+pub mod prefix;
+pub mod profile;
+pub mod range;
+pub mod redis;
+pub mod scan;
+pub mod schema;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod strings;
+pub mod tagged;
+pub mod text;
+pub mod try_encode;
+pub mod typed;
+#[cfg(feature = "primitive-types")]
+pub mod u256;
+#[cfg(feature = "ulid")]
+pub mod ulid;
+pub mod value;
+mod variant;
 
 // Re-export commonly used types at the crate root for convenient imports in tests and consumers
-pub use encoder::Encoder;
-pub use lexkey::LexKey;
+pub use arena::KeyArena;
+pub use calendar::{Bucket, Month, Weekday};
+pub use collections::{KeySet, LexMap};
+pub use compact::CompactKey;
+#[cfg(any(feature = "blake3", feature = "sha2"))]
+pub use content::{ContentHash, ContentKey};
+pub use decoder::{DecodeError, Decoder};
+#[cfg(feature = "derive")]
+pub use derive::{LexKeyDecode, LexKeyEncode};
+pub use encoder::{Encoder, EncoderSnapshot};
+pub use fixed::BufferTooSmall;
+#[cfg(feature = "keyed-hash")]
+pub use keyed::KeyedHasher;
+pub use lexkey::{F64Mode, KeyDiff, LexKey, NullOrder, NullsLast};
+pub use money::Money;
+pub use pool::EncoderPool;
+pub use range::KeyRange;
+pub use strings::StringMode;
+pub use try_encode::{CompositeError, EncodeError};
+pub use typed::{KeyOf, TypedKey};
+pub use value::Value;
 
 /// Trait for types that can be encoded into a lexkey.
 pub trait Encodable {
@@ -54,6 +124,81 @@ pub trait Encodable {
     fn encoded_len(&self) -> usize;
     /// Encodes this value into the given buffer, returning the number of bytes written.
     fn encode_into(&self, dst: &mut Vec<u8>) -> usize;
+
+    /// Returns the number of bytes `encode_desc_into` will write.
+    fn encoded_desc_len(&self) -> usize {
+        self.encoded_len()
+    }
+
+    /// Encodes this value so larger values sort first, as `Desc` and `SortOrder::Desc` write it.
+    ///
+    /// The default complements the `encode_into` bytes, which is only order-correct when no
+    /// encoding is a proper prefix of another. Strings and byte strings override it with the
+    /// escaped and terminated layout of `LexKey::encode_string_desc`; options and tuples write
+    /// their elements descending.
+    fn encode_desc_into(&self, dst: &mut Vec<u8>) -> usize {
+        let start = dst.len();
+        let n = self.encode_into(dst);
+        desc::complement_from(dst, start);
+        n
+    }
+}
+
+/// Trait for types whose encoding can fail, such as floats that may be NaN.
+///
+/// Implemented for every `Encodable` primitive; only `f32`, `f64` and options or references
+/// wrapping them can actually fail. Nothing is written to `dst` on error.
+pub trait TryEncodable {
+    /// Encodes this value into the given buffer, returning the number of bytes written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `EncodeError` if the value has no sortable encoding.
+    fn try_encode_into(&self, dst: &mut Vec<u8>) -> Result<usize, EncodeError>;
+}
+
+/// Trait for types that can be decoded from the bytes their `Encodable` impl writes.
+///
+/// `decode_from` reads exactly the bytes `encode_into` would have written for the value and
+/// advances `src` past them; separators between composite parts are left to the caller. On error
+/// `src` is unchanged.
+///
+/// ```
+/// use lexkey::{encode_composite, Decodable};
+///
+/// let key = encode_composite!(-3i64, 2.5f64);
+/// let mut src = key.as_bytes();
+/// assert_eq!(i64::decode_from(&mut src)?, -3);
+/// src = &src[1..]; // separator
+/// assert_eq!(f64::decode_from(&mut src)?, 2.5);
+/// assert!(src.is_empty());
+/// # Ok::<(), lexkey::DecodeError>(())
+/// ```
+pub trait Decodable: Sized {
+    /// Decodes a value from the front of `src`, advancing it past the consumed bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DecodeError` if `src` is too short or does not hold a valid encoding.
+    fn decode_from(src: &mut &[u8]) -> Result<Self, DecodeError>;
+
+    /// Decodes a value written by `Encodable::encode_desc_into`, advancing `src` past it.
+    ///
+    /// The default complements the rest of `src` and reads it with `decode_from`, which inverts
+    /// the default `encode_desc_into`. Fixed-width values complement only their own bytes,
+    /// strings read the escaped descending layout, and options and derived types decode their
+    /// parts descending.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DecodeError` if `src` is too short or does not hold a valid encoding.
+    fn decode_desc_from(src: &mut &[u8]) -> Result<Self, DecodeError> {
+        let flipped: Vec<u8> = src.iter().map(|b| !b).collect();
+        let mut rest = flipped.as_slice();
+        let value = Self::decode_from(&mut rest)?;
+        *src = &src[flipped.len() - rest.len()..];
+        Ok(value)
+    }
 }
 
 /// Macro to encode a composite key from mixed types.
@@ -109,6 +254,22 @@ macro_rules! encode_range_bounds {
     };
 }
 
+/// Return the length of the longest common prefix of two byte strings.
+///
+/// This is the shared-prefix length SSTable-style restart points and prefix-compressed blocks
+/// store, and the position at which two keys start to differ. `LexKey::common_prefix_len` is
+/// the method form.
+///
+/// ```
+/// assert_eq!(lexkey::common_prefix_len(b"acme\x00alice", b"acme\x00bob"), 5);
+/// assert_eq!(lexkey::common_prefix_len(b"ab", b"abc"), 2);
+/// ```
+#[inline]
+#[must_use]
+pub fn common_prefix_len(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(x, y)| x == y).count()
+}
+
 // Shared crate-level helpers/constants used by both `encoder` and `lexkey` modules.
 // Keep these `pub(crate)` so they are available across the crate but not exported in the public API.
 
@@ -128,121 +289,54 @@ pub(crate) fn encode_len(parts: &[&[u8]]) -> usize {
 
 /// Append already-encoded composite `parts` into `dst`, separated by 0x00.
 #[inline]
-pub(crate) fn encode_parts_into(dst: &mut Vec<u8>, parts: &[&[u8]]) -> usize {
-    let start = dst.len();
-
+pub(crate) fn encode_parts_into<B: bytes::BufMut + ?Sized>(dst: &mut B, parts: &[&[u8]]) -> usize {
     match parts {
         [] => {}
-        [a] => dst.extend_from_slice(a),
+        [a] => dst.put_slice(a),
         [a, b] => {
-            dst.extend_from_slice(a);
-            dst.push(LexKey::SEPARATOR);
-            dst.extend_from_slice(b);
+            dst.put_slice(a);
+            dst.put_u8(LexKey::SEPARATOR);
+            dst.put_slice(b);
         }
         [a, b, c] => {
-            dst.extend_from_slice(a);
-            dst.push(LexKey::SEPARATOR);
-            dst.extend_from_slice(b);
-            dst.push(LexKey::SEPARATOR);
-            dst.extend_from_slice(c);
+            dst.put_slice(a);
+            dst.put_u8(LexKey::SEPARATOR);
+            dst.put_slice(b);
+            dst.put_u8(LexKey::SEPARATOR);
+            dst.put_slice(c);
         }
         [a, b, c, d] => {
-            dst.extend_from_slice(a);
-            dst.push(LexKey::SEPARATOR);
-            dst.extend_from_slice(b);
-            dst.push(LexKey::SEPARATOR);
-            dst.extend_from_slice(c);
-            dst.push(LexKey::SEPARATOR);
-            dst.extend_from_slice(d);
+            dst.put_slice(a);
+            dst.put_u8(LexKey::SEPARATOR);
+            dst.put_slice(b);
+            dst.put_u8(LexKey::SEPARATOR);
+            dst.put_slice(c);
+            dst.put_u8(LexKey::SEPARATOR);
+            dst.put_slice(d);
         }
         _ => {
             for (i, part) in parts.iter().enumerate() {
-                dst.extend_from_slice(part);
+                dst.put_slice(part);
                 if i + 1 < parts.len() {
-                    dst.push(LexKey::SEPARATOR);
+                    dst.put_u8(LexKey::SEPARATOR);
                 }
             }
         }
     }
 
-    dst.len() - start
+    encode_len(parts)
 }
 
 #[doc(hidden)]
 pub mod __private {
     use super::{Encodable, LexKey};
 
-    pub trait EncodableTuple {
-        fn encoded_len(&self) -> usize;
-        fn encode_into(&self, dst: &mut Vec<u8>) -> usize;
-    }
-
     #[inline]
-    pub fn encode_composite_tuple<T: EncodableTuple>(parts: &T) -> LexKey {
+    pub fn encode_composite_tuple<T: Encodable>(parts: &T) -> LexKey {
         let mut buf = Vec::with_capacity(parts.encoded_len());
         parts.encode_into(&mut buf);
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_bytes(&buf);
         LexKey::from_bytes(buf)
     }
-
-    macro_rules! impl_encodable_tuple {
-        ($($ty:ident:$value:ident),+) => {
-            impl<$($ty),+> EncodableTuple for ($($ty,)+)
-            where
-                $($ty: Encodable,)+
-            {
-                #[inline]
-                fn encoded_len(&self) -> usize {
-                    let ($($value,)+) = self;
-                    0 $(+ $value.encoded_len())+ + impl_encodable_tuple!(@separators $($ty),+)
-                }
-
-                #[inline]
-                fn encode_into(&self, dst: &mut Vec<u8>) -> usize {
-                    let start = dst.len();
-                    let ($($value,)+) = self;
-                    impl_encodable_tuple!(@encode dst; $($value),+);
-                    dst.len() - start
-                }
-            }
-        };
-        (@separators $single:ident) => {
-            0
-        };
-        (@separators $first:ident, $($rest:ident),+) => {
-            impl_encodable_tuple!(@count $($rest),+)
-        };
-        (@count $single:ident) => {
-            1
-        };
-        (@count $first:ident, $($rest:ident),+) => {
-            1 + impl_encodable_tuple!(@count $($rest),+)
-        };
-        (@encode $dst:ident; $first:ident) => {
-            $first.encode_into($dst);
-        };
-        (@encode $dst:ident; $first:ident, $($rest:ident),+) => {
-            $first.encode_into($dst);
-            $(
-                $dst.push(LexKey::SEPARATOR);
-                $rest.encode_into($dst);
-            )+
-        };
-    }
-
-    impl_encodable_tuple!(A:a);
-    impl_encodable_tuple!(A:a, B:b);
-    impl_encodable_tuple!(A:a, B:b, C:c);
-    impl_encodable_tuple!(A:a, B:b, C:c, D:d);
-    impl_encodable_tuple!(A:a, B:b, C:c, D:d, E:e);
-    impl_encodable_tuple!(A:a, B:b, C:c, D:d, E:e, F:f);
-    impl_encodable_tuple!(A:a, B:b, C:c, D:d, E:e, F:f, G:g);
-    impl_encodable_tuple!(A:a, B:b, C:c, D:d, E:e, F:f, G:g, H:h);
-    impl_encodable_tuple!(A:a, B:b, C:c, D:d, E:e, F:f, G:g, H:h, I:i);
-    impl_encodable_tuple!(A:a, B:b, C:c, D:d, E:e, F:f, G:g, H:h, I:i, J:j);
-    impl_encodable_tuple!(A:a, B:b, C:c, D:d, E:e, F:f, G:g, H:h, I:i, J:j, K:k);
-    impl_encodable_tuple!(A:a, B:b, C:c, D:d, E:e, F:f, G:g, H:h, I:i, J:j, K:k, L:l);
-    impl_encodable_tuple!(A:a, B:b, C:c, D:d, E:e, F:f, G:g, H:h, I:i, J:j, K:k, L:l, M:m);
-    impl_encodable_tuple!(A:a, B:b, C:c, D:d, E:e, F:f, G:g, H:h, I:i, J:j, K:k, L:l, M:m, N:n);
-    impl_encodable_tuple!(A:a, B:b, C:c, D:d, E:e, F:f, G:g, H:h, I:i, J:j, K:k, L:l, M:m, N:n, O:o);
-    impl_encodable_tuple!(A:a, B:b, C:c, D:d, E:e, F:f, G:g, H:h, I:i, J:j, K:k, L:l, M:m, N:n, O:o, P:p);
 }
@@ -0,0 +1,109 @@
+use std::sync::OnceLock;
+
+use crate::LexKey;
+
+/// Receives the size of every key the crate finishes building.
+///
+/// The sink is notified from `Encoder::freeze`, `Encoder::into_vec`, `LexKey::encode_composite`,
+/// `LexKey::encode_composite_encodables` and the `encode_composite!` macro. Single-value
+/// `encode_*` constructors are not reported; they always produce a fixed-width or caller-sized part.
+///
+/// `parts` is exact when the crate knows it (the slice-based composite constructors). Otherwise it
+/// is the number of `0x00` bytes plus one, which over-counts when a part itself contains `0x00`
+/// (for example most small big-endian integers).
+pub trait KeyMetricsSink: Send + Sync {
+    /// Called once per finished key with its length in bytes and its part count.
+    fn record_key(&self, len: usize, parts: usize);
+}
+
+static SINK: OnceLock<&'static dyn KeyMetricsSink> = OnceLock::new();
+
+/// Install the process-wide metrics sink.
+///
+/// The sink can only be set once; later calls return the rejected sink as the error.
+///
+/// ```rust
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use lexkey::{metrics::{self, KeyMetricsSink}, LexKey};
+///
+/// struct MaxLen(AtomicUsize);
+/// impl KeyMetricsSink for MaxLen {
+///     fn record_key(&self, len: usize, _parts: usize) {
+///         self.0.fetch_max(len, Ordering::Relaxed);
+///     }
+/// }
+///
+/// static SINK: MaxLen = MaxLen(AtomicUsize::new(0));
+/// metrics::set_sink(&SINK).ok();
+/// let _ = LexKey::encode_composite(&[b"tenant", b"user"]);
+/// assert!(SINK.0.load(Ordering::Relaxed) >= 11);
+/// ```
+///
+/// # Errors
+///
+/// Returns `Err(sink)` if a sink was already installed.
+pub fn set_sink(sink: &'static dyn KeyMetricsSink) -> Result<(), &'static dyn KeyMetricsSink> {
+    SINK.set(sink)
+}
+
+/// Report a finished key whose part count is known.
+#[inline]
+pub(crate) fn record(len: usize, parts: usize) {
+    if let Some(sink) = SINK.get() {
+        sink.record_key(len, parts);
+    }
+}
+
+/// Report a finished key, estimating its part count from separator bytes.
+#[inline]
+pub(crate) fn record_bytes(bytes: &[u8]) {
+    if let Some(sink) = SINK.get() {
+        let parts = if bytes.is_empty() {
+            0
+        } else {
+            bytes.split(|&b| b == LexKey::SEPARATOR).count()
+        };
+        sink.record_key(bytes.len(), parts);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+    use crate::Encoder;
+
+    thread_local! {
+        static SEEN: RefCell<Vec<(usize, usize)>> = const { RefCell::new(Vec::new()) };
+    }
+
+    // Records per thread so parallel tests do not observe each other's keys.
+    struct ThreadSink;
+
+    impl KeyMetricsSink for ThreadSink {
+        fn record_key(&self, len: usize, parts: usize) {
+            SEEN.with(|s| s.borrow_mut().push((len, parts)));
+        }
+    }
+
+    static THREAD_SINK: ThreadSink = ThreadSink;
+
+    #[test]
+    fn should_report_composites_and_frozen_encoders() {
+        let _ = set_sink(&THREAD_SINK);
+        SEEN.with(|s| s.borrow_mut().clear());
+
+        let _ = LexKey::encode_composite(&[b"ab", b"", b"c"]);
+        let _ = crate::encode_composite!("ab", true);
+        let mut enc = Encoder::with_capacity(8);
+        enc.encode_string_into("x");
+        enc.push_separator();
+        enc.encode_string_into("yz");
+        let _ = enc.freeze();
+        let _ = Encoder::with_capacity(0).into_vec();
+
+        let seen = SEEN.with(|s| s.borrow().clone());
+        assert_eq!(seen, vec![(5, 3), (4, 2), (4, 2), (0, 0)]);
+    }
+}
@@ -0,0 +1,187 @@
+//! Money amounts keyed by currency and signed minor units.
+//!
+//! Amounts are integers in the currency's minor unit (cents for USD, yen for JPY, fils for BHD).
+//! Currencies use different scales, so amounts are only comparable within one currency: the
+//! default layout puts the currency first, `currency || 0x00 || amount`, and orders by amount
+//! inside each currency. Use `encode_money_amount` alone only when every amount in the key
+//! position shares one currency or has been normalized to a common scale by the caller.
+
+use crate::{Encodable, Encoder, LexKey};
+
+/// Width in bytes of an encoded amount.
+pub const AMOUNT_LEN: usize = 16;
+
+fn currency_code(currency: &str) -> [u8; 3] {
+    match currency.as_bytes() {
+        &[a, b, c] if [a, b, c].iter().all(u8::is_ascii_uppercase) => [a, b, c],
+        _ => panic!("currency must be a three-letter uppercase ISO 4217 code"),
+    }
+}
+
+/// An amount of money in minor units of an ISO 4217 currency.
+///
+/// Encodes as `currency || 0x00 || amount`, the same bytes as `LexKey::encode_money`.
+///
+/// ```rust
+/// use lexkey::{encode_composite, Money};
+///
+/// let small = encode_composite!("ledger", Money::new(-250, "EUR"));
+/// let large = encode_composite!("ledger", Money::new(1_000, "EUR"));
+/// assert!(small < large);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Money {
+    currency: [u8; 3],
+    minor_units: i128,
+}
+
+impl Money {
+    /// Create an amount of `minor_units` in `currency`.
+    ///
+    /// # Panics
+    ///
+    /// Panics unless `currency` is three ASCII uppercase letters.
+    #[must_use]
+    pub fn new(minor_units: i128, currency: &str) -> Self {
+        Self {
+            currency: currency_code(currency),
+            minor_units,
+        }
+    }
+
+    /// The ISO 4217 currency code.
+    #[inline]
+    #[must_use]
+    pub fn currency(&self) -> &str {
+        // `new` only accepts ASCII uppercase, so this never falls back.
+        std::str::from_utf8(&self.currency).unwrap_or_default()
+    }
+
+    /// The amount in minor units.
+    #[inline]
+    #[must_use]
+    pub fn minor_units(&self) -> i128 {
+        self.minor_units
+    }
+}
+
+impl Encodable for Money {
+    #[inline]
+    fn encoded_len(&self) -> usize {
+        3 + 1 + AMOUNT_LEN
+    }
+
+    #[inline]
+    fn encode_into(&self, dst: &mut Vec<u8>) -> usize {
+        dst.extend_from_slice(&self.currency);
+        dst.push(LexKey::SEPARATOR);
+        LexKey::encode_i128_into(dst, self.minor_units);
+        self.encoded_len()
+    }
+}
+
+impl LexKey {
+    /// Encode `(currency, amount)` so amounts sort numerically within each currency.
+    ///
+    /// Layout: the 3-byte currency code, `0x00`, then the amount as a 16-byte big-endian `i128`
+    /// with the sign bit flipped.
+    ///
+    /// ```rust
+    /// use lexkey::LexKey;
+    /// let refund = LexKey::encode_money(-1_99, "USD");
+    /// let charge = LexKey::encode_money(12_50, "USD");
+    /// assert!(refund < charge);
+    /// assert!(LexKey::encode_money(1, "JPY") < LexKey::encode_money(0, "USD"));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics unless `currency` is three ASCII uppercase letters.
+    #[inline]
+    #[must_use]
+    pub fn encode_money(minor_units: i128, currency: &str) -> Self {
+        let money = Money::new(minor_units, currency);
+        let mut buf = Vec::with_capacity(money.encoded_len());
+        money.encode_into(&mut buf);
+        Self::from_bytes(buf)
+    }
+
+    /// Encode only the amount: 16 bytes, big-endian `i128` with the sign bit flipped.
+    ///
+    /// This is the same encoding as `encode_i128`.
+    #[inline]
+    #[must_use]
+    pub fn encode_money_amount(minor_units: i128) -> Self {
+        Self::encode_i128(minor_units)
+    }
+}
+
+impl Encoder {
+    /// Append the `(currency, amount)` encoding and return bytes written.
+    ///
+    /// # Panics
+    ///
+    /// Panics unless `currency` is three ASCII uppercase letters.
+    #[inline]
+    pub fn encode_money_into(&mut self, minor_units: i128, currency: &str) -> usize {
+        Money::new(minor_units, currency).encode_into(&mut self.buf)
+    }
+
+    /// Append the 16-byte amount-only encoding.
+    #[inline]
+    pub fn encode_money_amount_into(&mut self, minor_units: i128) -> usize {
+        self.encode_i128_into(minor_units)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_order_amounts_within_currency() {
+        let amounts = [i128::MIN, -1_000_000, -1, 0, 1, 99, i128::MAX];
+        let keys: Vec<_> = amounts
+            .iter()
+            .map(|&a| LexKey::encode_money(a, "USD"))
+            .collect();
+        assert!(keys.windows(2).all(|w| w[0] < w[1]));
+
+        let bare: Vec<_> = amounts
+            .iter()
+            .map(|&a| LexKey::encode_money_amount(a))
+            .collect();
+        assert!(bare.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn should_group_by_currency_first() {
+        let eur_large = LexKey::encode_money(i128::MAX, "EUR");
+        let usd_small = LexKey::encode_money(i128::MIN, "USD");
+        assert!(eur_large < usd_small);
+        assert_eq!(
+            LexKey::encode_money(1, "USD").to_hex_string(),
+            format!("555344{}80{}01", "00", "00".repeat(14))
+        );
+    }
+
+    #[test]
+    fn should_match_encodable_and_encoder_paths() {
+        let money = Money::new(-42, "GBP");
+        let mut enc = Encoder::with_capacity(32);
+        enc.encode_money_into(-42, "GBP");
+
+        assert_eq!(
+            crate::encode_composite!(money),
+            LexKey::encode_money(-42, "GBP")
+        );
+        assert_eq!(enc.as_slice(), LexKey::encode_money(-42, "GBP").as_bytes());
+        assert_eq!((money.currency(), money.minor_units()), ("GBP", -42));
+    }
+
+    #[test]
+    #[should_panic(expected = "three-letter uppercase ISO 4217 code")]
+    fn should_reject_invalid_currency() {
+        let _ = LexKey::encode_money(1, "usd");
+    }
+}
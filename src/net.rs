@@ -0,0 +1,356 @@
+//! Encoders for IP addresses and socket addresses.
+//!
+//! `Ipv4Addr` and `Ipv6Addr` encode as their 4 or 16 network-order bytes, so keys sort
+//! numerically and every CIDR block is one contiguous key range. `IpAddr` adds a one-byte family
+//! tag first (`04` or `06`), so all IPv4 addresses sort before all IPv6 addresses and the two
+//! families never collide. `SocketAddr` is the tagged address followed by the 2-byte port;
+//! IPv6 flow info and scope ID are not stored.
+//!
+//! `KeyRange::cidr` turns a network into the range of tagged keys inside it, which also covers
+//! socket addresses and composites that start with an address in the network.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::ops::Bound;
+
+use bytes::BufMut;
+
+use crate::{DecodeError, Decoder, Encodable, Encoder, KeyRange, LexKey};
+
+/// Family tag written before an IPv4 address in the `IpAddr` and `SocketAddr` encodings.
+pub const TAG_IPV4: u8 = 0x04;
+/// Family tag written before an IPv6 address in the `IpAddr` and `SocketAddr` encodings.
+pub const TAG_IPV6: u8 = 0x06;
+
+impl LexKey {
+    /// Encode an IPv4 address as its 4 network-order bytes.
+    #[inline]
+    #[must_use]
+    pub fn encode_ipv4(addr: Ipv4Addr) -> Self {
+        Self::from_bytes(addr.octets().to_vec())
+    }
+
+    /// Encode an IPv6 address as its 16 network-order bytes.
+    #[inline]
+    #[must_use]
+    pub fn encode_ipv6(addr: Ipv6Addr) -> Self {
+        Self::from_bytes(addr.octets().to_vec())
+    }
+
+    /// Encode an IP address as a family tag followed by its network-order bytes.
+    ///
+    /// ```rust
+    /// use std::net::IpAddr;
+    /// use lexkey::LexKey;
+    ///
+    /// let ip = |s: &str| LexKey::encode_ip(s.parse::<IpAddr>().unwrap());
+    /// assert_eq!(ip("10.0.0.1").to_hex_string(), "040a000001");
+    /// assert!(ip("9.255.255.255") < ip("10.0.0.0"));
+    /// assert!(ip("255.255.255.255") < ip("::"));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn encode_ip(addr: IpAddr) -> Self {
+        let mut buf = Vec::with_capacity(addr.encoded_len());
+        Self::encode_ip_into(&mut buf, addr);
+        Self::from_bytes(buf)
+    }
+
+    /// Encode a socket address as its tagged IP address followed by the big-endian port.
+    #[inline]
+    #[must_use]
+    pub fn encode_socket_addr(addr: SocketAddr) -> Self {
+        let mut buf = Vec::with_capacity(addr.encoded_len());
+        Self::encode_socket_addr_into(&mut buf, addr);
+        Self::from_bytes(buf)
+    }
+
+    /// Append the tagged IP address encoding into `dst` and return bytes written (5 or 17).
+    #[inline]
+    pub fn encode_ip_into<B: BufMut + ?Sized>(dst: &mut B, addr: IpAddr) -> usize {
+        match addr {
+            IpAddr::V4(v4) => {
+                dst.put_u8(TAG_IPV4);
+                dst.put_slice(&v4.octets());
+                5
+            }
+            IpAddr::V6(v6) => {
+                dst.put_u8(TAG_IPV6);
+                dst.put_slice(&v6.octets());
+                17
+            }
+        }
+    }
+
+    /// Append the socket address encoding into `dst` and return bytes written (7 or 19).
+    #[inline]
+    pub fn encode_socket_addr_into<B: BufMut + ?Sized>(dst: &mut B, addr: SocketAddr) -> usize {
+        Self::encode_ip_into(dst, addr.ip()) + Self::encode_u16_into(dst, addr.port())
+    }
+}
+
+impl Encoder {
+    /// Append an IPv4 address's 4 network-order bytes; see `LexKey::encode_ipv4`.
+    #[inline]
+    pub fn encode_ipv4_into(&mut self, addr: Ipv4Addr) -> usize {
+        self.buf.extend_from_slice(&addr.octets());
+        4
+    }
+
+    /// Append an IPv6 address's 16 network-order bytes; see `LexKey::encode_ipv6`.
+    #[inline]
+    pub fn encode_ipv6_into(&mut self, addr: Ipv6Addr) -> usize {
+        self.buf.extend_from_slice(&addr.octets());
+        16
+    }
+
+    /// Append the tagged IP address encoding; see `LexKey::encode_ip`.
+    #[inline]
+    pub fn encode_ip_into(&mut self, addr: IpAddr) -> usize {
+        LexKey::encode_ip_into(&mut self.buf, addr)
+    }
+
+    /// Append the socket address encoding; see `LexKey::encode_socket_addr`.
+    #[inline]
+    pub fn encode_socket_addr_into(&mut self, addr: SocketAddr) -> usize {
+        LexKey::encode_socket_addr_into(&mut self.buf, addr)
+    }
+}
+
+impl Decoder<'_> {
+    /// Decode a tagged IP address written by `encode_ip`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DecodeError::Invalid` for an unknown family tag and
+    /// `DecodeError::UnexpectedEnd` if the address bytes are cut short.
+    pub fn decode_ip(&mut self) -> Result<IpAddr, DecodeError> {
+        let offset = self.position();
+        let tag = self.decode_u8()?;
+        let addr = match tag {
+            TAG_IPV4 => self.take::<4>().map(IpAddr::from),
+            TAG_IPV6 => self.take::<16>().map(IpAddr::from),
+            _ => Err(DecodeError::Invalid {
+                offset,
+                expected: "IP address family tag",
+            }),
+        };
+        if addr.is_err() {
+            self.rewind(offset);
+        }
+        addr
+    }
+
+    /// Decode a socket address written by `encode_socket_addr`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of `decode_ip`, or `DecodeError::UnexpectedEnd` if the port is
+    /// cut short.
+    pub fn decode_socket_addr(&mut self) -> Result<SocketAddr, DecodeError> {
+        let offset = self.position();
+        let ip = self.decode_ip()?;
+        self.decode_u16()
+            .map(|port| SocketAddr::new(ip, port))
+            .inspect_err(|_| self.rewind(offset))
+    }
+}
+
+impl KeyRange {
+    /// The range of `encode_ip` keys inside the network `addr/prefix_len`.
+    ///
+    /// Host bits of `addr` are ignored. The range also contains every key that starts with an
+    /// address in the network, such as `encode_socket_addr` keys or `(ip, ...)` composites.
+    ///
+    /// ```rust
+    /// use std::net::IpAddr;
+    /// use std::ops::RangeBounds;
+    /// use lexkey::{KeyRange, LexKey};
+    ///
+    /// let ip = |s: &str| LexKey::encode_ip(s.parse::<IpAddr>().unwrap());
+    /// let net = KeyRange::cidr("10.1.0.0".parse().unwrap(), 16);
+    /// assert!(net.contains(&ip("10.1.255.255")));
+    /// assert!(!net.contains(&ip("10.2.0.0")));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `prefix_len` exceeds the address width (32 for IPv4, 128 for IPv6).
+    #[must_use]
+    pub fn cidr(addr: IpAddr, prefix_len: u8) -> Self {
+        let (first, last) = match addr {
+            IpAddr::V4(v4) => {
+                assert!(prefix_len <= 32, "IPv4 prefix length must be at most 32");
+                let host = u32::MAX.checked_shr(u32::from(prefix_len)).unwrap_or(0);
+                let net = v4.to_bits() & !host;
+                (
+                    IpAddr::V4(Ipv4Addr::from_bits(net)),
+                    IpAddr::V4(Ipv4Addr::from_bits(net | host)),
+                )
+            }
+            IpAddr::V6(v6) => {
+                assert!(prefix_len <= 128, "IPv6 prefix length must be at most 128");
+                let host = u128::MAX.checked_shr(u32::from(prefix_len)).unwrap_or(0);
+                let net = v6.to_bits() & !host;
+                (
+                    IpAddr::V6(Ipv6Addr::from_bits(net)),
+                    IpAddr::V6(Ipv6Addr::from_bits(net | host)),
+                )
+            }
+        };
+        // The tag byte is below 0xff, so the successor always exists.
+        let end = LexKey::prefix_successor(LexKey::encode_ip(last).as_bytes())
+            .map_or(Bound::Unbounded, |upper| {
+                Bound::Excluded(LexKey::from(upper))
+            });
+        Self::new(Bound::Included(LexKey::encode_ip(first)), end)
+    }
+}
+
+impl Encodable for Ipv4Addr {
+    #[inline]
+    fn encoded_len(&self) -> usize {
+        4
+    }
+
+    #[inline]
+    fn encode_into(&self, dst: &mut Vec<u8>) -> usize {
+        dst.extend_from_slice(&self.octets());
+        4
+    }
+}
+
+impl Encodable for Ipv6Addr {
+    #[inline]
+    fn encoded_len(&self) -> usize {
+        16
+    }
+
+    #[inline]
+    fn encode_into(&self, dst: &mut Vec<u8>) -> usize {
+        dst.extend_from_slice(&self.octets());
+        16
+    }
+}
+
+impl Encodable for IpAddr {
+    #[inline]
+    fn encoded_len(&self) -> usize {
+        match self {
+            Self::V4(_) => 5,
+            Self::V6(_) => 17,
+        }
+    }
+
+    #[inline]
+    fn encode_into(&self, dst: &mut Vec<u8>) -> usize {
+        LexKey::encode_ip_into(dst, *self)
+    }
+}
+
+impl Encodable for SocketAddr {
+    #[inline]
+    fn encoded_len(&self) -> usize {
+        self.ip().encoded_len() + 2
+    }
+
+    #[inline]
+    fn encode_into(&self, dst: &mut Vec<u8>) -> usize {
+        LexKey::encode_socket_addr_into(dst, *self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::RangeBounds;
+
+    use super::*;
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn should_order_v4_before_v6_and_numerically_within_family() {
+        let addrs = [
+            "0.0.0.0",
+            "9.0.0.0",
+            "10.0.0.0",
+            "192.168.1.1",
+            "255.255.255.255",
+            "::",
+            "::ffff:0.0.0.1",
+            "2001:db8::1",
+            "ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff",
+        ];
+        let keys: Vec<_> = addrs.iter().map(|s| LexKey::encode_ip(ip(s))).collect();
+        assert!(keys.windows(2).all(|w| w[0] < w[1]));
+
+        let v4: Ipv4Addr = "1.2.3.4".parse().unwrap();
+        assert_eq!(LexKey::encode_ipv4(v4).to_hex_string(), "01020304");
+        assert_eq!(crate::encode_composite!(v4), LexKey::encode_ipv4(v4));
+        let v6: Ipv6Addr = "::1".parse().unwrap();
+        assert_eq!(LexKey::encode_ipv6(v6).as_bytes().len(), 16);
+    }
+
+    #[test]
+    fn should_round_trip_socket_addrs_and_order_by_ip_then_port() {
+        let socks: Vec<SocketAddr> = ["10.0.0.1:80", "10.0.0.1:443", "10.0.0.2:22", "[::1]:1"]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        let keys: Vec<_> = socks
+            .iter()
+            .map(|&s| LexKey::encode_socket_addr(s))
+            .collect();
+        assert!(keys.windows(2).all(|w| w[0] < w[1]));
+
+        let mut enc = Encoder::with_capacity(32);
+        enc.encode_socket_addr_into(socks[3]);
+        enc.encode_ip_into(socks[0].ip());
+        let buf = enc.as_slice().to_vec();
+        let mut dec = Decoder::new(&buf);
+        assert_eq!(dec.decode_socket_addr(), Ok(socks[3]));
+        assert_eq!(dec.decode_ip(), Ok(socks[0].ip()));
+        assert!(dec.is_empty());
+
+        let mut bad = Decoder::new(&[0x05, 1, 2, 3, 4]);
+        assert_eq!(
+            bad.decode_ip(),
+            Err(DecodeError::Invalid {
+                offset: 0,
+                expected: "IP address family tag"
+            })
+        );
+        assert!(Decoder::new(&[TAG_IPV4, 1, 2, 3, 4, 0])
+            .decode_socket_addr()
+            .is_err());
+    }
+
+    #[test]
+    fn should_cover_exactly_the_cidr_block() {
+        let net = KeyRange::cidr(ip("10.1.2.3"), 24);
+        let inside = ["10.1.2.0", "10.1.2.255"];
+        let outside = ["10.1.1.255", "10.1.3.0", "::a01:200"];
+        for s in inside {
+            assert!(net.contains(&LexKey::encode_ip(ip(s))), "{s}");
+        }
+        for s in outside {
+            assert!(!net.contains(&LexKey::encode_ip(ip(s))), "{s}");
+        }
+        let sock: SocketAddr = "10.1.2.255:65535".parse().unwrap();
+        assert!(net.contains(&LexKey::encode_socket_addr(sock)));
+
+        let all_v6 = KeyRange::cidr(ip("2001:db8::"), 0);
+        assert!(all_v6.contains(&LexKey::encode_ip(ip("ffff::"))));
+        assert!(!all_v6.contains(&LexKey::encode_ip(ip("255.255.255.255"))));
+        let host = KeyRange::cidr(ip("10.0.0.1"), 32);
+        assert!(host.contains(&LexKey::encode_ip(ip("10.0.0.1"))));
+        assert!(!host.contains(&LexKey::encode_ip(ip("10.0.0.2"))));
+    }
+
+    #[test]
+    #[should_panic(expected = "IPv4 prefix length must be at most 32")]
+    fn should_reject_oversized_prefix() {
+        let _ = KeyRange::cidr(ip("10.0.0.0"), 33);
+    }
+}
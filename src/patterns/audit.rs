@@ -0,0 +1,121 @@
+//! Audit and event log keys: `actor || 0x00 || ts_desc || 0x00 || event_id`.
+//!
+//! Events of one actor are stored newest first, so "latest N events" is a forward scan of
+//! `latest(actor)` that stops after N entries. The event id keeps keys unique when an actor
+//! records several events in the same nanosecond.
+//!
+//! ```rust
+//! use lexkey::patterns::audit;
+//! use lexkey::KeySet;
+//!
+//! let events: KeySet = [
+//!     audit::key(b"alice", 100, b"e1"),
+//!     audit::key(b"alice", 300, b"e3"),
+//!     audit::key(b"alice", 200, b"e2"),
+//!     audit::key(b"bob", 250, b"e9"),
+//! ]
+//! .into_iter()
+//! .collect();
+//!
+//! let latest: Vec<_> = events.iter_range(&audit::latest(b"alice")).take(2).collect();
+//! assert_eq!(latest, [&audit::key(b"alice", 300, b"e3"), &audit::key(b"alice", 200, b"e2")]);
+//! ```
+
+use crate::{KeyRange, LexKey};
+
+use super::ts_desc;
+
+/// Build the key for one event by `actor` at `ts_nanos`.
+///
+/// The timestamp is encoded descending so newer events sort first.
+#[inline]
+#[must_use]
+pub fn key(actor: &[u8], ts_nanos: i64, event_id: &[u8]) -> LexKey {
+    LexKey::encode_composite(&[actor, &ts_desc(ts_nanos), event_id])
+}
+
+/// All events of `actor`, newest first.
+///
+/// Take the first N entries of a forward scan to read the latest N events.
+#[inline]
+#[must_use]
+pub fn latest(actor: &[u8]) -> KeyRange {
+    KeyRange::children(actor)
+}
+
+/// Events of `actor` with `from_nanos <= ts < to_nanos`, newest first.
+///
+/// The range is empty (not inverted) when `from_nanos >= to_nanos`, so it is safe to pass to
+/// `BTreeMap::range`.
+#[must_use]
+pub fn window(actor: &[u8], from_nanos: i64, to_nanos: i64) -> KeyRange {
+    // Newer timestamps sort first, so the exclusive upper time is the lower key bound. Closing
+    // each bound with END_MARKER steps over every event id at that timestamp.
+    let lower = LexKey::encode_last(&[actor, &ts_desc(to_nanos)]);
+    if from_nanos >= to_nanos {
+        return KeyRange::half_open(lower.clone(), lower);
+    }
+    let upper = LexKey::encode_last(&[actor, &ts_desc(from_nanos)]);
+    KeyRange::half_open(lower, upper)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::KeySet;
+
+    fn events() -> KeySet {
+        [
+            key(b"alice", -5, b"a"),
+            key(b"alice", 10, b"b"),
+            key(b"alice", 10, b"c"),
+            key(b"alice", 20, b"d"),
+            key(b"alice", 30, b"e"),
+            key(b"bob", 15, b"x"),
+            key(b"alice2", 25, b"y"),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn should_order_events_newest_first_with_id_tiebreak() {
+        let set = events();
+        let hits: Vec<_> = set.iter_range(&latest(b"alice")).cloned().collect();
+        assert_eq!(
+            hits,
+            vec![
+                key(b"alice", 30, b"e"),
+                key(b"alice", 20, b"d"),
+                key(b"alice", 10, b"b"),
+                key(b"alice", 10, b"c"),
+                key(b"alice", -5, b"a"),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_select_half_open_time_window() {
+        let set = events();
+        let hits: Vec<_> = set.iter_range(&window(b"alice", 10, 30)).cloned().collect();
+        assert_eq!(
+            hits,
+            vec![
+                key(b"alice", 20, b"d"),
+                key(b"alice", 10, b"b"),
+                key(b"alice", 10, b"c"),
+            ]
+        );
+        assert!(window(b"alice", 30, 10).is_empty());
+    }
+
+    #[test]
+    fn should_return_empty_not_inverted_windows() {
+        let set: std::collections::BTreeSet<LexKey> = events().iter().cloned().collect();
+        for (from, to) in [(30, 10), (20, 20)] {
+            let range = window(b"alice", from, to);
+            assert!(range.is_empty());
+            assert_eq!(set.range(range).count(), 0);
+        }
+    }
+}
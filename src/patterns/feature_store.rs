@@ -0,0 +1,99 @@
+//! Feature store keys: `entity_id || 0x00 || feature_name || 0x00 || ts_desc`.
+//!
+//! Values of one feature are stored newest first, so the first entry of `latest` is the current
+//! value and the first entry of `as_of` is the value that was current at a given time.
+//!
+//! ```rust
+//! use lexkey::patterns::feature_store as fs;
+//! use lexkey::LexMap;
+//!
+//! let mut values = LexMap::new();
+//! values.insert(fs::key(b"user:1", b"clicks_7d", 100), 4);
+//! values.insert(fs::key(b"user:1", b"clicks_7d", 200), 9);
+//!
+//! let latest = values.scan_range(&fs::latest(b"user:1", b"clicks_7d")).next();
+//! assert_eq!(latest.map(|(_, v)| *v), Some(9));
+//!
+//! let at_150 = values.scan_range(&fs::as_of(b"user:1", b"clicks_7d", 150)).next();
+//! assert_eq!(at_150.map(|(_, v)| *v), Some(4));
+//! ```
+
+use std::ops::Bound;
+
+use crate::{KeyRange, LexKey};
+
+use super::ts_desc;
+
+/// Build the key for one value of `feature_name` on `entity_id` observed at `ts_nanos`.
+///
+/// The timestamp is encoded descending so newer values sort first.
+#[inline]
+#[must_use]
+pub fn key(entity_id: &[u8], feature_name: &[u8], ts_nanos: i64) -> LexKey {
+    LexKey::encode_composite(&[entity_id, feature_name, &ts_desc(ts_nanos)])
+}
+
+/// All values of one feature, newest first.
+///
+/// Features whose names extend `feature_name` (`clicks_7d` for `clicks`) are not included.
+#[inline]
+#[must_use]
+pub fn latest(entity_id: &[u8], feature_name: &[u8]) -> KeyRange {
+    KeyRange::children(LexKey::encode_composite(&[entity_id, feature_name]).as_bytes())
+}
+
+/// Values of one feature observed at or before `ts_nanos`, newest first.
+///
+/// The first entry is the point-in-time value as of `ts_nanos`.
+#[must_use]
+pub fn as_of(entity_id: &[u8], feature_name: &[u8], ts_nanos: i64) -> KeyRange {
+    KeyRange::new(
+        Bound::Included(key(entity_id, feature_name, ts_nanos)),
+        latest(entity_id, feature_name).end().cloned(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::KeySet;
+
+    fn values() -> KeySet {
+        [
+            key(b"e1", b"f", 10),
+            key(b"e1", b"f", 20),
+            key(b"e1", b"f", 30),
+            key(b"e1", b"g", 25),
+            key(b"e2", b"f", 40),
+            key(b"e1", b"f_7d", 5),
+            key(b"e1", b"fx", 15),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn should_list_feature_values_newest_first() {
+        let set = values();
+        let hits: Vec<_> = set.iter_range(&latest(b"e1", b"f")).cloned().collect();
+        assert_eq!(
+            hits,
+            vec![
+                key(b"e1", b"f", 30),
+                key(b"e1", b"f", 20),
+                key(b"e1", b"f", 10)
+            ]
+        );
+    }
+
+    #[test]
+    fn should_resolve_point_in_time_values() {
+        let set = values();
+        let first = |t| set.iter_range(&as_of(b"e1", b"f", t)).next().cloned();
+
+        assert_eq!(first(30), Some(key(b"e1", b"f", 30)));
+        assert_eq!(first(29), Some(key(b"e1", b"f", 20)));
+        // `f_7d` and `fx` sort after every `f` key but must not leak in.
+        assert_eq!(first(9), None);
+    }
+}
@@ -0,0 +1,20 @@
+//! Ready-made key layouts for common ordered key-value access patterns.
+//!
+//! Each submodule packages one layout as plain functions that return `LexKey`s and `KeyRange`s.
+//! Timestamps are UNIX nanoseconds, the same unit as `LexKey::encode_time_unix_nanos`.
+
+pub mod audit;
+pub mod feature_store;
+pub mod queue;
+pub mod ratelimit;
+pub mod session;
+
+const SIGN_BIT: u64 = 0x8000_0000_0000_0000;
+
+/// Encode `nanos` in 8 bytes so that newer timestamps sort first.
+///
+/// This is the bitwise-not of the ascending `encode_i64` form.
+#[inline]
+pub(crate) fn ts_desc(nanos: i64) -> [u8; 8] {
+    (!(nanos.cast_unsigned() ^ SIGN_BIT)).to_be_bytes()
+}
@@ -0,0 +1,128 @@
+//! Priority job queue keys: `priority || 0x00 || enqueue_ts || 0x00 || job_id`.
+//!
+//! A forward scan pops jobs by priority (lowest number first), then oldest enqueue time, with the
+//! job id as a tiebreak. `key_desc` flips the priority so the highest number pops first instead;
+//! use one layout per queue, since the two do not interleave.
+//!
+//! ```rust
+//! use lexkey::patterns::queue;
+//! use lexkey::KeySet;
+//!
+//! let jobs: KeySet = [
+//!     queue::key(2, 100, b"j1"),
+//!     queue::key(1, 300, b"j2"),
+//!     queue::key(1, 200, b"j3"),
+//! ]
+//! .into_iter()
+//! .collect();
+//!
+//! let next = jobs.iter_range(&queue::claim_range(u32::MAX)).next();
+//! assert_eq!(next, Some(&queue::key(1, 200, b"j3")));
+//! ```
+
+use std::ops::Bound;
+
+use crate::{KeyRange, LexKey};
+
+/// Build the key for a job where lower `priority` values pop first.
+#[inline]
+#[must_use]
+pub fn key(priority: u32, enqueue_ts_nanos: i64, job_id: &[u8]) -> LexKey {
+    LexKey::encode_composite(&[
+        &priority.to_be_bytes(),
+        LexKey::encode_time_unix_nanos(enqueue_ts_nanos).as_bytes(),
+        job_id,
+    ])
+}
+
+/// Build the key for a job where higher `priority` values pop first.
+#[inline]
+#[must_use]
+pub fn key_desc(priority: u32, enqueue_ts_nanos: i64, job_id: &[u8]) -> LexKey {
+    key(!priority, enqueue_ts_nanos, job_id)
+}
+
+/// Jobs with a priority of at most `max_priority`, in pop order (for `key`).
+#[inline]
+#[must_use]
+pub fn claim_range(max_priority: u32) -> KeyRange {
+    let upper = LexKey::encode_last(&[&max_priority.to_be_bytes()]);
+    KeyRange::new(Bound::Unbounded, Bound::Excluded(upper))
+}
+
+/// Jobs with a priority of at least `min_priority`, in pop order (for `key_desc`).
+#[inline]
+#[must_use]
+pub fn claim_range_desc(min_priority: u32) -> KeyRange {
+    claim_range(!min_priority)
+}
+
+/// Jobs of exactly `priority` enqueued at or before `now_nanos`, oldest first (for `key`).
+///
+/// Pass `!priority` to use it with `key_desc` layouts.
+#[must_use]
+pub fn ready_range(priority: u32, now_nanos: i64) -> KeyRange {
+    let prio = priority.to_be_bytes();
+    let lower = LexKey::encode_first(&[&prio]);
+    let upper = LexKey::encode_last(&[&prio, LexKey::encode_time_unix_nanos(now_nanos).as_bytes()]);
+    KeyRange::half_open(lower, upper)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::KeySet;
+
+    #[test]
+    fn should_pop_by_priority_then_time_then_id() {
+        let jobs: KeySet = [
+            key(1, 20, b"b"),
+            key(0, 50, b"z"),
+            key(1, 10, b"c"),
+            key(1, 20, b"a"),
+            key(7, 0, b"q"),
+        ]
+        .into_iter()
+        .collect();
+
+        let order: Vec<_> = jobs.iter_range(&claim_range(1)).cloned().collect();
+        assert_eq!(
+            order,
+            vec![
+                key(0, 50, b"z"),
+                key(1, 10, b"c"),
+                key(1, 20, b"a"),
+                key(1, 20, b"b"),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_pop_highest_priority_first_with_desc_layout() {
+        let jobs: KeySet = [
+            key_desc(1, 0, b"low"),
+            key_desc(9, 5, b"high"),
+            key_desc(5, 1, b"mid"),
+        ]
+        .into_iter()
+        .collect();
+
+        let order: Vec<_> = jobs.iter_range(&claim_range_desc(5)).cloned().collect();
+        assert_eq!(order, vec![key_desc(9, 5, b"high"), key_desc(5, 1, b"mid")]);
+    }
+
+    #[test]
+    fn should_limit_ready_range_to_enqueued_jobs() {
+        let jobs: KeySet = [
+            key(3, 10, b"a"),
+            key(3, 20, b"b"),
+            key(3, 30, b"c"),
+            key(4, 0, b"d"),
+        ]
+        .into_iter()
+        .collect();
+
+        let ready: Vec<_> = jobs.iter_range(&ready_range(3, 20)).cloned().collect();
+        assert_eq!(ready, vec![key(3, 10, b"a"), key(3, 20, b"b")]);
+    }
+}
@@ -0,0 +1,114 @@
+//! Rate limiter window keys: `subject || 0x00 || window_start`.
+//!
+//! Windows of one subject sort oldest first. A sliding-window limiter reads the counters at
+//! `window_keys(subject, now, window)` and weights the previous window by its remaining overlap.
+//!
+//! ```rust
+//! use lexkey::patterns::ratelimit;
+//!
+//! let (current, previous) = ratelimit::window_keys(b"api:alice", 1_250, 1_000);
+//! assert_eq!(current, ratelimit::key(b"api:alice", 1_000));
+//! assert_eq!(previous, ratelimit::key(b"api:alice", 0));
+//! ```
+
+use std::ops::Bound;
+
+use crate::{KeyRange, LexKey};
+
+/// Build the counter key for `subject` in the window starting at `window_start_nanos`.
+#[inline]
+#[must_use]
+pub fn key(subject: &[u8], window_start_nanos: i64) -> LexKey {
+    LexKey::encode_composite(&[
+        subject,
+        LexKey::encode_time_unix_nanos(window_start_nanos).as_bytes(),
+    ])
+}
+
+/// Return the start of the fixed window of size `window_nanos` that contains `now_nanos`.
+///
+/// Windows are aligned to the UNIX epoch, so timestamps before it round down as well. The
+/// partial window at the very start of the `i64` range saturates to `i64::MIN`.
+///
+/// # Panics
+///
+/// Panics if `window_nanos` is not positive.
+#[inline]
+#[must_use]
+pub fn window_start(now_nanos: i64, window_nanos: i64) -> i64 {
+    assert!(window_nanos > 0, "rate limit window must be positive");
+    now_nanos.saturating_sub(now_nanos.rem_euclid(window_nanos))
+}
+
+/// Return the `(current, previous)` window keys for `subject` at `now_nanos`.
+///
+/// # Panics
+///
+/// Panics if `window_nanos` is not positive.
+#[must_use]
+pub fn window_keys(subject: &[u8], now_nanos: i64, window_nanos: i64) -> (LexKey, LexKey) {
+    let current = window_start(now_nanos, window_nanos);
+    (
+        key(subject, current),
+        key(subject, current.saturating_sub(window_nanos)),
+    )
+}
+
+/// Windows of `subject` that start before `before_nanos`, for deleting stale counters.
+#[inline]
+#[must_use]
+pub fn expired_range(subject: &[u8], before_nanos: i64) -> KeyRange {
+    KeyRange::new(
+        Bound::Included(LexKey::encode_first(&[subject])),
+        Bound::Excluded(key(subject, before_nanos)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::KeySet;
+
+    #[test]
+    fn should_align_windows_to_epoch() {
+        assert_eq!(window_start(1_999, 1_000), 1_000);
+        assert_eq!(window_start(2_000, 1_000), 2_000);
+        assert_eq!(window_start(-1, 1_000), -1_000);
+    }
+
+    #[test]
+    fn should_saturate_windows_at_the_start_of_the_range() {
+        assert_eq!(window_start(i64::MIN, 1_000), i64::MIN);
+        assert_eq!(window_start(i64::MIN + 1, 1_000), i64::MIN);
+        assert_eq!(window_start(i64::MIN, i64::MAX), i64::MIN);
+        assert_eq!(window_start(i64::MAX, i64::MAX), i64::MAX);
+
+        let (current, previous) = window_keys(b"s", i64::MIN, 1_000);
+        assert_eq!(current, key(b"s", i64::MIN));
+        assert_eq!(previous, current);
+    }
+
+    #[test]
+    fn should_return_current_and_previous_windows() {
+        let (current, previous) = window_keys(b"s", 61_000, 60_000);
+        assert_eq!(current, key(b"s", 60_000));
+        assert_eq!(previous, key(b"s", 0));
+        assert!(previous < current);
+    }
+
+    #[test]
+    fn should_select_expired_windows_of_one_subject() {
+        let set: KeySet = [key(b"a", 0), key(b"a", 10), key(b"a", 20), key(b"b", 0)]
+            .into_iter()
+            .collect();
+
+        let stale: Vec<_> = set.iter_range(&expired_range(b"a", 20)).cloned().collect();
+        assert_eq!(stale, vec![key(b"a", 0), key(b"a", 10)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "rate limit window must be positive")]
+    fn should_reject_zero_window() {
+        let _ = window_start(5, 0);
+    }
+}
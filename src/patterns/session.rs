@@ -0,0 +1,90 @@
+//! Session store keys ordered by expiry: `expiry_bucket || 0x00 || session_id`.
+//!
+//! Putting the expiry first means every expired session sits in one contiguous range at the start
+//! of the keyspace, so a sweeper can range-delete `expired_range(now)` instead of scanning all
+//! sessions. Look sessions up by id through a secondary index, or recompute the bucket from the
+//! stored expiry.
+//!
+//! ```rust
+//! use std::ops::RangeBounds;
+//! use lexkey::patterns::session;
+//!
+//! let bucket = session::expiry_bucket(1_250, 1_000);
+//! assert_eq!(bucket, 2_000);
+//!
+//! let k = session::key(bucket, b"sess-1");
+//! assert!(!session::expired_range(1_999).contains(&k));
+//! assert!(session::expired_range(2_000).contains(&k));
+//! ```
+
+use std::ops::Bound;
+
+use crate::{KeyRange, LexKey};
+
+/// Build the key for `session_id` in `expiry_bucket`.
+#[inline]
+#[must_use]
+pub fn key(expiry_bucket: i64, session_id: &[u8]) -> LexKey {
+    LexKey::encode_composite(&[
+        LexKey::encode_time_unix_nanos(expiry_bucket).as_bytes(),
+        session_id,
+    ])
+}
+
+/// Round `expires_at_nanos` up to the next multiple of `bucket_nanos`.
+///
+/// Rounding up keeps a session out of `expired_range` until its bucket has fully passed, so a
+/// sweep never removes a session before its own expiry.
+///
+/// # Panics
+///
+/// Panics if `bucket_nanos` is not positive.
+#[inline]
+#[must_use]
+pub fn expiry_bucket(expires_at_nanos: i64, bucket_nanos: i64) -> i64 {
+    assert!(bucket_nanos > 0, "session expiry bucket must be positive");
+    match expires_at_nanos.rem_euclid(bucket_nanos) {
+        0 => expires_at_nanos,
+        r => expires_at_nanos.saturating_add(bucket_nanos - r),
+    }
+}
+
+/// Sessions whose expiry bucket is at or before `now_nanos`.
+#[inline]
+#[must_use]
+pub fn expired_range(now_nanos: i64) -> KeyRange {
+    let upper = LexKey::encode_last(&[LexKey::encode_time_unix_nanos(now_nanos).as_bytes()]);
+    KeyRange::new(Bound::Unbounded, Bound::Excluded(upper))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::KeySet;
+
+    #[test]
+    fn should_round_expiry_up_to_bucket() {
+        assert_eq!(expiry_bucket(1_000, 1_000), 1_000);
+        assert_eq!(expiry_bucket(1_001, 1_000), 2_000);
+        assert_eq!(expiry_bucket(-1, 1_000), 0);
+    }
+
+    #[test]
+    fn should_select_only_expired_buckets() {
+        let set: KeySet = [
+            key(100, b"a"),
+            key(200, b"b"),
+            key(200, b"c"),
+            key(300, b"d"),
+        ]
+        .into_iter()
+        .collect();
+
+        let expired: Vec<_> = set.iter_range(&expired_range(200)).cloned().collect();
+        assert_eq!(
+            expired,
+            vec![key(100, b"a"), key(200, b"b"), key(200, b"c")]
+        );
+        assert_eq!(set.iter_range(&expired_range(99)).count(), 0);
+    }
+}
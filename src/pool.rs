@@ -0,0 +1,233 @@
+//! Reusable encoders for multi-threaded services.
+//!
+//! `EncoderPool` hands out pre-sized encoders that return to the pool when dropped, so threads
+//! build keys without allocating a buffer per key. For code that only needs an encoder for the
+//! duration of a call, `with_encoder` borrows one kept per thread.
+//!
+//! ```rust
+//! use lexkey::EncoderPool;
+//!
+//! let pool = EncoderPool::new(64);
+//! std::thread::scope(|s| {
+//!     for tenant in ["acme", "globex"] {
+//!         let pool = &pool;
+//!         s.spawn(move || {
+//!             let mut enc = pool.get();
+//!             enc.encode_string_into(tenant);
+//!             enc.push_separator();
+//!             enc.encode_u64_into(1);
+//!             enc.split_key()
+//!         });
+//!     }
+//! });
+//! assert!(pool.idle() >= 1);
+//! ```
+
+use std::cell::RefCell;
+use std::fmt;
+use std::mem::ManuallyDrop;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Mutex, PoisonError};
+
+use crate::Encoder;
+
+/// Encoders kept by a pool unless `EncoderPool::with_max_idle` says otherwise.
+const DEFAULT_MAX_IDLE: usize = 64;
+
+/// Capacity of the encoder `with_encoder` keeps per thread.
+const THREAD_ENCODER_CAPACITY: usize = 256;
+
+/// A thread-safe pool of reusable encoders.
+pub struct EncoderPool {
+    idle: Mutex<Vec<Encoder>>,
+    capacity: usize,
+    max_idle: usize,
+}
+
+impl EncoderPool {
+    /// Create an empty pool whose new encoders start with `capacity` bytes.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            idle: Mutex::new(Vec::new()),
+            capacity,
+            max_idle: DEFAULT_MAX_IDLE,
+        }
+    }
+
+    /// Keep at most `max_idle` returned encoders; extra ones are dropped instead.
+    #[must_use]
+    pub fn with_max_idle(mut self, max_idle: usize) -> Self {
+        self.max_idle = max_idle;
+        self
+    }
+
+    /// Check out a cleared encoder, allocating one only when none is idle.
+    ///
+    /// The encoder is cleared, including any prefix pinned with `Encoder::with_prefix`, and
+    /// returned to the pool when the guard drops.
+    pub fn get(&self) -> PooledEncoder<'_> {
+        let enc = self
+            .lock()
+            .pop()
+            .unwrap_or_else(|| Encoder::with_capacity(self.capacity));
+        PooledEncoder { enc, pool: self }
+    }
+
+    /// Number of encoders waiting to be checked out.
+    #[must_use]
+    pub fn idle(&self) -> usize {
+        self.lock().len()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Encoder>> {
+        // The pool holds only cleared encoders, so a panic elsewhere cannot leave it invalid.
+        self.idle.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn put(&self, mut enc: Encoder) {
+        // A prefix pinned while checked out must not leak into the next borrower's keys.
+        enc.reset();
+        let mut idle = self.lock();
+        if idle.len() < self.max_idle {
+            idle.push(enc);
+        }
+    }
+}
+
+impl fmt::Debug for EncoderPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EncoderPool")
+            .field("idle", &self.idle())
+            .field("capacity", &self.capacity)
+            .field("max_idle", &self.max_idle)
+            .finish()
+    }
+}
+
+/// An encoder checked out of an `EncoderPool`; dereferences to `Encoder`.
+pub struct PooledEncoder<'a> {
+    enc: Encoder,
+    pool: &'a EncoderPool,
+}
+
+impl PooledEncoder<'_> {
+    /// Keep the encoder instead of returning it to the pool.
+    #[must_use]
+    pub fn detach(self) -> Encoder {
+        let mut this = ManuallyDrop::new(self);
+        std::mem::replace(&mut this.enc, Encoder::with_capacity(0))
+    }
+}
+
+impl Deref for PooledEncoder<'_> {
+    type Target = Encoder;
+
+    fn deref(&self) -> &Encoder {
+        &self.enc
+    }
+}
+
+impl DerefMut for PooledEncoder<'_> {
+    fn deref_mut(&mut self) -> &mut Encoder {
+        &mut self.enc
+    }
+}
+
+impl Drop for PooledEncoder<'_> {
+    fn drop(&mut self) {
+        // An empty encoder does not allocate.
+        let enc = std::mem::replace(&mut self.enc, Encoder::with_capacity(0));
+        self.pool.put(enc);
+    }
+}
+
+thread_local! {
+    static THREAD_ENCODER: RefCell<Encoder> =
+        RefCell::new(Encoder::with_capacity(THREAD_ENCODER_CAPACITY));
+}
+
+/// Run `f` with this thread's cleared encoder, which has no pinned prefix.
+///
+/// Nested calls on the same thread get a fresh encoder rather than panicking.
+///
+/// ```rust
+/// use lexkey::{encode_composite, pool::with_encoder};
+///
+/// let key = with_encoder(|enc| {
+///     enc.encode_string_into("acme");
+///     enc.push_separator();
+///     enc.encode_i64_into(-1);
+///     enc.split_key()
+/// });
+/// assert_eq!(key, encode_composite!("acme", -1i64).as_bytes());
+/// ```
+pub fn with_encoder<R>(f: impl FnOnce(&mut Encoder) -> R) -> R {
+    THREAD_ENCODER.with(|cell| {
+        if let Ok(mut enc) = cell.try_borrow_mut() {
+            enc.reset();
+            f(&mut enc)
+        } else {
+            f(&mut Encoder::with_capacity(THREAD_ENCODER_CAPACITY))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_reuse_cleared_encoders_up_to_max_idle() {
+        let pool = EncoderPool::new(32).with_max_idle(1);
+        let mut a = pool.get();
+        a.encode_u8_into(1);
+        a.finish_key();
+        let ptr = a.as_slice().as_ptr();
+        let b = pool.get();
+        drop(a);
+        drop(b);
+        assert_eq!(pool.idle(), 1);
+
+        let c = pool.get();
+        assert!(c.is_empty());
+        assert_eq!(c.finished_keys(), 0);
+        assert_eq!(c.as_slice().as_ptr(), ptr);
+        let kept = c.detach();
+        assert!(kept.is_empty());
+        assert_eq!(pool.idle(), 0);
+    }
+
+    #[test]
+    fn should_drop_pinned_prefixes_when_encoders_return() {
+        let pool = EncoderPool::new(32).with_max_idle(1);
+        let mut a = pool.get();
+        *a = Encoder::with_prefix(b"acme\x00", 32);
+        a.encode_u8_into(1);
+        drop(a);
+
+        let mut b = pool.get();
+        assert!(b.is_empty());
+        assert!(b.prefix().is_empty());
+        b.encode_u8_into(2);
+        assert_eq!(b.split_key(), &[2][..]);
+
+        with_encoder(|enc| *enc = Encoder::with_prefix(b"t\x00", 8));
+        assert!(with_encoder(|enc| enc.is_empty() && enc.prefix().is_empty()));
+    }
+
+    #[test]
+    fn should_clear_thread_encoder_and_allow_nesting() {
+        let outer = with_encoder(|enc| {
+            enc.encode_u8_into(1);
+            let inner = with_encoder(|inner| {
+                inner.encode_u8_into(2);
+                inner.as_slice().to_vec()
+            });
+            assert_eq!(inner, [2]);
+            enc.as_slice().to_vec()
+        });
+        assert_eq!(outer, [1]);
+        assert!(with_encoder(|enc| enc.is_empty()));
+    }
+}
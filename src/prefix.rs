@@ -0,0 +1,117 @@
+//! Prefix extraction for prefix bloom filters and prefix-partitioned stores.
+//!
+//! `first_n_parts` cuts a composite key after its first `n` parts, the prefix a store should
+//! hash when every query scans within such a prefix. `transform` and `in_domain` are the same
+//! rule as plain functions with `n` fixed at compile time, matching the function-pointer
+//! signatures the `rocksdb` crate's `SliceTransform::create` expects:
+//!
+//! ```rust,ignore
+//! use rocksdb::{Options, SliceTransform};
+//!
+//! let mut opts = Options::default();
+//! opts.set_prefix_extractor(SliceTransform::create(
+//!     lexkey::prefix::name::<2>(),
+//!     lexkey::prefix::transform::<2>,
+//!     Some(lexkey::prefix::in_domain::<2>),
+//! ));
+//! ```
+//!
+//! Like `LexKey::parts`, parts are split at every `0x00` byte, so the leading `n` parts must not
+//! contain `0x00` themselves (strings and raw bytes, not fixed-width numbers or UUIDs).
+
+use crate::LexKey;
+
+/// Return the first `n` parts of `key`, without the separator after them.
+///
+/// Keys with fewer than `n` parts are returned whole, and `n == 0` returns an empty slice.
+///
+/// ```rust
+/// use lexkey::prefix::first_n_parts;
+///
+/// assert_eq!(first_n_parts(b"acme\x00orders\x0042", 2), b"acme\x00orders");
+/// assert_eq!(first_n_parts(b"acme", 2), b"acme");
+/// ```
+#[must_use]
+pub fn first_n_parts(key: &[u8], n: usize) -> &[u8] {
+    if n == 0 {
+        return &key[..0];
+    }
+    key.iter()
+        .enumerate()
+        .filter(|&(_, &b)| b == LexKey::SEPARATOR)
+        .nth(n - 1)
+        .map_or(key, |(i, _)| &key[..i])
+}
+
+/// Return true if `key` has at least `n` parts, so `first_n_parts` is a proper prefix rule for it.
+#[must_use]
+pub fn has_n_parts(key: &[u8], n: usize) -> bool {
+    match n {
+        0 => true,
+        _ if key.is_empty() => false,
+        _ => key.split(|&b| b == LexKey::SEPARATOR).count() >= n,
+    }
+}
+
+/// `first_n_parts(key, N)` as a plain function, for `SliceTransform`-style transform callbacks.
+#[inline]
+#[must_use]
+pub fn transform<const N: usize>(key: &[u8]) -> &[u8] {
+    first_n_parts(key, N)
+}
+
+/// `has_n_parts(key, N)` as a plain function, for `SliceTransform`-style domain callbacks.
+///
+/// Keys outside the domain (fewer than `N` parts) are not added to the prefix bloom filter.
+#[inline]
+#[must_use]
+pub fn in_domain<const N: usize>(key: &[u8]) -> bool {
+    has_n_parts(key, N)
+}
+
+/// Stable extractor name for `N` parts, such as `lexkey.parts.2`.
+///
+/// The store records the extractor name in table properties and skips prefix blooms built by a
+/// differently named extractor, so changing `N` safely invalidates old filters.
+#[must_use]
+pub fn name<const N: usize>() -> String {
+    format!("lexkey.parts.{N}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_cut_after_whole_parts() {
+        let key = LexKey::encode_composite(&[b"acme", b"orders", b"42"]);
+        let key = key.as_bytes();
+        assert_eq!(first_n_parts(key, 0), b"");
+        assert_eq!(first_n_parts(key, 1), b"acme");
+        assert_eq!(first_n_parts(key, 2), b"acme\x00orders");
+        assert_eq!(first_n_parts(key, 3), key);
+        assert_eq!(first_n_parts(key, 9), key);
+        assert_eq!(transform::<2>(key), first_n_parts(key, 2));
+
+        assert!(in_domain::<3>(key));
+        assert!(!in_domain::<4>(key));
+        assert!(!in_domain::<1>(b""));
+        assert!(has_n_parts(b"", 0));
+        assert_eq!(name::<2>(), "lexkey.parts.2");
+    }
+
+    #[test]
+    fn should_give_every_key_in_a_partition_the_same_prefix() {
+        let partition = LexKey::encode_composite(&[b"acme", b"orders"]);
+        let keys = [
+            LexKey::encode_composite(&[b"acme", b"orders", b""]),
+            LexKey::encode_composite(&[b"acme", b"orders", b"1"]),
+            LexKey::encode_composite(&[b"acme", b"orders", b"2", b"x"]),
+        ];
+        for key in &keys {
+            assert_eq!(transform::<2>(key.as_bytes()), partition.as_bytes());
+        }
+        let other = LexKey::encode_composite(&[b"acme", b"ordersx", b"1"]);
+        assert_ne!(transform::<2>(other.as_bytes()), partition.as_bytes());
+    }
+}
@@ -0,0 +1,937 @@
+//! Named presets over the encoding options, so a table picks one coherent layout instead of
+//! choosing separators, string escaping, type tags, float and null handling and text
+//! representation separately.
+//!
+//! | Profile      | Parts                         | Strings       | NaN      | Null         | Key bytes      |
+//! |--------------|-------------------------------|---------------|----------|--------------|----------------|
+//! | `Compact`    | untagged, `SEPARATOR`-joined  | raw           | rejected | marker `00`  | binary         |
+//! | `Debuggable` | tagged (`Value`)              | escaped       | rejected | `TAG_NULL`   | binary         |
+//! | `Utf8Safe`   | untagged, `SEPARATOR`-joined  | raw           | rejected | marker `00`  | base32hex text |
+//! | `FdbCompat`  | `FoundationDB` tuple elements | tuple-escaped | ordered  | code `00`    | binary         |
+//!
+//! `Compact` is the layout `encode_composite!` and `KeySchema::encode` write. `Debuggable` writes
+//! each field as a tagged `Value`, so `value::decode_values` and `Decoder::decode_value` read the
+//! key back without the schema. `Utf8Safe` renders the `Compact` bytes with
+//! `LexKey::to_base32hex`, for stores that only accept UTF-8 keys; the text sorts like the bytes.
+//! `FdbCompat` writes the elements of the `FoundationDB` tuple layer back to back, so its keys
+//! are the bytes `fdb.tuple.pack` produces for the same values and can share a keyspace with
+//! tuple-layer clients.
+//!
+//! `KeySchema::encode_profile` and `decode_profile` apply a profile to a whole key;
+//! `Encoder::encode_profile_into` and `encode_opt_profile_into` apply it when building keys by
+//! hand, including optional fields.
+//!
+//! ```rust
+//! use lexkey::profile::Profile;
+//! use lexkey::schema::{FieldType, KeySchema, Value};
+//!
+//! let schema = KeySchema::new([FieldType::Str, FieldType::U32]);
+//! let values = [Value::Str("acme".into()), Value::U32(7)];
+//!
+//! for profile in Profile::ALL {
+//!     let key = schema.encode_profile(&values, profile).unwrap();
+//!     assert_eq!(schema.decode_profile(key.as_bytes(), profile)?, values);
+//! }
+//! let text = schema.encode_profile(&values, Profile::Utf8Safe).unwrap();
+//! assert!(std::str::from_utf8(text.as_bytes()).is_ok());
+//! let tuple = schema.encode_profile(&values, Profile::FdbCompat).unwrap();
+//! assert_eq!(tuple.as_bytes(), b"\x02acme\x00\x15\x07");
+//! # Ok::<(), lexkey::DecodeError>(())
+//! ```
+
+use crate::desc::SortOrder;
+use crate::lexkey::SOME_MARKER;
+use crate::schema::{FieldType, KeySchema, Value};
+use crate::tagged::TAG_NULL;
+use crate::text::Base32Error;
+use crate::{
+    value, DecodeError, Decoder, Encodable, EncodeError, Encoder, F64Mode, LexKey, NullOrder,
+    StringMode,
+};
+
+/// Tuple-layer type code for null.
+const FDB_NULL: u8 = 0x00;
+/// Tuple-layer type code for byte strings, followed by the escaped bytes and `00`.
+const FDB_BYTES: u8 = 0x01;
+/// Tuple-layer type code for UTF-8 strings, followed by the escaped bytes and `00`.
+const FDB_STRING: u8 = 0x02;
+/// Tuple-layer type code for zero; integers of `n` bytes use `FDB_INT_ZERO ± n`.
+const FDB_INT_ZERO: u8 = 0x14;
+/// Tuple-layer type code for 4-byte floats.
+const FDB_FLOAT: u8 = 0x20;
+/// Tuple-layer type code for 8-byte doubles.
+const FDB_DOUBLE: u8 = 0x21;
+/// Tuple-layer type code for `false`; `true` is the next code.
+const FDB_FALSE: u8 = 0x26;
+/// Tuple-layer type code for `true`.
+const FDB_TRUE: u8 = 0x27;
+/// Tuple-layer type code for UUIDs, followed by the 16 raw bytes.
+const FDB_UUID: u8 = 0x30;
+/// The byte that follows an embedded `00` in tuple-layer strings.
+const FDB_ESCAPE: u8 = 0xFF;
+
+/// A preset of encoding options.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Profile {
+    /// Untagged binary parts: the smallest keys, decodable only with the schema.
+    #[default]
+    Compact,
+    /// Type-tagged binary parts with escaped strings: a few bytes larger, but self-describing.
+    Debuggable,
+    /// The `Compact` bytes as base32hex text: valid UTF-8 and still order-preserving.
+    Utf8Safe,
+    /// `FoundationDB` tuple-layer elements: self-describing and readable by tuple-layer clients.
+    FdbCompat,
+}
+
+impl Profile {
+    /// Every profile, in declaration order.
+    pub const ALL: [Self; 4] = [
+        Self::Compact,
+        Self::Debuggable,
+        Self::Utf8Safe,
+        Self::FdbCompat,
+    ];
+
+    /// The `StringMode` the profile escapes strings with, for use with
+    /// `Encoder::encode_string_with_into` when building keys by hand.
+    ///
+    /// `FdbCompat` escapes embedded NULs like `StringMode::Escaped` but ends strings with a single
+    /// `00`; `Encoder::encode_profile_into` writes that form.
+    ///
+    /// ```rust
+    /// use lexkey::profile::Profile;
+    /// use lexkey::{Encoder, StringMode};
+    ///
+    /// let mut enc = Encoder::with_capacity(16);
+    /// enc.encode_string_with_into("a\0b", Profile::Debuggable.string_mode());
+    /// assert_eq!(enc.as_slice(), b"a\0\xffb\0\x01");
+    /// assert_eq!(Profile::Compact.string_mode(), StringMode::Raw);
+    /// ```
+    #[must_use]
+    pub fn string_mode(self) -> StringMode {
+        match self {
+            Self::Compact | Self::Utf8Safe => StringMode::Raw,
+            Self::Debuggable | Self::FdbCompat => StringMode::Escaped,
+        }
+    }
+
+    /// The NaN policy for floats: only `FdbCompat` orders NaN, as the tuple layer does.
+    #[must_use]
+    pub fn f64_mode(self) -> F64Mode {
+        match self {
+            Self::Compact | Self::Debuggable | Self::Utf8Safe => F64Mode::RejectNaN,
+            Self::FdbCompat => F64Mode::TotalOrder,
+        }
+    }
+
+    /// Whether each part starts with a type tag (or tuple-layer type code).
+    #[must_use]
+    pub fn is_tagged(self) -> bool {
+        matches!(self, Self::Debuggable | Self::FdbCompat)
+    }
+
+    /// Whether parts are joined by `SEPARATOR`; tuple-layer elements are self-delimiting.
+    #[must_use]
+    pub fn is_separated(self) -> bool {
+        self != Self::FdbCompat
+    }
+
+    /// Whether keys are rendered as base32hex text rather than raw bytes.
+    #[must_use]
+    pub fn is_text(self) -> bool {
+        self == Self::Utf8Safe
+    }
+
+    /// Append `value` as one part, without separators or text rendering.
+    fn write(self, dst: &mut Vec<u8>, value: &Value) -> Result<usize, EncodeError> {
+        if self.f64_mode() == F64Mode::RejectNaN && is_nan(value) {
+            return Err(EncodeError::NaN);
+        }
+        Ok(match self {
+            Self::Compact | Self::Utf8Safe => value.encode_into(dst),
+            Self::Debuggable => tagged(value).encode_into(dst),
+            Self::FdbCompat => fdb_into(dst, value),
+        })
+    }
+
+    /// Append an optional part: untagged profiles add a presence marker, tagged ones a null tag.
+    fn write_opt(self, dst: &mut Vec<u8>, value: Option<&Value>) -> Result<usize, EncodeError> {
+        match (self, value) {
+            (Self::Compact | Self::Utf8Safe, Some(v)) => {
+                let start = dst.len();
+                dst.push(SOME_MARKER);
+                self.write(dst, v)
+                    .map(|n| 1 + n)
+                    .inspect_err(|_| dst.truncate(start))
+            }
+            (_, Some(v)) => self.write(dst, v),
+            (Self::Compact | Self::Utf8Safe, None) => {
+                dst.push(NullOrder::First.none_marker());
+                Ok(1)
+            }
+            (Self::Debuggable, None) => {
+                dst.push(TAG_NULL);
+                Ok(1)
+            }
+            (Self::FdbCompat, None) => {
+                dst.push(FDB_NULL);
+                Ok(1)
+            }
+        }
+    }
+
+    /// Append parts joined as the profile joins them, rendering them as text for `Utf8Safe`.
+    /// Leaves `dst` unchanged on error.
+    fn write_all<'a>(
+        self,
+        dst: &mut Vec<u8>,
+        parts: impl IntoIterator<Item = Option<&'a Value>>,
+        optional: bool,
+    ) -> Result<usize, EncodeError> {
+        let start = dst.len();
+        for (i, part) in parts.into_iter().enumerate() {
+            if i > 0 && self.is_separated() {
+                dst.push(LexKey::SEPARATOR);
+            }
+            let written = match part {
+                Some(v) if !optional => self.write(dst, v),
+                _ => self.write_opt(dst, part),
+            };
+            if let Err(e) = written {
+                dst.truncate(start);
+                return Err(e);
+            }
+        }
+        if self.is_text() {
+            let text = LexKey::from(&dst[start..]).to_base32hex();
+            dst.truncate(start);
+            dst.extend_from_slice(text.as_bytes());
+        }
+        Ok(dst.len() - start)
+    }
+}
+
+impl Encoder {
+    /// Append `values` as the parts of a key under `profile`, returning bytes written.
+    ///
+    /// Parts are tagged, escaped and joined as the profile selects, and floats follow its
+    /// `f64_mode`. Under `Utf8Safe` the appended parts are rendered as one base32hex text, so
+    /// write each key's parts with a single call. With an ascending, unversioned schema the
+    /// result matches `KeySchema::encode_profile`.
+    ///
+    /// ```rust
+    /// use lexkey::profile::Profile;
+    /// use lexkey::schema::Value;
+    /// use lexkey::Encoder;
+    ///
+    /// let mut enc = Encoder::with_capacity(16);
+    /// enc.encode_profile_into(&[Value::Str("a".into()), Value::I16(-1)], Profile::FdbCompat)?;
+    /// assert_eq!(enc.as_slice(), b"\x02a\x00\x13\xfe");
+    /// # Ok::<(), lexkey::EncodeError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `EncodeError::NaN` for a NaN float under a profile that rejects NaN, leaving the
+    /// encoder unchanged.
+    pub fn encode_profile_into(
+        &mut self,
+        values: &[Value],
+        profile: Profile,
+    ) -> Result<usize, EncodeError> {
+        profile.write_all(&mut self.buf, values.iter().map(Some), false)
+    }
+
+    /// Append optional parts under `profile`, writing `None` as the profile's null.
+    ///
+    /// Untagged profiles put the `encode_opt` presence marker before every part, with nulls
+    /// first; `Debuggable` writes `TAG_NULL` and `FdbCompat` the tuple-layer null code `00`.
+    ///
+    /// ```rust
+    /// use lexkey::profile::Profile;
+    /// use lexkey::schema::Value;
+    /// use lexkey::Encoder;
+    ///
+    /// let mut enc = Encoder::with_capacity(16);
+    /// enc.encode_opt_profile_into(&[None, Some(Value::U8(7))], Profile::Compact)?;
+    /// assert_eq!(enc.as_slice(), b"\x00\x00\x01\x07");
+    /// # Ok::<(), lexkey::EncodeError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `EncodeError::NaN` for a NaN float under a profile that rejects NaN, leaving the
+    /// encoder unchanged.
+    pub fn encode_opt_profile_into(
+        &mut self,
+        values: &[Option<Value>],
+        profile: Profile,
+    ) -> Result<usize, EncodeError> {
+        profile.write_all(&mut self.buf, values.iter().map(Option::as_ref), true)
+    }
+}
+
+impl KeySchema {
+    /// Encode `values` under `profile`, or `None` if they do not match the schema's field count
+    /// and types.
+    ///
+    /// Also returns `None` for a NaN float under a profile whose `f64_mode` rejects NaN, and for a
+    /// schema with a descending field under a tagged profile, which cannot represent one. A
+    /// versioned schema writes its version byte first in every profile; under `Utf8Safe` it is
+    /// part of the text.
+    #[must_use]
+    pub fn encode_profile(&self, values: &[Value], profile: Profile) -> Option<LexKey> {
+        let matches = values.len() == self.fields().len()
+            && values
+                .iter()
+                .zip(self.fields())
+                .all(|(v, &t)| v.field_type() == t);
+        if !matches || profile.f64_mode() == F64Mode::RejectNaN && values.iter().any(is_nan) {
+            return None;
+        }
+        match profile {
+            Profile::Compact => self.encode(values),
+            Profile::Utf8Safe => {
+                let text = self.encode(values)?.to_base32hex();
+                Some(LexKey::from_bytes(text.into_bytes()))
+            }
+            Profile::Debuggable | Profile::FdbCompat => {
+                if (0..self.fields().len()).any(|i| self.order(i) == SortOrder::Desc) {
+                    return None;
+                }
+                let mut buf = Vec::new();
+                buf.extend(self.version());
+                profile
+                    .write_all(&mut buf, values.iter().map(Some), false)
+                    .ok()?;
+                Some(LexKey::from_bytes(buf))
+            }
+        }
+    }
+
+    /// Decode a key written by `encode_profile` with the same `profile`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DecodeError` under the same conditions as `decode`, and additionally when a
+    /// tagged part has the wrong tag or a value out of range for its field, or when a
+    /// `Utf8Safe` key is not canonical base32hex text. `Utf8Safe` offsets of field errors refer to
+    /// the bytes the text decodes to.
+    pub fn decode_profile(&self, key: &[u8], profile: Profile) -> Result<Vec<Value>, DecodeError> {
+        match profile {
+            Profile::Compact => self.decode(key),
+            Profile::Utf8Safe => self.decode(from_text(key)?.as_bytes()),
+            Profile::Debuggable | Profile::FdbCompat => self.decode_tagged(key, profile),
+        }
+    }
+
+    fn decode_tagged(&self, key: &[u8], profile: Profile) -> Result<Vec<Value>, DecodeError> {
+        let mut dec = Decoder::new(key);
+        if let Some(version) = self.version() {
+            if dec.decode_u8()? != version {
+                return Err(DecodeError::Invalid {
+                    offset: 0,
+                    expected: "schema version",
+                });
+            }
+        }
+        let mut values = Vec::with_capacity(self.fields().len());
+        for (i, &field) in self.fields().iter().enumerate() {
+            if i > 0 && profile.is_separated() {
+                dec.expect_separator()?;
+            }
+            let offset = dec.position();
+            let value = if profile == Profile::FdbCompat {
+                read_fdb(&mut dec, field)?
+            } else {
+                untagged(field, dec.decode_value()?)
+            };
+            values.push(value.ok_or(DecodeError::Invalid {
+                offset,
+                expected: expected(field),
+            })?);
+        }
+        dec.finish()?;
+        Ok(values)
+    }
+}
+
+fn is_nan(value: &Value) -> bool {
+    match value {
+        Value::F32(x) => x.is_nan(),
+        Value::F64(x) => x.is_nan(),
+        _ => false,
+    }
+}
+
+/// The tagged value a field is written as.
+fn tagged(value: &Value) -> value::Value {
+    match value {
+        Value::Str(s) => value::Value::String(s.clone()),
+        Value::Bytes(b) => value::Value::Bytes(b.clone()),
+        Value::U8(n) => value::Value::U64(u64::from(*n)),
+        Value::U16(n) => value::Value::U64(u64::from(*n)),
+        Value::U32(n) => value::Value::U64(u64::from(*n)),
+        Value::U64(n) => value::Value::U64(*n),
+        Value::I8(n) => value::Value::I64(i64::from(*n)),
+        Value::I16(n) => value::Value::I64(i64::from(*n)),
+        Value::I32(n) => value::Value::I64(i64::from(*n)),
+        Value::I64(n) => value::Value::I64(*n),
+        Value::F32(x) => value::Value::F64(f64::from(*x)),
+        Value::F64(x) => value::Value::F64(*x),
+        Value::Bool(b) => value::Value::Bool(*b),
+        Value::Uuid(u) => value::Value::Uuid(*u),
+    }
+}
+
+/// The field value a tagged part decodes to, or `None` if its tag or range does not fit.
+fn untagged(field: FieldType, part: value::Value) -> Option<Value> {
+    use value::Value as V;
+    Some(match (field, part) {
+        (FieldType::Str, V::String(s)) => Value::Str(s),
+        (FieldType::Bytes, V::Bytes(b)) => Value::Bytes(b),
+        (FieldType::U8, V::U64(n)) => Value::U8(n.try_into().ok()?),
+        (FieldType::U16, V::U64(n)) => Value::U16(n.try_into().ok()?),
+        (FieldType::U32, V::U64(n)) => Value::U32(n.try_into().ok()?),
+        (FieldType::U64, V::U64(n)) => Value::U64(n),
+        (FieldType::I8, V::I64(n)) => Value::I8(n.try_into().ok()?),
+        (FieldType::I16, V::I64(n)) => Value::I16(n.try_into().ok()?),
+        (FieldType::I32, V::I64(n)) => Value::I32(n.try_into().ok()?),
+        (FieldType::I64, V::I64(n)) => Value::I64(n),
+        (FieldType::F32, V::F64(x)) => Value::F32(narrow_f32(x)?),
+        (FieldType::F64, V::F64(x)) => Value::F64(x),
+        (FieldType::Bool, V::Bool(b)) => Value::Bool(b),
+        (FieldType::Uuid, V::Uuid(u)) => Value::Uuid(u),
+        _ => return None,
+    })
+}
+
+/// `x` as an `f32`, or `None` if the conversion is not exact.
+// The cast rounds; the comparison rejects every `x` it changed.
+#[allow(clippy::cast_possible_truncation)]
+fn narrow_f32(x: f64) -> Option<f32> {
+    let y = x as f32;
+    (f64::from(y).to_bits() == x.to_bits()).then_some(y)
+}
+
+/// Append `value` as a tuple-layer element.
+fn fdb_into(dst: &mut Vec<u8>, value: &Value) -> usize {
+    let start = dst.len();
+    match value {
+        Value::Str(s) => fdb_escaped_into(dst, FDB_STRING, s.as_bytes()),
+        Value::Bytes(b) => fdb_escaped_into(dst, FDB_BYTES, b),
+        Value::U8(n) => fdb_int_into(dst, i128::from(*n)),
+        Value::U16(n) => fdb_int_into(dst, i128::from(*n)),
+        Value::U32(n) => fdb_int_into(dst, i128::from(*n)),
+        Value::U64(n) => fdb_int_into(dst, i128::from(*n)),
+        Value::I8(n) => fdb_int_into(dst, i128::from(*n)),
+        Value::I16(n) => fdb_int_into(dst, i128::from(*n)),
+        Value::I32(n) => fdb_int_into(dst, i128::from(*n)),
+        Value::I64(n) => fdb_int_into(dst, i128::from(*n)),
+        Value::F32(x) => {
+            // One NaN per sign, as `F64Mode::TotalOrder` does for doubles.
+            let x = if x.is_nan() {
+                f32::NAN.copysign(*x)
+            } else {
+                *x
+            };
+            let bits = x.to_bits();
+            let sortable = if bits >> 31 == 1 {
+                !bits
+            } else {
+                bits ^ (1 << 31)
+            };
+            dst.push(FDB_FLOAT);
+            dst.extend_from_slice(&sortable.to_be_bytes());
+        }
+        Value::F64(x) => {
+            dst.push(FDB_DOUBLE);
+            LexKey::encode_f64_with_into(dst, *x, F64Mode::TotalOrder);
+        }
+        Value::Bool(b) => dst.push(if *b { FDB_TRUE } else { FDB_FALSE }),
+        Value::Uuid(u) => {
+            dst.push(FDB_UUID);
+            dst.extend_from_slice(u.as_bytes());
+        }
+    }
+    dst.len() - start
+}
+
+/// Append `code`, then `bytes` with each `00` written as `00 ff`, then a terminating `00`.
+fn fdb_escaped_into(dst: &mut Vec<u8>, code: u8, bytes: &[u8]) {
+    dst.push(code);
+    for &b in bytes {
+        dst.push(b);
+        if b == 0 {
+            dst.push(FDB_ESCAPE);
+        }
+    }
+    dst.push(0);
+}
+
+/// Append an integer as its minimal big-endian magnitude behind a length-carrying type code;
+/// negative magnitudes are one's-complemented so they sort below zero.
+fn fdb_int_into(dst: &mut Vec<u8>, n: i128) {
+    let magnitude = n.unsigned_abs();
+    let len = 16
+        - magnitude
+            .to_be_bytes()
+            .iter()
+            .take_while(|&&b| b == 0)
+            .count();
+    let len_code = u8::try_from(len).expect("integers fit in 16 bytes");
+    let body = if n < 0 {
+        dst.push(FDB_INT_ZERO - len_code);
+        (!magnitude).to_be_bytes()
+    } else {
+        dst.push(FDB_INT_ZERO + len_code);
+        magnitude.to_be_bytes()
+    };
+    dst.extend_from_slice(&body[16 - len..]);
+}
+
+/// Read one tuple-layer element for `field`, or `None` if its type code or value does not fit.
+fn read_fdb(dec: &mut Decoder<'_>, field: FieldType) -> Result<Option<Value>, DecodeError> {
+    let code = dec.decode_u8()?;
+    Ok(match (field, code) {
+        (FieldType::Str, FDB_STRING) => String::from_utf8(read_fdb_escaped(dec)?)
+            .ok()
+            .map(Value::Str),
+        (FieldType::Bytes, FDB_BYTES) => Some(Value::Bytes(read_fdb_escaped(dec)?)),
+        (FieldType::F32, FDB_FLOAT) => {
+            let t = u32::from_be_bytes(dec.take::<4>()?);
+            let bits = if t >> 31 == 1 { t ^ (1 << 31) } else { !t };
+            Some(Value::F32(f32::from_bits(bits)))
+        }
+        (FieldType::F64, FDB_DOUBLE) => Some(Value::F64(dec.decode_f64_with(F64Mode::TotalOrder)?)),
+        (FieldType::Bool, FDB_FALSE | FDB_TRUE) => Some(Value::Bool(code == FDB_TRUE)),
+        (FieldType::Uuid, FDB_UUID) => Some(Value::Uuid(dec.decode_uuid()?)),
+        (_, 0x0c..=0x1c) => read_fdb_int(dec, code)?.and_then(|n| int_field(field, n)),
+        _ => None,
+    })
+}
+
+/// Undo `fdb_escaped_into` after its type code.
+fn read_fdb_escaped(dec: &mut Decoder<'_>) -> Result<Vec<u8>, DecodeError> {
+    let rest = dec.remaining();
+    let mut out = Vec::new();
+    let mut i = 0;
+    loop {
+        match rest.get(i) {
+            None => {
+                return Err(DecodeError::UnexpectedEnd {
+                    offset: dec.position() + i,
+                    needed: 1,
+                    available: 0,
+                })
+            }
+            Some(0) if rest.get(i + 1) == Some(&FDB_ESCAPE) => {
+                out.push(0);
+                i += 2;
+            }
+            Some(0) => break,
+            Some(&b) => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    dec.skip(i + 1);
+    Ok(out)
+}
+
+/// Read the magnitude after an integer type code, or `None` if it is not minimal.
+fn read_fdb_int(dec: &mut Decoder<'_>, code: u8) -> Result<Option<i128>, DecodeError> {
+    let negative = code < FDB_INT_ZERO;
+    let len = usize::from(code.abs_diff(FDB_INT_ZERO));
+    let rest = dec.remaining();
+    let Some(body) = rest.get(..len) else {
+        return Err(DecodeError::UnexpectedEnd {
+            offset: dec.position(),
+            needed: len,
+            available: rest.len(),
+        });
+    };
+    if body.first() == Some(if negative { &0xFF } else { &0x00 }) {
+        return Ok(None);
+    }
+    dec.skip(len);
+    let raw = body
+        .iter()
+        .fold(0u128, |acc, &b| (acc << 8) | u128::from(b));
+    let magnitude = if negative {
+        raw ^ ((1u128 << (8 * len)) - 1)
+    } else {
+        raw
+    };
+    Ok(i128::try_from(magnitude)
+        .ok()
+        .map(|m| if negative { -m } else { m }))
+}
+
+fn int_field(field: FieldType, n: i128) -> Option<Value> {
+    Some(match field {
+        FieldType::U8 => Value::U8(n.try_into().ok()?),
+        FieldType::U16 => Value::U16(n.try_into().ok()?),
+        FieldType::U32 => Value::U32(n.try_into().ok()?),
+        FieldType::U64 => Value::U64(n.try_into().ok()?),
+        FieldType::I8 => Value::I8(n.try_into().ok()?),
+        FieldType::I16 => Value::I16(n.try_into().ok()?),
+        FieldType::I32 => Value::I32(n.try_into().ok()?),
+        FieldType::I64 => Value::I64(n.try_into().ok()?),
+        _ => return None,
+    })
+}
+
+fn expected(field: FieldType) -> &'static str {
+    match field {
+        FieldType::Str => "tagged string",
+        FieldType::Bytes => "tagged bytes",
+        FieldType::U8 => "tagged u8",
+        FieldType::U16 => "tagged u16",
+        FieldType::U32 => "tagged u32",
+        FieldType::U64 => "tagged u64",
+        FieldType::I8 => "tagged i8",
+        FieldType::I16 => "tagged i16",
+        FieldType::I32 => "tagged i32",
+        FieldType::I64 => "tagged i64",
+        FieldType::F32 => "tagged f32",
+        FieldType::F64 => "tagged f64",
+        FieldType::Bool => "tagged bool",
+        FieldType::Uuid => "tagged uuid",
+    }
+}
+
+fn from_text(key: &[u8]) -> Result<LexKey, DecodeError> {
+    let text = std::str::from_utf8(key).map_err(|e| DecodeError::Invalid {
+        offset: e.valid_up_to(),
+        expected: "base32hex text",
+    })?;
+    LexKey::from_base32hex(text).map_err(|e| DecodeError::Invalid {
+        offset: match e {
+            Base32Error::InvalidChar { offset } => offset,
+            Base32Error::NonCanonical => key.len(),
+        },
+        expected: "base32hex text",
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn schema() -> KeySchema {
+        KeySchema::new([
+            FieldType::Str,
+            FieldType::U8,
+            FieldType::I16,
+            FieldType::F32,
+            FieldType::Bool,
+            FieldType::Uuid,
+        ])
+        .versioned(3)
+    }
+
+    fn row(name: &str, n: u8, i: i16, x: f32) -> Vec<Value> {
+        vec![
+            Value::Str(name.into()),
+            Value::U8(n),
+            Value::I16(i),
+            Value::F32(x),
+            Value::Bool(n % 2 == 1),
+            Value::Uuid(Uuid::from_u128(u128::from(n))),
+        ]
+    }
+
+    #[test]
+    fn should_round_trip_and_keep_order_in_every_profile() {
+        let rows = [
+            row("acme", 1, -300, -0.5),
+            row("acme", 1, 7, 1e-40),
+            row("acme", 200, -1, f32::INFINITY),
+            row("globex", 0, 0, 3.25),
+        ];
+        for profile in Profile::ALL {
+            let keys: Vec<LexKey> = rows
+                .iter()
+                .map(|r| schema().encode_profile(r, profile).unwrap())
+                .collect();
+            for (key, r) in keys.iter().zip(&rows) {
+                assert_eq!(
+                    &schema().decode_profile(key.as_bytes(), profile).unwrap(),
+                    r
+                );
+            }
+            assert!(keys.windows(2).all(|w| w[0] < w[1]), "{profile:?}");
+        }
+    }
+
+    #[test]
+    fn should_write_the_layout_each_profile_selects() {
+        let values = row("a\0b", 5, -2, 0.5);
+        let compact = schema().encode(&values);
+        assert_eq!(schema().encode_profile(&values, Profile::Compact), compact);
+
+        let text = schema().encode_profile(&values, Profile::Utf8Safe).unwrap();
+        assert_eq!(text.as_bytes(), compact.unwrap().to_base32hex().as_bytes());
+
+        let tagged = schema()
+            .encode_profile(&values, Profile::Debuggable)
+            .unwrap();
+        assert_eq!(tagged.as_bytes()[0], 3);
+        let parts = value::decode_values(&tagged.as_bytes()[1..]).unwrap();
+        assert_eq!(parts[0], value::Value::String("a\0b".into()));
+        assert_eq!(parts[2], value::Value::I64(-2));
+
+        assert!(Profile::Debuggable.is_tagged() && !Profile::Compact.is_tagged());
+        assert!(Profile::Utf8Safe.is_text() && !Profile::Debuggable.is_text());
+        assert_eq!(Profile::default(), Profile::Compact);
+    }
+
+    #[test]
+    fn should_reject_what_the_tagged_layout_cannot_hold() {
+        let desc = KeySchema::new([FieldType::U32]).with_order(0, SortOrder::Desc);
+        assert!(desc
+            .encode_profile(&[Value::U32(1)], Profile::Compact)
+            .is_some());
+        assert!(desc
+            .encode_profile(&[Value::U32(1)], Profile::Debuggable)
+            .is_none());
+
+        assert!(desc
+            .encode_profile(&[Value::U32(1)], Profile::FdbCompat)
+            .is_none());
+
+        let floats = KeySchema::new([FieldType::F64, FieldType::F32]);
+        let nan = [Value::F64(f64::NAN), Value::F32(-f32::NAN)];
+        for profile in [Profile::Compact, Profile::Debuggable, Profile::Utf8Safe] {
+            assert!(
+                floats.encode_profile(&nan, profile).is_none(),
+                "{profile:?}"
+            );
+        }
+        let key = floats.encode_profile(&nan, Profile::FdbCompat).unwrap();
+        let decoded = floats
+            .decode_profile(key.as_bytes(), Profile::FdbCompat)
+            .unwrap();
+        assert!(matches!(decoded[..], [Value::F64(x), Value::F32(y)]
+            if x.is_nan() && y.is_nan() && y.is_sign_negative()));
+        let inf = floats
+            .encode_profile(
+                &[Value::F64(f64::INFINITY), Value::F32(0.0)],
+                Profile::FdbCompat,
+            )
+            .unwrap();
+        assert!(inf < key);
+    }
+
+    #[test]
+    fn should_write_tuple_layer_elements_for_fdb_compat() {
+        let schema = KeySchema::new([
+            FieldType::Str,
+            FieldType::Bytes,
+            FieldType::I64,
+            FieldType::U64,
+            FieldType::F64,
+            FieldType::Bool,
+            FieldType::Uuid,
+        ]);
+        let key = |s: &str, n: i64, u: u64, x: f64| {
+            schema
+                .encode_profile(
+                    &[
+                        Value::Str(s.into()),
+                        Value::Bytes(b"\x00".to_vec()),
+                        Value::I64(n),
+                        Value::U64(u),
+                        Value::F64(x),
+                        Value::Bool(true),
+                        Value::Uuid(Uuid::from_u128(1)),
+                    ],
+                    Profile::FdbCompat,
+                )
+                .unwrap()
+        };
+
+        // The bytes `fdb.tuple.pack(("a\0b", b"\0", -1, 256, 1.0, True, UUID(int=1)))` returns.
+        assert_eq!(
+            key("a\0b", -1, 256, 1.0).to_hex_string(),
+            "026100ff6200\
+             0100ff00\
+             13fe\
+             160100\
+             21bff0000000000000\
+             27\
+             3000000000000000000000000000000001"
+        );
+        assert_eq!(
+            key("", 0, 0, 0.0).as_bytes()[..8],
+            *b"\x02\x00\x01\x00\xff\x00\x14\x14"
+        );
+
+        let ints = [i64::MIN, -256, -255, -1, 0, 1, 255, 256, i64::MAX];
+        let keys: Vec<LexKey> = ints.iter().map(|&n| key("a", n, u64::MAX, -0.5)).collect();
+        assert!(keys.windows(2).all(|w| w[0] < w[1]));
+        for (k, &n) in keys.iter().zip(&ints) {
+            let values = schema
+                .decode_profile(k.as_bytes(), Profile::FdbCompat)
+                .unwrap();
+            assert_eq!(
+                (&values[2], &values[3]),
+                (&Value::I64(n), &Value::U64(u64::MAX))
+            );
+        }
+        assert!(key("a", 0, 0, 0.0) < key("a\0", 0, 0, 0.0));
+        assert!(key("a\0", 0, 0, 0.0) < key("a\x01", 0, 0, 0.0));
+    }
+
+    #[test]
+    fn should_reject_tuple_elements_that_do_not_fit_the_field() {
+        let schema = KeySchema::new([FieldType::U8, FieldType::Str]);
+        assert_eq!(
+            schema.decode_profile(b"\x16\x01\x00\x02a\x00", Profile::FdbCompat),
+            Err(DecodeError::Invalid {
+                offset: 0,
+                expected: "tagged u8"
+            })
+        );
+        assert_eq!(
+            schema.decode_profile(b"\x15\x00\x02a\x00", Profile::FdbCompat),
+            Err(DecodeError::Invalid {
+                offset: 0,
+                expected: "tagged u8"
+            })
+        );
+        assert_eq!(
+            schema.decode_profile(b"\x15\x07\x01a\x00", Profile::FdbCompat),
+            Err(DecodeError::Invalid {
+                offset: 2,
+                expected: "tagged string"
+            })
+        );
+        assert_eq!(
+            schema.decode_profile(b"\x15\x07\x02a", Profile::FdbCompat),
+            Err(DecodeError::UnexpectedEnd {
+                offset: 4,
+                needed: 1,
+                available: 0
+            })
+        );
+        assert_eq!(
+            schema.decode_profile(b"\x15\x07\x02a\x00\x00", Profile::FdbCompat),
+            Err(DecodeError::TrailingBytes {
+                offset: 5,
+                remaining: 1
+            })
+        );
+    }
+
+    #[test]
+    fn should_apply_every_profile_setting_when_encoding_by_hand() {
+        let schema = KeySchema::new([FieldType::Str, FieldType::I16, FieldType::F32]);
+        let values = [Value::Str("a\0b".into()), Value::I16(-2), Value::F32(0.5)];
+        for profile in Profile::ALL {
+            let mut enc = Encoder::with_capacity(32);
+            let n = enc.encode_profile_into(&values, profile).unwrap();
+            assert_eq!(n, enc.len());
+            assert_eq!(
+                Some(enc.as_slice()),
+                schema
+                    .encode_profile(&values, profile)
+                    .as_ref()
+                    .map(LexKey::as_bytes),
+                "{profile:?}"
+            );
+        }
+
+        let nulls = [None, Some(Value::U8(7))];
+        let written = |profile| {
+            let mut enc = Encoder::with_capacity(16);
+            enc.encode_opt_profile_into(&nulls, profile).unwrap();
+            enc.into_vec()
+        };
+        assert_eq!(written(Profile::Compact), b"\x00\x00\x01\x07");
+        assert_eq!(
+            written(Profile::Debuggable),
+            b"\x01\x00\x09\0\0\0\0\0\0\0\x07"
+        );
+        assert_eq!(written(Profile::FdbCompat), b"\x00\x15\x07");
+        assert_eq!(
+            written(Profile::Utf8Safe),
+            LexKey::from(&b"\x00\x00\x01\x07"[..])
+                .to_base32hex()
+                .as_bytes()
+        );
+
+        let mut enc = Encoder::with_capacity(16);
+        enc.push_byte(0xAA);
+        for profile in [Profile::Compact, Profile::Debuggable, Profile::Utf8Safe] {
+            let nan = [Value::U8(1), Value::F64(f64::NAN)];
+            assert_eq!(
+                enc.encode_profile_into(&nan, profile),
+                Err(EncodeError::NaN)
+            );
+            let nan = [Some(Value::U8(1)), Some(Value::F64(f64::NAN))];
+            assert_eq!(
+                enc.encode_opt_profile_into(&nan, profile),
+                Err(EncodeError::NaN)
+            );
+            assert_eq!(enc.as_slice(), b"\xaa");
+        }
+        assert_eq!(
+            enc.encode_profile_into(&[Value::F64(f64::NAN)], Profile::FdbCompat),
+            Ok(9)
+        );
+    }
+
+    #[test]
+    fn should_only_narrow_exact_f32_values() {
+        assert_eq!(narrow_f32(0.5), Some(0.5));
+        assert_eq!(narrow_f32(f64::from(f32::MAX)), Some(f32::MAX));
+        assert_eq!(narrow_f32(f64::NEG_INFINITY), Some(f32::NEG_INFINITY));
+        assert_eq!(narrow_f32(0.1), None);
+        assert_eq!(narrow_f32(1e300), None);
+        assert_eq!(
+            narrow_f32(-0.0).map(f32::to_bits),
+            Some((-0.0f32).to_bits())
+        );
+    }
+
+    #[test]
+    fn should_report_keys_that_do_not_fit_the_profile() {
+        let narrow = KeySchema::new([FieldType::U8, FieldType::F32]);
+        let wide = LexKey::encode_values(&[value::Value::U64(256), value::Value::F64(0.5)]);
+        assert_eq!(
+            narrow.decode_profile(wide.as_bytes(), Profile::Debuggable),
+            Err(DecodeError::Invalid {
+                offset: 0,
+                expected: "tagged u8"
+            })
+        );
+        let inexact = LexKey::encode_values(&[value::Value::U64(1), value::Value::F64(0.1)]);
+        assert_eq!(
+            narrow.decode_profile(inexact.as_bytes(), Profile::Debuggable),
+            Err(DecodeError::Invalid {
+                offset: 10,
+                expected: "tagged f32"
+            })
+        );
+
+        assert_eq!(
+            narrow.decode_profile(b"01x", Profile::Utf8Safe),
+            Err(DecodeError::Invalid {
+                offset: 2,
+                expected: "base32hex text"
+            })
+        );
+        assert_eq!(
+            narrow.decode_profile(b"0", Profile::Utf8Safe),
+            Err(DecodeError::Invalid {
+                offset: 1,
+                expected: "base32hex text"
+            })
+        );
+    }
+}
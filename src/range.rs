@@ -0,0 +1,411 @@
+use std::borrow::Cow;
+use std::ops::{Bound, RangeBounds};
+
+use crate::LexKey;
+
+/// A range of keys with independently inclusive, exclusive or unbounded ends.
+///
+/// Ends compare by raw bytes, like `LexKey` itself. `KeyRange` implements `RangeBounds<LexKey>`,
+/// so it can be passed directly to `BTreeMap::range` and similar APIs.
+///
+/// ```rust
+/// use lexkey::{KeyRange, LexKey};
+///
+/// let range = KeyRange::partition(b"tenant");
+/// let (lower, upper) = LexKey::encode_range_bounds(b"tenant");
+/// assert_eq!(range, KeyRange::half_open(lower, upper));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KeyRange {
+    start: Bound<LexKey>,
+    end: Bound<LexKey>,
+}
+
+impl KeyRange {
+    /// Create a range from explicit bounds.
+    #[inline]
+    #[must_use]
+    pub fn new(start: Bound<LexKey>, end: Bound<LexKey>) -> Self {
+        Self { start, end }
+    }
+
+    /// The range covering every key.
+    #[inline]
+    #[must_use]
+    pub fn all() -> Self {
+        Self::new(Bound::Unbounded, Bound::Unbounded)
+    }
+
+    /// The half-open range `[start, end)`.
+    #[inline]
+    #[must_use]
+    pub fn half_open(start: LexKey, end: LexKey) -> Self {
+        Self::new(Bound::Included(start), Bound::Excluded(end))
+    }
+
+    /// The structured partition range `[partition || 0x00, partition || 0xff)`.
+    ///
+    /// Matches `LexKey::encode_range_bounds`.
+    #[inline]
+    #[must_use]
+    pub fn partition(partition: &[u8]) -> Self {
+        let (lower, upper) = LexKey::encode_range_bounds(partition);
+        Self::half_open(lower, upper)
+    }
+
+    /// The child range `[parent || 0x00, parent || 0x01)`: keys whose leading parts are exactly
+    /// the composite `parent`, followed by at least one more part.
+    ///
+    /// Unlike `partition`, this excludes keys whose last leading part merely extends `parent`'s,
+    /// such as `acme2 00 alice` under `acme`, because their byte after `parent` is not a
+    /// separator.
+    ///
+    /// ```rust
+    /// use lexkey::{KeyRange, LexKey};
+    ///
+    /// let range = KeyRange::children(b"acme");
+    /// assert!(range.contains_key(LexKey::encode_composite(&[b"acme", b"alice"]).as_bytes()));
+    /// assert!(!range.contains_key(LexKey::encode_composite(&[b"acme2", b"bob"]).as_bytes()));
+    /// assert!(KeyRange::partition(b"acme").contains_key(b"acme2\x00bob"));
+    /// ```
+    #[must_use]
+    pub fn children(parent: &[u8]) -> Self {
+        let bound = |last: u8| {
+            let mut buf = Vec::with_capacity(parent.len() + 1);
+            buf.extend_from_slice(parent);
+            buf.push(last);
+            LexKey::from(buf)
+        };
+        Self::half_open(bound(LexKey::SEPARATOR), bound(LexKey::SEPARATOR + 1))
+    }
+
+    /// The raw-byte prefix range `[prefix, prefix_successor(prefix))`.
+    ///
+    /// The end is unbounded when the prefix has no finite successor (empty or all `0xff`).
+    #[inline]
+    #[must_use]
+    pub fn prefix(prefix: &[u8]) -> Self {
+        let end = match LexKey::prefix_successor(prefix) {
+            Some(upper) => Bound::Excluded(LexKey::from(upper)),
+            None => Bound::Unbounded,
+        };
+        Self::new(Bound::Included(LexKey::from(prefix)), end)
+    }
+
+    /// The lower bound.
+    #[inline]
+    #[must_use]
+    pub fn start(&self) -> Bound<&LexKey> {
+        self.start.as_ref()
+    }
+
+    /// The upper bound.
+    #[inline]
+    #[must_use]
+    pub fn end(&self) -> Bound<&LexKey> {
+        self.end.as_ref()
+    }
+
+    /// Return true when no byte string can lie inside the range.
+    ///
+    /// Inverted bounds are empty, as is `(k, k || 0x00)` with both ends exclusive.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        match (&self.start, &self.end) {
+            (Bound::Included(s), Bound::Included(e)) => e < s,
+            (Bound::Included(s), Bound::Excluded(e)) | (Bound::Excluded(s), Bound::Included(e)) => {
+                e <= s
+            }
+            (Bound::Excluded(s), Bound::Excluded(e)) => {
+                let e = e.as_bytes();
+                e <= s.as_bytes()
+                    || (e.len() == s.as_bytes().len() + 1
+                        && e.starts_with(s.as_bytes())
+                        && e[e.len() - 1] == LexKey::SEPARATOR)
+            }
+            (Bound::Unbounded, _) | (_, Bound::Unbounded) => false,
+        }
+    }
+}
+
+/// A position between byte strings, used to compare bounds of different kinds.
+///
+/// Every bound maps to the point just before the first key it admits (starts) or just before
+/// the first key it rejects (ends). `Excluded(k)` as a start and `Included(k)` as an end both
+/// become the point before `k || 0x00`, the next byte string after `k`.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Point<'a> {
+    Min,
+    Before(Cow<'a, [u8]>),
+    Max,
+}
+
+/// `k || 0x00`, the smallest byte string greater than `k`.
+fn next_key(k: &LexKey) -> Cow<'_, [u8]> {
+    let mut buf = Vec::with_capacity(k.as_bytes().len() + 1);
+    buf.extend_from_slice(k.as_bytes());
+    buf.push(LexKey::SEPARATOR);
+    Cow::Owned(buf)
+}
+
+fn start_point(bound: Bound<&LexKey>) -> Point<'_> {
+    match bound {
+        Bound::Unbounded => Point::Min,
+        Bound::Included(k) => Point::Before(Cow::Borrowed(k.as_bytes())),
+        Bound::Excluded(k) => Point::Before(next_key(k)),
+    }
+}
+
+fn end_point(bound: Bound<&LexKey>) -> Point<'_> {
+    match bound {
+        Bound::Unbounded => Point::Max,
+        Bound::Included(k) => Point::Before(next_key(k)),
+        Bound::Excluded(k) => Point::Before(Cow::Borrowed(k.as_bytes())),
+    }
+}
+
+impl KeyRange {
+    /// Return true if `key` lies inside the range.
+    ///
+    /// ```rust
+    /// use lexkey::KeyRange;
+    ///
+    /// let range = KeyRange::prefix(b"ab");
+    /// assert!(range.contains_key(b"abc"));
+    /// assert!(!range.contains_key(b"b"));
+    /// ```
+    #[must_use]
+    pub fn contains_key(&self, key: &[u8]) -> bool {
+        let point = Point::Before(Cow::Borrowed(key));
+        start_point(self.start()) <= point && point < end_point(self.end())
+    }
+
+    /// Return true if some byte string lies in both ranges.
+    #[must_use]
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.intersect(other).is_some()
+    }
+
+    /// Return the keys in both ranges, or `None` if the ranges do not overlap.
+    ///
+    /// ```rust
+    /// use lexkey::{KeyRange, LexKey};
+    ///
+    /// let a = KeyRange::half_open(LexKey::from("a"), LexKey::from("m"));
+    /// let b = KeyRange::half_open(LexKey::from("k"), LexKey::from("z"));
+    /// assert_eq!(
+    ///     a.intersect(&b),
+    ///     Some(KeyRange::half_open(LexKey::from("k"), LexKey::from("m")))
+    /// );
+    /// assert_eq!(a.intersect(&KeyRange::prefix(b"x")), None);
+    /// ```
+    #[must_use]
+    pub fn intersect(&self, other: &Self) -> Option<Self> {
+        let start = if start_point(self.start()) >= start_point(other.start()) {
+            &self.start
+        } else {
+            &other.start
+        };
+        let end = if end_point(self.end()) <= end_point(other.end()) {
+            &self.end
+        } else {
+            &other.end
+        };
+        let range = Self::new(start.clone(), end.clone());
+        (!range.is_empty()).then_some(range)
+    }
+
+    /// Return the keys in either range as a normalized list of ranges.
+    ///
+    /// The result is sorted, holds no empty ranges, and merges ranges that overlap or touch
+    /// (such as `[a, b)` and `[b, c)`), so it has one element when the union is contiguous.
+    ///
+    /// ```rust
+    /// use lexkey::{KeyRange, LexKey};
+    ///
+    /// let a = KeyRange::half_open(LexKey::from("a"), LexKey::from("c"));
+    /// let b = KeyRange::half_open(LexKey::from("c"), LexKey::from("e"));
+    /// let far = KeyRange::half_open(LexKey::from("x"), LexKey::from("z"));
+    /// assert_eq!(a.union(&b), vec![KeyRange::half_open(LexKey::from("a"), LexKey::from("e"))]);
+    /// assert_eq!(a.union(&far), vec![a.clone(), far.clone()]);
+    /// ```
+    #[must_use]
+    pub fn union(&self, other: &Self) -> Vec<Self> {
+        let (first, second) = if start_point(other.start()) < start_point(self.start()) {
+            (other, self)
+        } else {
+            (self, other)
+        };
+        match (first.is_empty(), second.is_empty()) {
+            (true, true) => Vec::new(),
+            (true, false) => vec![second.clone()],
+            (false, true) => vec![first.clone()],
+            (false, false) if start_point(second.start()) <= end_point(first.end()) => {
+                let end = if end_point(second.end()) > end_point(first.end()) {
+                    &second.end
+                } else {
+                    &first.end
+                };
+                vec![Self::new(first.start.clone(), end.clone())]
+            }
+            (false, false) => vec![first.clone(), second.clone()],
+        }
+    }
+}
+
+impl RangeBounds<LexKey> for KeyRange {
+    #[inline]
+    fn start_bound(&self) -> Bound<&LexKey> {
+        self.start.as_ref()
+    }
+
+    #[inline]
+    fn end_bound(&self) -> Bound<&LexKey> {
+        self.end.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_build_partition_range_from_range_bounds() {
+        let range = KeyRange::partition(b"part");
+        assert_eq!(
+            range.start(),
+            Bound::Included(&LexKey::from(&b"part\x00"[..]))
+        );
+        assert_eq!(
+            range.end(),
+            Bound::Excluded(&LexKey::from(&b"part\xff"[..]))
+        );
+    }
+
+    #[test]
+    fn should_build_child_range_below_sibling_parts() {
+        let range = KeyRange::children(b"part");
+        assert_eq!(
+            range.start(),
+            Bound::Included(&LexKey::from(&b"part\x00"[..]))
+        );
+        assert_eq!(
+            range.end(),
+            Bound::Excluded(&LexKey::from(&b"part\x01"[..]))
+        );
+        assert!(range.contains_key(b"part\x00\xff\xff"));
+        assert!(!range.contains_key(b"part"));
+        assert!(!range.contains_key(b"part2\x00a"));
+    }
+
+    #[test]
+    fn should_build_raw_prefix_range_with_successor() {
+        let range = KeyRange::prefix(b"ab\xff");
+        assert_eq!(
+            range.start(),
+            Bound::Included(&LexKey::from(&b"ab\xff"[..]))
+        );
+        assert_eq!(range.end(), Bound::Excluded(&LexKey::from(&b"ac"[..])));
+
+        let unbounded = KeyRange::prefix(b"\xff\xff");
+        assert_eq!(unbounded.end(), Bound::Unbounded);
+    }
+
+    #[test]
+    fn should_detect_empty_ranges() {
+        let a = LexKey::from(&b"a"[..]);
+        let b = LexKey::from(&b"b"[..]);
+        let a0 = LexKey::from(&b"a\x00"[..]);
+
+        assert!(!KeyRange::half_open(a.clone(), b.clone()).is_empty());
+        assert!(KeyRange::half_open(b.clone(), a.clone()).is_empty());
+        assert!(KeyRange::half_open(a.clone(), a.clone()).is_empty());
+        assert!(!KeyRange::new(Bound::Included(a.clone()), Bound::Included(a.clone())).is_empty());
+        assert!(KeyRange::new(Bound::Excluded(a.clone()), Bound::Excluded(a0.clone())).is_empty());
+        assert!(!KeyRange::new(Bound::Excluded(a), Bound::Included(a0)).is_empty());
+        assert!(!KeyRange::all().is_empty());
+    }
+
+    #[test]
+    fn should_drive_btreemap_range_queries() {
+        use std::collections::BTreeMap;
+
+        let mut map = BTreeMap::new();
+        for (i, k) in [&b"a"[..], b"ab", b"abc", b"b"].into_iter().enumerate() {
+            map.insert(LexKey::from(k), i);
+        }
+
+        let hits: Vec<_> = map
+            .range(KeyRange::prefix(b"ab"))
+            .map(|(k, _)| k.as_bytes().to_vec())
+            .collect();
+
+        assert_eq!(hits, vec![b"ab".to_vec(), b"abc".to_vec()]);
+    }
+
+    #[test]
+    fn should_intersect_and_union_mixed_bounds() {
+        let key = |s: &str| LexKey::from(s);
+        let closed = KeyRange::new(Bound::Included(key("b")), Bound::Included(key("d")));
+        let open = KeyRange::new(Bound::Excluded(key("d")), Bound::Unbounded);
+        let from_d = KeyRange::new(Bound::Included(key("d")), Bound::Unbounded);
+
+        // [b, d] and (d, ..) touch without sharing a key.
+        assert!(!closed.overlaps(&open));
+        assert_eq!(
+            closed.union(&open),
+            vec![KeyRange::new(Bound::Included(key("b")), Bound::Unbounded)]
+        );
+        assert_eq!(
+            closed.intersect(&from_d),
+            Some(KeyRange::new(
+                Bound::Included(key("d")),
+                Bound::Included(key("d"))
+            ))
+        );
+
+        // [b, d) and (d, ..) leave `d` uncovered.
+        let half = KeyRange::half_open(key("b"), key("d"));
+        assert_eq!(half.union(&open), vec![half.clone(), open.clone()]);
+
+        // (a, a\0) is empty, and nothing lies between `a` and `a\0`.
+        let empty = KeyRange::new(Bound::Excluded(key("a")), Bound::Excluded(key("a\0")));
+        assert_eq!(empty.union(&half), vec![half.clone()]);
+        let upto_a = KeyRange::new(Bound::Unbounded, Bound::Included(key("a")));
+        let after_a = KeyRange::new(Bound::Included(key("a\0")), Bound::Excluded(key("b")));
+        assert_eq!(
+            after_a.union(&upto_a),
+            vec![KeyRange::new(Bound::Unbounded, Bound::Excluded(key("b")))]
+        );
+        assert_eq!(KeyRange::all().intersect(&half), Some(half.clone()));
+    }
+
+    #[test]
+    fn should_agree_with_range_bounds_contains() {
+        let ranges = [
+            KeyRange::prefix(b"ab"),
+            KeyRange::partition(b"ab"),
+            KeyRange::new(
+                Bound::Excluded(LexKey::from("ab")),
+                Bound::Included(LexKey::from("b")),
+            ),
+            KeyRange::all(),
+        ];
+        let keys: [&[u8]; 7] = [b"", b"a", b"ab", b"ab\x00", b"ab\xff", b"b", b"b\x00"];
+        for range in &ranges {
+            for &k in &keys {
+                assert_eq!(
+                    range.contains_key(k),
+                    range.contains(&LexKey::from(k)),
+                    "{range:?} {k:?}"
+                );
+                for other in &ranges {
+                    let both = range.intersect(other).is_some_and(|r| r.contains_key(k));
+                    assert_eq!(both, range.contains_key(k) && other.contains_key(k));
+                    let either = range.union(other).iter().any(|r| r.contains_key(k));
+                    assert_eq!(either, range.contains_key(k) || other.contains_key(k));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,84 @@
+//! Drive Redis `ZRANGEBYLEX`/`ZREVRANGEBYLEX`/`ZLEXCOUNT` queries from `KeyRange`s.
+//!
+//! Store each `LexKey` unchanged as a sorted-set member, with every member at the same score.
+//! Redis compares members bytewise (`memcmp`), which is exactly `LexKey` order, so no member
+//! transformation is needed. Members that start with `[`, `(`, `+` or `-` are safe too: Redis
+//! reads only the first byte of each *argument* as the bound type, and the helpers here always
+//! write that byte explicitly, so the member itself is never parsed.
+//!
+//! ```rust
+//! use lexkey::{redis, KeyRange, LexKey};
+//!
+//! let (min, max) = redis::zrangebylex_args(&KeyRange::partition(b"tenant"));
+//! assert_eq!(min, b"[tenant\x00");
+//! assert_eq!(max, b"(tenant\xff");
+//!
+//! let (min, max) = redis::zrangebylex_args(&KeyRange::all());
+//! assert_eq!((min.as_slice(), max.as_slice()), (&b"-"[..], &b"+"[..]));
+//! ```
+
+use std::ops::Bound;
+
+use crate::{KeyRange, LexKey};
+
+fn bound_arg(bound: Bound<&LexKey>, unbounded: u8) -> Vec<u8> {
+    let (marker, key) = match bound {
+        Bound::Included(k) => (b'[', k.as_bytes()),
+        Bound::Excluded(k) => (b'(', k.as_bytes()),
+        Bound::Unbounded => return vec![unbounded],
+    };
+    let mut arg = Vec::with_capacity(key.len() + 1);
+    arg.push(marker);
+    arg.extend_from_slice(key);
+    arg
+}
+
+/// Return the `min` argument for `ZRANGEBYLEX`: `[key`, `(key`, or `-` when unbounded.
+#[inline]
+#[must_use]
+pub fn min_arg(range: &KeyRange) -> Vec<u8> {
+    bound_arg(range.start(), b'-')
+}
+
+/// Return the `max` argument for `ZRANGEBYLEX`: `[key`, `(key`, or `+` when unbounded.
+#[inline]
+#[must_use]
+pub fn max_arg(range: &KeyRange) -> Vec<u8> {
+    bound_arg(range.end(), b'+')
+}
+
+/// Return `(min, max)` for `ZRANGEBYLEX key min max` and `ZLEXCOUNT key min max`.
+///
+/// `ZREVRANGEBYLEX` takes the same arguments in the order `max min`.
+#[inline]
+#[must_use]
+pub fn zrangebylex_args(range: &KeyRange) -> (Vec<u8>, Vec<u8>) {
+    (min_arg(range), max_arg(range))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_prefix_bounds_with_redis_markers() {
+        let a = LexKey::from(&b"-a"[..]);
+        let b = LexKey::from(&b"[b"[..]);
+        let range = KeyRange::new(Bound::Excluded(a), Bound::Included(b));
+
+        assert_eq!(zrangebylex_args(&range), (b"(-a".to_vec(), b"[[b".to_vec()));
+    }
+
+    #[test]
+    fn should_use_infinities_for_unbounded_ends() {
+        let range = KeyRange::prefix(b"\xff");
+        assert_eq!(min_arg(&range), b"[\xff".to_vec());
+        assert_eq!(max_arg(&range), b"+".to_vec());
+    }
+
+    #[test]
+    fn should_encode_empty_key_as_bare_marker() {
+        let range = KeyRange::half_open(LexKey::empty(), LexKey::from(&b"a"[..]));
+        assert_eq!(min_arg(&range), b"[".to_vec());
+    }
+}
@@ -0,0 +1,125 @@
+use std::iter::FusedIterator;
+
+/// Iterator adapter that yields the entries of a sorted key/value iterator whose keys start with
+/// `prefix`.
+///
+/// Entries before the prefix are skipped, and iteration stops at the first key after it, so the
+/// wrapped iterator is never drained past the matching run. The input must be in ascending raw
+/// byte order, as returned by ordered maps and store iterators; this is a raw byte prefix, so use
+/// `LexKey::first_child` when only whole child parts of a composite key should match.
+///
+/// ```rust
+/// use std::collections::BTreeMap;
+///
+/// use lexkey::scan::PrefixScan;
+/// use lexkey::LexKey;
+///
+/// let mut map = BTreeMap::new();
+/// map.insert(LexKey::encode_composite(&[b"acme", b"alice"]), 1);
+/// map.insert(LexKey::encode_composite(&[b"acme", b"bob"]), 2);
+/// map.insert(LexKey::encode_composite(&[b"globex", b"carol"]), 3);
+///
+/// let tenant = LexKey::from("acme").first_child();
+/// let values: Vec<_> = PrefixScan::new(&map, tenant).map(|(_, v)| *v).collect();
+/// assert_eq!(values, [1, 2]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct PrefixScan<I, P> {
+    iter: I,
+    prefix: P,
+    started: bool,
+    done: bool,
+}
+
+impl<I, P> PrefixScan<I, P> {
+    /// Wrap `iter`, yielding only the entries whose keys start with `prefix`.
+    pub fn new<T: IntoIterator<IntoIter = I>>(iter: T, prefix: P) -> Self {
+        Self {
+            iter: iter.into_iter(),
+            prefix,
+            started: false,
+            done: false,
+        }
+    }
+
+    /// Return the wrapped iterator, positioned after the last entry consumed.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.iter
+    }
+}
+
+impl<I, K, V, P> Iterator for PrefixScan<I, P>
+where
+    I: Iterator<Item = (K, V)>,
+    K: AsRef<[u8]>,
+    P: AsRef<[u8]>,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let prefix = self.prefix.as_ref();
+        loop {
+            let Some((key, value)) = self.iter.next() else {
+                self.done = true;
+                return None;
+            };
+            if key.as_ref().starts_with(prefix) {
+                self.started = true;
+                return Some((key, value));
+            }
+            if self.started || key.as_ref() > prefix {
+                self.done = true;
+                return None;
+            }
+        }
+    }
+}
+
+impl<I, K, V, P> FusedIterator for PrefixScan<I, P>
+where
+    I: Iterator<Item = (K, V)>,
+    K: AsRef<[u8]>,
+    P: AsRef<[u8]>,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_yield_only_the_matching_run() {
+        let entries: Vec<(&[u8], u32)> = vec![
+            (b"a", 0),
+            (b"ab", 1),
+            (b"abc", 2),
+            (b"abd", 3),
+            (b"ac", 4),
+            (b"abz", 5),
+        ];
+        let got: Vec<_> = PrefixScan::new(entries.clone(), b"ab")
+            .map(|(_, v)| v)
+            .collect();
+        assert_eq!(got, [1, 2, 3]);
+
+        let all: Vec<_> = PrefixScan::new(entries, b"").map(|(_, v)| v).collect();
+        assert_eq!(all.len(), 6);
+    }
+
+    #[test]
+    fn should_stop_without_draining_the_source() {
+        let entries: Vec<(Vec<u8>, u32)> = vec![
+            (b"b1".to_vec(), 1),
+            (b"c1".to_vec(), 2),
+            (b"c2".to_vec(), 3),
+        ];
+        let mut scan = PrefixScan::new(entries, b"a".to_vec());
+        assert_eq!(scan.next(), None);
+        assert_eq!(scan.next(), None);
+        assert_eq!(scan.into_inner().count(), 2);
+    }
+}
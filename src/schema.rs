@@ -0,0 +1,1245 @@
+//! Schema-driven decoding of composite keys into dynamic values.
+//!
+//! Keys carry no type information. A `KeySchema` lists the field types of a composite in order,
+//! so tooling that renders keys from many tables can decode any of them into `Value`s without
+//! per-table code.
+//!
+//! The layout is the one `encode_composite!` writes: fields joined by `SEPARATOR`. Fixed-width
+//! fields are read at their width; `Str` and `Bytes` run to the next separator (or the end of the
+//! key), so they must not contain `0x00`.
+//!
+//! A schema made with `KeySchema::versioned` adds a 1-byte version before the first field, so
+//! keys written under different layouts of the same table can coexist. `key_version` and
+//! `KeySchema::for_key` pick the layout of a stored key, and `reencode` rewrites a key from one
+//! layout to another during a migration.
+//!
+//! During a rolling upgrade that appends fields to a layout, old readers still see keys written
+//! by new writers. `KeySchema::decode_with_tail` decodes the fields it knows and returns whatever
+//! follows them as an opaque tail instead of failing.
+//!
+//! Fixed-width fields can be made descending with `KeySchema::with_order`, matching keys built
+//! with `desc::Desc` or `encode_composite_ordered`. `LexKey::explain_parts` reports each field's
+//! name, value, direction and byte span for admin UIs and log enrichers.
+
+use std::fmt;
+use std::ops::Range;
+
+use uuid::Uuid;
+
+use crate::decoder::shift;
+use crate::desc::{complement_from, SortOrder};
+use crate::{Decodable, DecodeError, Decoder, Encodable, LexKey};
+
+/// The type of one field in a `KeySchema`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FieldType {
+    Str,
+    Bytes,
+    U8,
+    U16,
+    U32,
+    U64,
+    I8,
+    I16,
+    I32,
+    I64,
+    F32,
+    F64,
+    Bool,
+    Uuid,
+}
+
+impl FieldType {
+    /// The encoded width in bytes, or `None` for the variable-length `Str` and `Bytes`.
+    #[must_use]
+    pub fn width(self) -> Option<usize> {
+        match self {
+            Self::Str | Self::Bytes => None,
+            Self::U8 | Self::I8 | Self::Bool => Some(1),
+            Self::U16 | Self::I16 => Some(2),
+            Self::U32 | Self::I32 | Self::F32 => Some(4),
+            Self::U64 | Self::I64 | Self::F64 => Some(8),
+            Self::Uuid => Some(16),
+        }
+    }
+}
+
+/// A decoded field value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Str(String),
+    Bytes(Vec<u8>),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+    Bool(bool),
+    Uuid(Uuid),
+}
+
+impl Value {
+    /// The schema type this value decodes from.
+    #[must_use]
+    pub fn field_type(&self) -> FieldType {
+        match self {
+            Self::Str(_) => FieldType::Str,
+            Self::Bytes(_) => FieldType::Bytes,
+            Self::U8(_) => FieldType::U8,
+            Self::U16(_) => FieldType::U16,
+            Self::U32(_) => FieldType::U32,
+            Self::U64(_) => FieldType::U64,
+            Self::I8(_) => FieldType::I8,
+            Self::I16(_) => FieldType::I16,
+            Self::I32(_) => FieldType::I32,
+            Self::I64(_) => FieldType::I64,
+            Self::F32(_) => FieldType::F32,
+            Self::F64(_) => FieldType::F64,
+            Self::Bool(_) => FieldType::Bool,
+            Self::Uuid(_) => FieldType::Uuid,
+        }
+    }
+}
+
+/// Renders strings quoted and bytes as hex, so every value is readable in admin tools.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Str(s) => write!(f, "{s:?}"),
+            Self::Bytes(b) => write!(f, "0x{}", hex::encode(b)),
+            Self::U8(n) => write!(f, "{n}"),
+            Self::U16(n) => write!(f, "{n}"),
+            Self::U32(n) => write!(f, "{n}"),
+            Self::U64(n) => write!(f, "{n}"),
+            Self::I8(n) => write!(f, "{n}"),
+            Self::I16(n) => write!(f, "{n}"),
+            Self::I32(n) => write!(f, "{n}"),
+            Self::I64(n) => write!(f, "{n}"),
+            Self::F32(x) => write!(f, "{x}"),
+            Self::F64(x) => write!(f, "{x}"),
+            Self::Bool(b) => write!(f, "{b}"),
+            Self::Uuid(u) => write!(f, "{u}"),
+        }
+    }
+}
+
+/// Writes the same bytes as the wrapped value's own `Encodable` impl.
+impl Encodable for Value {
+    fn encoded_len(&self) -> usize {
+        match self {
+            Self::Str(s) => s.len(),
+            Self::Bytes(b) => b.len(),
+            Self::U8(_) | Self::I8(_) | Self::Bool(_) => 1,
+            Self::U16(_) | Self::I16(_) => 2,
+            Self::U32(_) | Self::I32(_) | Self::F32(_) => 4,
+            Self::U64(_) | Self::I64(_) | Self::F64(_) => 8,
+            Self::Uuid(_) => 16,
+        }
+    }
+
+    fn encode_into(&self, dst: &mut Vec<u8>) -> usize {
+        match self {
+            Self::Str(s) => s.encode_into(dst),
+            Self::Bytes(b) => b.encode_into(dst),
+            Self::U8(n) => n.encode_into(dst),
+            Self::U16(n) => n.encode_into(dst),
+            Self::U32(n) => n.encode_into(dst),
+            Self::U64(n) => n.encode_into(dst),
+            Self::I8(n) => n.encode_into(dst),
+            Self::I16(n) => n.encode_into(dst),
+            Self::I32(n) => n.encode_into(dst),
+            Self::I64(n) => n.encode_into(dst),
+            Self::F32(x) => x.encode_into(dst),
+            Self::F64(x) => x.encode_into(dst),
+            Self::Bool(b) => b.encode_into(dst),
+            Self::Uuid(u) => u.encode_into(dst),
+        }
+    }
+
+    fn encoded_desc_len(&self) -> usize {
+        match self {
+            Self::Str(s) => s.encoded_desc_len(),
+            Self::Bytes(b) => b.encoded_desc_len(),
+            _ => self.encoded_len(),
+        }
+    }
+
+    fn encode_desc_into(&self, dst: &mut Vec<u8>) -> usize {
+        match self {
+            Self::Str(s) => s.encode_desc_into(dst),
+            Self::Bytes(b) => b.encode_desc_into(dst),
+            _ => {
+                let start = dst.len();
+                let n = self.encode_into(dst);
+                complement_from(dst, start);
+                n
+            }
+        }
+    }
+}
+
+/// The ordered field types of a composite key.
+///
+/// ```rust
+/// use lexkey::encode_composite;
+/// use lexkey::schema::{FieldType, KeySchema, Value};
+/// use uuid::Uuid;
+///
+/// let schema = KeySchema::new([FieldType::Str, FieldType::I64, FieldType::Uuid]);
+/// let key = encode_composite!("orders", -5i64, Uuid::nil());
+///
+/// let values = schema.decode(key.as_bytes())?;
+/// assert_eq!(values, [Value::Str("orders".into()), Value::I64(-5), Value::Uuid(Uuid::nil())]);
+/// assert_eq!(schema.encode(&values), Some(key));
+/// # Ok::<(), lexkey::DecodeError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KeySchema {
+    fields: Vec<FieldType>,
+    names: Vec<String>,
+    orders: Vec<SortOrder>,
+    version: Option<u8>,
+}
+
+impl KeySchema {
+    /// Create a schema from field types in key order.
+    #[must_use]
+    pub fn new(fields: impl Into<Vec<FieldType>>) -> Self {
+        let fields = fields.into();
+        Self {
+            orders: vec![SortOrder::Asc; fields.len()],
+            fields,
+            names: Vec::new(),
+            version: None,
+        }
+    }
+
+    /// Create a schema from named fields in key order.
+    ///
+    /// Names label fields in decoded output such as `decode_to_json`; they do not affect the
+    /// key bytes.
+    ///
+    /// ```rust
+    /// use lexkey::schema::{FieldType, KeySchema};
+    ///
+    /// let schema = KeySchema::named([("tenant", FieldType::Str), ("ts", FieldType::I64)]);
+    /// assert_eq!(schema.fields(), [FieldType::Str, FieldType::I64]);
+    /// assert_eq!(schema.field_name(1), Some("ts"));
+    /// ```
+    #[must_use]
+    pub fn named<S: Into<String>>(fields: impl IntoIterator<Item = (S, FieldType)>) -> Self {
+        let (names, fields): (Vec<String>, Vec<FieldType>) = fields
+            .into_iter()
+            .map(|(name, field)| (name.into(), field))
+            .unzip();
+        Self {
+            orders: vec![SortOrder::Asc; fields.len()],
+            fields,
+            names,
+            version: None,
+        }
+    }
+
+    /// Prefix every key with the schema version byte `version`.
+    ///
+    /// ```rust
+    /// use lexkey::schema::{key_version, FieldType, KeySchema, Value};
+    ///
+    /// let v2 = KeySchema::new([FieldType::Str, FieldType::U32]).versioned(2);
+    /// let key = v2.encode(&[Value::Str("acme".into()), Value::U32(7)]).unwrap();
+    /// assert_eq!(key.to_hex_string(), "0261636d650000000007");
+    /// assert_eq!(key_version(key.as_bytes()), Some(2));
+    /// ```
+    #[must_use]
+    pub fn versioned(mut self, version: u8) -> Self {
+        self.version = Some(version);
+        self
+    }
+
+    /// Encode field `index` in `order`; fields are ascending by default.
+    ///
+    /// Descending fields are the byte complement of the ascending form, as written by
+    /// `desc::Desc` and `encode_composite_ordered`.
+    ///
+    /// ```rust
+    /// use lexkey::desc::{Desc, SortOrder};
+    /// use lexkey::encode_composite;
+    /// use lexkey::schema::{FieldType, KeySchema, Value};
+    ///
+    /// let schema = KeySchema::new([FieldType::Str, FieldType::I64]).with_order(1, SortOrder::Desc);
+    /// let key = encode_composite!("acme", Desc(1_700_000_000i64));
+    /// assert_eq!(schema.decode(key.as_bytes())?[1], Value::I64(1_700_000_000));
+    /// # Ok::<(), lexkey::DecodeError>(())
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of range, or if `order` is `Desc` for a `Str` or `Bytes` field:
+    /// descending strings use the escaped layout of `LexKey::encode_string_desc`, which the
+    /// schema's separator-delimited fields cannot hold.
+    #[must_use]
+    pub fn with_order(mut self, index: usize, order: SortOrder) -> Self {
+        assert!(
+            order == SortOrder::Asc || self.fields[index].width().is_some(),
+            "only fixed-width fields can be descending"
+        );
+        self.orders[index] = order;
+        self
+    }
+
+    /// The direction of field `index`; `Asc` for an index out of range.
+    #[inline]
+    #[must_use]
+    pub fn order(&self, index: usize) -> SortOrder {
+        self.orders.get(index).copied().unwrap_or_default()
+    }
+
+    /// The version byte keys start with, or `None` for an unversioned schema.
+    #[inline]
+    #[must_use]
+    pub fn version(&self) -> Option<u8> {
+        self.version
+    }
+
+    /// Pick the versioned schema whose version matches the first byte of `key`.
+    #[must_use]
+    pub fn for_key<'a>(
+        schemas: impl IntoIterator<Item = &'a Self>,
+        key: &[u8],
+    ) -> Option<&'a Self> {
+        let version = key_version(key)?;
+        schemas.into_iter().find(|s| s.version == Some(version))
+    }
+
+    /// The field types in key order.
+    #[inline]
+    #[must_use]
+    pub fn fields(&self) -> &[FieldType] {
+        &self.fields
+    }
+
+    /// The name of field `index`, or `None` for schemas made with `new` or an index out of range.
+    #[inline]
+    #[must_use]
+    pub fn field_name(&self, index: usize) -> Option<&str> {
+        self.names.get(index).map(String::as_str)
+    }
+
+    /// The name of field `index`, falling back to the index itself for unnamed fields.
+    pub(crate) fn label(&self, index: usize) -> String {
+        self.field_name(index)
+            .map_or_else(|| index.to_string(), str::to_owned)
+    }
+
+    /// Decode every field of `key`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DecodeError` if the version byte does not match a versioned schema, a field is
+    /// truncated or invalid, a separator is missing between fields, or bytes remain after the last
+    /// field. Offsets are relative to the start of `key`.
+    pub fn decode(&self, key: &[u8]) -> Result<Vec<Value>, DecodeError> {
+        let mut dec = Decoder::new(key);
+        let values = self.decode_fields(&mut dec, None)?;
+        dec.finish()?;
+        Ok(values)
+    }
+
+    /// Decode the schema's fields from the front of `key` and return the rest as an opaque tail.
+    ///
+    /// Fields appended by a newer layout follow a separator after the last known field; the tail
+    /// is everything after that separator, and is empty when the key ends with the last field.
+    /// Keys written by the same layout decode exactly as with `decode`.
+    ///
+    /// ```rust
+    /// use lexkey::encode_composite;
+    /// use lexkey::schema::{FieldType, KeySchema, Value};
+    ///
+    /// let v1 = KeySchema::new([FieldType::Str, FieldType::U32]);
+    /// let written_by_v2 = encode_composite!("acme", 7u32, "eu-west");
+    ///
+    /// let (values, tail) = v1.decode_with_tail(written_by_v2.as_bytes())?;
+    /// assert_eq!(values, [Value::Str("acme".into()), Value::U32(7)]);
+    /// assert_eq!(tail, b"eu-west");
+    /// # Ok::<(), lexkey::DecodeError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DecodeError` if the version byte does not match a versioned schema, a field is
+    /// truncated or invalid, or a separator is missing between fields or before the tail.
+    pub fn decode_with_tail<'a>(
+        &self,
+        key: &'a [u8],
+    ) -> Result<(Vec<Value>, &'a [u8]), DecodeError> {
+        let mut dec = Decoder::new(key);
+        let values = self.decode_fields(&mut dec, None)?;
+        if !dec.is_empty() {
+            dec.expect_separator()?;
+        }
+        Ok((values, dec.remaining()))
+    }
+
+    /// Decode the version byte and every field, recording each field's byte range in `spans`.
+    fn decode_fields(
+        &self,
+        dec: &mut Decoder<'_>,
+        mut spans: Option<&mut Vec<Range<usize>>>,
+    ) -> Result<Vec<Value>, DecodeError> {
+        if let Some(version) = self.version {
+            if dec.decode_u8()? != version {
+                return Err(DecodeError::Invalid {
+                    offset: 0,
+                    expected: "schema version",
+                });
+            }
+        }
+        let mut values = Vec::with_capacity(self.fields.len());
+        for (i, &field) in self.fields.iter().enumerate() {
+            if i > 0 {
+                dec.expect_separator()?;
+            }
+            let start = dec.position();
+            values.push(decode_ordered(dec, field, self.orders[i])?);
+            if let Some(spans) = spans.as_deref_mut() {
+                spans.push(start..dec.position());
+            }
+        }
+        Ok(values)
+    }
+
+    /// Encode `values` as a composite key, or `None` if they do not match the schema's field
+    /// count and types.
+    #[must_use]
+    pub fn encode(&self, values: &[Value]) -> Option<LexKey> {
+        if values.len() != self.fields.len() {
+            return None;
+        }
+        self.encode_leading(values).map(LexKey::from_bytes)
+    }
+
+    /// Encode the leading fields `values` as a filter key shared by every key that starts with
+    /// them, or `None` if they are not the schema's first `values.len()` field types in order.
+    ///
+    /// The result is the version byte and the fields exactly as `encode` writes them, followed by
+    /// a separator when fields remain after them, so it is a byte prefix of precisely the keys whose leading
+    /// fields equal `values`: a `Str` field `acme` does not match keys for `acme2`. Equal leading
+    /// values always give equal filter keys, which makes the result usable as a bloom filter key
+    /// or, with `KeyRange::prefix`, as the bounds of a scan.
+    ///
+    /// ```rust
+    /// use lexkey::schema::{FieldType, KeySchema, Value};
+    /// use lexkey::KeyRange;
+    ///
+    /// let schema = KeySchema::new([FieldType::Str, FieldType::U32, FieldType::U64]);
+    /// let tenant = schema.filter_prefix(&[Value::Str("acme".into())]).unwrap();
+    /// assert_eq!(tenant.as_bytes(), b"acme\x00");
+    ///
+    /// let key = schema
+    ///     .encode(&[Value::Str("acme".into()), Value::U32(7), Value::U64(1)])
+    ///     .unwrap();
+    /// assert!(KeyRange::prefix(tenant.as_bytes()).contains_key(key.as_bytes()));
+    /// assert_eq!(schema.filter_prefix(&[Value::U32(7)]), None);
+    /// ```
+    #[must_use]
+    pub fn filter_prefix(&self, values: &[Value]) -> Option<LexKey> {
+        let mut buf = self.encode_leading(values)?;
+        if !values.is_empty() && values.len() < self.fields.len() {
+            buf.push(LexKey::SEPARATOR);
+        }
+        Some(LexKey::from_bytes(buf))
+    }
+
+    /// Encode `values` as the schema's leading fields, with the version byte, or `None` if they
+    /// do not match the first `values.len()` field types.
+    fn encode_leading(&self, values: &[Value]) -> Option<Vec<u8>> {
+        let matches = values.len() <= self.fields.len()
+            && values
+                .iter()
+                .zip(&self.fields)
+                .all(|(v, &t)| v.field_type() == t);
+        if !matches {
+            return None;
+        }
+        let parts: Vec<(&dyn Encodable, SortOrder)> = values
+            .iter()
+            .zip(&self.orders)
+            .map(|(v, &order)| (v as &dyn Encodable, order))
+            .collect();
+        let key = LexKey::encode_composite_ordered(&parts);
+        let mut buf = Vec::with_capacity(1 + key.as_bytes().len());
+        buf.extend(self.version);
+        buf.extend_from_slice(key.as_bytes());
+        Some(buf)
+    }
+}
+
+/// The version byte of a key written by a versioned schema: its first byte.
+///
+/// Only meaningful when every layout of the table is versioned; an unversioned key's first byte
+/// is part of its first field.
+#[inline]
+#[must_use]
+pub fn key_version(key: &[u8]) -> Option<u8> {
+    key.first().copied()
+}
+
+/// Error returned by `reencode` when a key cannot be moved to the new layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReencodeError {
+    /// The key does not decode under the old schema.
+    Decode(DecodeError),
+    /// The values do not match the field types of the new schema.
+    SchemaMismatch,
+}
+
+impl fmt::Display for ReencodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Decode(e) => write!(f, "key does not match the old schema: {e}"),
+            Self::SchemaMismatch => f.write_str("values do not match the new schema"),
+        }
+    }
+}
+
+impl std::error::Error for ReencodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Decode(e) => Some(e),
+            Self::SchemaMismatch => None,
+        }
+    }
+}
+
+impl From<DecodeError> for ReencodeError {
+    fn from(e: DecodeError) -> Self {
+        Self::Decode(e)
+    }
+}
+
+/// Rewrite `key` from `old` to `new` when both list the same field types, such as a version bump.
+///
+/// ```rust
+/// use lexkey::schema::{reencode, FieldType, KeySchema, Value};
+///
+/// let fields = [FieldType::Str, FieldType::U32];
+/// let v1 = KeySchema::new(fields);
+/// let v2 = KeySchema::new(fields).versioned(2);
+///
+/// let old = v1.encode(&[Value::Str("acme".into()), Value::U32(7)]).unwrap();
+/// let new = reencode(&v1, &v2, old.as_bytes())?;
+/// assert_eq!(v2.decode(new.as_bytes())?, v1.decode(old.as_bytes())?);
+/// # Ok::<(), lexkey::schema::ReencodeError>(())
+/// ```
+///
+/// # Errors
+///
+/// Returns `ReencodeError::Decode` if `key` does not decode under `old` and
+/// `ReencodeError::SchemaMismatch` if the decoded values do not fit `new`.
+pub fn reencode(old: &KeySchema, new: &KeySchema, key: &[u8]) -> Result<LexKey, ReencodeError> {
+    reencode_with(old, new, key, |values| values)
+}
+
+/// Rewrite `key` from `old` to `new`, passing the decoded values through `migrate` first.
+///
+/// `migrate` is the hook for layout changes: reorder, convert, add or drop fields so the result
+/// matches `new`.
+///
+/// ```rust
+/// use lexkey::schema::{reencode_with, FieldType, KeySchema, Value};
+///
+/// let v1 = KeySchema::new([FieldType::Str, FieldType::U32]).versioned(1);
+/// let v2 = KeySchema::new([FieldType::Str, FieldType::U64, FieldType::Bool]).versioned(2);
+///
+/// let old = v1.encode(&[Value::Str("acme".into()), Value::U32(7)]).unwrap();
+/// let new = reencode_with(&v1, &v2, old.as_bytes(), |mut values| {
+///     if let Value::U32(n) = values[1] {
+///         values[1] = Value::U64(n.into());
+///     }
+///     values.push(Value::Bool(false));
+///     values
+/// })?;
+/// assert_eq!(
+///     v2.decode(new.as_bytes())?,
+///     [Value::Str("acme".into()), Value::U64(7), Value::Bool(false)]
+/// );
+/// # Ok::<(), lexkey::schema::ReencodeError>(())
+/// ```
+///
+/// # Errors
+///
+/// Returns `ReencodeError::Decode` if `key` does not decode under `old` and
+/// `ReencodeError::SchemaMismatch` if the migrated values do not fit `new`.
+pub fn reencode_with(
+    old: &KeySchema,
+    new: &KeySchema,
+    key: &[u8],
+    migrate: impl FnOnce(Vec<Value>) -> Vec<Value>,
+) -> Result<LexKey, ReencodeError> {
+    let values = migrate(old.decode(key)?);
+    new.encode(&values).ok_or(ReencodeError::SchemaMismatch)
+}
+
+/// Migrate a keyspace: rewrite the key of every `(key, value)` pair from `old` to `new`.
+///
+/// Each key is decoded with `old`, passed through `mapping` as in `reencode_with` and encoded
+/// with `new`; values pass through untouched. Pairs are processed lazily as the result is
+/// iterated, so a whole store can be streamed into a batch writer. A key that fails to migrate
+/// yields an error in its place and the remaining pairs are still processed.
+///
+/// ```rust
+/// use lexkey::schema::{rewrite_keys, FieldType, KeySchema, Value};
+///
+/// let v1 = KeySchema::new([FieldType::Str, FieldType::U32]).versioned(1);
+/// let v2 = KeySchema::new([FieldType::Str, FieldType::U64]).versioned(2);
+/// let store = vec![
+///     (v1.encode(&[Value::Str("a".into()), Value::U32(1)]).unwrap(), "first"),
+///     (v1.encode(&[Value::Str("b".into()), Value::U32(2)]).unwrap(), "second"),
+/// ];
+///
+/// let widen = |mut values: Vec<Value>| {
+///     if let Value::U32(n) = values[1] {
+///         values[1] = Value::U64(n.into());
+///     }
+///     values
+/// };
+/// let migrated = rewrite_keys(&v1, &v2, widen, store).collect::<Result<Vec<_>, _>>()?;
+/// assert_eq!(
+///     v2.decode(migrated[1].0.as_bytes())?,
+///     [Value::Str("b".into()), Value::U64(2)]
+/// );
+/// assert_eq!(migrated[1].1, "second");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn rewrite_keys<'a, K, V>(
+    old: &'a KeySchema,
+    new: &'a KeySchema,
+    mut mapping: impl FnMut(Vec<Value>) -> Vec<Value> + 'a,
+    pairs: impl IntoIterator<Item = (K, V)> + 'a,
+) -> impl Iterator<Item = Result<(LexKey, V), ReencodeError>> + 'a
+where
+    K: AsRef<[u8]>,
+{
+    pairs.into_iter().map(move |(key, value)| {
+        let key = reencode_with(old, new, key.as_ref(), &mut mapping)?;
+        Ok((key, value))
+    })
+}
+
+/// One decoded field of a key, as returned by `LexKey::explain_parts`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExplainedField {
+    /// The field's name, or its index for schemas made with `KeySchema::new`.
+    pub name: String,
+    /// The decoded value.
+    pub value: Value,
+    /// The field's direction in the key.
+    pub order: SortOrder,
+    /// Byte range of the field's encoding within the key, excluding separators and the version
+    /// byte.
+    pub span: Range<usize>,
+}
+
+impl LexKey {
+    /// Decode this key with `schema` and describe each field as structured data.
+    ///
+    /// The programmatic counterpart of `to_debug_string`: admin UIs can label and highlight each
+    /// field's bytes without parsing a rendered string.
+    ///
+    /// ```rust
+    /// use lexkey::desc::{Desc, SortOrder};
+    /// use lexkey::encode_composite;
+    /// use lexkey::schema::{FieldType, KeySchema, Value};
+    ///
+    /// let schema = KeySchema::named([("tenant", FieldType::Str), ("ts", FieldType::I64)])
+    ///     .with_order(1, SortOrder::Desc);
+    /// let key = encode_composite!("acme", Desc(42i64));
+    ///
+    /// let fields = key.explain_parts(&schema)?;
+    /// assert_eq!(fields[0].name, "tenant");
+    /// assert_eq!(fields[0].span, 0..4);
+    /// assert_eq!(fields[1].value, Value::I64(42));
+    /// assert_eq!((fields[1].order, fields[1].span.clone()), (SortOrder::Desc, 5..13));
+    /// # Ok::<(), lexkey::DecodeError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns the `DecodeError` from `KeySchema::decode`.
+    pub fn explain_parts(&self, schema: &KeySchema) -> Result<Vec<ExplainedField>, DecodeError> {
+        let mut dec = Decoder::new(self.as_bytes());
+        let mut spans = Vec::with_capacity(schema.fields.len());
+        let values = schema.decode_fields(&mut dec, Some(&mut spans))?;
+        dec.finish()?;
+        Ok(values
+            .into_iter()
+            .zip(spans)
+            .enumerate()
+            .map(|(i, (value, span))| ExplainedField {
+                name: schema.label(i),
+                value,
+                order: schema.order(i),
+                span,
+            })
+            .collect())
+    }
+}
+
+/// A field whose decoded value differs between two keys, as returned by `LexKey::diff_with`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldDiff {
+    /// Position of the field in the schema.
+    pub index: usize,
+    /// The field's name, or its index for schemas made with `KeySchema::new`.
+    pub name: String,
+    /// The value in the left key (`self`).
+    pub left: Value,
+    /// The value in the right key (`other`).
+    pub right: Value,
+}
+
+impl LexKey {
+    /// Decode this key and `other` with `schema` and list the fields whose values differ.
+    ///
+    /// The schema-aware counterpart of `diff`: instead of a byte offset it names the fields,
+    /// in key order, so the first entry is the field that decides how the keys sort.
+    ///
+    /// ```rust
+    /// use lexkey::encode_composite;
+    /// use lexkey::schema::{FieldType, KeySchema, Value};
+    ///
+    /// let schema = KeySchema::named([("tenant", FieldType::Str), ("shard", FieldType::U16)]);
+    /// let expected = encode_composite!("acme", 3u16);
+    /// let actual = encode_composite!("acme", 259u16);
+    ///
+    /// let diffs = actual.diff_with(&schema, &expected)?;
+    /// assert_eq!(diffs.len(), 1);
+    /// assert_eq!(diffs[0].name, "shard");
+    /// assert_eq!((&diffs[0].left, &diffs[0].right), (&Value::U16(259), &Value::U16(3)));
+    /// # Ok::<(), lexkey::DecodeError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns the `DecodeError` from `KeySchema::decode` for whichever key fails to decode first.
+    pub fn diff_with(
+        &self,
+        schema: &KeySchema,
+        other: &Self,
+    ) -> Result<Vec<FieldDiff>, DecodeError> {
+        let left = schema.decode(self.as_bytes())?;
+        let right = schema.decode(other.as_bytes())?;
+        Ok(left
+            .into_iter()
+            .zip(right)
+            .enumerate()
+            .filter(|(_, (l, r))| l != r)
+            .map(|(index, (left, right))| FieldDiff {
+                index,
+                name: schema.label(index),
+                left,
+                right,
+            })
+            .collect())
+    }
+}
+
+/// Decode a field written in `order`; descending fields are complemented fixed-width values.
+fn decode_ordered(
+    dec: &mut Decoder<'_>,
+    field: FieldType,
+    order: SortOrder,
+) -> Result<Value, DecodeError> {
+    let (SortOrder::Desc, Some(width)) = (order, field.width()) else {
+        return decode_field(dec, field);
+    };
+    let start = dec.position();
+    let flipped: Vec<u8> = dec.remaining().iter().take(width).map(|b| !b).collect();
+    let value = decode_field(&mut Decoder::new(&flipped), field).map_err(|e| shift(e, start))?;
+    dec.skip(width);
+    Ok(value)
+}
+
+fn decode_field(dec: &mut Decoder<'_>, field: FieldType) -> Result<Value, DecodeError> {
+    Ok(match field {
+        FieldType::Str => Value::Str(read_part(dec, String::decode_from)?),
+        FieldType::Bytes => Value::Bytes(read_part(dec, |src| {
+            let len = src
+                .iter()
+                .position(|&b| b == LexKey::SEPARATOR)
+                .unwrap_or(src.len());
+            let (part, rest) = src.split_at(len);
+            *src = rest;
+            Ok(part.to_vec())
+        })?),
+        FieldType::U8 => Value::U8(dec.decode_u8()?),
+        FieldType::U16 => Value::U16(dec.decode_u16()?),
+        FieldType::U32 => Value::U32(dec.decode_u32()?),
+        FieldType::U64 => Value::U64(dec.decode_u64()?),
+        FieldType::I8 => Value::I8(dec.decode_i8()?),
+        FieldType::I16 => Value::I16(dec.decode_i16()?),
+        FieldType::I32 => Value::I32(dec.decode_i32()?),
+        FieldType::I64 => Value::I64(dec.decode_i64()?),
+        FieldType::F32 => Value::F32(dec.decode_f32()?),
+        FieldType::F64 => Value::F64(dec.decode_f64()?),
+        FieldType::Bool => Value::Bool(dec.decode_bool()?),
+        FieldType::Uuid => Value::Uuid(dec.decode_uuid()?),
+    })
+}
+
+/// Read a separator-terminated field, leaving the separator for the next `expect_separator`.
+fn read_part<T>(
+    dec: &mut Decoder<'_>,
+    read: impl FnOnce(&mut &[u8]) -> Result<T, DecodeError>,
+) -> Result<T, DecodeError> {
+    let start = dec.position();
+    let mut src = dec.remaining();
+    let value = read(&mut src).map_err(|e| match e {
+        DecodeError::Invalid { offset, expected } => DecodeError::Invalid {
+            offset: start + offset,
+            expected,
+        },
+        other => other,
+    })?;
+    let consumed = dec.remaining().len() - src.len();
+    dec.skip(consumed);
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::KeyRange;
+
+    #[test]
+    fn should_decode_every_field_type() {
+        let schema = KeySchema::new(vec![
+            FieldType::Str,
+            FieldType::Bytes,
+            FieldType::U8,
+            FieldType::U16,
+            FieldType::U32,
+            FieldType::U64,
+            FieldType::I8,
+            FieldType::I16,
+            FieldType::I32,
+            FieldType::I64,
+            FieldType::F32,
+            FieldType::F64,
+            FieldType::Bool,
+            FieldType::Uuid,
+        ]);
+        let values = vec![
+            Value::Str("tenant".into()),
+            Value::Bytes(vec![0xde, 0xad]),
+            Value::U8(1),
+            Value::U16(2),
+            Value::U32(3),
+            Value::U64(4),
+            Value::I8(-1),
+            Value::I16(-2),
+            Value::I32(-3),
+            Value::I64(-4),
+            Value::F32(0.5),
+            Value::F64(-0.25),
+            Value::Bool(true),
+            Value::Uuid(Uuid::from_u128(9)),
+        ];
+
+        let key = schema.encode(&values).unwrap();
+
+        assert_eq!(schema.decode(key.as_bytes()), Ok(values));
+    }
+
+    #[test]
+    fn should_allow_empty_trailing_string() {
+        let schema = KeySchema::new([FieldType::U8, FieldType::Str]);
+        let key = crate::encode_composite!(7u8, "");
+        assert_eq!(
+            schema.decode(key.as_bytes()),
+            Ok(vec![Value::U8(7), Value::Str(String::new())])
+        );
+    }
+
+    #[test]
+    fn should_report_structural_errors() {
+        let schema = KeySchema::new([FieldType::Str, FieldType::U16]);
+        assert_eq!(
+            schema.decode(b"ab\x00\x01"),
+            Err(DecodeError::UnexpectedEnd {
+                offset: 3,
+                needed: 2,
+                available: 1
+            })
+        );
+        assert_eq!(
+            schema.decode(b"ab\x00\x00\x01\x00"),
+            Err(DecodeError::TrailingBytes {
+                offset: 5,
+                remaining: 1
+            })
+        );
+        assert_eq!(
+            schema.decode(b"a\xff\x00\x00\x01"),
+            Err(DecodeError::Invalid {
+                offset: 1,
+                expected: "UTF-8 string"
+            })
+        );
+        assert_eq!(schema.encode(&[Value::U16(1), Value::U16(1)]), None);
+        assert_eq!(schema.encode(&[Value::Str("a".into())]), None);
+    }
+
+    #[test]
+    fn should_bound_scans_with_filter_prefixes_of_leading_fields() {
+        let schema = KeySchema::new([FieldType::Str, FieldType::I64, FieldType::U16])
+            .with_order(1, SortOrder::Desc)
+            .versioned(2);
+        let key = |tenant: &str, ts: i64, n: u16| {
+            schema
+                .encode(&[Value::Str(tenant.into()), Value::I64(ts), Value::U16(n)])
+                .unwrap()
+        };
+        let mut keys = [
+            key("acme", 5, 1),
+            key("acme", 5, 2),
+            key("acme", 9, 1),
+            key("acme2", 5, 1),
+            key("acm", 5, 1),
+            key("beta", 5, 1),
+        ];
+        keys.sort();
+
+        let tenant = schema.filter_prefix(&[Value::Str("acme".into())]).unwrap();
+        assert_eq!(tenant.as_bytes(), b"\x02acme\x00");
+        let range = KeyRange::prefix(tenant.as_bytes());
+        let hits: Vec<_> = keys
+            .iter()
+            .filter(|k| range.contains_key(k.as_bytes()))
+            .collect();
+        assert_eq!(
+            hits,
+            [&key("acme", 9, 1), &key("acme", 5, 1), &key("acme", 5, 2)]
+        );
+
+        let leading = [Value::Str("acme".into()), Value::I64(5)];
+        let range = KeyRange::prefix(schema.filter_prefix(&leading).unwrap().as_bytes());
+        let hits: Vec<_> = keys
+            .iter()
+            .filter(|k| range.contains_key(k.as_bytes()))
+            .collect();
+        assert_eq!(hits, [&key("acme", 5, 1), &key("acme", 5, 2)]);
+        assert_eq!(
+            schema.filter_prefix(&leading),
+            schema.filter_prefix(&[Value::Str(String::from("acme")), Value::I64(5)])
+        );
+
+        let full = [Value::Str("acme".into()), Value::I64(5), Value::U16(1)];
+        assert_eq!(schema.filter_prefix(&full), Some(key("acme", 5, 1)));
+        assert_eq!(schema.filter_prefix(&[]).unwrap().as_bytes(), b"\x02");
+    }
+
+    #[test]
+    fn should_reject_filter_prefixes_out_of_field_order_or_count() {
+        let schema = KeySchema::new([FieldType::Str, FieldType::U32]);
+        assert_eq!(schema.filter_prefix(&[Value::U32(1)]), None);
+        assert_eq!(
+            schema.filter_prefix(&[Value::U32(1), Value::Str("acme".into())]),
+            None
+        );
+        assert_eq!(
+            schema.filter_prefix(&[Value::Str("a".into()), Value::U32(1), Value::U32(2)]),
+            None
+        );
+        assert_eq!(schema.filter_prefix(&[]).unwrap().as_bytes(), b"");
+    }
+
+    #[test]
+    fn should_decode_keys_with_appended_fields_under_the_old_schema() {
+        let old = KeySchema::new([FieldType::Str, FieldType::U16]).versioned(3);
+        let new = KeySchema::new([FieldType::Str, FieldType::U16, FieldType::Uuid]).versioned(3);
+        let id = Uuid::from_u128(0x0102);
+        let key = new
+            .encode(&[Value::Str("acme".into()), Value::U16(9), Value::Uuid(id)])
+            .unwrap();
+
+        let (values, tail) = old.decode_with_tail(key.as_bytes()).unwrap();
+        assert_eq!(values, [Value::Str("acme".into()), Value::U16(9)]);
+        assert_eq!(tail, id.as_bytes());
+        assert_eq!(
+            KeySchema::new([FieldType::Uuid]).decode(tail),
+            Ok(vec![Value::Uuid(id)])
+        );
+        assert!(matches!(
+            old.decode(key.as_bytes()),
+            Err(DecodeError::TrailingBytes { offset: 8, .. })
+        ));
+
+        let own = old
+            .encode(&[Value::Str("acme".into()), Value::U16(9)])
+            .unwrap();
+        assert_eq!(old.decode_with_tail(own.as_bytes()), Ok((values, &b""[..])));
+        assert_eq!(
+            old.decode_with_tail(b"\x03acme\x00\x00\x09\x01"),
+            Err(DecodeError::Invalid {
+                offset: 8,
+                expected: "separator"
+            })
+        );
+    }
+
+    #[test]
+    fn should_check_and_detect_schema_versions() {
+        let v1 = KeySchema::new([FieldType::Str]).versioned(1);
+        let v2 = KeySchema::new([FieldType::Str, FieldType::U8]).versioned(2);
+        let key = v2.encode(&[Value::Str("a".into()), Value::U8(3)]).unwrap();
+        assert_eq!(key.as_bytes(), b"\x02a\x00\x03");
+        assert_eq!(v2.version(), Some(2));
+
+        let schemas = [v1.clone(), v2.clone()];
+        assert_eq!(KeySchema::for_key(&schemas, key.as_bytes()), Some(&v2));
+        assert_eq!(KeySchema::for_key(&schemas, b"\x03a"), None);
+        assert_eq!(KeySchema::for_key(&schemas, b""), None);
+
+        assert_eq!(
+            v1.decode(key.as_bytes()),
+            Err(DecodeError::Invalid {
+                offset: 0,
+                expected: "schema version"
+            })
+        );
+        assert!(matches!(
+            v1.decode(b""),
+            Err(DecodeError::UnexpectedEnd { offset: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn should_report_reencode_failures() {
+        let v1 = KeySchema::new([FieldType::Str, FieldType::U8]).versioned(1);
+        let v2 = KeySchema::new([FieldType::Str, FieldType::U16]).versioned(2);
+        let key = v1.encode(&[Value::Str("a".into()), Value::U8(3)]).unwrap();
+
+        assert_eq!(
+            reencode(&v1, &v2, key.as_bytes()),
+            Err(ReencodeError::SchemaMismatch)
+        );
+        assert!(matches!(
+            reencode(&v2, &v1, key.as_bytes()),
+            Err(ReencodeError::Decode(DecodeError::Invalid { .. }))
+        ));
+        let migrated = reencode_with(&v1, &v2, key.as_bytes(), |mut values| {
+            values[1] = Value::U16(3);
+            values
+        })
+        .unwrap();
+        assert_eq!(migrated.as_bytes(), b"\x02a\x00\x00\x03");
+    }
+
+    #[test]
+    fn should_migrate_a_keyspace_and_report_bad_keys() {
+        let v1 = KeySchema::new([FieldType::Str, FieldType::U8]);
+        let v2 = KeySchema::new([FieldType::U8, FieldType::Str]).versioned(2);
+        let good = |name: &str, n: u8| {
+            v1.encode(&[Value::Str(name.into()), Value::U8(n)])
+                .unwrap()
+                .as_bytes()
+                .to_vec()
+        };
+        let store = vec![
+            (good("alice", 2), 10),
+            (b"bob".to_vec(), 20),
+            (good("carol", 1), 30),
+        ];
+
+        let mut swaps = 0;
+        let migrated: Vec<_> = rewrite_keys(
+            &v1,
+            &v2,
+            |mut values| {
+                swaps += 1;
+                values.swap(0, 1);
+                values
+            },
+            store,
+        )
+        .collect();
+
+        assert_eq!(swaps, 2);
+        assert_eq!(migrated.len(), 3);
+        assert_eq!(
+            migrated[0],
+            Ok((LexKey::from_bytes(&b"\x02\x02\x00alice"[..]), 10))
+        );
+        assert_eq!(
+            migrated[1],
+            Err(ReencodeError::Decode(DecodeError::UnexpectedEnd {
+                offset: 3,
+                needed: 1,
+                available: 0
+            }))
+        );
+        assert_eq!(
+            migrated[2],
+            Ok((LexKey::from_bytes(&b"\x02\x01\x00carol"[..]), 30))
+        );
+
+        let mismatched: Vec<_> = rewrite_keys(&v1, &v2, |v| v, [(good("dave", 4), ())]).collect();
+        assert_eq!(mismatched, [Err(ReencodeError::SchemaMismatch)]);
+    }
+
+    #[test]
+    fn should_decode_descending_fields() {
+        let schema = KeySchema::new([FieldType::Str, FieldType::I64, FieldType::U16])
+            .with_order(1, SortOrder::Desc)
+            .versioned(1);
+        assert_eq!(schema.order(1), SortOrder::Desc);
+        assert_eq!(schema.order(9), SortOrder::Asc);
+        let key = |ts: i64| {
+            schema
+                .encode(&[Value::Str("a".into()), Value::I64(ts), Value::U16(3)])
+                .unwrap()
+        };
+        assert!(key(20) < key(10));
+        assert_eq!(
+            key(20).as_bytes(),
+            [
+                &b"\x01a\x00"[..],
+                LexKey::encode_i64_desc(20).as_bytes(),
+                b"\x00\x00\x03",
+            ]
+            .concat()
+        );
+        assert_eq!(
+            schema.decode(key(-5).as_bytes()),
+            Ok(vec![Value::Str("a".into()), Value::I64(-5), Value::U16(3)])
+        );
+        assert_eq!(
+            schema.decode(&key(1).as_bytes()[..6]),
+            Err(DecodeError::UnexpectedEnd {
+                offset: 3,
+                needed: 8,
+                available: 3
+            })
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "only fixed-width fields can be descending")]
+    fn should_reject_descending_variable_length_fields() {
+        let _ = KeySchema::new([FieldType::Str]).with_order(0, SortOrder::Desc);
+    }
+
+    #[test]
+    fn should_explain_fields_with_spans_that_tile_the_key() {
+        let schema = KeySchema::named([
+            ("tenant", FieldType::Str),
+            ("ts", FieldType::I64),
+            ("tag", FieldType::Bytes),
+            ("id", FieldType::Uuid),
+        ])
+        .with_order(1, SortOrder::Desc)
+        .versioned(7);
+        let values = [
+            Value::Str("acme".into()),
+            Value::I64(-3),
+            Value::Bytes(b"x".to_vec()),
+            Value::Uuid(Uuid::from_u128(5)),
+        ];
+        let key = schema.encode(&values).unwrap();
+        let bytes = key.as_bytes();
+
+        let fields = key.explain_parts(&schema).unwrap();
+        let names: Vec<&str> = fields.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["tenant", "ts", "tag", "id"]);
+        assert_eq!(
+            fields.iter().map(|f| &f.value).collect::<Vec<_>>(),
+            values.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(fields[1].order, SortOrder::Desc);
+        assert_eq!(
+            &bytes[fields[1].span.clone()],
+            LexKey::encode_i64_desc(-3).as_bytes()
+        );
+
+        // Version byte, then each field's span, with exactly one separator between spans.
+        let mut pos = 1;
+        for (i, field) in fields.iter().enumerate() {
+            if i > 0 {
+                assert_eq!(bytes[pos], LexKey::SEPARATOR);
+                pos += 1;
+            }
+            assert_eq!(field.span.start, pos);
+            pos = field.span.end;
+        }
+        assert_eq!(pos, bytes.len());
+
+        let unnamed = KeySchema::new([FieldType::U8]);
+        let fields = LexKey::encode_u8(9).explain_parts(&unnamed).unwrap();
+        assert_eq!(fields[0].name, "0");
+        assert_eq!(fields[0].span, 0..1);
+        assert!(LexKey::encode_u16(9).explain_parts(&unnamed).is_err());
+    }
+
+    #[test]
+    fn should_diff_keys_field_by_field() {
+        let schema = KeySchema::named([
+            ("tenant", FieldType::Str),
+            ("day", FieldType::U32),
+            ("id", FieldType::Uuid),
+        ]);
+        let key = |tenant: &str, day: u32, id: u128| {
+            schema
+                .encode(&[
+                    Value::Str(tenant.into()),
+                    Value::U32(day),
+                    Value::Uuid(Uuid::from_u128(id)),
+                ])
+                .unwrap()
+        };
+        let a = key("acme", 10, 1);
+
+        assert_eq!(a.diff_with(&schema, &a), Ok(vec![]));
+        assert_eq!(
+            a.diff_with(&schema, &key("acme", 11, 2)),
+            Ok(vec![
+                FieldDiff {
+                    index: 1,
+                    name: "day".into(),
+                    left: Value::U32(10),
+                    right: Value::U32(11),
+                },
+                FieldDiff {
+                    index: 2,
+                    name: "id".into(),
+                    left: Value::Uuid(Uuid::from_u128(1)),
+                    right: Value::Uuid(Uuid::from_u128(2)),
+                },
+            ])
+        );
+        // The byte-level diff counts the zero bytes inside `day` as separators.
+        assert_eq!(a.diff(&key("acme", 11, 2)).part, 4);
+        assert!(a
+            .diff_with(&schema, &LexKey::encode_string("acme"))
+            .is_err());
+    }
+
+    #[test]
+    fn should_render_values_for_display() {
+        let rendered: Vec<String> = [
+            Value::Str("a\"b".into()),
+            Value::Bytes(vec![0x01, 0xff]),
+            Value::I64(-3),
+            Value::Bool(false),
+        ]
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+        assert_eq!(rendered, ["\"a\\\"b\"", "0x01ff", "-3", "false"]);
+    }
+}
@@ -0,0 +1,171 @@
+use std::ops::Bound;
+
+use rusqlite::types::{FromSql, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+
+use crate::{KeyRange, LexKey};
+
+/// Binds as a BLOB. `SQLite` compares BLOBs with `memcmp`, so `ORDER BY` and range predicates on a
+/// BLOB primary key follow `LexKey` order.
+impl ToSql for LexKey {
+    #[inline]
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::Borrowed(ValueRef::Blob(self.as_bytes())))
+    }
+}
+
+/// Reads a BLOB column; any other storage class is a type error.
+impl FromSql for LexKey {
+    #[inline]
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value.as_blob().map(LexKey::from)
+    }
+}
+
+/// Return inclusive `(low, high)` parameters for `column BETWEEN ? AND ?`.
+///
+/// An exclusive lower bound `k` becomes `k || 0x00`, the smallest key after `k`, and an unbounded
+/// lower end becomes the empty BLOB. Returns `None` when the upper end is exclusive or unbounded,
+/// since neither has an exact inclusive equivalent; use `where_clause` for those ranges,
+/// including `KeyRange::partition` and `KeyRange::prefix`.
+#[must_use]
+pub fn between_params(range: &KeyRange) -> Option<(LexKey, LexKey)> {
+    let Bound::Included(high) = range.end() else {
+        return None;
+    };
+    let low = match range.start() {
+        Bound::Included(k) => k.clone(),
+        Bound::Excluded(k) => LexKey::encode_first(&[k.as_bytes()]),
+        Bound::Unbounded => LexKey::empty(),
+    };
+    Some((low, high.clone()))
+}
+
+/// Build a `WHERE` fragment restricting `column` to `range`, plus its parameters in order.
+///
+/// The fragment uses anonymous `?` placeholders so it can be spliced into a larger statement.
+/// `column` is quoted as an SQL identifier. The unbounded range yields `1` and no parameters.
+///
+/// ```rust
+/// use lexkey::{sqlite, KeyRange, LexKey};
+/// use rusqlite::{params_from_iter, Connection};
+///
+/// let conn = Connection::open_in_memory().unwrap();
+/// conn.execute("CREATE TABLE kv (k BLOB PRIMARY KEY, v TEXT) WITHOUT ROWID", []).unwrap();
+/// for (tenant, v) in [(&b"acme"[..], "a"), (b"acme", "b"), (b"globex", "c")] {
+///     let k = LexKey::encode_composite(&[tenant, v.as_bytes()]);
+///     conn.execute("INSERT INTO kv VALUES (?1, ?2)", (&k, v)).unwrap();
+/// }
+///
+/// let (clause, params) = sqlite::where_clause("k", &KeyRange::partition(b"acme"));
+/// assert_eq!(clause, r#""k" >= ? AND "k" < ?"#);
+/// let sql = format!("SELECT v FROM kv WHERE {clause} ORDER BY k");
+/// let mut stmt = conn.prepare(&sql).unwrap();
+/// let values: Vec<String> = stmt
+///     .query_map(params_from_iter(params), |row| row.get(0))
+///     .unwrap()
+///     .collect::<Result<_, _>>()
+///     .unwrap();
+/// assert_eq!(values, ["a", "b"]);
+/// ```
+#[must_use]
+pub fn where_clause(column: &str, range: &KeyRange) -> (String, Vec<LexKey>) {
+    let column = format!("\"{}\"", column.replace('"', "\"\""));
+    let mut terms = Vec::with_capacity(2);
+    let mut params = Vec::with_capacity(2);
+    let mut push = |op: &str, key: &LexKey| {
+        terms.push(format!("{column} {op} ?"));
+        params.push(key.clone());
+    };
+    match range.start() {
+        Bound::Included(k) => push(">=", k),
+        Bound::Excluded(k) => push(">", k),
+        Bound::Unbounded => {}
+    }
+    match range.end() {
+        Bound::Included(k) => push("<=", k),
+        Bound::Excluded(k) => push("<", k),
+        Bound::Unbounded => {}
+    }
+    if terms.is_empty() {
+        return ("1".to_owned(), params);
+    }
+    (terms.join(" AND "), params)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::Connection;
+
+    fn key(raw: &[u8]) -> LexKey {
+        LexKey::from(raw)
+    }
+
+    #[test]
+    fn should_roundtrip_keys_as_blobs() {
+        let conn = Connection::open_in_memory().unwrap();
+        let k = LexKey::encode_composite(&[b"t", b"\x00\xff"]);
+
+        let back: LexKey = conn.query_row("SELECT ?1", [&k], |row| row.get(0)).unwrap();
+        let kind: String = conn
+            .query_row("SELECT typeof(?1)", [&k], |row| row.get(0))
+            .unwrap();
+
+        assert_eq!(back, k);
+        assert_eq!(kind, "blob");
+        assert!(conn
+            .query_row("SELECT 'text'", [], |row| row.get::<_, LexKey>(0))
+            .is_err());
+    }
+
+    #[test]
+    fn should_build_between_params_for_inclusive_ends() {
+        let range = KeyRange::new(Bound::Excluded(key(b"a")), Bound::Included(key(b"c")));
+        assert_eq!(between_params(&range), Some((key(b"a\x00"), key(b"c"))));
+
+        let open_low = KeyRange::new(Bound::Unbounded, Bound::Included(key(b"c")));
+        assert_eq!(
+            between_params(&open_low),
+            Some((LexKey::empty(), key(b"c")))
+        );
+
+        assert_eq!(between_params(&KeyRange::partition(b"a")), None);
+    }
+
+    #[test]
+    fn should_filter_rows_with_between_params() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute("CREATE TABLE t (k BLOB PRIMARY KEY) WITHOUT ROWID", [])
+            .unwrap();
+        for raw in [&b"a"[..], b"a\x00", b"b", b"c", b"c\x00"] {
+            conn.execute("INSERT INTO t VALUES (?1)", [key(raw)])
+                .unwrap();
+        }
+
+        let range = KeyRange::new(Bound::Excluded(key(b"a")), Bound::Included(key(b"c")));
+        let (low, high) = between_params(&range).unwrap();
+        let mut stmt = conn
+            .prepare("SELECT k FROM t WHERE k BETWEEN ?1 AND ?2 ORDER BY k")
+            .unwrap();
+        let hits: Vec<LexKey> = stmt
+            .query_map([low, high], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(hits, vec![key(b"a\x00"), key(b"b"), key(b"c")]);
+    }
+
+    #[test]
+    fn should_build_where_clause_for_each_bound_kind() {
+        let range = KeyRange::new(Bound::Excluded(key(b"a")), Bound::Included(key(b"z")));
+        let (clause, params) = where_clause("my\"col", &range);
+        assert_eq!(clause, r#""my""col" > ? AND "my""col" <= ?"#);
+        assert_eq!(params, vec![key(b"a"), key(b"z")]);
+
+        assert_eq!(
+            where_clause("k", &KeyRange::all()),
+            ("1".to_owned(), vec![])
+        );
+    }
+}
@@ -0,0 +1,242 @@
+//! Selectable string encodings for composite parts.
+//!
+//! The default `encode_string` copies UTF-8 bytes as-is, which is compact but relies on the
+//! composite separator: a string containing `0x00` breaks part boundaries, and inside a composite
+//! `"ab" + SEP + x` is compared byte-for-byte against `"abc"`. `StringMode` offers two
+//! self-delimiting alternatives that sort correctly in every position.
+
+use bytes::BufMut;
+
+use crate::{escape, DecodeError, Decoder, Encoder, LexKey};
+
+/// Bytes per group in `StringMode::Chunked`.
+const CHUNK: usize = 8;
+/// Marker after a full group when more groups follow.
+const CHUNK_CONTINUE: u8 = 0xFF;
+
+/// How a string is written into a key.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum StringMode {
+    /// UTF-8 bytes as-is, the same as `encode_string`. Smallest, but relies on separators.
+    #[default]
+    Raw,
+    /// Each `0x00` becomes `00 ff` and the string ends with `00 01`, as `encode_bytes_escaped`.
+    /// Adds two bytes plus one per embedded NUL.
+    Escaped,
+    /// Memcomparable groups: 8 bytes zero-padded, each followed by a marker byte that is `ff`
+    /// when more groups follow and `ff - padding` for the last group. Adds one byte per 8, plus
+    /// padding, regardless of content.
+    Chunked,
+}
+
+fn chunked_len(len: usize) -> usize {
+    (len / CHUNK + 1) * (CHUNK + 1)
+}
+
+fn chunked_into<B: BufMut + ?Sized>(dst: &mut B, bytes: &[u8]) -> usize {
+    let mut chunks = bytes.chunks_exact(CHUNK);
+    for chunk in &mut chunks {
+        dst.put_slice(chunk);
+        dst.put_u8(CHUNK_CONTINUE);
+    }
+    // The last group is always present, possibly empty, so an exact multiple of 8 still ends
+    // with a marker below `ff`.
+    let tail = chunks.remainder();
+    let padding = CHUNK - tail.len();
+    dst.put_slice(tail);
+    dst.put_bytes(0x00, padding);
+    dst.put_u8(CHUNK_CONTINUE - u8::try_from(padding).unwrap_or(0));
+    chunked_len(bytes.len())
+}
+
+impl StringMode {
+    /// Append `s` in this mode and return bytes written.
+    pub fn encode_into<B: BufMut + ?Sized>(self, dst: &mut B, s: &str) -> usize {
+        match self {
+            Self::Raw => {
+                dst.put_slice(s.as_bytes());
+                s.len()
+            }
+            Self::Escaped => escape::escape_into(dst, s.as_bytes()),
+            Self::Chunked => chunked_into(dst, s.as_bytes()),
+        }
+    }
+
+    /// Number of bytes `encode_into` writes for `s`.
+    #[must_use]
+    pub fn encoded_len(self, s: &str) -> usize {
+        match self {
+            Self::Raw => s.len(),
+            Self::Escaped => escape::escaped_len(s.as_bytes()),
+            Self::Chunked => chunked_len(s.len()),
+        }
+    }
+}
+
+impl LexKey {
+    /// Encode a string in the given `StringMode`.
+    ///
+    /// ```rust
+    /// use lexkey::{LexKey, StringMode};
+    ///
+    /// // With raw strings, ("ab", "z") sorts after ("abc", "a") once separators are compared.
+    /// let key = |a: &str, b: &str, mode| {
+    ///     let mut buf = LexKey::encode_string_with(a, mode).as_bytes().to_vec();
+    ///     buf.push(LexKey::SEPARATOR);
+    ///     buf.extend_from_slice(b.as_bytes());
+    ///     LexKey::from_bytes(buf)
+    /// };
+    /// assert!(key("ab", "z", StringMode::Chunked) < key("abc", "a", StringMode::Chunked));
+    /// assert_eq!(
+    ///     LexKey::encode_string_with("ab", StringMode::Chunked).to_hex_string(),
+    ///     "6162000000000000f9"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn encode_string_with(s: &str, mode: StringMode) -> Self {
+        let mut buf = Vec::with_capacity(mode.encoded_len(s));
+        mode.encode_into(&mut buf, s);
+        Self::from_bytes(buf)
+    }
+}
+
+impl Encoder {
+    /// Append a string in the given `StringMode`; see `LexKey::encode_string_with`.
+    #[inline]
+    pub fn encode_string_with_into(&mut self, s: &str, mode: StringMode) -> usize {
+        mode.encode_into(&mut self.buf, s)
+    }
+}
+
+impl Decoder<'_> {
+    /// Decode a string written in `mode`.
+    ///
+    /// `StringMode::Raw` reads up to the next separator like `decode_str`; the self-delimiting
+    /// modes consume exactly their own bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DecodeError::UnexpectedEnd` for truncated input and `DecodeError::Invalid` for a
+    /// malformed group marker, padding or escape, or for bytes that are not UTF-8.
+    pub fn decode_string_with(&mut self, mode: StringMode) -> Result<String, DecodeError> {
+        let start = self.position();
+        let bytes = match mode {
+            StringMode::Raw => return self.decode_str().map(str::to_owned),
+            StringMode::Escaped => self.decode_bytes_escaped()?,
+            StringMode::Chunked => self.decode_chunked()?,
+        };
+        String::from_utf8(bytes).map_err(|_| {
+            self.rewind(start);
+            DecodeError::Invalid {
+                offset: start,
+                expected: "UTF-8 string",
+            }
+        })
+    }
+
+    fn decode_chunked(&mut self) -> Result<Vec<u8>, DecodeError> {
+        let rest = self.remaining();
+        let mut out = Vec::new();
+        for (i, group) in rest.chunks(CHUNK + 1).enumerate() {
+            let offset = self.position() + i * (CHUNK + 1);
+            let Some((&marker, data)) = group.split_last().filter(|_| group.len() == CHUNK + 1)
+            else {
+                return Err(DecodeError::UnexpectedEnd {
+                    offset,
+                    needed: CHUNK + 1,
+                    available: group.len(),
+                });
+            };
+            if marker == CHUNK_CONTINUE {
+                out.extend_from_slice(data);
+                continue;
+            }
+            let padding = usize::from(CHUNK_CONTINUE - marker);
+            if padding > CHUNK || data[CHUNK - padding..].iter().any(|&b| b != 0) {
+                return Err(DecodeError::Invalid {
+                    offset,
+                    expected: "chunked string group",
+                });
+            }
+            out.extend_from_slice(&data[..CHUNK - padding]);
+            self.skip((i + 1) * (CHUNK + 1));
+            return Ok(out);
+        }
+        Err(DecodeError::UnexpectedEnd {
+            offset: self.position() + rest.len(),
+            needed: CHUNK + 1,
+            available: 0,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLES: [&str; 9] = [
+        "",
+        "\0",
+        "a",
+        "a\0",
+        "ab",
+        "abcdefg",
+        "abcdefgh",
+        "abcdefgh\0",
+        "abcdefghi",
+    ];
+
+    #[test]
+    fn should_sort_self_delimiting_modes_inside_composites() {
+        for mode in [StringMode::Escaped, StringMode::Chunked] {
+            let keys: Vec<_> = SAMPLES
+                .iter()
+                .map(|s| {
+                    let mut enc = Encoder::with_capacity(32);
+                    enc.encode_string_with_into(s, mode);
+                    enc.push_separator();
+                    enc.encode_string_into("zzz");
+                    enc.freeze()
+                })
+                .collect();
+            assert!(keys.windows(2).all(|w| w[0] < w[1]), "{mode:?}");
+        }
+    }
+
+    #[test]
+    fn should_round_trip_every_mode() {
+        for mode in [StringMode::Raw, StringMode::Escaped, StringMode::Chunked] {
+            for s in SAMPLES
+                .iter()
+                .filter(|s| mode != StringMode::Raw || !s.contains('\0'))
+            {
+                let key = LexKey::encode_string_with(s, mode);
+                assert_eq!(key.as_bytes().len(), mode.encoded_len(s));
+                let mut dec = Decoder::new(key.as_bytes());
+                if !s.is_empty() || mode != StringMode::Raw {
+                    assert_eq!(dec.decode_string_with(mode).as_deref(), Ok(*s));
+                }
+                assert!(dec.is_empty());
+            }
+        }
+        assert_eq!(
+            LexKey::encode_string_with("abcdefgh", StringMode::Chunked).to_hex_string(),
+            "6162636465666768ff0000000000000000f7"
+        );
+    }
+
+    #[test]
+    fn should_reject_malformed_chunks() {
+        let mut bad_padding = b"ab\x00\x00\x00\x00\x00\x01\xf9".to_vec();
+        assert!(Decoder::new(&bad_padding)
+            .decode_string_with(StringMode::Chunked)
+            .is_err());
+        bad_padding.truncate(5);
+        let mut dec = Decoder::new(&bad_padding);
+        assert!(matches!(
+            dec.decode_string_with(StringMode::Chunked),
+            Err(DecodeError::UnexpectedEnd { .. })
+        ));
+        assert_eq!(dec.position(), 0);
+    }
+}
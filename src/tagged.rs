@@ -0,0 +1,397 @@
+//! Self-describing, type-tagged composite parts.
+//!
+//! Every part starts with a one-byte type tag, so values of different types in the same key
+//! position order by type first, as `null < false < true < int < float < bytes < string < uuid`,
+//! and then by value within a type, like the `FoundationDB` tuple layer. Bytes and strings use
+//! the escaped form of `encode_bytes_escaped`, so every part is self-terminating and a tagged
+//! key decodes without a schema.
+//!
+//! ```rust
+//! use lexkey::tagged::TaggedValue;
+//! use lexkey::LexKey;
+//!
+//! let key = |v: TaggedValue| LexKey::encode_tagged(&[TaggedValue::from("idx"), v]);
+//! assert!(key(TaggedValue::Null) < key(true.into()));
+//! assert!(key(true.into()) < key(i64::MAX.into()));
+//! assert!(key(i64::MAX.into()) < key((-1.5).into()));
+//! assert!(key((-1.5).into()) < key("a".into()));
+//!
+//! let k = key(42i64.into());
+//! assert_eq!(
+//!     lexkey::tagged::decode_tagged(k.as_bytes())?,
+//!     [TaggedValue::from("idx"), TaggedValue::Int(42)]
+//! );
+//! # Ok::<(), lexkey::DecodeError>(())
+//! ```
+
+use uuid::Uuid;
+
+use crate::escape::{escape_into, escaped_len};
+use crate::{
+    Decodable, DecodeError, Decoder, Encodable, EncodeError, Encoder, LexKey, TryEncodable,
+};
+
+/// Type tag for null.
+pub const TAG_NULL: u8 = 0x01;
+/// Type tag for `false`.
+pub const TAG_FALSE: u8 = 0x02;
+/// Type tag for `true`.
+pub const TAG_TRUE: u8 = 0x03;
+/// Type tag for integers, followed by the 8-byte sortable `i64`.
+pub const TAG_INT: u8 = 0x04;
+/// Type tag for floats, followed by the 8-byte sortable `f64`.
+pub const TAG_FLOAT: u8 = 0x05;
+/// Type tag for byte strings, followed by the escaped bytes.
+pub const TAG_BYTES: u8 = 0x06;
+/// Type tag for UTF-8 strings, followed by the escaped bytes.
+pub const TAG_STRING: u8 = 0x07;
+/// Type tag for UUIDs, followed by the 16 raw bytes.
+pub const TAG_UUID: u8 = 0x08;
+/// Type tag for unsigned integers, followed by the 8-byte big-endian `u64`.
+///
+/// Reserved for `value::Value`: `TaggedValue` has no unsigned variant, so `decode_tagged`
+/// rejects this tag and `Decoder::decode_value::<Value>` reads it.
+pub const TAG_UINT: u8 = 0x09;
+/// Type tag for timestamps, followed by the 8-byte sortable UNIX nanoseconds.
+///
+/// Reserved for `value::Value`, like `TAG_UINT`.
+pub const TAG_TIMESTAMP: u8 = 0x0A;
+
+/// A value that carries its type into the key.
+///
+/// All integers share one tag and encode as `i64`, so `3i32` and `3i64` produce the same part.
+/// Keys that may hold unsigned integers or timestamps (`TAG_UINT`, `TAG_TIMESTAMP`) decode with
+/// `value::Value` instead.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TaggedValue {
+    /// `TAG_NULL` alone; sorts before every other value.
+    Null,
+    /// `TAG_FALSE` or `TAG_TRUE` alone.
+    Bool(bool),
+    /// `TAG_INT` and the 8-byte sortable `i64`.
+    Int(i64),
+    /// `TAG_FLOAT` and the 8-byte sortable `f64`; NaN is not encodable.
+    Float(f64),
+    /// `TAG_BYTES` and the escaped bytes.
+    Bytes(Vec<u8>),
+    /// `TAG_STRING` and the escaped UTF-8 bytes.
+    Str(String),
+    /// `TAG_UUID` and the 16 raw bytes.
+    Uuid(Uuid),
+}
+
+impl Encodable for TaggedValue {
+    fn encoded_len(&self) -> usize {
+        1 + match self {
+            Self::Null | Self::Bool(_) => 0,
+            Self::Int(_) | Self::Float(_) => 8,
+            Self::Bytes(b) => escaped_len(b),
+            Self::Str(s) => escaped_len(s.as_bytes()),
+            Self::Uuid(_) => 16,
+        }
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the value is a NaN float; use `TryEncodable` to get an error instead.
+    fn encode_into(&self, dst: &mut Vec<u8>) -> usize {
+        match self {
+            Self::Null => {
+                dst.push(TAG_NULL);
+                1
+            }
+            Self::Bool(false) => {
+                dst.push(TAG_FALSE);
+                1
+            }
+            Self::Bool(true) => {
+                dst.push(TAG_TRUE);
+                1
+            }
+            Self::Int(n) => {
+                dst.push(TAG_INT);
+                1 + LexKey::encode_i64_into(dst, *n)
+            }
+            Self::Float(x) => {
+                dst.push(TAG_FLOAT);
+                1 + LexKey::encode_f64_into(dst, *x)
+            }
+            Self::Bytes(b) => {
+                dst.push(TAG_BYTES);
+                1 + escape_into(dst, b)
+            }
+            Self::Str(s) => {
+                dst.push(TAG_STRING);
+                1 + escape_into(dst, s.as_bytes())
+            }
+            Self::Uuid(u) => {
+                dst.push(TAG_UUID);
+                1 + u.encode_into(dst)
+            }
+        }
+    }
+}
+
+impl TryEncodable for TaggedValue {
+    fn try_encode_into(&self, dst: &mut Vec<u8>) -> Result<usize, EncodeError> {
+        match self {
+            Self::Float(x) if x.is_nan() => Err(EncodeError::NaN),
+            _ => Ok(self.encode_into(dst)),
+        }
+    }
+}
+
+/// Reads one tagged part, dispatching on its tag; a following separator is left unread.
+impl Decodable for TaggedValue {
+    fn decode_from(src: &mut &[u8]) -> Result<Self, DecodeError> {
+        let mut dec = Decoder::new(src);
+        let value = dec.decode_tagged()?;
+        *src = dec.remaining();
+        Ok(value)
+    }
+}
+
+impl From<bool> for TaggedValue {
+    fn from(b: bool) -> Self {
+        Self::Bool(b)
+    }
+}
+
+impl From<i64> for TaggedValue {
+    fn from(n: i64) -> Self {
+        Self::Int(n)
+    }
+}
+
+impl From<i32> for TaggedValue {
+    fn from(n: i32) -> Self {
+        Self::Int(n.into())
+    }
+}
+
+impl From<u32> for TaggedValue {
+    fn from(n: u32) -> Self {
+        Self::Int(n.into())
+    }
+}
+
+impl From<f64> for TaggedValue {
+    fn from(x: f64) -> Self {
+        Self::Float(x)
+    }
+}
+
+impl From<&[u8]> for TaggedValue {
+    fn from(b: &[u8]) -> Self {
+        Self::Bytes(b.to_vec())
+    }
+}
+
+impl From<Vec<u8>> for TaggedValue {
+    fn from(b: Vec<u8>) -> Self {
+        Self::Bytes(b)
+    }
+}
+
+impl From<&str> for TaggedValue {
+    fn from(s: &str) -> Self {
+        Self::Str(s.to_owned())
+    }
+}
+
+impl From<String> for TaggedValue {
+    fn from(s: String) -> Self {
+        Self::Str(s)
+    }
+}
+
+impl From<Uuid> for TaggedValue {
+    fn from(u: Uuid) -> Self {
+        Self::Uuid(u)
+    }
+}
+
+/// `None` becomes `TaggedValue::Null`.
+impl<T: Into<TaggedValue>> From<Option<T>> for TaggedValue {
+    fn from(v: Option<T>) -> Self {
+        v.map_or(Self::Null, Into::into)
+    }
+}
+
+impl LexKey {
+    /// Encode tagged values as a composite, one tagged part per value joined by `SEPARATOR`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a value is a NaN float.
+    #[must_use]
+    pub fn encode_tagged(values: &[TaggedValue]) -> Self {
+        let parts: Vec<&dyn Encodable> = values.iter().map(|v| v as &dyn Encodable).collect();
+        Self::encode_composite_encodables(&parts)
+    }
+}
+
+impl Encoder {
+    /// Append one tagged part and return bytes written.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` is a NaN float.
+    #[inline]
+    pub fn encode_tagged_into(&mut self, value: &TaggedValue) -> usize {
+        self.encode_value_into(value)
+    }
+}
+
+impl Decoder<'_> {
+    /// Decode one tagged part written by `TaggedValue`'s `Encodable` impl.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DecodeError::UnexpectedEnd` if the part is truncated and `DecodeError::Invalid`
+    /// for an unknown tag, a tag reserved for `value::Value`, or a malformed payload.
+    pub fn decode_tagged(&mut self) -> Result<TaggedValue, DecodeError> {
+        let start = self.position();
+        let value = match self.decode_u8()? {
+            TAG_NULL => Ok(TaggedValue::Null),
+            TAG_FALSE => Ok(TaggedValue::Bool(false)),
+            TAG_TRUE => Ok(TaggedValue::Bool(true)),
+            TAG_INT => self.decode_i64().map(TaggedValue::Int),
+            TAG_FLOAT => self.decode_f64().map(TaggedValue::Float),
+            TAG_BYTES => self.decode_bytes_escaped().map(TaggedValue::Bytes),
+            TAG_STRING => self.decode_bytes_escaped().and_then(|b| {
+                String::from_utf8(b)
+                    .map(TaggedValue::Str)
+                    .map_err(|e| DecodeError::Invalid {
+                        offset: start + 1 + e.utf8_error().valid_up_to(),
+                        expected: "UTF-8 string",
+                    })
+            }),
+            TAG_UUID => self.decode_uuid().map(TaggedValue::Uuid),
+            TAG_UINT | TAG_TIMESTAMP => Err(DecodeError::Invalid {
+                offset: start,
+                expected: "TaggedValue type tag (09 and 0a are Value-only)",
+            }),
+            _ => Err(DecodeError::Invalid {
+                offset: start,
+                expected: "type tag",
+            }),
+        };
+        if value.is_err() {
+            self.rewind(start);
+        }
+        value
+    }
+}
+
+/// Decode every part of a key written by `LexKey::encode_tagged`.
+///
+/// # Errors
+///
+/// Returns a `DecodeError` if a part is truncated or invalid, a separator is missing between
+/// parts, or bytes remain after the last part.
+pub fn decode_tagged(key: &[u8]) -> Result<Vec<TaggedValue>, DecodeError> {
+    let mut dec = Decoder::new(key);
+    let mut values = Vec::new();
+    while !dec.is_empty() {
+        if !values.is_empty() {
+            dec.expect_separator()?;
+        }
+        values.push(dec.decode_tagged()?);
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples() -> Vec<TaggedValue> {
+        vec![
+            TaggedValue::Null,
+            false.into(),
+            true.into(),
+            i64::MIN.into(),
+            (-1i64).into(),
+            i64::MAX.into(),
+            f64::NEG_INFINITY.into(),
+            0.5.into(),
+            TaggedValue::from(&b""[..]),
+            TaggedValue::from(&b"\x00"[..]),
+            TaggedValue::from(&b"\x00\x00"[..]),
+            TaggedValue::from(&b"\xff"[..]),
+            "".into(),
+            "a".into(),
+            "a\u{0}b".into(),
+            "b".into(),
+            Uuid::nil().into(),
+            Uuid::max().into(),
+        ]
+    }
+
+    #[test]
+    fn should_order_by_type_then_value() {
+        let keys: Vec<LexKey> = samples()
+            .into_iter()
+            .map(|v| LexKey::encode_tagged(&[v, TaggedValue::Int(0)]))
+            .collect();
+        for pair in keys.windows(2) {
+            assert!(pair[0] < pair[1], "{:?} !< {:?}", pair[0], pair[1]);
+        }
+    }
+
+    #[test]
+    fn should_round_trip_every_type() {
+        let values = samples();
+        let key = LexKey::encode_tagged(&values);
+        assert_eq!(decode_tagged(key.as_bytes()), Ok(values));
+        assert_eq!(decode_tagged(b""), Ok(vec![]));
+        assert_eq!(
+            LexKey::encode_tagged(&["a".into(), 1i64.into()]).to_hex_string(),
+            "0761000100048000000000000001"
+        );
+
+        let mut enc = Encoder::with_capacity(16);
+        let n = enc.encode_tagged_into(&TaggedValue::Str("hi".into()));
+        assert_eq!(enc.as_slice(), b"\x07hi\x00\x01");
+        assert_eq!(n, TaggedValue::Str("hi".into()).encoded_len());
+        assert_eq!(TaggedValue::from(None::<i64>), TaggedValue::Null);
+    }
+
+    #[test]
+    fn should_reject_bad_tags_and_nan() {
+        let mut dec = Decoder::new(b"\xfe");
+        assert_eq!(
+            dec.decode_tagged(),
+            Err(DecodeError::Invalid {
+                offset: 0,
+                expected: "type tag"
+            })
+        );
+        assert_eq!(dec.position(), 0);
+        for tag in [TAG_UINT, TAG_TIMESTAMP] {
+            let part = [tag, 0x80, 0, 0, 0, 0, 0, 0, 0];
+            let mut dec = Decoder::new(&part);
+            assert_eq!(
+                dec.decode_tagged(),
+                Err(DecodeError::Invalid {
+                    offset: 0,
+                    expected: "TaggedValue type tag (09 and 0a are Value-only)"
+                })
+            );
+            assert_eq!(dec.position(), 0);
+            assert!(dec.decode_value::<crate::Value>().is_ok());
+        }
+        assert!(matches!(
+            decode_tagged(b"\x04\x00"),
+            Err(DecodeError::UnexpectedEnd { offset: 1, .. })
+        ));
+
+        let mut buf = Vec::new();
+        assert_eq!(
+            TaggedValue::Float(f64::NAN).try_encode_into(&mut buf),
+            Err(EncodeError::NaN)
+        );
+        assert!(buf.is_empty());
+    }
+}
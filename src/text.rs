@@ -0,0 +1,245 @@
+//! Order-preserving UTF-8 representation for stores that only accept text keys.
+//!
+//! `LexKey::to_base32hex` packs the key's bits five at a time into the base32hex alphabet
+//! (`0-9A-V`, RFC 4648 section 7), whose characters are in ascending ASCII order. Without padding,
+//! byte order of the original keys equals byte order of the text: a shorter key pads its last
+//! group with zero bits and always yields a strictly shorter string, so prefixes still sort first.
+//! The text is 1.6 times the binary length, against 2 times for `to_hex_string`, which is also
+//! order-preserving but less compact.
+//!
+//! `LexKey::from_base32hex` inverts the transform losslessly, and `LexKey::from_hex` parses the
+//! `to_hex_string` and `{:x}`/`{:X}` forms back into a key.
+
+use std::fmt;
+
+use crate::LexKey;
+
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHIJKLMNOPQRSTUV";
+
+/// Error returned when text is not a valid unpadded base32hex key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Base32Error {
+    /// A character outside `0-9A-V` at the given byte offset.
+    InvalidChar { offset: usize },
+    /// The length or trailing bits cannot come from `to_base32hex`.
+    NonCanonical,
+}
+
+impl fmt::Display for Base32Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidChar { offset } => {
+                write!(f, "invalid base32hex character at offset {offset}")
+            }
+            Self::NonCanonical => f.write_str("base32hex text is not a canonical key encoding"),
+        }
+    }
+}
+
+impl std::error::Error for Base32Error {}
+
+/// Error returned when text is not a valid hex key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum HexError {
+    /// A character outside `0-9a-fA-F` at the given byte offset (after any `0x` prefix).
+    InvalidChar { offset: usize },
+    /// An odd number of hex digits.
+    OddLength,
+}
+
+impl fmt::Display for HexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidChar { offset } => write!(f, "invalid hex character at offset {offset}"),
+            Self::OddLength => f.write_str("hex text has an odd number of digits"),
+        }
+    }
+}
+
+impl std::error::Error for HexError {}
+
+fn hex_digit(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        b'A'..=b'F' => Some(c - b'A' + 10),
+        _ => None,
+    }
+}
+
+fn digit(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'A'..=b'V' => Some(c - b'A' + 10),
+        _ => None,
+    }
+}
+
+impl LexKey {
+    /// Encode the key as unpadded base32hex text that sorts like the binary key.
+    ///
+    /// ```rust
+    /// use lexkey::LexKey;
+    ///
+    /// let a = LexKey::encode_composite(&[b"user", b"alice"]);
+    /// let b = LexKey::encode_composite(&[b"user", b"bob"]);
+    /// assert!(a.to_base32hex() < b.to_base32hex());
+    /// assert_eq!(LexKey::from_base32hex(&a.to_base32hex()), Ok(a));
+    /// ```
+    #[must_use]
+    pub fn to_base32hex(&self) -> String {
+        let bytes = self.as_bytes();
+        let mut out = String::with_capacity((bytes.len() * 8).div_ceil(5));
+        let mut acc: u16 = 0;
+        let mut bits = 0;
+        for &b in bytes {
+            acc = (acc << 8) | u16::from(b);
+            bits += 8;
+            while bits >= 5 {
+                bits -= 5;
+                out.push(char::from(ALPHABET[usize::from((acc >> bits) & 0x1F)]));
+            }
+        }
+        if bits > 0 {
+            out.push(char::from(
+                ALPHABET[usize::from((acc << (5 - bits)) & 0x1F)],
+            ));
+        }
+        out
+    }
+
+    /// Decode text produced by `to_base32hex`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Base32Error::InvalidChar` for characters outside `0-9A-V` (lowercase is rejected
+    /// because it would sort differently) and `Base32Error::NonCanonical` when the length or the
+    /// padding bits of the last character could not have been produced by `to_base32hex`.
+    pub fn from_base32hex(text: &str) -> Result<Self, Base32Error> {
+        let mut buf = Vec::with_capacity(text.len() * 5 / 8);
+        let mut acc: u16 = 0;
+        let mut bits = 0;
+        for (offset, c) in text.bytes().enumerate() {
+            let d = digit(c).ok_or(Base32Error::InvalidChar { offset })?;
+            acc = (acc << 5) | u16::from(d);
+            bits += 5;
+            if bits >= 8 {
+                bits -= 8;
+                buf.push(((acc >> bits) & 0xFF) as u8);
+            }
+        }
+        // A full character of leftover bits, or non-zero padding, never comes from the encoder.
+        if bits >= 5 || acc & ((1 << bits) - 1) != 0 {
+            return Err(Base32Error::NonCanonical);
+        }
+        Ok(Self::from_bytes(buf))
+    }
+
+    /// Parse a key from hex text, as printed by `to_hex_string`, `{:x}`, `{:X}` or `{:#x}`.
+    ///
+    /// Digits may be upper- or lowercase and an optional `0x` prefix is skipped.
+    ///
+    /// ```rust
+    /// use lexkey::LexKey;
+    ///
+    /// let key = LexKey::encode_composite(&[b"tenant", b"alice"]);
+    /// assert_eq!(LexKey::from_hex(&key.to_hex_string()), Ok(key.clone()));
+    /// assert_eq!(LexKey::from_hex(&format!("{key:#X}")), Ok(key));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `HexError::InvalidChar` for a non-hex character and `HexError::OddLength` when
+    /// the digits do not form whole bytes.
+    pub fn from_hex(text: &str) -> Result<Self, HexError> {
+        let digits = text.strip_prefix("0x").unwrap_or(text).as_bytes();
+        if !digits.len().is_multiple_of(2) {
+            return Err(HexError::OddLength);
+        }
+        let mut buf = Vec::with_capacity(digits.len() / 2);
+        for (i, pair) in digits.chunks_exact(2).enumerate() {
+            let hi = hex_digit(pair[0]).ok_or(HexError::InvalidChar { offset: 2 * i })?;
+            let lo = hex_digit(pair[1]).ok_or(HexError::InvalidChar { offset: 2 * i + 1 })?;
+            buf.push((hi << 4) | lo);
+        }
+        Ok(Self::from_bytes(buf))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_match_rfc4648_vectors_without_padding() {
+        for (raw, text) in [
+            ("", ""),
+            ("f", "CO"),
+            ("fo", "CPNG"),
+            ("foo", "CPNMU"),
+            ("foob", "CPNMUOG"),
+            ("fooba", "CPNMUOJ1"),
+            ("foobar", "CPNMUOJ1E8"),
+        ] {
+            assert_eq!(LexKey::encode_string(raw).to_base32hex(), text);
+            assert_eq!(LexKey::from_base32hex(text), Ok(LexKey::encode_string(raw)));
+        }
+    }
+
+    #[test]
+    fn should_preserve_order_including_prefixes() {
+        let mut keys: Vec<LexKey> = [
+            &b""[..],
+            b"\x00",
+            b"\x00\x00",
+            b"\x00\x01",
+            b"\x01",
+            b"a",
+            b"a\x00",
+            b"a\x00b",
+            b"ab",
+            b"\xff",
+            b"\xff\xff\xff",
+        ]
+        .iter()
+        .map(|b| LexKey::from_bytes(b.to_vec()))
+        .collect();
+        keys.sort();
+        let texts: Vec<String> = keys.iter().map(LexKey::to_base32hex).collect();
+        assert!(texts.windows(2).all(|w| w[0] < w[1]), "{texts:?}");
+    }
+
+    #[test]
+    fn should_reject_non_canonical_text() {
+        assert_eq!(
+            LexKey::from_base32hex("CPz"),
+            Err(Base32Error::InvalidChar { offset: 2 })
+        );
+        assert_eq!(LexKey::from_base32hex("C"), Err(Base32Error::NonCanonical));
+        assert_eq!(LexKey::from_base32hex("CP"), Err(Base32Error::NonCanonical));
+    }
+
+    #[test]
+    fn should_round_trip_hex_forms() {
+        let key = LexKey::encode_composite(&[b"t", &[0xab, 0xff]]);
+        for text in [
+            key.to_hex_string(),
+            format!("{key:x}"),
+            format!("{key:X}"),
+            format!("{key:#x}"),
+        ] {
+            assert_eq!(LexKey::from_hex(&text), Ok(key.clone()));
+        }
+        assert_eq!(LexKey::from_hex(""), Ok(LexKey::empty()));
+        assert_eq!(LexKey::from_hex("abc"), Err(HexError::OddLength));
+        assert_eq!(
+            LexKey::from_hex("0x0g"),
+            Err(HexError::InvalidChar { offset: 1 })
+        );
+        assert_eq!(
+            HexError::InvalidChar { offset: 1 }.to_string(),
+            "invalid hex character at offset 1"
+        );
+    }
+}
@@ -0,0 +1,450 @@
+//! Fallible encoding for values that some encoders reject, such as NaN floats.
+//!
+//! `encode_f64` and friends panic on NaN. Servers that key on untrusted numbers use the
+//! `try_` variants here or the `TryEncodable` trait to get an `EncodeError` instead.
+//!
+//! `encode_composite` copies raw parts as-is, so a part containing `0x00` or `0xFF` silently
+//! shifts composite boundaries or collides with range sentinels. `try_encode_composite` checks
+//! parts first and reports the offending one as a `CompositeError`.
+
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use bytes::Bytes;
+use uuid::Uuid;
+
+use crate::desc::Desc;
+use crate::lexkey::SOME_MARKER;
+use crate::{Encodable, Encoder, LexKey, NullOrder, NullsLast, TryEncodable};
+
+/// Error returned when a value has no sortable encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EncodeError {
+    /// A floating-point value was NaN.
+    NaN,
+    /// A value lies outside the range its encoding can represent, such as a timestamp whose
+    /// Unix nanoseconds do not fit in an `i64`.
+    OutOfRange,
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NaN => f.write_str("NaN is not encodable"),
+            Self::OutOfRange => f.write_str("value is outside the encodable range"),
+        }
+    }
+}
+
+impl std::error::Error for EncodeError {}
+
+/// Error returned when a raw composite part contains a reserved byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompositeError {
+    /// Index of the offending part.
+    pub part: usize,
+    /// Offset of the reserved byte within that part.
+    pub offset: usize,
+    /// The reserved byte: `SEPARATOR` (`0x00`) or `END_MARKER` (`0xFF`).
+    pub byte: u8,
+}
+
+impl fmt::Display for CompositeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = if self.byte == LexKey::SEPARATOR {
+            "separator"
+        } else {
+            "end marker"
+        };
+        write!(
+            f,
+            "composite part {} contains the {name} byte 0x{:02x} at offset {}",
+            self.part, self.byte, self.offset
+        )
+    }
+}
+
+impl std::error::Error for CompositeError {}
+
+fn check_parts(parts: &[&[u8]]) -> Result<(), CompositeError> {
+    for (part, bytes) in parts.iter().enumerate() {
+        if let Some(offset) = bytes
+            .iter()
+            .position(|&b| b == LexKey::SEPARATOR || b == LexKey::END_MARKER)
+        {
+            return Err(CompositeError {
+                part,
+                offset,
+                byte: bytes[offset],
+            });
+        }
+    }
+    Ok(())
+}
+
+impl LexKey {
+    /// Encode a composite like `encode_composite`, rejecting parts that contain `0x00` or `0xFF`.
+    ///
+    /// Use this for raw textual parts (identifiers, names). Binary encodings such as numbers and
+    /// UUIDs legitimately contain these bytes; escape them with `encode_bytes_escaped` or build
+    /// typed composites with `encode_composite!` instead.
+    ///
+    /// ```rust
+    /// use lexkey::LexKey;
+    ///
+    /// assert!(LexKey::try_encode_composite(&[b"tenant", b"alice"]).is_ok());
+    /// let err = LexKey::try_encode_composite(&[b"tenant", b"al\x00ice"]).unwrap_err();
+    /// assert_eq!((err.part, err.offset, err.byte), (1, 2, 0x00));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a `CompositeError` naming the first part that contains a reserved byte.
+    pub fn try_encode_composite(parts: &[&[u8]]) -> Result<Self, CompositeError> {
+        check_parts(parts)?;
+        Ok(Self::encode_composite(parts))
+    }
+
+    /// Encode an `f64` like `encode_f64`, returning an error for NaN instead of panicking.
+    ///
+    /// ```rust
+    /// use lexkey::{EncodeError, LexKey};
+    /// assert_eq!(LexKey::try_encode_f64(1.5), Ok(LexKey::encode_f64(1.5)));
+    /// assert_eq!(LexKey::try_encode_f64(f64::NAN), Err(EncodeError::NaN));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `EncodeError::NaN` if `x` is NaN.
+    #[inline]
+    pub fn try_encode_f64(x: f64) -> Result<Self, EncodeError> {
+        if x.is_nan() {
+            return Err(EncodeError::NaN);
+        }
+        Ok(Self::encode_f64(x))
+    }
+
+    /// Encode an `f32` like `encode_f32`, returning an error for NaN instead of panicking.
+    ///
+    /// # Errors
+    ///
+    /// Returns `EncodeError::NaN` if `x` is NaN.
+    #[inline]
+    pub fn try_encode_f32(x: f32) -> Result<Self, EncodeError> {
+        if x.is_nan() {
+            return Err(EncodeError::NaN);
+        }
+        Ok(Self::encode_f32(x))
+    }
+}
+
+impl Encoder {
+    /// Append an `f64` like `encode_f64_into`; on NaN nothing is written.
+    ///
+    /// # Errors
+    ///
+    /// Returns `EncodeError::NaN` if `x` is NaN.
+    #[inline]
+    pub fn try_encode_f64_into(&mut self, x: f64) -> Result<usize, EncodeError> {
+        x.try_encode_into(&mut self.buf)
+    }
+
+    /// Append an `f32` like `encode_f32_into`; on NaN nothing is written.
+    ///
+    /// # Errors
+    ///
+    /// Returns `EncodeError::NaN` if `x` is NaN.
+    #[inline]
+    pub fn try_encode_f32_into(&mut self, x: f32) -> Result<usize, EncodeError> {
+        x.try_encode_into(&mut self.buf)
+    }
+
+    /// Append a composite like `encode_composite_into_buf`, rejecting parts that contain `0x00`
+    /// or `0xFF`; on error nothing is written.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `CompositeError` naming the first part that contains a reserved byte.
+    pub fn try_encode_composite_into_buf(
+        &mut self,
+        parts: &[&[u8]],
+    ) -> Result<usize, CompositeError> {
+        check_parts(parts)?;
+        Ok(self.encode_composite_into_buf(parts))
+    }
+
+    /// Append any `TryEncodable` value; on error nothing is written.
+    ///
+    /// # Errors
+    ///
+    /// Returns the value's `EncodeError`.
+    #[inline]
+    pub fn try_encode_into<T: TryEncodable + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<usize, EncodeError> {
+        value.try_encode_into(&mut self.buf)
+    }
+}
+
+impl TryEncodable for f64 {
+    #[inline]
+    fn try_encode_into(&self, dst: &mut Vec<u8>) -> Result<usize, EncodeError> {
+        if self.is_nan() {
+            return Err(EncodeError::NaN);
+        }
+        Ok(self.encode_into(dst))
+    }
+}
+
+impl TryEncodable for f32 {
+    #[inline]
+    fn try_encode_into(&self, dst: &mut Vec<u8>) -> Result<usize, EncodeError> {
+        if self.is_nan() {
+            return Err(EncodeError::NaN);
+        }
+        Ok(self.encode_into(dst))
+    }
+}
+
+impl<T: TryEncodable + ?Sized> TryEncodable for &T {
+    #[inline]
+    fn try_encode_into(&self, dst: &mut Vec<u8>) -> Result<usize, EncodeError> {
+        (**self).try_encode_into(dst)
+    }
+}
+
+/// Same bytes as `Option<T>`'s `Encodable` impl; on error the marker is removed again.
+impl<T: TryEncodable> TryEncodable for Option<T> {
+    fn try_encode_into(&self, dst: &mut Vec<u8>) -> Result<usize, EncodeError> {
+        try_encode_opt_into(dst, self.as_ref(), NullOrder::First)
+    }
+}
+
+/// Same bytes as `NullsLast<T>`'s `Encodable` impl; on error the marker is removed again.
+impl<T: TryEncodable> TryEncodable for NullsLast<T> {
+    fn try_encode_into(&self, dst: &mut Vec<u8>) -> Result<usize, EncodeError> {
+        try_encode_opt_into(dst, self.0.as_ref(), NullOrder::Last)
+    }
+}
+
+fn try_encode_opt_into<T: TryEncodable>(
+    dst: &mut Vec<u8>,
+    value: Option<&T>,
+    nulls: NullOrder,
+) -> Result<usize, EncodeError> {
+    let Some(v) = value else {
+        dst.push(nulls.none_marker());
+        return Ok(1);
+    };
+    let start = dst.len();
+    dst.push(SOME_MARKER);
+    match v.try_encode_into(dst) {
+        Ok(n) => Ok(1 + n),
+        Err(e) => {
+            dst.truncate(start);
+            Err(e)
+        }
+    }
+}
+
+/// Same bytes as `Desc<T>`'s `Encodable` impl, written once the value has encoded without error;
+/// on error nothing is written.
+impl<T: TryEncodable + Encodable> TryEncodable for Desc<T> {
+    fn try_encode_into(&self, dst: &mut Vec<u8>) -> Result<usize, EncodeError> {
+        let start = dst.len();
+        self.0.try_encode_into(dst)?;
+        dst.truncate(start);
+        Ok(self.0.encode_desc_into(dst))
+    }
+}
+
+// Same bytes as the `Encodable` tuple impls; on error the elements already written are removed
+// again. Implemented up to arity 16.
+macro_rules! impl_try_encodable_tuple {
+    ($first:ident:$first_value:ident $(, $ty:ident:$value:ident)*) => {
+        impl<$first $(, $ty)*> TryEncodable for ($first, $($ty,)*)
+        where
+            $first: TryEncodable,
+            $($ty: TryEncodable,)*
+        {
+            fn try_encode_into(&self, dst: &mut Vec<u8>) -> Result<usize, EncodeError> {
+                let start = dst.len();
+                let ($first_value, $($value,)*) = self;
+                let written = (|| {
+                    $first_value.try_encode_into(dst)?;
+                    $(
+                        dst.push(LexKey::SEPARATOR);
+                        $value.try_encode_into(dst)?;
+                    )*
+                    Ok(())
+                })();
+                match written {
+                    Ok(()) => Ok(dst.len() - start),
+                    Err(e) => {
+                        dst.truncate(start);
+                        Err(e)
+                    }
+                }
+            }
+        }
+    };
+}
+
+impl_try_encodable_tuple!(A:a);
+impl_try_encodable_tuple!(A:a, B:b);
+impl_try_encodable_tuple!(A:a, B:b, C:c);
+impl_try_encodable_tuple!(A:a, B:b, C:c, D:d);
+impl_try_encodable_tuple!(A:a, B:b, C:c, D:d, E:e);
+impl_try_encodable_tuple!(A:a, B:b, C:c, D:d, E:e, F:f);
+impl_try_encodable_tuple!(A:a, B:b, C:c, D:d, E:e, F:f, G:g);
+impl_try_encodable_tuple!(A:a, B:b, C:c, D:d, E:e, F:f, G:g, H:h);
+impl_try_encodable_tuple!(A:a, B:b, C:c, D:d, E:e, F:f, G:g, H:h, I:i);
+impl_try_encodable_tuple!(A:a, B:b, C:c, D:d, E:e, F:f, G:g, H:h, I:i, J:j);
+impl_try_encodable_tuple!(A:a, B:b, C:c, D:d, E:e, F:f, G:g, H:h, I:i, J:j, K:k);
+impl_try_encodable_tuple!(A:a, B:b, C:c, D:d, E:e, F:f, G:g, H:h, I:i, J:j, K:k, L:l);
+impl_try_encodable_tuple!(A:a, B:b, C:c, D:d, E:e, F:f, G:g, H:h, I:i, J:j, K:k, L:l, M:m);
+impl_try_encodable_tuple!(A:a, B:b, C:c, D:d, E:e, F:f, G:g, H:h, I:i, J:j, K:k, L:l, M:m, N:n);
+impl_try_encodable_tuple!(A:a, B:b, C:c, D:d, E:e, F:f, G:g, H:h, I:i, J:j, K:k, L:l, M:m, N:n, O:o);
+impl_try_encodable_tuple!(A:a, B:b, C:c, D:d, E:e, F:f, G:g, H:h, I:i, J:j, K:k, L:l, M:m, N:n, O:o, P:p);
+
+impl<const N: usize> TryEncodable for [u8; N] {
+    #[inline]
+    fn try_encode_into(&self, dst: &mut Vec<u8>) -> Result<usize, EncodeError> {
+        Ok(self.encode_into(dst))
+    }
+}
+
+macro_rules! impl_try_encodable_infallible {
+    ($($ty:ty),+ $(,)?) => {
+        $(
+            impl TryEncodable for $ty {
+                #[inline]
+                fn try_encode_into(&self, dst: &mut Vec<u8>) -> Result<usize, EncodeError> {
+                    Ok(self.encode_into(dst))
+                }
+            }
+        )+
+    };
+}
+
+impl_try_encodable_infallible!(
+    u8,
+    u16,
+    u32,
+    u64,
+    i8,
+    i16,
+    i32,
+    i64,
+    u128,
+    i128,
+    bool,
+    Uuid,
+    str,
+    String,
+    [u8],
+    Vec<u8>,
+    LexKey,
+    Bytes,
+    Ipv4Addr,
+    Ipv6Addr,
+    IpAddr,
+    SocketAddr,
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_reject_nan_without_writing() {
+        let mut enc = Encoder::with_capacity(16);
+        enc.encode_u8_into(7);
+        assert_eq!(enc.try_encode_f64_into(f64::NAN), Err(EncodeError::NaN));
+        assert_eq!(enc.try_encode_f32_into(-f32::NAN), Err(EncodeError::NaN));
+        assert_eq!(enc.try_encode_into(&Some(f64::NAN)), Err(EncodeError::NaN));
+        assert_eq!(enc.as_slice(), &[7]);
+        assert_eq!(LexKey::try_encode_f32(f32::NAN), Err(EncodeError::NaN));
+    }
+
+    #[test]
+    fn should_name_part_with_reserved_byte() {
+        let mut enc = Encoder::with_capacity(16);
+        let err = enc
+            .try_encode_composite_into_buf(&[b"ok", b"", b"x\xff"])
+            .unwrap_err();
+        assert_eq!(
+            err,
+            CompositeError {
+                part: 2,
+                offset: 1,
+                byte: 0xFF
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "composite part 2 contains the end marker byte 0xff at offset 1"
+        );
+        assert!(enc.as_slice().is_empty());
+
+        assert_eq!(enc.try_encode_composite_into_buf(&[b"a", b"b"]), Ok(3));
+        assert_eq!(
+            LexKey::try_encode_composite(&[b"a", b"b"]),
+            Ok(LexKey::encode_composite(&[b"a", b"b"]))
+        );
+    }
+
+    #[test]
+    fn should_match_infallible_encoders() {
+        let mut enc = Encoder::with_capacity(64);
+        assert_eq!(enc.try_encode_f64_into(-2.5), Ok(8));
+        assert_eq!(enc.try_encode_into(&Some(1.0f32)), Ok(5));
+        assert_eq!(enc.try_encode_into("ab"), Ok(2));
+        assert_eq!(enc.try_encode_into(&None::<f64>), Ok(1));
+
+        let mut expected = Vec::new();
+        LexKey::encode_f64_into(&mut expected, -2.5);
+        Some(1.0f32).encode_into(&mut expected);
+        "ab".encode_into(&mut expected);
+        None::<f64>.encode_into(&mut expected);
+        assert_eq!(enc.as_slice(), expected.as_slice());
+        assert_eq!(LexKey::try_encode_f32(0.5), Ok(LexKey::encode_f32(0.5)));
+    }
+
+    #[test]
+    fn should_reject_nan_inside_composites_without_writing() {
+        let mut enc = Encoder::with_capacity(32);
+        enc.encode_u8_into(7);
+        assert_eq!(
+            enc.try_encode_into(&("acme", 1u64, f64::NAN)),
+            Err(EncodeError::NaN)
+        );
+        assert_eq!(
+            enc.try_encode_into(&("acme", Desc(f64::NAN))),
+            Err(EncodeError::NaN)
+        );
+        assert_eq!(
+            enc.try_encode_into(&(Some(f32::NAN),)),
+            Err(EncodeError::NaN)
+        );
+        assert_eq!(enc.try_encode_into(&Desc(f64::NAN)), Err(EncodeError::NaN));
+        assert_eq!(enc.as_slice(), &[7]);
+    }
+
+    #[test]
+    fn should_match_encodable_tuples_and_desc() {
+        let row = ("acme", Desc(2.5f64), Some(1i64));
+        let mut buf = Vec::new();
+        assert_eq!(row.try_encode_into(&mut buf), Ok(row.encoded_len()));
+        assert_eq!(
+            buf,
+            crate::encode_composite!("acme", Desc(2.5f64), Some(1i64)).as_bytes()
+        );
+
+        buf.clear();
+        assert_eq!(Desc(-1.0f64).try_encode_into(&mut buf), Ok(8));
+        assert_eq!(buf, LexKey::encode_f64_desc(-1.0).as_bytes());
+    }
+}
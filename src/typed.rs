@@ -0,0 +1,229 @@
+//! Keys tagged with the table or index they belong to.
+//!
+//! A `TypedKey<T>` is a `LexKey` plus a zero-sized marker type `T`, usually an empty enum or
+//! struct naming a table or index. Keys for different `T` are different types, so comparing an
+//! order key with a user key, or passing an index key to a primary-key store API, fails to
+//! compile. The marker costs nothing at run time.
+//!
+//! Implement `KeyOf<T>` for each type that builds keys for `T`; `TypedKey::<T>::encode` only
+//! accepts those types.
+//!
+//! ```rust
+//! use lexkey::{Encodable, KeyOf, LexKey, TypedKey};
+//!
+//! enum Orders {}
+//! enum Users {}
+//!
+//! struct OrderId(u64);
+//!
+//! impl Encodable for OrderId {
+//!     fn encoded_len(&self) -> usize {
+//!         self.0.encoded_len()
+//!     }
+//!     fn encode_into(&self, dst: &mut Vec<u8>) -> usize {
+//!         self.0.encode_into(dst)
+//!     }
+//! }
+//!
+//! impl KeyOf<Orders> for OrderId {}
+//!
+//! fn load_order(key: &TypedKey<Orders>) -> &[u8] {
+//!     key.as_bytes()
+//! }
+//!
+//! let key = TypedKey::<Orders>::encode(&OrderId(42));
+//! assert_eq!(load_order(&key), LexKey::encode_u64(42).as_bytes());
+//!
+//! // A user key is a different type and is rejected at compile time:
+//! let user = TypedKey::<Users>::from_lexkey(LexKey::from("alice"));
+//! // load_order(&user);
+//! # let _ = user;
+//! ```
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+use crate::{Encodable, LexKey};
+
+/// Marker trait for values that encode keys of table or index `T`.
+///
+/// The `Encodable` impl defines the key layout; implementing `KeyOf<T>` declares which table it
+/// belongs to. A type may build keys for several tables, such as a primary key that is also a
+/// foreign key elsewhere.
+pub trait KeyOf<T: ?Sized>: Encodable {}
+
+/// A `LexKey` for table or index `T`.
+///
+/// Ordering, equality and hashing are those of the underlying bytes, and only defined between
+/// keys of the same `T`. `T` is never stored, so it needs no trait impls of its own.
+pub struct TypedKey<T: ?Sized> {
+    key: LexKey,
+    table: PhantomData<fn() -> T>,
+}
+
+impl<T: ?Sized> TypedKey<T> {
+    /// Encode `value` as a key of `T`.
+    #[must_use]
+    pub fn encode<K: KeyOf<T> + ?Sized>(value: &K) -> Self {
+        let mut buf = Vec::with_capacity(value.encoded_len());
+        value.encode_into(&mut buf);
+        Self::from_lexkey(LexKey::from_bytes(buf))
+    }
+
+    /// Tag an existing key as belonging to `T`, such as a key read back from `T`'s store.
+    ///
+    /// Nothing checks that the bytes have `T`'s layout.
+    #[inline]
+    #[must_use]
+    pub fn from_lexkey(key: LexKey) -> Self {
+        Self {
+            key,
+            table: PhantomData,
+        }
+    }
+
+    /// Borrow the untyped key.
+    #[inline]
+    #[must_use]
+    pub fn as_lexkey(&self) -> &LexKey {
+        &self.key
+    }
+
+    /// Drop the table tag and return the untyped key.
+    #[inline]
+    #[must_use]
+    pub fn into_lexkey(self) -> LexKey {
+        self.key
+    }
+
+    /// Get the raw key bytes.
+    #[inline]
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        self.key.as_bytes()
+    }
+}
+
+impl<T: ?Sized> Clone for TypedKey<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self::from_lexkey(self.key.clone())
+    }
+}
+
+impl<T: ?Sized> PartialEq for TypedKey<T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl<T: ?Sized> Eq for TypedKey<T> {}
+
+impl<T: ?Sized> PartialOrd for TypedKey<T> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: ?Sized> Ord for TypedKey<T> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+impl<T: ?Sized> Hash for TypedKey<T> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key.hash(state);
+    }
+}
+
+/// Formats as `TypedKey<path::to::T>(<to_debug_string>)`.
+impl<T: ?Sized> fmt::Debug for TypedKey<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "TypedKey<{}>({})",
+            std::any::type_name::<T>(),
+            self.key.to_debug_string()
+        )
+    }
+}
+
+impl<T: ?Sized> AsRef<[u8]> for TypedKey<T> {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        self.key.as_bytes()
+    }
+}
+
+impl<T: ?Sized> From<TypedKey<T>> for LexKey {
+    #[inline]
+    fn from(key: TypedKey<T>) -> Self {
+        key.key
+    }
+}
+
+/// Writes the key bytes, so a typed key can be a part of a larger composite.
+impl<T: ?Sized> Encodable for TypedKey<T> {
+    #[inline]
+    fn encoded_len(&self) -> usize {
+        self.key.encoded_len()
+    }
+
+    #[inline]
+    fn encode_into(&self, dst: &mut Vec<u8>) -> usize {
+        self.key.encode_into(dst)
+    }
+
+    #[inline]
+    fn encoded_desc_len(&self) -> usize {
+        self.key.encoded_desc_len()
+    }
+
+    #[inline]
+    fn encode_desc_into(&self, dst: &mut Vec<u8>) -> usize {
+        self.key.encode_desc_into(dst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+
+    enum Orders {}
+    struct Users;
+
+    impl KeyOf<Orders> for u64 {}
+    impl KeyOf<Users> for &str {}
+
+    #[test]
+    fn should_encode_through_the_key_layout() {
+        let key = TypedKey::<Orders>::encode(&42u64);
+        assert_eq!(key.as_lexkey(), &LexKey::encode_u64(42));
+        assert_eq!(key.clone().into_lexkey(), LexKey::encode_u64(42));
+        assert_eq!(LexKey::from(key.clone()), LexKey::encode_u64(42));
+        assert_eq!(key.encoded_len(), 8);
+
+        let user = TypedKey::<Users>::encode(&"alice");
+        assert_eq!(user.as_bytes(), b"alice");
+        assert!(format!("{user:?}").starts_with("TypedKey<"));
+    }
+
+    #[test]
+    fn should_order_like_the_untyped_key() {
+        let keys: BTreeSet<_> = [3u64, 1, 2]
+            .iter()
+            .map(TypedKey::<Orders>::encode)
+            .collect();
+        let raw: Vec<_> = keys.into_iter().map(TypedKey::into_lexkey).collect();
+        assert_eq!(raw, [1u64, 2, 3].map(LexKey::encode_u64).to_vec());
+    }
+}
@@ -0,0 +1,143 @@
+//! 256-bit unsigned integers from `primitive-types`.
+//!
+//! A `U256` encodes as its 32-byte big-endian form, the natural width for Ethereum balances,
+//! storage slots and block-hash-sized counters. Fixed width keeps it valid anywhere inside a
+//! composite, and the `_desc` variants complement it for largest-first scans.
+
+use bytes::BufMut;
+use primitive_types::U256;
+
+use crate::desc::complement_from;
+use crate::{DecodeError, Decoder, Encodable, Encoder, LexKey};
+
+/// Width of an encoded `U256` in bytes.
+pub const U256_LEN: usize = 32;
+
+impl LexKey {
+    /// Encode a `U256` as 32 big-endian bytes, so keys order numerically.
+    ///
+    /// ```rust
+    /// use lexkey::LexKey;
+    /// use primitive_types::U256;
+    ///
+    /// let small = LexKey::encode_u256(U256::from(u128::MAX));
+    /// let large = LexKey::encode_u256(U256::from(u128::MAX) + 1);
+    /// assert!(small < large);
+    /// assert_eq!(large.as_bytes().len(), 32);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn encode_u256(n: U256) -> Self {
+        let mut buf = Vec::with_capacity(U256_LEN);
+        Self::encode_u256_into(&mut buf, n);
+        Self::from_bytes(buf)
+    }
+
+    /// Append the 32-byte big-endian encoding of a `U256` into `dst`.
+    #[inline]
+    pub fn encode_u256_into<B: BufMut + ?Sized>(dst: &mut B, n: U256) -> usize {
+        dst.put_slice(&n.to_big_endian());
+        U256_LEN
+    }
+
+    /// Encode a `U256` so larger values sort first.
+    #[inline]
+    #[must_use]
+    pub fn encode_u256_desc(n: U256) -> Self {
+        let mut buf = Vec::with_capacity(U256_LEN);
+        Self::encode_u256_desc_into(&mut buf, n);
+        Self::from_bytes(buf)
+    }
+
+    /// Append the complemented 32-byte encoding of a `U256` into `dst`.
+    #[inline]
+    pub fn encode_u256_desc_into<B: BufMut + ?Sized>(dst: &mut B, n: U256) -> usize {
+        let mut bytes = n.to_big_endian();
+        complement_from(&mut bytes, 0);
+        dst.put_slice(&bytes);
+        U256_LEN
+    }
+}
+
+impl Encoder {
+    /// Append the 32-byte big-endian encoding of a `U256`.
+    #[inline]
+    pub fn encode_u256_into(&mut self, n: U256) -> usize {
+        LexKey::encode_u256_into(&mut self.buf, n)
+    }
+
+    /// Append the descending 32-byte encoding of a `U256`.
+    #[inline]
+    pub fn encode_u256_desc_into(&mut self, n: U256) -> usize {
+        LexKey::encode_u256_desc_into(&mut self.buf, n)
+    }
+}
+
+impl Decoder<'_> {
+    /// Decode a 32-byte big-endian `U256`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DecodeError::UnexpectedEnd` if fewer than 32 bytes remain.
+    #[inline]
+    pub fn decode_u256(&mut self) -> Result<U256, DecodeError> {
+        self.take::<U256_LEN>().map(|b| U256::from_big_endian(&b))
+    }
+}
+
+impl Encodable for U256 {
+    #[inline]
+    fn encoded_len(&self) -> usize {
+        U256_LEN
+    }
+
+    #[inline]
+    fn encode_into(&self, dst: &mut Vec<u8>) -> usize {
+        LexKey::encode_u256_into(dst, *self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_order_u256_across_word_boundaries() {
+        let values = [
+            U256::zero(),
+            U256::one(),
+            U256::from(u64::MAX),
+            U256::from(u64::MAX) + 1,
+            U256::from(u128::MAX),
+            U256::from(u128::MAX) + 1,
+            U256::MAX - 1,
+            U256::MAX,
+        ];
+        let asc: Vec<_> = values.iter().map(|&n| LexKey::encode_u256(n)).collect();
+        let desc: Vec<_> = values
+            .iter()
+            .map(|&n| LexKey::encode_u256_desc(n))
+            .collect();
+
+        assert!(asc.windows(2).all(|w| w[0] < w[1]));
+        assert!(desc.windows(2).all(|w| w[0] > w[1]));
+        assert_eq!(crate::encode_composite!(values[3]), asc[3]);
+    }
+
+    #[test]
+    fn should_round_trip_through_encoder_and_decoder() {
+        let n = U256::from_dec_str("1000000000000000000000000").unwrap();
+        let mut enc = Encoder::with_capacity(40);
+        enc.encode_u256_into(n);
+        enc.push_separator();
+        enc.encode_u256_desc_into(n);
+        let key = enc.freeze();
+
+        let mut dec = Decoder::new(&key);
+        assert_eq!(dec.decode_u256(), Ok(n));
+        assert_eq!(dec.expect_separator(), Ok(()));
+        assert_eq!(dec.decode_u256(), Ok(!n));
+        assert!(dec.is_empty());
+        assert!(Decoder::new(&[0; 31]).decode_u256().is_err());
+    }
+}
@@ -0,0 +1,143 @@
+//! ULID keys from the `ulid` crate.
+//!
+//! A ULID is a 48-bit millisecond timestamp followed by 80 random bits. Its 16 big-endian bytes
+//! already sort by creation time, so they are written unchanged and compare like the ULIDs
+//! themselves (and like their Crockford base32 strings).
+
+use std::sync::Mutex;
+
+use ::ulid::{Generator, Ulid};
+use bytes::BufMut;
+
+use crate::{DecodeError, Decoder, Encodable, EncodeError, Encoder, LexKey, TryEncodable};
+
+/// Width of an encoded ULID in bytes.
+pub const ULID_LEN: usize = 16;
+
+/// Process-wide generator behind `LexKey::new_ulid`, so IDs increase across threads.
+static GENERATOR: Mutex<Generator> = Mutex::new(Generator::new());
+
+impl LexKey {
+    /// Encode a ULID as its 16 big-endian bytes.
+    ///
+    /// ```rust
+    /// use lexkey::LexKey;
+    /// use ulid::Ulid;
+    ///
+    /// let older = Ulid::from_parts(1_700_000_000_000, u128::MAX >> 48);
+    /// let newer = Ulid::from_parts(1_700_000_000_001, 0);
+    /// assert!(LexKey::encode_ulid(&older) < LexKey::encode_ulid(&newer));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn encode_ulid(ulid: &Ulid) -> Self {
+        Self::from_bytes(ulid.to_bytes().to_vec())
+    }
+
+    /// Append the 16-byte ULID encoding into `dst`.
+    #[inline]
+    pub fn encode_ulid_into<B: BufMut + ?Sized>(dst: &mut B, ulid: &Ulid) -> usize {
+        dst.put_slice(&ulid.to_bytes());
+        ULID_LEN
+    }
+
+    /// Generate a new ULID key for the current time.
+    ///
+    /// Keys come from one process-wide monotonic generator: within the same millisecond the
+    /// random part is incremented, so every call returns a key strictly greater than the last.
+    ///
+    /// ```rust
+    /// use lexkey::LexKey;
+    ///
+    /// let a = LexKey::new_ulid();
+    /// let b = LexKey::new_ulid();
+    /// assert!(a < b);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the 80 random bits overflow within one millisecond.
+    #[must_use]
+    pub fn new_ulid() -> Self {
+        let ulid = GENERATOR
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .generate()
+            .expect("ULID random bits overflowed within one millisecond");
+        Self::encode_ulid(&ulid)
+    }
+}
+
+impl Encoder {
+    /// Append the 16-byte ULID encoding.
+    #[inline]
+    pub fn encode_ulid_into(&mut self, ulid: &Ulid) -> usize {
+        LexKey::encode_ulid_into(&mut self.buf, ulid)
+    }
+}
+
+impl Decoder<'_> {
+    /// Decode a 16-byte ULID.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DecodeError::UnexpectedEnd` if fewer than 16 bytes remain.
+    #[inline]
+    pub fn decode_ulid(&mut self) -> Result<Ulid, DecodeError> {
+        self.take::<ULID_LEN>().map(Ulid::from_bytes)
+    }
+}
+
+impl Encodable for Ulid {
+    #[inline]
+    fn encoded_len(&self) -> usize {
+        ULID_LEN
+    }
+
+    #[inline]
+    fn encode_into(&self, dst: &mut Vec<u8>) -> usize {
+        LexKey::encode_ulid_into(dst, self)
+    }
+}
+
+impl TryEncodable for Ulid {
+    #[inline]
+    fn try_encode_into(&self, dst: &mut Vec<u8>) -> Result<usize, EncodeError> {
+        Ok(self.encode_into(dst))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_order_like_ulid_strings() {
+        let mut ulids = [
+            Ulid::nil(),
+            Ulid::from_parts(0, 1),
+            Ulid::from_parts(1, 0),
+            Ulid::from_parts(1_700_000_000_000, 42),
+            Ulid::from_parts((1 << 48) - 1, u128::MAX),
+        ];
+        ulids.reverse();
+        let mut keys: Vec<_> = ulids.iter().map(LexKey::encode_ulid).collect();
+        keys.sort();
+        ulids.sort_by_key(Ulid::to_string);
+        assert_eq!(
+            keys,
+            ulids.iter().map(LexKey::encode_ulid).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn should_generate_increasing_keys_that_decode() {
+        let keys: Vec<_> = (0..1000).map(|_| LexKey::new_ulid()).collect();
+        assert!(keys.windows(2).all(|w| w[0] < w[1]));
+
+        let ulid = Decoder::new(keys[0].as_bytes()).decode_ulid().unwrap();
+        let mut enc = Encoder::with_capacity(ULID_LEN);
+        enc.encode_ulid_into(&ulid);
+        assert_eq!(enc.as_slice(), keys[0].as_bytes());
+    }
+}
@@ -0,0 +1,347 @@
+//! A dynamically typed key part for callers that only learn field types at run time.
+//!
+//! `Value` uses the type-tagged layout of the `tagged` module, so a key built from values
+//! decodes back into the same values without a schema. The tags shared with `TaggedValue` write
+//! identical bytes; `Value` adds unsigned integers (`TAG_UINT`) and timestamps
+//! (`TAG_TIMESTAMP`). Parts order by type first, as
+//! `null < false < true < i64 < f64 < bytes < string < uuid < u64 < timestamp`, then by value.
+//!
+//! The two added tags come after every shared one, so numbers do not form one class: every
+//! `U64` sorts after every `I64`, `F64`, string and UUID (`U64(0) > I64(1)`), and timestamps sort
+//! after all of them rather than next to `I64`. Keep one variant per key position, or convert
+//! unsigned values that fit to `I64` first, when numeric order across variants matters.
+//!
+//! ```rust
+//! use lexkey::{LexKey, Value};
+//!
+//! let row = [Value::from("orders"), Value::U64(42), Value::Null];
+//! let key = LexKey::encode_values(&row);
+//! assert_eq!(lexkey::value::decode_values(key.as_bytes())?, row);
+//! assert!(LexKey::encode_values(&[Value::I64(-1)]) < LexKey::encode_values(&[Value::I64(0)]));
+//! # Ok::<(), lexkey::DecodeError>(())
+//! ```
+
+use uuid::Uuid;
+
+use crate::escape::{escape_into, escaped_len};
+use crate::tagged::{
+    TaggedValue, TAG_BYTES, TAG_FALSE, TAG_FLOAT, TAG_INT, TAG_NULL, TAG_STRING, TAG_TIMESTAMP,
+    TAG_TRUE, TAG_UINT, TAG_UUID,
+};
+use crate::{
+    Decodable, DecodeError, Decoder, Encodable, EncodeError, Encoder, LexKey, TryEncodable,
+};
+
+/// A key part whose type is carried in the key.
+///
+/// Variants order by type tag before value, in declaration order except that `U64` and
+/// `Timestamp` sort last: `U64(0)` is greater than `I64(1)`, `F64(1.0)` and any string.
+///
+/// ```rust
+/// use lexkey::{LexKey, Value};
+///
+/// let key = |v: Value| LexKey::encode_values(&[v]);
+/// assert!(key(Value::U64(0)) > key(Value::I64(1)));
+/// assert!(key(Value::U64(0)) > key(Value::from("zzz")));
+/// assert!(key(Value::Timestamp(0)) > key(Value::U64(u64::MAX)));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    I64(i64),
+    /// Tagged `09`, after every shared tag, so it does not interleave with `I64` or `F64`.
+    U64(u64),
+    F64(f64),
+    Bytes(Vec<u8>),
+    String(String),
+    Uuid(Uuid),
+    /// UNIX nanoseconds, as written by `LexKey::encode_time_unix_nanos`. Tagged `0a`, the last
+    /// type.
+    Timestamp(i64),
+}
+
+impl Value {
+    fn tag(&self) -> u8 {
+        match self {
+            Self::Null => TAG_NULL,
+            Self::Bool(false) => TAG_FALSE,
+            Self::Bool(true) => TAG_TRUE,
+            Self::I64(_) => TAG_INT,
+            Self::U64(_) => TAG_UINT,
+            Self::F64(_) => TAG_FLOAT,
+            Self::Bytes(_) => TAG_BYTES,
+            Self::String(_) => TAG_STRING,
+            Self::Uuid(_) => TAG_UUID,
+            Self::Timestamp(_) => TAG_TIMESTAMP,
+        }
+    }
+}
+
+impl Encodable for Value {
+    fn encoded_len(&self) -> usize {
+        1 + match self {
+            Self::Null | Self::Bool(_) => 0,
+            Self::I64(_) | Self::U64(_) | Self::F64(_) | Self::Timestamp(_) => 8,
+            Self::Bytes(b) => escaped_len(b),
+            Self::String(s) => escaped_len(s.as_bytes()),
+            Self::Uuid(_) => 16,
+        }
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the value is a NaN float; use `TryEncodable` to get an error instead.
+    fn encode_into(&self, dst: &mut Vec<u8>) -> usize {
+        dst.push(self.tag());
+        1 + match self {
+            Self::Null | Self::Bool(_) => 0,
+            Self::I64(n) | Self::Timestamp(n) => LexKey::encode_i64_into(dst, *n),
+            Self::U64(n) => LexKey::encode_u64_into(dst, *n),
+            Self::F64(x) => LexKey::encode_f64_into(dst, *x),
+            Self::Bytes(b) => escape_into(dst, b),
+            Self::String(s) => escape_into(dst, s.as_bytes()),
+            Self::Uuid(u) => u.encode_into(dst),
+        }
+    }
+}
+
+impl TryEncodable for Value {
+    fn try_encode_into(&self, dst: &mut Vec<u8>) -> Result<usize, EncodeError> {
+        match self {
+            Self::F64(x) if x.is_nan() => Err(EncodeError::NaN),
+            _ => Ok(self.encode_into(dst)),
+        }
+    }
+}
+
+/// Reads one part, dispatching on its tag; a following separator is left unread.
+impl Decodable for Value {
+    fn decode_from(src: &mut &[u8]) -> Result<Self, DecodeError> {
+        let mut dec = Decoder::new(src);
+        let start = dec.position();
+        let value = match dec.decode_u8()? {
+            TAG_UINT => dec.decode_u64().map(Self::U64),
+            TAG_TIMESTAMP => dec.decode_i64().map(Self::Timestamp),
+            _ => {
+                dec.rewind(start);
+                dec.decode_tagged().map(Self::from)
+            }
+        }?;
+        *src = dec.remaining();
+        Ok(value)
+    }
+}
+
+/// Every tagged value has a `Value` with the same bytes.
+impl From<TaggedValue> for Value {
+    fn from(v: TaggedValue) -> Self {
+        match v {
+            TaggedValue::Null => Self::Null,
+            TaggedValue::Bool(b) => Self::Bool(b),
+            TaggedValue::Int(n) => Self::I64(n),
+            TaggedValue::Float(x) => Self::F64(x),
+            TaggedValue::Bytes(b) => Self::Bytes(b),
+            TaggedValue::Str(s) => Self::String(s),
+            TaggedValue::Uuid(u) => Self::Uuid(u),
+        }
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Self::Bool(b)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(n: i64) -> Self {
+        Self::I64(n)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(n: i32) -> Self {
+        Self::I64(n.into())
+    }
+}
+
+impl From<u64> for Value {
+    fn from(n: u64) -> Self {
+        Self::U64(n)
+    }
+}
+
+impl From<u32> for Value {
+    fn from(n: u32) -> Self {
+        Self::U64(n.into())
+    }
+}
+
+impl From<f64> for Value {
+    fn from(x: f64) -> Self {
+        Self::F64(x)
+    }
+}
+
+impl From<&[u8]> for Value {
+    fn from(b: &[u8]) -> Self {
+        Self::Bytes(b.to_vec())
+    }
+}
+
+impl From<Vec<u8>> for Value {
+    fn from(b: Vec<u8>) -> Self {
+        Self::Bytes(b)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Self::String(s.to_owned())
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Self::String(s)
+    }
+}
+
+impl From<Uuid> for Value {
+    fn from(u: Uuid) -> Self {
+        Self::Uuid(u)
+    }
+}
+
+/// `None` becomes `Value::Null`.
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(v: Option<T>) -> Self {
+        v.map_or(Self::Null, Into::into)
+    }
+}
+
+impl LexKey {
+    /// Encode values as a composite, one tagged part per value joined by `SEPARATOR`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a value is a NaN float.
+    #[must_use]
+    pub fn encode_values(values: &[Value]) -> Self {
+        let parts: Vec<&dyn Encodable> = values.iter().map(|v| v as &dyn Encodable).collect();
+        Self::encode_composite_encodables(&parts)
+    }
+}
+
+impl Encoder {
+    /// Append values as separator-joined tagged parts; see `LexKey::encode_values`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a value is a NaN float.
+    #[inline]
+    pub fn encode_values_into(&mut self, values: &[Value]) -> usize {
+        let start = self.buf.len();
+        self.encode_parts_into(values);
+        self.buf.len() - start
+    }
+}
+
+/// Decode every part of a key written by `LexKey::encode_values`.
+///
+/// # Errors
+///
+/// Returns a `DecodeError` if a part is truncated or invalid, a separator is missing between
+/// parts, or bytes remain after the last part.
+pub fn decode_values(key: &[u8]) -> Result<Vec<Value>, DecodeError> {
+    let mut dec = Decoder::new(key);
+    let mut values = Vec::new();
+    while !dec.is_empty() {
+        if !values.is_empty() {
+            dec.expect_separator()?;
+        }
+        values.push(dec.decode_value()?);
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples() -> Vec<Value> {
+        vec![
+            Value::Null,
+            false.into(),
+            true.into(),
+            i64::MIN.into(),
+            0i64.into(),
+            f64::NEG_INFINITY.into(),
+            1.5.into(),
+            Value::from(&b"\x00"[..]),
+            Value::from(&b"\xff"[..]),
+            "".into(),
+            "a".into(),
+            Uuid::max().into(),
+            0u64.into(),
+            u64::MAX.into(),
+            Value::Timestamp(-1),
+            Value::Timestamp(1_700_000_000_000_000_000),
+        ]
+    }
+
+    #[test]
+    fn should_order_by_type_then_value() {
+        let keys: Vec<LexKey> = samples()
+            .into_iter()
+            .map(|v| LexKey::encode_values(&[v, Value::Null]))
+            .collect();
+        for pair in keys.windows(2) {
+            assert!(pair[0] < pair[1], "{:?} !< {:?}", pair[0], pair[1]);
+        }
+    }
+
+    #[test]
+    fn should_round_trip_and_share_bytes_with_tagged_values() {
+        let values = samples();
+        let key = LexKey::encode_values(&values);
+        assert_eq!(decode_values(key.as_bytes()), Ok(values));
+        assert_eq!(
+            LexKey::encode_values(&["a".into(), 1i64.into()]),
+            LexKey::encode_tagged(&["a".into(), 1i64.into()])
+        );
+        assert_eq!(
+            LexKey::encode_values(&[Value::U64(1)]).to_hex_string(),
+            "090000000000000001"
+        );
+
+        let mut enc = Encoder::with_capacity(16);
+        let n = enc.encode_value_into(&Value::Timestamp(0));
+        assert_eq!(enc.as_slice(), b"\x0a\x80\0\0\0\0\0\0\0");
+        assert_eq!(n, Value::Timestamp(0).encoded_len());
+    }
+
+    #[test]
+    fn should_reject_bad_tags_and_nan() {
+        let mut src: &[u8] = b"\x0b";
+        assert_eq!(
+            Value::decode_from(&mut src),
+            Err(DecodeError::Invalid {
+                offset: 0,
+                expected: "type tag"
+            })
+        );
+        assert!(matches!(
+            decode_values(b"\x09\x00"),
+            Err(DecodeError::UnexpectedEnd { offset: 1, .. })
+        ));
+
+        let mut buf = Vec::new();
+        assert_eq!(
+            Value::F64(f64::NAN).try_encode_into(&mut buf),
+            Err(EncodeError::NaN)
+        );
+        assert!(buf.is_empty());
+    }
+}
@@ -0,0 +1,107 @@
+//! Enum keys ordered by variant ordinal.
+//!
+//! An enum value encodes as its ordinal, a 2-byte big-endian `u16`, followed directly by the
+//! encoding of its payload, if any. Keys order by declaration order of the variants rather than
+//! by their names, so a state machine keyed as `Pending < Active < Done` scans in that order.
+//! The ordinal is fixed-width, so no separator is needed before the payload.
+
+use crate::{DecodeError, Decoder, Encodable, Encoder, LexKey};
+
+/// Width of an encoded enum ordinal in bytes.
+pub(crate) const ORDINAL_LEN: usize = 2;
+
+impl LexKey {
+    /// Encode an enum variant as its `ordinal` followed by its `payload`.
+    ///
+    /// ```rust
+    /// use lexkey::LexKey;
+    ///
+    /// const PENDING: u16 = 0;
+    /// const ACTIVE: u16 = 1;
+    /// const DONE: u16 = 2;
+    ///
+    /// let active = LexKey::encode_enum(ACTIVE, Some(&"worker-7"));
+    /// assert!(LexKey::encode_enum(PENDING, None::<&u8>) < active);
+    /// assert!(active < LexKey::encode_enum(DONE, None::<&u8>));
+    /// assert_eq!(active.to_hex_string(), "0001776f726b65722d37");
+    /// ```
+    #[must_use]
+    pub fn encode_enum<T: Encodable + ?Sized>(ordinal: u16, payload: Option<&T>) -> Self {
+        let mut buf = Vec::with_capacity(ORDINAL_LEN + payload.map_or(0, Encodable::encoded_len));
+        Self::encode_enum_into(&mut buf, ordinal, payload);
+        Self::from_bytes(buf)
+    }
+
+    /// Append an enum variant's `ordinal` and `payload` into `dst` and return bytes written.
+    #[inline]
+    pub fn encode_enum_into<T: Encodable + ?Sized>(
+        dst: &mut Vec<u8>,
+        ordinal: u16,
+        payload: Option<&T>,
+    ) -> usize {
+        Self::encode_u16_into(dst, ordinal) + payload.map_or(0, |p| p.encode_into(dst))
+    }
+}
+
+impl Encoder {
+    /// Append an enum variant's `ordinal` and `payload`; see `LexKey::encode_enum`.
+    #[inline]
+    pub fn encode_enum_into<T: Encodable + ?Sized>(
+        &mut self,
+        ordinal: u16,
+        payload: Option<&T>,
+    ) -> usize {
+        LexKey::encode_enum_into(&mut self.buf, ordinal, payload)
+    }
+}
+
+impl Decoder<'_> {
+    /// Decode the ordinal written by `encode_enum`, leaving the payload to the caller.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DecodeError::UnexpectedEnd` if fewer than 2 bytes remain.
+    #[inline]
+    pub fn decode_enum_ordinal(&mut self) -> Result<u16, DecodeError> {
+        self.decode_u16()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_order_by_ordinal_before_payload() {
+        let keys = [
+            LexKey::encode_enum(0, Some(&u64::MAX)),
+            LexKey::encode_enum(1, None::<&u64>),
+            LexKey::encode_enum(1, Some(&0u64)),
+            LexKey::encode_enum(1, Some(&1u64)),
+            LexKey::encode_enum(300, None::<&u64>),
+        ];
+        for pair in keys.windows(2) {
+            assert!(pair[0] < pair[1]);
+        }
+        assert_eq!(keys[4].to_hex_string(), "012c");
+    }
+
+    #[test]
+    fn should_match_encoder_and_decode_ordinal() {
+        let mut enc = Encoder::with_capacity(8);
+        let n = enc.encode_enum_into(2, Some(&-1i32));
+        assert_eq!(n, 6);
+        assert_eq!(
+            enc.as_slice(),
+            LexKey::encode_enum(2, Some(&-1i32)).as_bytes()
+        );
+
+        let mut dec = Decoder::new(enc.as_slice());
+        assert_eq!(dec.decode_enum_ordinal(), Ok(2));
+        assert_eq!(dec.decode_i32(), Ok(-1));
+        assert!(matches!(
+            Decoder::new(&[0x01]).decode_enum_ordinal(),
+            Err(DecodeError::UnexpectedEnd { .. })
+        ));
+    }
+}
@@ -0,0 +1,248 @@
+#![cfg(feature = "derive")]
+
+use uuid::Uuid;
+
+use lexkey::desc::Desc;
+use lexkey::{
+    encode_composite, Decodable, DecodeError, Decoder, Encodable, Encoder, LexKey, LexKeyDecode,
+    LexKeyEncode,
+};
+
+#[derive(Debug, PartialEq, LexKeyEncode, LexKeyDecode)]
+struct OrderKey {
+    tenant: String,
+    #[lexkey(desc)]
+    created_at: i64,
+    id: Uuid,
+}
+
+#[derive(LexKeyEncode)]
+struct Scored<'a>(&'a str, #[lexkey(nulls_last)] Option<u32>, Option<u32>);
+
+#[derive(Debug, PartialEq, LexKeyEncode, LexKeyDecode)]
+struct Empty;
+
+#[derive(Debug, PartialEq, LexKeyEncode, LexKeyDecode)]
+struct Reading(u16, #[lexkey(nulls_last, desc)] Option<i32>, OrderKey);
+
+#[test]
+fn should_match_encode_composite_field_by_field() {
+    let key = OrderKey {
+        tenant: "acme".into(),
+        created_at: 7,
+        id: Uuid::from_u128(1),
+    };
+    let expected = encode_composite!("acme", Desc(7i64), Uuid::from_u128(1));
+    assert_eq!(key.to_lexkey(), expected);
+    assert_eq!(key.encoded_len(), expected.as_bytes().len());
+
+    let mut enc = Encoder::with_capacity(0);
+    enc.encode_string_into("x");
+    enc.push_separator();
+    let n = key.encode_into(&mut enc);
+    assert_eq!(n, expected.as_bytes().len());
+    assert_eq!(&enc.as_slice()[2..], expected.as_bytes());
+
+    assert_eq!(Empty.to_lexkey(), LexKey::empty());
+}
+
+#[test]
+fn should_apply_null_order_attributes() {
+    let some = Scored("a", Some(0), None).to_lexkey();
+    let none = Scored("a", None, None).to_lexkey();
+    assert!(some < none);
+    assert_eq!(none.to_hex_string(), "6100020000");
+
+    assert!(Scored("a", Some(1), None).to_lexkey() < Scored("a", Some(1), Some(0)).to_lexkey());
+}
+
+#[test]
+fn should_round_trip_through_decode_derive() {
+    let order = OrderKey {
+        tenant: "acme".into(),
+        created_at: -5,
+        id: Uuid::from_u128(9),
+    };
+    let key = order.to_lexkey();
+    assert_eq!(OrderKey::from_lexkey(key.as_bytes()), Ok(order));
+
+    for value in [Some(-3), Some(0), None] {
+        let reading = Reading(
+            7,
+            value,
+            OrderKey {
+                tenant: "t".into(),
+                created_at: 1,
+                id: Uuid::nil(),
+            },
+        );
+        let key = reading.to_lexkey();
+        assert_eq!(Reading::from_lexkey(key.as_bytes()), Ok(reading));
+    }
+    assert_eq!(Empty::from_lexkey(b""), Ok(Empty));
+}
+
+#[test]
+fn should_report_decode_errors_at_key_offsets() {
+    let key = encode_composite!("acme", Desc(1i64), Uuid::nil());
+    let bytes = key.as_bytes();
+
+    assert_eq!(
+        OrderKey::from_lexkey(&bytes[..bytes.len() - 1]),
+        Err(DecodeError::UnexpectedEnd {
+            offset: 14,
+            needed: 16,
+            available: 15,
+        })
+    );
+
+    let mut trailing = bytes.to_vec();
+    trailing.push(0);
+    assert_eq!(
+        OrderKey::from_lexkey(&trailing),
+        Err(DecodeError::TrailingBytes {
+            offset: bytes.len(),
+            remaining: 1,
+        })
+    );
+
+    let mut src = &bytes[..3];
+    assert!(OrderKey::decode_from(&mut src).is_err());
+    assert_eq!(src.len(), 3);
+}
+
+#[derive(Debug, PartialEq, LexKeyEncode, LexKeyDecode)]
+struct Newest {
+    #[lexkey(desc)]
+    name: String,
+}
+
+#[derive(Debug, PartialEq, LexKeyEncode, LexKeyDecode)]
+struct Tenant {
+    name: String,
+}
+
+#[test]
+fn should_measure_descending_string_fields() {
+    let key = Newest { name: "ab".into() };
+    assert_eq!(key.to_lexkey(), encode_composite!(Desc("ab")));
+    assert_eq!(key.encoded_len(), key.to_lexkey().as_bytes().len());
+    assert_eq!(key.encoded_len(), 4);
+}
+
+#[test]
+fn should_reverse_derived_values_under_desc() {
+    let tenant = |name: &str| Tenant { name: name.into() };
+    let shorter = encode_composite!(Desc(tenant("a")));
+    let longer = encode_composite!(Desc(tenant("ab")));
+    assert!(longer < shorter);
+    assert_eq!(shorter, encode_composite!(Desc("a")));
+    assert_eq!(Desc(tenant("ab")).encoded_len(), longer.as_bytes().len());
+
+    // Fields that are already descending flip back to ascending.
+    let newest = |name: &str| Newest { name: name.into() };
+    assert_eq!(
+        encode_composite!(Desc(newest("ab"))),
+        encode_composite!("ab")
+    );
+    assert_eq!(Desc(newest("ab")).encoded_len(), 2);
+
+    let reading = |shard| {
+        Reading(
+            shard,
+            Some(-1),
+            OrderKey {
+                tenant: "t".into(),
+                created_at: 1,
+                id: Uuid::nil(),
+            },
+        )
+    };
+    let desc = |shard| encode_composite!(Desc(reading(shard)));
+    assert!(desc(2) < desc(1));
+    assert_eq!(Desc(reading(1)).encoded_len(), desc(1).as_bytes().len());
+    assert!(encode_composite!(Desc(State::Done(1, 0))) < encode_composite!(Desc(State::Pending)));
+}
+
+#[test]
+fn should_decode_descending_fields_and_values() {
+    for name in ["", "ab", "a\x00b"] {
+        let key = Newest { name: name.into() };
+        assert_eq!(Newest::from_lexkey(key.to_lexkey().as_bytes()), Ok(key));
+    }
+
+    let key = encode_composite!(Desc(Tenant { name: "ab".into() }), 5u8);
+    let mut dec = Decoder::new(key.as_bytes());
+    assert_eq!(
+        dec.decode_value_desc::<Tenant>(),
+        Ok(Tenant { name: "ab".into() })
+    );
+    dec.expect_separator().unwrap();
+    assert_eq!(dec.decode_value::<u8>(), Ok(5));
+
+    let reading = Reading(
+        3,
+        Some(-2),
+        OrderKey {
+            tenant: "t".into(),
+            created_at: 9,
+            id: Uuid::from_u128(4),
+        },
+    );
+    let key = encode_composite!(Desc(&reading));
+    assert_eq!(
+        Decoder::new(key.as_bytes()).decode_value_desc::<Reading>(),
+        Ok(reading)
+    );
+    for state in [State::Active { worker: "w".into() }, State::Done(-1, 2)] {
+        let key = encode_composite!(Desc(&state));
+        assert_eq!(
+            Decoder::new(key.as_bytes()).decode_value_desc::<State>(),
+            Ok(state)
+        );
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, LexKeyEncode, LexKeyDecode)]
+enum State {
+    Pending,
+    Active {
+        worker: String,
+    },
+    #[lexkey(ordinal = 10)]
+    Done(#[lexkey(desc)] i64, u8),
+    Archived,
+}
+
+#[test]
+fn should_order_enums_by_declaration_not_name() {
+    let states = [
+        State::Pending,
+        State::Active { worker: "a".into() },
+        State::Active { worker: "b".into() },
+        State::Done(2, 0),
+        State::Done(1, 0),
+        State::Archived,
+    ];
+    for pair in states.windows(2) {
+        assert!(pair[0].to_lexkey() < pair[1].to_lexkey(), "{pair:?}");
+    }
+    for state in &states {
+        let key = state.to_lexkey();
+        assert_eq!(key.as_bytes().len(), state.encoded_len());
+        assert_eq!(State::from_lexkey(key.as_bytes()).as_ref(), Ok(state));
+    }
+
+    assert_eq!(
+        State::Active { worker: "w".into() }.to_lexkey(),
+        LexKey::encode_enum(1, Some(&"w"))
+    );
+    assert_eq!(State::Archived.to_lexkey().to_hex_string(), "000b");
+    assert_eq!(
+        State::from_lexkey(b"\x00\x05"),
+        Err(DecodeError::Invalid {
+            offset: 0,
+            expected: "enum ordinal",
+        })
+    );
+}